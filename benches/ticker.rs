@@ -0,0 +1,129 @@
+//! Benchmarks of the per-update and per-frame paths: merging a `!ticker@arr` payload into the
+//! store, sorting the table, and building the table's rows.
+//!
+//! Run with `cargo bench`; pass a filter such as `cargo bench -- sort` to run one group.
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use crypto_tui_ticker::{
+    alerts::AlertEngine,
+    app::{App, Stores},
+    config::Config,
+    exchange::Exchange,
+    model::{update_tickers, HrTicker, SortColumn, TickerMap},
+    stream::Selection,
+    ui::render_table,
+};
+use ratatui::{backend::TestBackend, Terminal};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+
+/// Roughly the number of symbols in a Binance spot `!ticker@arr` frame.
+const PAYLOAD_SYMBOLS: usize = 2000;
+/// A large futures watchlist.
+const TABLE_SYMBOLS: usize = 500;
+
+/// A `!ticker@arr` frame of `count` symbols with prices shifted by `tick`.
+fn payload(count: usize, tick: f32) -> String {
+    let tickers: Vec<_> = (0..count)
+        .map(|i| {
+            let last = 1.0 + (i * 7919 % count) as f32 + tick;
+            json!({
+                "e": "24hrTicker", "E": 0, "s": format!("C{:04}USDT", i),
+                "p": "0.5", "P": format!("{:.2}", (i % 41) as f32 - 20.0), "w": last.to_string(),
+                "c": last.to_string(), "Q": "1", "o": last.to_string(),
+                "h": (last * 1.1).to_string(), "l": (last * 0.9).to_string(),
+                "v": (i * 1000).to_string(), "q": (i * 12345).to_string(),
+                "O": 0, "C": 0, "F": 0, "L": 0, "n": i
+            })
+        })
+        .collect();
+    serde_json::to_string(&tickers).unwrap()
+}
+
+fn tickers(count: usize, tick: f32) -> Vec<HrTicker> {
+    serde_json::from_str(&payload(count, tick)).unwrap()
+}
+
+/// An app over `count` symbols, isolated from the user's config directory.
+fn app(count: usize) -> (App, Arc<Mutex<TickerMap>>) {
+    std::env::set_var(
+        "XDG_CONFIG_HOME",
+        std::env::temp_dir().join("crypto_tui_ticker_benches"),
+    );
+    let store = Arc::new(Mutex::new(TickerMap::new()));
+    update_tickers(tickers(count, 0.0), store.clone());
+    let stores = Stores {
+        tickers: store.clone(),
+        comparison: Default::default(),
+        market_data: Default::default(),
+        global: Default::default(),
+        fx_rates: Default::default(),
+        indicators: Default::default(),
+        liquidations: Default::default(),
+        status: Default::default(),
+        alerts: Arc::new(Mutex::new(AlertEngine::new(Vec::new()))),
+        account: None,
+        selection: watch::Sender::new(Selection::All),
+        exchange: None,
+    };
+    (
+        App::new(Config::default(), Exchange::Binance, stores),
+        store,
+    )
+}
+
+fn ingestion(c: &mut Criterion) {
+    let text = payload(PAYLOAD_SYMBOLS, 0.5);
+    c.bench_function("parse ticker@arr payload", |b| {
+        b.iter(|| serde_json::from_str::<Vec<HrTicker>>(black_box(&text)).unwrap())
+    });
+
+    let store = Arc::new(Mutex::new(TickerMap::new()));
+    update_tickers(tickers(PAYLOAD_SYMBOLS, 0.0), store.clone());
+    let update = tickers(PAYLOAD_SYMBOLS, 0.5);
+    c.bench_function("update_tickers full payload", |b| {
+        b.iter_batched(
+            || update.clone(),
+            |update| update_tickers(update, store.clone()),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn sorting(c: &mut Criterion) {
+    let (mut app, store) = app(TABLE_SYMBOLS);
+    let store = store.lock().unwrap();
+    let mut group = c.benchmark_group("sort_tickers");
+    for column in [
+        SortColumn::Symbol,
+        SortColumn::Last,
+        SortColumn::PercentChange,
+        SortColumn::Volume,
+    ] {
+        app.sort_by(column);
+        group.bench_function(column.title(), |b| {
+            b.iter_batched(
+                || store.values().collect::<Vec<_>>(),
+                |mut rows| app.sort_tickers(&mut rows),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn rendering(c: &mut Criterion) {
+    let (mut app, store) = app(TABLE_SYMBOLS);
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).unwrap();
+    c.bench_function("render_table 500 symbols", |b| {
+        b.iter(|| {
+            terminal
+                .draw(|f| render_table(f, &mut app, f.size(), store.clone()))
+                .unwrap();
+        })
+    });
+}
+
+criterion_group!(benches, ingestion, sorting, rendering);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+//! Accessibility mode: price moves are spelled out with glyphs and signs instead of color
+//! alone, rows do not flash, colors are brighter, and the selected row can be read out on a
+//! status line of its own.
+
+use crate::{model::HrTicker, theme::HexColor};
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+/// `[accessibility]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    pub enabled: bool,
+    /// Shows the selected row's data as plain text above the footer.
+    pub announce: bool,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            announce: true,
+        }
+    }
+}
+
+/// `▲` for a rise, `▼` for a fall and `=` for no change.
+pub fn glyph(change: f64) -> char {
+    if change > 0.0 {
+        '▲'
+    } else if change < 0.0 {
+        '▼'
+    } else {
+        '='
+    }
+}
+
+/// `text` of a change, prefixed by its glyph and with an explicit `+` on rises.
+pub fn signed(text: &str, change: f64) -> String {
+    let sign = if change > 0.0 && !text.starts_with('+') {
+        "+"
+    } else {
+        ""
+    };
+    format!("{} {}{}", glyph(change), sign, text)
+}
+
+/// `color` pushed halfway toward white on a `dark` background, or toward black on a light one.
+pub fn contrasted(color: Color, dark: bool) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    // Pushing every channel toward the far end keeps the hue recognizable.
+    let push = |v: u8| {
+        if dark {
+            v.saturating_add((255 - v) / 2)
+        } else {
+            v / 2
+        }
+    };
+    HexColor(push(r), push(g), push(b)).into()
+}
+
+/// The selected row as a plain sentence for screen readers.
+pub fn announcement(ticker: &HrTicker) -> String {
+    let direction = if ticker.P > 0.0 {
+        "up"
+    } else if ticker.P < 0.0 {
+        "down"
+    } else {
+        "unchanged"
+    };
+    format!(
+        "{}: last {}, {} {}% in 24 hours, open {}, high {}, low {}, volume {}",
+        ticker.s,
+        ticker.c,
+        direction,
+        ticker.P.abs(),
+        ticker.o,
+        ticker.h,
+        ticker.l,
+        ticker.v
+    )
+}
@@ -0,0 +1,335 @@
+//! Read-only view of a Binance USDⓈ-M futures account: a signed REST snapshot of balances and
+//! positions, kept current by the user-data websocket stream.
+
+use crate::{config::Config, error::Error, net, ratelimit};
+use futures::StreamExt;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+const REST_URL: &str = "https://fapi.binance.com";
+const USER_STREAM_URL: &str = "wss://fstream.binance.com/ws";
+const CREDENTIALS_FILE: &str = "credentials.toml";
+/// Listen keys expire after 60 minutes without a keepalive.
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(30 * 60);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+#[derive(Deserialize, Clone)]
+pub struct Credentials {
+    pub api_key: String,
+    pub api_secret: String,
+}
+
+#[derive(Deserialize)]
+struct CredentialsFile {
+    binance: Option<Credentials>,
+}
+
+impl Credentials {
+    /// Reads `BINANCE_API_KEY` and `BINANCE_API_SECRET`, falling back to the `[binance]` table of
+    /// `credentials.toml` in the config directory. Keys are deliberately not accepted as CLI
+    /// arguments, where they would end up in shell history and process listings.
+    pub fn load() -> Option<Self> {
+        if let (Ok(api_key), Ok(api_secret)) =
+            (env::var("BINANCE_API_KEY"), env::var("BINANCE_API_SECRET"))
+        {
+            return Some(Self {
+                api_key,
+                api_secret,
+            });
+        }
+        let path = Config::dir()?.join(CREDENTIALS_FILE);
+        let contents = fs::read_to_string(path).ok()?;
+        toml::from_str::<CredentialsFile>(&contents).ok()?.binance
+    }
+
+    fn sign(&self, query: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.api_secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(query.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Balance {
+    pub wallet: f64,
+    pub cross_wallet: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct FuturesPosition {
+    pub symbol: String,
+    /// `BOTH` in one-way mode, `LONG`/`SHORT` in hedge mode.
+    pub side: String,
+    /// Signed size: positive long, negative short.
+    pub amount: f64,
+    pub entry_price: f64,
+    /// Only known from the REST snapshot; stream updates keep the last value.
+    pub leverage: Option<f64>,
+    pub isolated: bool,
+    pub isolated_margin: f64,
+}
+
+impl FuturesPosition {
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        self.amount * (price - self.entry_price)
+    }
+
+    /// Margin backing the position: the isolated wallet, or for cross positions the initial
+    /// margin at the position's leverage.
+    pub fn margin(&self, price: f64) -> Option<f64> {
+        if self.isolated {
+            Some(self.isolated_margin)
+        } else {
+            self.leverage
+                .filter(|leverage| *leverage > 0.0)
+                .map(|leverage| (self.amount * price).abs() / leverage)
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum AccountStatus {
+    #[default]
+    Connecting,
+    Live,
+    Error(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Account {
+    pub status: AccountStatus,
+    pub balances: BTreeMap<String, Balance>,
+    /// Open positions keyed by symbol and position side.
+    pub positions: BTreeMap<(String, String), FuturesPosition>,
+}
+
+impl Account {
+    fn update_position(&mut self, position: FuturesPosition) {
+        let key = (position.symbol.clone(), position.side.clone());
+        if position.amount == 0.0 {
+            self.positions.remove(&key);
+            return;
+        }
+        let leverage = self.positions.get(&key).and_then(|p| p.leverage);
+        self.positions.insert(
+            key,
+            FuturesPosition {
+                leverage: position.leverage.or(leverage),
+                ..position
+            },
+        );
+    }
+}
+
+fn parse(value: &str) -> f64 {
+    value.parse().unwrap_or(0.0)
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct PositionRisk {
+    symbol: String,
+    positionAmt: String,
+    entryPrice: String,
+    leverage: String,
+    marginType: String,
+    isolatedMargin: String,
+    positionSide: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct AssetBalance {
+    asset: String,
+    balance: String,
+    crossWalletBalance: String,
+}
+
+#[derive(Deserialize)]
+struct ListenKey {
+    #[serde(rename = "listenKey")]
+    listen_key: String,
+}
+
+#[derive(Deserialize)]
+struct AccountUpdateEvent {
+    a: AccountUpdate,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct AccountUpdate {
+    B: Vec<BalanceUpdate>,
+    P: Vec<PositionUpdate>,
+}
+
+#[derive(Deserialize)]
+struct BalanceUpdate {
+    a: String,  // Asset
+    wb: String, // Wallet balance
+    cw: String, // Cross wallet balance
+}
+
+#[derive(Deserialize)]
+struct PositionUpdate {
+    s: String,  // Symbol
+    pa: String, // Position amount
+    ep: String, // Entry price
+    mt: String, // Margin type
+    iw: String, // Isolated wallet
+    ps: String, // Position side
+}
+
+async fn signed_get<T: serde::de::DeserializeOwned>(
+    client: &reqwest::Client,
+    credentials: &Credentials,
+    path: &str,
+) -> Result<T, Error> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis());
+    let query = format!("timestamp={}&recvWindow=5000", timestamp);
+    let url = format!(
+        "{}{}?{}&signature={}",
+        REST_URL,
+        path,
+        query,
+        credentials.sign(&query)
+    );
+    // Both endpoints used weigh 5.
+    let request = client.get(url).header("X-MBX-APIKEY", &credentials.api_key);
+    Ok(ratelimit::send(request, 5)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+async fn snapshot(
+    client: &reqwest::Client,
+    credentials: &Credentials,
+    account: &Arc<Mutex<Account>>,
+) -> Result<(), Error> {
+    let positions: Vec<PositionRisk> =
+        signed_get(client, credentials, "/fapi/v2/positionRisk").await?;
+    let balances: Vec<AssetBalance> = signed_get(client, credentials, "/fapi/v2/balance").await?;
+
+    let mut account = account.lock().unwrap();
+    account.positions.clear();
+    for p in positions {
+        account.update_position(FuturesPosition {
+            symbol: p.symbol,
+            side: p.positionSide,
+            amount: parse(&p.positionAmt),
+            entry_price: parse(&p.entryPrice),
+            leverage: Some(parse(&p.leverage)),
+            isolated: p.marginType == "isolated",
+            isolated_margin: parse(&p.isolatedMargin),
+        });
+    }
+    account.balances = balances
+        .into_iter()
+        .map(|b| {
+            let balance = Balance {
+                wallet: parse(&b.balance),
+                cross_wallet: parse(&b.crossWalletBalance),
+            };
+            (b.asset, balance)
+        })
+        .filter(|(_, b)| b.wallet != 0.0)
+        .collect();
+    Ok(())
+}
+
+fn apply_update(account: &mut Account, update: AccountUpdate) {
+    for b in update.B {
+        account.balances.insert(
+            b.a,
+            Balance {
+                wallet: parse(&b.wb),
+                cross_wallet: parse(&b.cw),
+            },
+        );
+    }
+    for p in update.P {
+        account.update_position(FuturesPosition {
+            symbol: p.s,
+            side: p.ps,
+            amount: parse(&p.pa),
+            entry_price: parse(&p.ep),
+            leverage: None,
+            isolated: p.mt == "isolated",
+            isolated_margin: parse(&p.iw),
+        });
+    }
+}
+
+/// One user-data session: snapshot, listen key, then stream until the connection ends.
+async fn session(
+    client: &reqwest::Client,
+    credentials: &Credentials,
+    account: &Arc<Mutex<Account>>,
+) -> Result<(), Error> {
+    snapshot(client, credentials, account).await?;
+    let request = client
+        .post(format!("{}/fapi/v1/listenKey", REST_URL))
+        .header("X-MBX-APIKEY", &credentials.api_key);
+    let ListenKey { listen_key } = ratelimit::send(request, 1)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let (ws_stream, _) = net::connect(format!("{}/{}", USER_STREAM_URL, listen_key)).await?;
+    let (_, mut read) = ws_stream.split();
+    account.lock().unwrap().status = AccountStatus::Live;
+
+    let mut keepalive = tokio::time::interval(KEEPALIVE_INTERVAL);
+    keepalive.tick().await;
+    loop {
+        tokio::select! {
+            msg = read.next() => {
+                let Some(msg) = msg else {
+                    return Ok(());
+                };
+                let Message::Text(text) = msg? else {
+                    continue;
+                };
+                let event: serde_json::Value = serde_json::from_str(&text)?;
+                match event["e"].as_str() {
+                    Some("ACCOUNT_UPDATE") => {
+                        let update: AccountUpdateEvent = serde_json::from_value(event)?;
+                        apply_update(&mut account.lock().unwrap(), update.a);
+                    }
+                    Some("listenKeyExpired") => return Ok(()),
+                    _ => {}
+                }
+            }
+            _ = keepalive.tick() => {
+                let request = client
+                    .put(format!("{}/fapi/v1/listenKey", REST_URL))
+                    .header("X-MBX-APIKEY", &credentials.api_key);
+                ratelimit::send(request, 1).await?.error_for_status()?;
+            }
+        }
+    }
+}
+
+/// Keeps `account` in sync, starting a new session whenever one ends or fails.
+pub async fn run(credentials: Credentials, account: Arc<Mutex<Account>>) {
+    let client = net::client();
+    loop {
+        if let Err(err) = session(&client, &credentials, &account).await {
+            account.lock().unwrap().status = AccountStatus::Error(err.to_string());
+        }
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
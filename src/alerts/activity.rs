@@ -0,0 +1,86 @@
+use crate::model::HrTicker;
+
+/// Span of the baseline's exponential moving average, in minutes.
+const BASELINE_MINUTES: f64 = 30.0;
+/// Whole minutes seen before the ratios are given, so a single quiet minute is no baseline.
+const WARMUP_MINUTES: u32 = 10;
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Counts {
+    trades: f64,
+    volume: f64,
+}
+
+/// Trades and base volume per minute of one symbol, compared with their usual rate to spot
+/// unusual activity. Both are taken from the growth of the 24h totals between updates, timed by
+/// the exchange's event time.
+#[derive(Clone, Debug, Default)]
+pub struct Activity {
+    /// 24h trade count and base volume as of the previous update.
+    totals: Option<(u64, f64)>,
+    /// Minute since the epoch that `current` counts.
+    minute: u64,
+    current: Counts,
+    /// Moving average of the counts of whole minutes.
+    baseline: Counts,
+    /// Whole minutes in the baseline.
+    minutes: u32,
+    /// Whether `current` counts the minute of the first update, which is only seen in part.
+    partial: bool,
+}
+
+impl Activity {
+    pub fn record(&mut self, ticker: &HrTicker) {
+        let minute = ticker.E / 60_000;
+        let volume = ticker.v.parse().unwrap_or(0.0);
+        let Some((trades_before, volume_before)) = self.totals.replace((ticker.n, volume)) else {
+            self.minute = minute;
+            self.partial = true;
+            return;
+        };
+        if minute > self.minute {
+            if !std::mem::take(&mut self.partial) {
+                self.close(self.current);
+            }
+            // Minutes without updates had no activity. After a few spans of them the baseline
+            // is next to nothing either way.
+            let quiet = (minute - self.minute - 1).min(BASELINE_MINUTES as u64 * 4);
+            for _ in 0..quiet {
+                self.close(Counts::default());
+            }
+            self.minute = minute;
+            self.current = Counts::default();
+        }
+        // The 24h totals also lose what leaves their window, so they can shrink.
+        self.current.trades += ticker.n.saturating_sub(trades_before) as f64;
+        self.current.volume += (volume - volume_before).max(0.0);
+    }
+
+    /// Trades so far this minute as a multiple of the usual per minute, or not a number while
+    /// there is no baseline yet.
+    pub fn trades_ratio(&self) -> f64 {
+        self.ratio(self.current.trades, self.baseline.trades)
+    }
+
+    /// Base volume so far this minute as a multiple of the usual per minute.
+    pub fn volume_ratio(&self) -> f64 {
+        self.ratio(self.current.volume, self.baseline.volume)
+    }
+
+    fn ratio(&self, current: f64, baseline: f64) -> f64 {
+        if self.minutes < WARMUP_MINUTES || baseline <= 0.0 {
+            return f64::NAN;
+        }
+        current / baseline
+    }
+
+    fn close(&mut self, counts: Counts) {
+        if self.minutes == 0 {
+            self.baseline = counts;
+        } else {
+            self.baseline.trades += (counts.trades - self.baseline.trades) / BASELINE_MINUTES;
+            self.baseline.volume += (counts.volume - self.baseline.volume) / BASELINE_MINUTES;
+        }
+        self.minutes += 1;
+    }
+}
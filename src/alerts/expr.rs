@@ -0,0 +1,530 @@
+//! A small expression language for alert rules, e.g. `P < -7 && quote_volume > 50e6` or
+//! `c crosses 70000`.
+//!
+//! Numeric operands are ticker fields (see [`Field`]), number literals, `change(minutes)` and
+//! `abs(x)`, combined with `+ - * /`. Comparisons (`< <= > >= == !=`) and `crosses`,
+//! `crosses_above` and `crosses_below` produce booleans, which combine with `&&`, `||`, `!` and
+//! parentheses.
+
+use super::activity::Activity;
+use crate::model::{HrTicker, PriceHistory};
+use std::fmt;
+
+/// Longest window of `change(minutes)`: a day.
+pub const MAX_CHANGE_MINUTES: u64 = 24 * 60;
+
+/// A symbol as rules see it: its ticker and what the alert engine remembers of its prices.
+pub struct Subject<'a> {
+    pub ticker: &'a HrTicker,
+    /// Recent last prices, for `change(minutes)`.
+    pub history: &'a PriceHistory,
+    /// Highest last price seen since the app started, for `drawdown`.
+    pub session_high: f32,
+    /// Trades and volume per minute against their baseline, for `trades_ratio` and
+    /// `volume_ratio`.
+    pub activity: &'a Activity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Field {
+    Last,
+    PriceChange,
+    PercentChange,
+    WeightedAvg,
+    LastQuantity,
+    Open,
+    High,
+    Low,
+    BaseVolume,
+    QuoteVolume,
+    Trades,
+    FundingRate,
+    /// Percent the last price is below the session high.
+    Drawdown,
+    /// Trades this minute as a multiple of the usual per minute.
+    TradesRatio,
+    /// Base volume this minute as a multiple of the usual per minute.
+    VolumeRatio,
+}
+
+impl Field {
+    /// Accepts both the Binance field letters and readable names.
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "c" | "last" | "price" => Field::Last,
+            "p" | "price_change" => Field::PriceChange,
+            "P" | "percent_change" => Field::PercentChange,
+            "w" | "weighted_avg" => Field::WeightedAvg,
+            "Q" | "last_quantity" => Field::LastQuantity,
+            "o" | "open" => Field::Open,
+            "h" | "high" => Field::High,
+            "l" | "low" => Field::Low,
+            "v" | "volume" | "base_volume" => Field::BaseVolume,
+            "q" | "quote_volume" => Field::QuoteVolume,
+            "n" | "trades" => Field::Trades,
+            "funding_rate" => Field::FundingRate,
+            "drawdown" => Field::Drawdown,
+            "trades_ratio" => Field::TradesRatio,
+            "volume_ratio" => Field::VolumeRatio,
+            _ => return None,
+        })
+    }
+
+    fn value(self, subject: &Subject) -> f64 {
+        let t = subject.ticker;
+        match self {
+            Field::Last => t.c as f64,
+            Field::PriceChange => t.p as f64,
+            Field::PercentChange => t.P as f64,
+            Field::WeightedAvg => t.w as f64,
+            Field::LastQuantity => t.Q as f64,
+            Field::Open => t.o as f64,
+            Field::High => t.h as f64,
+            Field::Low => t.l as f64,
+            Field::BaseVolume => t.v.parse().unwrap_or(0.0),
+            Field::QuoteVolume => t.q.parse().unwrap_or(0.0),
+            Field::Trades => t.n as f64,
+            Field::FundingRate => t.funding_rate.map_or(0.0, |r| r as f64),
+            Field::Drawdown if subject.session_high > 0.0 => {
+                ((subject.session_high - t.c) / subject.session_high * 100.0) as f64
+            }
+            Field::Drawdown => f64::NAN,
+            Field::TradesRatio => subject.activity.trades_ratio(),
+            Field::VolumeRatio => subject.activity.volume_ratio(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compare {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+    Crosses,
+    CrossesAbove,
+    CrossesBelow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Arith {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Num {
+    Literal(f64),
+    Field(Field),
+    /// Percent change of the last price over the past `minutes`; not a number, and so false in
+    /// every comparison, until the history reaches back that far.
+    Change(u64),
+    Abs(Box<Num>),
+    Neg(Box<Num>),
+    Binary(Arith, Box<Num>, Box<Num>),
+}
+
+impl Num {
+    fn longest_change(&self) -> u64 {
+        match self {
+            Num::Literal(_) | Num::Field(_) => 0,
+            Num::Change(minutes) => *minutes,
+            Num::Abs(inner) | Num::Neg(inner) => inner.longest_change(),
+            Num::Binary(_, a, b) => a.longest_change().max(b.longest_change()),
+        }
+    }
+
+    fn eval(&self, subject: &Subject) -> f64 {
+        match self {
+            Num::Literal(value) => *value,
+            Num::Field(field) => field.value(subject),
+            Num::Change(minutes) => subject
+                .history
+                .change(minutes * 60_000, subject.ticker.c)
+                .map_or(f64::NAN, |change| change as f64),
+            Num::Abs(inner) => inner.eval(subject).abs(),
+            Num::Neg(inner) => -inner.eval(subject),
+            Num::Binary(op, a, b) => {
+                let (a, b) = (a.eval(subject), b.eval(subject));
+                match op {
+                    Arith::Add => a + b,
+                    Arith::Sub => a - b,
+                    Arith::Mul => a * b,
+                    Arith::Div => a / b,
+                }
+            }
+        }
+    }
+}
+
+/// A parsed rule condition.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Compare(Compare, Num, Num),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn parse(source: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.len(),
+        };
+        let expr = parser.or()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some((offset, token)) => Err(ParseError::new(
+                *offset,
+                format!("unexpected {}", token.describe()),
+            )),
+        }
+    }
+
+    /// Longest `change(minutes)` window in the expression, in minutes; 0 without one.
+    pub fn longest_change(&self) -> u64 {
+        match self {
+            Expr::Compare(_, a, b) => a.longest_change().max(b.longest_change()),
+            Expr::Not(inner) => inner.longest_change(),
+            Expr::And(a, b) | Expr::Or(a, b) => a.longest_change().max(b.longest_change()),
+        }
+    }
+
+    /// Evaluates against the current state of a symbol. `previous` is the symbol as of its
+    /// previous update, needed by the `crosses` operators, which are false without it.
+    pub fn eval(&self, current: &Subject, previous: Option<&Subject>) -> bool {
+        match self {
+            Expr::Compare(op, a, b) => {
+                let (x, y) = (a.eval(current), b.eval(current));
+                let crossed = |above: bool, below: bool| {
+                    previous.is_some_and(|prev| {
+                        let (px, py) = (a.eval(prev), b.eval(prev));
+                        (above && px < py && x >= y) || (below && px > py && x <= y)
+                    })
+                };
+                match op {
+                    Compare::Lt => x < y,
+                    Compare::Le => x <= y,
+                    Compare::Gt => x > y,
+                    Compare::Ge => x >= y,
+                    Compare::Eq => x == y,
+                    Compare::Ne => x != y,
+                    Compare::Crosses => crossed(true, true),
+                    Compare::CrossesAbove => crossed(true, false),
+                    Compare::CrossesBelow => crossed(false, true),
+                }
+            }
+            Expr::Not(inner) => !inner.eval(current, previous),
+            Expr::And(a, b) => a.eval(current, previous) && b.eval(current, previous),
+            Expr::Or(a, b) => a.eval(current, previous) || b.eval(current, previous),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// Byte offset into the source where the problem was found.
+    pub offset: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.offset + 1)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Number(n) => format!("number {}", n),
+            Token::Ident(name) => format!("`{}`", name),
+            Token::Op(op) => format!("`{}`", op),
+            Token::LParen => "`(`".to_string(),
+            Token::RParen => "`)`".to_string(),
+        }
+    }
+}
+
+/// Operators, longest first so `<=` is not read as `<`.
+const OPERATORS: [&str; 13] = [
+    "&&", "||", "<=", ">=", "==", "!=", "<", ">", "!", "+", "-", "*", "/",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(c) = rest.chars().next() {
+        let offset = source.len() - rest.len();
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let (token, len) = if c.is_ascii_digit() || c == '.' {
+            let len = number_len(rest);
+            let number = rest[..len].parse().map_err(|_| {
+                ParseError::new(offset, format!("invalid number `{}`", &rest[..len]))
+            })?;
+            (Token::Number(number), len)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            (Token::Ident(rest[..len].to_string()), len)
+        } else if c == '(' {
+            (Token::LParen, 1)
+        } else if c == ')' {
+            (Token::RParen, 1)
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            (Token::Op(op), op.len())
+        } else {
+            return Err(ParseError::new(offset, format!("unexpected `{}`", c)));
+        };
+        tokens.push((offset, token));
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+/// Length of the number literal at the start of `s`, including an exponent such as `50e6`.
+fn number_len(s: &str) -> usize {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() && (bytes[i].is_ascii_digit() || bytes[i] == b'.') {
+        i += 1;
+    }
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        let mut j = i + 1;
+        if j < bytes.len() && (bytes[j] == b'+' || bytes[j] == b'-') {
+            j += 1;
+        }
+        if j < bytes.len() && bytes[j].is_ascii_digit() {
+            while j < bytes.len() && bytes[j].is_ascii_digit() {
+                j += 1;
+            }
+            i = j;
+        }
+    }
+    i
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    /// Length of the source, where an expression cut short is reported.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Offset of the next token, or the end of the input.
+    fn offset(&self) -> usize {
+        self.peek().map_or(self.end, |(offset, _)| *offset)
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some((_, Token::Op(o))) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn open_call(&mut self, name: &str) -> Result<(), ParseError> {
+        match self.peek() {
+            Some((_, Token::LParen)) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(ParseError::new(
+                self.offset(),
+                format!("expected `(` after `{}`", name),
+            )),
+        }
+    }
+
+    /// Consumes the `)` closing a call or a parenthesized operand.
+    fn close_call(&mut self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some((_, Token::RParen)) => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => Err(ParseError::new(self.offset(), "expected `)`")),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.and()?;
+        while self.eat_op("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.not()?;
+        while self.eat_op("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, ParseError> {
+        if self.eat_op("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        // A parenthesis here may open either a condition or an arithmetic operand; try the
+        // condition first and fall back to a comparison.
+        if matches!(self.peek(), Some((_, Token::LParen))) {
+            let start = self.pos;
+            self.pos += 1;
+            // A condition cannot be an operand, so one left open is missing its `)`.
+            if let Ok(expr) = self.or() {
+                self.close_call()?;
+                return Ok(expr);
+            }
+            self.pos = start;
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParseError> {
+        let lhs = self.sum()?;
+        let offset = self.offset();
+        let op = match self.peek().map(|(_, token)| token) {
+            Some(Token::Op("<")) => Compare::Lt,
+            Some(Token::Op("<=")) => Compare::Le,
+            Some(Token::Op(">")) => Compare::Gt,
+            Some(Token::Op(">=")) => Compare::Ge,
+            Some(Token::Op("==")) => Compare::Eq,
+            Some(Token::Op("!=")) => Compare::Ne,
+            Some(Token::Ident(name)) if name == "crosses" => Compare::Crosses,
+            Some(Token::Ident(name)) if name == "crosses_above" => Compare::CrossesAbove,
+            Some(Token::Ident(name)) if name == "crosses_below" => Compare::CrossesBelow,
+            _ => {
+                return Err(ParseError::new(
+                    offset,
+                    "expected a comparison such as `<`, `>=` or `crosses`",
+                ))
+            }
+        };
+        self.pos += 1;
+        Ok(Expr::Compare(op, lhs, self.sum()?))
+    }
+
+    fn sum(&mut self) -> Result<Num, ParseError> {
+        let mut num = self.product()?;
+        loop {
+            let op = if self.eat_op("+") {
+                Arith::Add
+            } else if self.eat_op("-") {
+                Arith::Sub
+            } else {
+                return Ok(num);
+            };
+            num = Num::Binary(op, Box::new(num), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Num, ParseError> {
+        let mut num = self.unary()?;
+        loop {
+            let op = if self.eat_op("*") {
+                Arith::Mul
+            } else if self.eat_op("/") {
+                Arith::Div
+            } else {
+                return Ok(num);
+            };
+            num = Num::Binary(op, Box::new(num), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Num, ParseError> {
+        if self.eat_op("-") {
+            return Ok(Num::Neg(Box::new(self.unary()?)));
+        }
+        let offset = self.offset();
+        let Some((_, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(ParseError::new(offset, "unexpected end of expression"));
+        };
+        self.pos += 1;
+        match token {
+            Token::Number(value) => Ok(Num::Literal(value)),
+            Token::Ident(name) if name == "change" => {
+                self.open_call(&name)?;
+                let offset = self.offset();
+                let minutes = match self.tokens.get(self.pos) {
+                    Some((_, Token::Number(minutes)))
+                        if minutes.fract() == 0.0
+                            && (1.0..=MAX_CHANGE_MINUTES as f64).contains(minutes) =>
+                    {
+                        *minutes as u64
+                    }
+                    _ => {
+                        return Err(ParseError::new(
+                            offset,
+                            format!(
+                                "`change` takes whole minutes from 1 to {}",
+                                MAX_CHANGE_MINUTES
+                            ),
+                        ))
+                    }
+                };
+                self.pos += 1;
+                self.close_call()?;
+                Ok(Num::Change(minutes))
+            }
+            Token::Ident(name) if name == "abs" => {
+                self.open_call(&name)?;
+                let num = self.sum()?;
+                self.close_call()?;
+                Ok(Num::Abs(Box::new(num)))
+            }
+            Token::Ident(name) => Field::from_name(&name)
+                .map(Num::Field)
+                .ok_or_else(|| ParseError::new(offset, format!("unknown field `{}`", name))),
+            Token::LParen => {
+                let num = self.sum()?;
+                self.close_call()?;
+                Ok(num)
+            }
+            other => Err(ParseError::new(
+                offset,
+                format!("expected a field or number, found {}", other.describe()),
+            )),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use super::Alert;
+use std::{
+    collections::VecDeque,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// Alerts kept in memory for the alert history pane; the log file keeps all of them.
+const KEPT_ALERTS: usize = 1000;
+
+/// Every triggered alert, appended to a JSON lines file so alerts that fired while nobody was
+/// watching can be reviewed later.
+#[derive(Default)]
+pub struct AlertHistory {
+    /// The log file; without one, alerts are only kept in memory.
+    path: Option<PathBuf>,
+    alerts: VecDeque<Alert>,
+}
+
+impl AlertHistory {
+    /// `alerts.jsonl` in the platform data directory, next to the recorder's database.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("crypto_tui_ticker").join("alerts.jsonl"))
+    }
+
+    /// Opens the log at `path` with its latest alerts. A missing file is an empty log, and
+    /// lines that are not alerts are skipped.
+    pub fn open(path: PathBuf) -> Self {
+        let mut alerts: VecDeque<Alert> = fs::read_to_string(&path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        alerts.drain(..alerts.len().saturating_sub(KEPT_ALERTS));
+        Self {
+            path: Some(path),
+            alerts,
+        }
+    }
+
+    /// Keeps `alert` and appends it to the log file.
+    pub fn record(&mut self, alert: &Alert) -> io::Result<()> {
+        if self.alerts.len() == KEPT_ALERTS {
+            self.alerts.pop_front();
+        }
+        self.alerts.push_back(alert.clone());
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut line = serde_json::to_string(alert)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// The kept alerts, newest first, only those of `symbol` when given.
+    pub fn alerts<'a>(&'a self, symbol: Option<&'a str>) -> impl Iterator<Item = &'a Alert> {
+        self.alerts
+            .iter()
+            .rev()
+            .filter(move |alert| symbol.is_none_or(|symbol| alert.symbol == symbol))
+    }
+}
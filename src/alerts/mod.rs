@@ -0,0 +1,316 @@
+use crate::{
+    model::{HrTicker, PriceHistory},
+    script::Script,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+pub mod activity;
+pub mod expr;
+pub mod history;
+pub mod sink;
+
+use activity::Activity;
+use expr::{Expr, ParseError, Subject};
+use history::AlertHistory;
+use sink::Sink;
+
+/// Window of a rule's `moves` when `within_mins` is not set.
+const DEFAULT_MOVE_MINUTES: u64 = 15;
+
+/// An `[[alerts]]` entry in the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct AlertRule {
+    pub name: String,
+    /// Symbol the rule watches; every symbol when unset.
+    #[serde(default)]
+    pub symbol: Option<String>,
+    /// Condition in the rule language, e.g. `P < -7 && quote_volume > 50e6`.
+    #[serde(default)]
+    pub when: Option<String>,
+    /// Fires when the last price rises to or above this value.
+    #[serde(default)]
+    pub above: Option<f32>,
+    /// Fires when the last price falls to or below this value.
+    #[serde(default)]
+    pub below: Option<f32>,
+    /// Fires when the last price moves this many percent, up or down, within `within_mins`.
+    #[serde(default)]
+    pub moves: Option<f32>,
+    /// Window of `moves` in minutes, 15 by default.
+    #[serde(default)]
+    pub within_mins: Option<u64>,
+    /// Fires when the last price is this many percent below its session high.
+    #[serde(default)]
+    pub drops_from_high: Option<f32>,
+    /// Fires when this minute's base volume reaches this multiple of the usual per minute.
+    #[serde(default)]
+    pub volume_spike: Option<f32>,
+    /// Fires when this minute's trade count reaches this multiple of the usual per minute.
+    #[serde(default)]
+    pub trades_spike: Option<f32>,
+    /// Seconds after firing during which the rule stays quiet for that symbol, even if its
+    /// condition is false and true again in between.
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    /// Fires only once per symbol, until the rules are edited or the app restarts.
+    #[serde(default)]
+    pub once: bool,
+    /// Where triggered alerts are delivered.
+    #[serde(default)]
+    pub sinks: Vec<Sink>,
+}
+
+impl AlertRule {
+    fn watches(&self, symbol: &str) -> bool {
+        self.symbol.as_deref().is_none_or(|s| s == symbol)
+    }
+
+    /// The rule's condition as an expression; `above`, `below`, `moves`, `drops_from_high`,
+    /// `volume_spike` and `trades_spike` are shorthands that are combined with `when` by `||`.
+    pub fn expression(&self) -> String {
+        let mut parts: Vec<String> = self.when.iter().cloned().collect();
+        parts.extend(self.above.map(|above| format!("c >= {}", above)));
+        parts.extend(self.below.map(|below| format!("c <= {}", below)));
+        parts.extend(self.moves.map(|moves| {
+            format!(
+                "abs(change({})) >= {}",
+                self.within_mins.unwrap_or(DEFAULT_MOVE_MINUTES),
+                moves
+            )
+        }));
+        parts.extend(
+            self.drops_from_high
+                .map(|drop| format!("drawdown >= {}", drop)),
+        );
+        parts.extend(
+            self.volume_spike
+                .map(|spike| format!("volume_ratio >= {}", spike)),
+        );
+        parts.extend(
+            self.trades_spike
+                .map(|spike| format!("trades_ratio >= {}", spike)),
+        );
+        match parts.len() {
+            1 => parts.remove(0),
+            _ => parts
+                .iter()
+                .map(|part| format!("({})", part))
+                .collect::<Vec<_>>()
+                .join(" || "),
+        }
+    }
+
+    fn condition(&self) -> Result<Expr, ParseError> {
+        Expr::parse(&self.expression())
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Alert {
+    pub rule: String,
+    pub symbol: String,
+    pub price: f32,
+    /// Local time the alert fired, in milliseconds since the epoch.
+    pub time: u64,
+}
+
+impl Alert {
+    pub fn message(&self) -> String {
+        format!("{}: {} at {}", self.rule, self.symbol, self.price)
+    }
+}
+
+/// A symbol as the alert engine last saw it.
+#[derive(Default)]
+struct Tracked {
+    /// The ticker as of the previous update, for `crosses`.
+    ticker: Option<HrTicker>,
+    history: PriceHistory,
+    session_high: f32,
+    activity: Activity,
+}
+
+/// Evaluates rules against ticker updates. A rule fires when its condition becomes true for a
+/// symbol and re-arms once the condition is false again, so a held level does not fire repeatedly.
+/// A symbol oscillating around a level is kept quiet by a rule's cooldown, or by snoozing it.
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    /// Compiled condition per rule; rules that failed to parse never fire.
+    conditions: Vec<Result<Expr, ParseError>>,
+    /// What the rules need to remember of each symbol.
+    symbols: HashMap<String, Tracked>,
+    /// Longest `change(minutes)` window of the rules, in milliseconds; no price history is kept
+    /// when it is 0.
+    history_ms: u64,
+    /// (rule index, symbol) pairs whose condition held at the last update.
+    active: HashSet<(usize, String)>,
+    /// (rule index, symbol) pairs of one-shot rules that have fired.
+    spent: HashSet<(usize, String)>,
+    /// (rule name, symbol) pairs that do not fire before the given time, from cooldowns and
+    /// snoozes. Keyed by name so editing other rules keeps them.
+    quiet_until: HashMap<(String, String), Instant>,
+    /// Alerts fired so far, kept in memory only unless a log file is opened.
+    pub history: AlertHistory,
+    /// User script that raises alerts of its own and can drop those of the rules.
+    pub script: Option<Script>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let mut engine = Self {
+            rules: Vec::new(),
+            conditions: Vec::new(),
+            symbols: HashMap::new(),
+            history_ms: 0,
+            active: HashSet::new(),
+            spent: HashSet::new(),
+            quiet_until: HashMap::new(),
+            history: AlertHistory::default(),
+            script: None,
+        };
+        engine.set_rules(rules);
+        engine
+    }
+
+    /// Replaces the rules, re-arming all of them.
+    pub fn set_rules(&mut self, rules: Vec<AlertRule>) {
+        self.conditions = rules.iter().map(AlertRule::condition).collect();
+        self.history_ms = self
+            .conditions
+            .iter()
+            .flatten()
+            .map(|condition| condition.longest_change() * 60_000)
+            .max()
+            .unwrap_or(0);
+        self.rules = rules;
+        self.active.clear();
+        self.spent.clear();
+    }
+
+    /// Keeps `alert`'s rule quiet for its symbol for `duration`.
+    pub fn snooze(&mut self, alert: &Alert, duration: Duration) {
+        self.quiet_until.insert(
+            (alert.rule.clone(), alert.symbol.clone()),
+            Instant::now() + duration,
+        );
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// The parse error of rule `i`, if its condition is invalid.
+    pub fn error(&self, i: usize) -> Option<&ParseError> {
+        self.conditions.get(i).and_then(|c| c.as_ref().err())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty() && self.script.is_none()
+    }
+
+    /// Symbols for which rule `i`'s condition held at their last update, sorted.
+    pub fn triggered(&self, i: usize) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .active
+            .iter()
+            .filter(|(rule, _)| *rule == i)
+            .map(|(_, symbol)| symbol.clone())
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Returns the alerts triggered by `tickers`, each with the sinks of its rule.
+    pub fn evaluate(&mut self, tickers: &[HrTicker]) -> Vec<(Alert, Vec<Sink>)> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let instant = Instant::now();
+        self.quiet_until.retain(|_, until| *until > instant);
+        let mut triggered = Vec::new();
+        for ticker in tickers {
+            let tracked = self.symbols.entry(ticker.s.clone()).or_default();
+            if tracked.history.reach_ms() != self.history_ms {
+                tracked.history = PriceHistory::with_reach(self.history_ms);
+            }
+            if self.history_ms > 0 {
+                tracked.history.record(ticker.E, ticker.c);
+            }
+            let previous_activity = tracked.activity.clone();
+            tracked.activity.record(ticker);
+            let previous_high = tracked.session_high;
+            tracked.session_high = tracked.session_high.max(ticker.c);
+            let current = Subject {
+                ticker,
+                history: &tracked.history,
+                session_high: tracked.session_high,
+                activity: &tracked.activity,
+            };
+            let previous = tracked.ticker.as_ref().map(|previous| Subject {
+                ticker: previous,
+                history: &tracked.history,
+                session_high: previous_high,
+                activity: &previous_activity,
+            });
+            for (i, rule) in self.rules.iter().enumerate() {
+                let Ok(condition) = &self.conditions[i] else {
+                    continue;
+                };
+                if !rule.watches(&ticker.s) {
+                    continue;
+                }
+                let key = (i, ticker.s.clone());
+                if !condition.eval(&current, previous.as_ref()) {
+                    self.active.remove(&key);
+                    continue;
+                }
+                // A spent or quiet rule still becomes active, so once it may fire again it
+                // waits for the condition to become true anew.
+                if !self.active.insert(key.clone()) || self.spent.contains(&key) {
+                    continue;
+                }
+                let quiet = (rule.name.clone(), ticker.s.clone());
+                if self.quiet_until.contains_key(&quiet) {
+                    continue;
+                }
+                let alert = Alert {
+                    rule: rule.name.clone(),
+                    symbol: ticker.s.clone(),
+                    price: ticker.c,
+                    time: now,
+                };
+                // A dropped alert does not spend the rule or start its cooldown.
+                if let Some(script) = &mut self.script {
+                    if !script.on_alert(&alert) {
+                        continue;
+                    }
+                }
+                if rule.once {
+                    self.spent.insert(key);
+                }
+                if rule.cooldown_secs > 0 {
+                    let until = instant + Duration::from_secs(rule.cooldown_secs);
+                    self.quiet_until.insert(quiet, until);
+                }
+                triggered.push((alert, rule.sinks.clone()));
+            }
+            tracked.ticker = Some(ticker.clone());
+            if let Some(script) = &mut self.script {
+                if let Some(name) = script.on_ticker_update(ticker) {
+                    let alert = Alert {
+                        rule: name,
+                        symbol: ticker.s.clone(),
+                        price: ticker.c,
+                        time: now,
+                    };
+                    triggered.push((alert, script.sinks.clone()));
+                }
+            }
+        }
+        triggered
+    }
+}
@@ -0,0 +1,52 @@
+use super::Alert;
+use crate::error;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const TELEGRAM_API: &str = "https://api.telegram.org";
+
+/// A destination for triggered alerts, written as `[[alerts.sinks]]` with a `type` key.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Sink {
+    /// POSTs the alert as JSON to an arbitrary URL.
+    Webhook {
+        url: String,
+    },
+    Discord {
+        url: String,
+    },
+    Slack {
+        url: String,
+    },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+    },
+}
+
+impl Sink {
+    pub async fn deliver(&self, client: &reqwest::Client, alert: &Alert) -> reqwest::Result<()> {
+        let request = match self {
+            Sink::Webhook { url } => client.post(url).json(alert),
+            Sink::Discord { url } => client
+                .post(url)
+                .json(&json!({ "content": alert.message() })),
+            Sink::Slack { url } => client.post(url).json(&json!({ "text": alert.message() })),
+            Sink::Telegram { bot_token, chat_id } => client
+                .post(format!("{}/bot{}/sendMessage", TELEGRAM_API, bot_token))
+                .json(&json!({ "chat_id": chat_id, "text": alert.message() })),
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Delivers `alert` to every sink, reporting failures without retrying.
+pub async fn deliver_all(client: reqwest::Client, alert: Alert, sinks: Vec<Sink>) {
+    for sink in &sinks {
+        if let Err(err) = sink.deliver(&client, &alert).await {
+            error::report(format!("alert delivery: {}", err));
+        }
+    }
+}
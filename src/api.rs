@@ -0,0 +1,99 @@
+//! Optional read-only HTTP API (`--api-addr`) serving the live state as JSON, so local tools
+//! can query the running app instead of opening their own exchange connections.
+
+use crate::{
+    alerts::AlertEngine,
+    model::{HrTicker, TickerMap},
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::net::TcpListener;
+
+#[derive(Clone)]
+struct ApiState {
+    tickers: Arc<Mutex<TickerMap>>,
+    alerts: Arc<Mutex<AlertEngine>>,
+}
+
+#[derive(Serialize)]
+struct ApiError {
+    error: String,
+}
+
+/// An alert rule as served by `/alerts`. Sinks are left out, since their URLs and tokens are
+/// credentials.
+#[derive(Serialize)]
+struct AlertStatus {
+    name: String,
+    symbol: Option<String>,
+    expression: String,
+    /// Why the expression failed to parse, for rules that never fire.
+    error: Option<String>,
+    /// Symbols for which the condition currently holds.
+    triggered: Vec<String>,
+}
+
+/// Serves `/tickers`, `/tickers/{symbol}` and `/alerts` on `addr` until the process exits.
+pub async fn serve(
+    addr: SocketAddr,
+    tickers: Arc<Mutex<TickerMap>>,
+    alerts: Arc<Mutex<AlertEngine>>,
+) -> std::io::Result<()> {
+    let app = Router::new()
+        .route("/tickers", get(tickers_handler))
+        .route("/tickers/:symbol", get(ticker_handler))
+        .route("/alerts", get(alerts_handler))
+        .with_state(ApiState { tickers, alerts });
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, app).await
+}
+
+/// Every ticker, sorted by symbol.
+async fn tickers_handler(State(state): State<ApiState>) -> Json<Vec<HrTicker>> {
+    let tickers = state.tickers.lock().unwrap();
+    let mut tickers: Vec<HrTicker> = tickers.values().cloned().collect();
+    tickers.sort_by(|a, b| a.s.cmp(&b.s));
+    Json(tickers)
+}
+
+async fn ticker_handler(
+    State(state): State<ApiState>,
+    Path(symbol): Path<String>,
+) -> Result<Json<HrTicker>, (StatusCode, Json<ApiError>)> {
+    let tickers = state.tickers.lock().unwrap();
+    tickers
+        .get(&symbol)
+        .or_else(|| tickers.get(&symbol.to_uppercase()))
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| {
+            let error = format!("no ticker for {}", symbol);
+            (StatusCode::NOT_FOUND, Json(ApiError { error }))
+        })
+}
+
+async fn alerts_handler(State(state): State<ApiState>) -> Json<Vec<AlertStatus>> {
+    let engine = state.alerts.lock().unwrap();
+    let alerts = engine
+        .rules()
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| AlertStatus {
+            name: rule.name.clone(),
+            symbol: rule.symbol.clone(),
+            expression: rule.expression(),
+            error: engine.error(i).map(|err| err.to_string()),
+            triggered: engine.triggered(i),
+        })
+        .collect();
+    Json(alerts)
+}
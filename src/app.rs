@@ -0,0 +1,2932 @@
+//! Application state and key handling for the TUI, and the loops that feed and draw it.
+
+use crate::{
+    accessibility,
+    account::Account,
+    alerts::{self, expr::Expr, AlertEngine, AlertRule},
+    ascii,
+    chart::{ChartSource, ChartStyle, ChartView, Overlay},
+    clipboard::{self, Clipboard},
+    coingecko::{self, GlobalCache, MarketData, MarketDataCache},
+    command::{Command, CommandPalette},
+    comparison::Comparison,
+    config::Config,
+    control::{ControlMessage, ControlReply, ControlRequest},
+    denomination::{Converter, Denomination, FxRates},
+    error::{self, Error},
+    exchange::Exchange,
+    export,
+    graphics::{self, Graphics, Image},
+    indicators::{IndicatorCache, Indicators},
+    keymap::{Action, KeyBinding, Keymap, Lookup},
+    liquidations::Liquidations,
+    listings::Listings,
+    maintenance::Maintenance,
+    metrics,
+    model::{self, update_tickers, ChangeBasis, HrTicker, SortColumn, SortOrder, TickerMap},
+    movers::Basis,
+    net,
+    news::News,
+    notes::SymbolNote,
+    notify::{self, Level, Notification},
+    paper::{PaperAccount, Side},
+    plugins::Plugins,
+    portfolio::Portfolio,
+    script::Script,
+    session::Session,
+    status::ConnectionStatus,
+    stream::{Selection, TickerStream},
+    switcher::ExchangeSwitcher,
+    term,
+    theme::{self, ColorMode, HexColor, Palette},
+    ui::ui,
+    watchlist::{Import, TabState, WatchlistConfig},
+    workspace::{PaneKind, Slot},
+};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::{backend::Backend, style::Color, widgets::TableState, Terminal};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet, VecDeque},
+    hash::{Hash, Hasher},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{mpsc, watch};
+
+pub(crate) struct TableColors {
+    pub(crate) buffer_bg: Color,
+    pub(crate) header_bg: Color,
+    pub(crate) header_fg: Color,
+    pub(crate) row_fg: Color,
+    pub(crate) selected_style_fg: Color,
+    pub(crate) normal_row_color: Color,
+    pub(crate) alt_row_color: Color,
+    pub(crate) pinned_fg: Color,
+    pub(crate) focused_border_color: Color,
+    pub(crate) footer_border_color: Color,
+    pub(crate) up: Color,
+    pub(crate) down: Color,
+    pub(crate) flash_up: Color,
+    pub(crate) flash_down: Color,
+}
+
+impl TableColors {
+    fn new(palette: &Palette) -> Self {
+        Self {
+            buffer_bg: palette.background.into(),
+            header_bg: palette.header_bg.into(),
+            header_fg: palette.header_fg.into(),
+            row_fg: palette.row_fg.into(),
+            selected_style_fg: palette.selection.into(),
+            normal_row_color: palette.background.into(),
+            alt_row_color: palette.alt_row.into(),
+            pinned_fg: palette.pinned.into(),
+            focused_border_color: palette.border.into(),
+            footer_border_color: palette.border.into(),
+            up: palette.up.into(),
+            down: palette.down.into(),
+            flash_up: palette.flash_up.into(),
+            flash_down: palette.flash_down.into(),
+        }
+    }
+}
+
+pub(crate) const ITEM_HEIGHT: usize = 1;
+pub(crate) const COLUMN_WIDTH: u16 = 10;
+/// Frame rate cap of `--fps`.
+pub const MAX_FPS: u32 = 120;
+/// Longest time the screen goes without a redraw, for the clock and the time-based markers.
+const HEARTBEAT: Duration = Duration::from_secs(1);
+/// How long the first key of a chord waits for the second.
+const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
+/// Notifications kept for the history pane, oldest dropped first.
+const NOTIFICATION_HISTORY: usize = 200;
+/// How long rows that changed while paused stay highlighted after resuming.
+const RESUME_HIGHLIGHT: Duration = Duration::from_secs(3);
+/// How long `z` keeps an alert quiet.
+const ALERT_SNOOZE: Duration = Duration::from_secs(60 * 60);
+
+/// In-progress edit of an alert rule in the alerts pane, written as `[SYMBOL:] expression`.
+pub(crate) struct RuleEditor {
+    /// Rule being edited, or `None` for a new rule.
+    pub(crate) index: Option<usize>,
+    pub(crate) input: String,
+    /// Parse error of the last submitted input, with its byte offset into `input`.
+    pub(crate) error: Option<(usize, String)>,
+}
+
+/// Quantity entry for a paper-trading market order.
+pub(crate) struct OrderTicket {
+    pub(crate) side: Side,
+    pub(crate) symbol: String,
+    pub(crate) input: String,
+    pub(crate) error: Option<String>,
+}
+
+/// Note and tags of a symbol being edited, written as `tag, tag: note`.
+pub(crate) struct NoteEditor {
+    pub(crate) symbol: String,
+    pub(crate) input: String,
+}
+
+/// Applies a text-editing key to `text`, returning whether it was one.
+fn edit_text(text: &mut String, key: &KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Backspace => {
+            text.pop();
+            true
+        }
+        KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+            text.push(c);
+            true
+        }
+        _ => false,
+    }
+}
+
+/// A focusable region of the screen with its own key handling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Pane {
+    Table,
+    Detail,
+    Chart,
+    Comparison,
+    Liquidations,
+    Alerts,
+    Notifications,
+    AlertHistory,
+    News,
+    Plugins,
+    Status,
+    Portfolio,
+    Paper,
+    Account,
+    Grid,
+    Heatmap,
+    Gainers,
+    Losers,
+}
+
+/// What the main area of the screen shows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum View {
+    #[default]
+    Table,
+    Comparison,
+    Portfolio,
+    Paper,
+    Account,
+    /// Mini-charts of the top symbols.
+    Grid,
+    Heatmap,
+    /// Top gainers and losers side by side.
+    Movers,
+}
+
+/// Everything a sorted order depends on besides the rows themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SortKey {
+    column: SortColumn,
+    order: SortOrder,
+    secondary: SortColumn,
+    pinned: Vec<String>,
+    generation: u64,
+    /// The frozen snapshot and the live store hold different data at the same generation.
+    paused: bool,
+}
+
+/// Orders `sort_tickers` produced since the sort key last changed, so unchanged lists are not
+/// sorted again every frame.
+#[derive(Debug, Default)]
+struct SortCache {
+    key: Option<SortKey>,
+    /// Permutation that sorts each list, by a hash of the list's symbols in input order.
+    orders: HashMap<u64, Vec<usize>>,
+}
+
+/// Lists cached per sort key; grouped tables sort one list per expanded group.
+const SORT_CACHE_LISTS: usize = 64;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    #[default]
+    Running,
+    Quit,
+}
+
+pub struct App {
+    mode: Mode,
+    pub(crate) view: View,
+    /// Index of the shown workspace; 0 is the table screen, others come from the config.
+    pub(crate) workspace: usize,
+    pub(crate) focus: Pane,
+    pub(crate) exchange: Exchange,
+    pub(crate) comparison: Arc<Mutex<Comparison>>,
+    pub(crate) market_data: MarketDataCache,
+    pub(crate) global: GlobalCache,
+    pub(crate) indicators: IndicatorCache,
+    pub(crate) liquidations: Arc<Mutex<Liquidations>>,
+    pub(crate) status: Arc<Mutex<ConnectionStatus>>,
+    pub(crate) alerts: Arc<Mutex<AlertEngine>>,
+    pub(crate) account: Option<Arc<Mutex<Account>>>,
+    pub(crate) state: TableState,
+    pub(crate) colors: TableColors,
+    palettes: Vec<Palette>,
+    color_index: usize,
+    color_mode: ColorMode,
+    pub(crate) ticker_length: usize,
+    /// Rows that fit in the table body, as of the last render.
+    pub(crate) page_size: usize,
+    /// Number of columns after Symbol scrolled out of view on the left.
+    pub(crate) column_offset: usize,
+    /// Candlestick popup for one symbol, when open.
+    pub(crate) chart: Option<ChartView>,
+    /// Chart of the selected symbol for a workspace with a chart pane.
+    pub(crate) chart_pane: Option<ChartView>,
+    /// Candles of two symbols drawn against each other, when the compare popup is open.
+    pub(crate) compare_charts: Option<[ChartView; 2]>,
+    /// Symbol marked as the first of a comparison, waiting for the second.
+    compare_mark: Option<String>,
+    /// Overlays drawn on charts, toggled from the chart popup or a focused chart pane.
+    pub(crate) chart_overlays: Vec<Overlay>,
+    /// Whether charts show the volume traded at each price level beside the candles.
+    pub(crate) volume_profile: bool,
+    /// Whether charts draw candles, a line or an area, cycled like the overlays.
+    pub(crate) chart_style: ChartStyle,
+    /// Whether charts space prices logarithmically.
+    pub(crate) log_scale: bool,
+    ascii: bool,
+    /// Set by changes that have to be drawn, and cleared once they are.
+    dirty: bool,
+    /// When the last frame was drawn, or skipped as unchanged.
+    drawn_at: Instant,
+    /// Store generation the last frame showed, and when a newer one was first seen.
+    drawn_generation: u64,
+    data_changed_at: Option<Instant>,
+    /// Image protocol the chart popup is drawn with, if any.
+    pub(crate) graphics: Option<Graphics>,
+    /// Image the chart popup drew in the last frame, and the one on the terminal.
+    pub(crate) chart_image: RefCell<Option<Image>>,
+    shown_image: Option<Image>,
+    /// Charts shown by the grid, in table order.
+    pub(crate) grid: Vec<ChartView>,
+    grid_loaded_at: Instant,
+    selection: watch::Sender<Selection>,
+    /// Exchange the live stream connects to; `None` when the updates come from a recording.
+    switch_exchange: Option<watch::Sender<Exchange>>,
+    pub(crate) exchange_switcher: Option<ExchangeSwitcher>,
+    /// The selection chosen on the command line, streamed outside watchlist-only mode.
+    streamed: Selection,
+    /// Shows and streams only the pinned symbols.
+    pub(crate) watchlist_only: bool,
+    /// Symbols in the heatmap, largest block first, as last drawn.
+    pub(crate) heatmap_symbols: Vec<String>,
+    pub(crate) heatmap_selected: usize,
+    pub(crate) movers_basis: Basis,
+    /// Symbols in the gainers and losers lists, as last drawn.
+    pub(crate) gainers: Vec<String>,
+    pub(crate) losers: Vec<String>,
+    pub(crate) gainers_state: TableState,
+    pub(crate) losers_state: TableState,
+    pub(crate) show_detail: bool,
+    pub(crate) detail_scroll: u16,
+    pub(crate) show_liquidations: bool,
+    pub(crate) liquidations_scroll: usize,
+    pub(crate) show_alerts: bool,
+    pub(crate) alerts_state: TableState,
+    pub(crate) rule_editor: Option<RuleEditor>,
+    pub(crate) command_palette: Option<CommandPalette>,
+    pub(crate) note_editor: Option<NoteEditor>,
+    clipboard: Clipboard,
+    /// New-listing detection; off while replaying a recording.
+    listings: Option<Listings>,
+    /// Shows only the symbols with a tag starting with this, chosen in the command palette.
+    pub(crate) tag_filter: Option<String>,
+    /// Index of the shown watchlist tab; 0 lists every symbol, others come from the config.
+    pub(crate) watchlist: usize,
+    /// Sort column, filter and selection of each watchlist tab, the shown one as it was left.
+    tabs: Vec<TabState>,
+    /// Symbol to select once the table is next drawn, after switching tabs.
+    pending_selection: Option<String>,
+    /// Row of the viewport the pending selection goes to, when restoring a session; the
+    /// selection then waits for its symbol to be listed.
+    pending_row: Option<usize>,
+    /// File the session is saved to on quit.
+    session_path: Option<PathBuf>,
+    /// Symbols listed on the exchange during this session.
+    listed: Vec<String>,
+    /// Everything posted with `notify`, oldest first; recent ones are also shown as toasts.
+    pub(crate) notifications: VecDeque<Notification>,
+    pub(crate) show_notifications: bool,
+    pub(crate) notifications_scroll: usize,
+    pub(crate) show_alert_history: bool,
+    pub(crate) alert_history_scroll: usize,
+    /// Symbol the alert history pane is filtered to.
+    pub(crate) alert_history_symbol: Option<String>,
+    pub(crate) show_news: bool,
+    /// Headlines, fetched while the news pane is shown.
+    pub(crate) news: News,
+    pub(crate) news_state: TableState,
+    pub(crate) show_plugins: bool,
+    /// WASM plugins, refreshed in the background for their tickers and panes.
+    pub(crate) plugins: Plugins,
+    pub(crate) show_status: bool,
+    /// Commands from the control socket.
+    control: Option<mpsc::UnboundedReceiver<ControlMessage>>,
+    /// Maintenance and delistings reported by the exchange.
+    pub(crate) maintenance: Maintenance,
+    pub(crate) paused: bool,
+    /// Snapshot rendered while paused; the live store keeps updating underneath.
+    frozen: Option<Arc<Mutex<TickerMap>>>,
+    /// Symbols whose price changed while paused, highlighted for a moment after resuming.
+    resume_diff: HashSet<String>,
+    resumed_at: Option<Instant>,
+    pub(crate) comparison_state: TableState,
+    pub(crate) portfolio: Portfolio,
+    pub(crate) portfolio_state: TableState,
+    pub(crate) paper: PaperAccount,
+    pub(crate) paper_state: TableState,
+    pub(crate) order_ticket: Option<OrderTicket>,
+    /// Outcome of the last paper order, shown in the paper view's title.
+    pub(crate) paper_status: Option<String>,
+    /// The live ticker store, for paper fills at the current price even while paused.
+    pub(crate) live: Arc<Mutex<TickerMap>>,
+    pub(crate) sort_column: SortColumn,
+    sort_cache: SortCache,
+    /// First key of a chord waiting for its second key, and when it was pressed.
+    pub(crate) pending_key: Option<(KeyBinding, Instant)>,
+    pub(crate) config: Config,
+    pub(crate) keymap: Keymap,
+    /// Footer text generated from the active key bindings.
+    pub(crate) help_text: String,
+    /// Symbols in the order they were last rendered, so row indices can be mapped back to tickers.
+    /// A group row maps to its highest-volume market.
+    pub(crate) visible_symbols: Vec<String>,
+    /// The group key of each group row as last rendered, `None` for market rows.
+    pub(crate) visible_groups: Vec<Option<String>>,
+    /// Unit that prices in the table are shown in.
+    pub(crate) denomination: Denomination,
+    /// What the Percent Change column measures against.
+    pub(crate) change_basis: ChangeBasis,
+    fx_rates: FxRates,
+    /// Collapses the markets of each base asset into one row.
+    pub(crate) group_by_base: bool,
+    /// Groups showing their markets under the group row.
+    pub(crate) expanded_groups: HashSet<String>,
+}
+
+/// Handles to the state shared with background tasks.
+pub struct Stores {
+    pub tickers: Arc<Mutex<TickerMap>>,
+    pub comparison: Arc<Mutex<Comparison>>,
+    pub market_data: MarketDataCache,
+    /// Global market figures from CoinGecko, for the summary bar.
+    pub global: GlobalCache,
+    /// FX rates for a fiat denomination, from `denomination::refresh_fx`.
+    pub fx_rates: FxRates,
+    pub indicators: IndicatorCache,
+    pub liquidations: Arc<Mutex<Liquidations>>,
+    pub status: Arc<Mutex<ConnectionStatus>>,
+    pub alerts: Arc<Mutex<AlertEngine>>,
+    /// Present when Binance API credentials are configured.
+    pub account: Option<Arc<Mutex<Account>>>,
+    /// Symbols the live stream carries; the value it starts with is the one from the command
+    /// line.
+    pub selection: watch::Sender<Selection>,
+    /// Exchange the live stream connects to, changed by the exchange switcher; `None` when
+    /// the updates are replayed from a file and cannot be switched.
+    pub exchange: Option<watch::Sender<Exchange>>,
+}
+
+impl App {
+    pub fn new(config: Config, exchange: Exchange, stores: Stores) -> Self {
+        let (keymap, errors) = Keymap::new(&config.keys);
+        for err in errors {
+            error::report(Error::Config(format!("key binding: {}", err)));
+        }
+        let help_text = keymap.help_text();
+        let streamed = stores.selection.borrow().clone();
+        let palettes = config.theme.all_palettes();
+        let color_index = palettes
+            .iter()
+            .position(|palette| palette.name == config.theme.name)
+            .unwrap_or_else(|| {
+                error::report(Error::Config(format!(
+                    "unknown theme {:?}",
+                    config.theme.name
+                )));
+                0
+            });
+        let app = Self {
+            mode: Mode::Running,
+            view: View::Table,
+            workspace: 0,
+            focus: Pane::Table,
+            exchange,
+            comparison: stores.comparison,
+            market_data: stores.market_data,
+            global: stores.global,
+            indicators: stores.indicators,
+            liquidations: stores.liquidations,
+            status: stores.status,
+            alerts: stores.alerts,
+            account: stores.account,
+            streamed,
+            selection: stores.selection,
+            switch_exchange: stores.exchange,
+            exchange_switcher: None,
+            watchlist_only: false,
+            state: TableState::default(),
+            colors: TableColors::new(&palettes[color_index]),
+            palettes,
+            color_index,
+            color_mode: config.theme.color_mode.unwrap_or_else(ColorMode::detect),
+            ticker_length: 25,
+            page_size: 1,
+            column_offset: 0,
+            chart: None,
+            chart_pane: None,
+            compare_charts: None,
+            compare_mark: None,
+            chart_overlays: config.chart.overlays.clone(),
+            volume_profile: config.chart.volume_profile,
+            chart_style: config.chart.style,
+            log_scale: config.chart.log_scale,
+            ascii: false,
+            dirty: true,
+            drawn_at: Instant::now(),
+            drawn_generation: model::generation(),
+            data_changed_at: None,
+            graphics: None,
+            chart_image: RefCell::new(None),
+            shown_image: None,
+            grid: Vec::new(),
+            grid_loaded_at: Instant::now(),
+            heatmap_symbols: Vec::new(),
+            heatmap_selected: 0,
+            movers_basis: config.movers.basis,
+            change_basis: config.change_basis,
+            gainers: Vec::new(),
+            losers: Vec::new(),
+            gainers_state: TableState::default(),
+            losers_state: TableState::default(),
+            show_detail: false,
+            detail_scroll: 0,
+            show_liquidations: false,
+            liquidations_scroll: 0,
+            show_alerts: false,
+            alerts_state: TableState::default(),
+            rule_editor: None,
+            command_palette: None,
+            note_editor: None,
+            clipboard: Clipboard::default(),
+            listings: None,
+            tag_filter: None,
+            watchlist: 0,
+            tabs: std::iter::once(TabState::new(SortColumn::Symbol))
+                .chain(
+                    config
+                        .watchlists
+                        .iter()
+                        .map(|w| TabState::new(w.sort.unwrap_or(SortColumn::Symbol))),
+                )
+                .collect(),
+            pending_selection: None,
+            pending_row: None,
+            session_path: None,
+            listed: Vec::new(),
+            notifications: VecDeque::new(),
+            show_notifications: false,
+            notifications_scroll: 0,
+            show_alert_history: false,
+            alert_history_scroll: 0,
+            alert_history_symbol: None,
+            show_news: false,
+            news: News::default(),
+            news_state: TableState::default(),
+            show_plugins: false,
+            plugins: Plugins::default(),
+            show_status: false,
+            control: None,
+            maintenance: Maintenance::default(),
+            paused: false,
+            frozen: None,
+            resume_diff: HashSet::new(),
+            resumed_at: None,
+            comparison_state: TableState::default(),
+            portfolio: Portfolio::load(config.home_dir().as_deref()),
+            portfolio_state: TableState::default(),
+            paper: PaperAccount::load(config.home_dir().as_deref()),
+            paper_state: TableState::default(),
+            order_ticket: None,
+            paper_status: None,
+            live: stores.tickers,
+            sort_column: SortColumn::Symbol,
+            sort_cache: SortCache::default(),
+            pending_key: None,
+            config,
+            keymap,
+            help_text,
+            visible_symbols: Vec::new(),
+            visible_groups: Vec::new(),
+            group_by_base: false,
+            denomination: Denomination::Quote,
+            fx_rates: stores.fx_rates,
+            expanded_groups: HashSet::new(),
+        };
+        app.track_indicators();
+        app
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.mode != Mode::Quit
+    }
+
+    /// Selects row `i`, clamped to the table; the viewport follows it on the next render.
+    fn select_row(&mut self, i: usize) {
+        let i = i.min(self.ticker_length.saturating_sub(1));
+        self.state.select(Some(i));
+    }
+
+    /// Keeps the selection and the viewport within the table after its row count changed, so
+    /// a filter or regrouping that shrinks it never leaves the selected row off screen, and
+    /// scrolls the viewport just far enough to show the selection.
+    pub(crate) fn clamp_viewport(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state
+                .select((self.ticker_length > 0).then(|| i.min(self.ticker_length - 1)));
+        }
+        let max_offset = self.ticker_length.saturating_sub(self.page_size);
+        let mut offset = self.state.offset().min(max_offset);
+        if let Some(i) = self.state.selected() {
+            offset = offset.clamp((i + 1).saturating_sub(self.page_size), i);
+        }
+        *self.state.offset_mut() = offset;
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.ticker_length => i + 1,
+            _ => 0,
+        };
+        self.select_row(i);
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.state.selected() {
+            Some(0) | None => self.ticker_length.saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.select_row(i);
+    }
+
+    pub fn page_down(&mut self) {
+        let i = self.state.selected().map_or(0, |i| i + self.page_size);
+        self.select_row(i);
+    }
+
+    pub fn page_up(&mut self) {
+        let i = self
+            .state
+            .selected()
+            .map_or(0, |i| i.saturating_sub(self.page_size));
+        self.select_row(i);
+    }
+
+    pub fn next_color(&mut self) {
+        self.color_index = (self.color_index + 1) % self.palettes.len();
+        self.show_theme();
+    }
+
+    pub fn previous_color(&mut self) {
+        let count = self.palettes.len();
+        self.color_index = (self.color_index + count - 1) % count;
+        self.show_theme();
+    }
+
+    fn show_theme(&mut self) {
+        let notice = format!("Theme: {}", self.palettes[self.color_index].name);
+        self.notify(Level::Info, notice);
+    }
+
+    /// Draws every frame with ASCII characters only.
+    pub fn set_ascii(&mut self, ascii: bool) {
+        self.ascii = ascii;
+    }
+
+    /// Draws the chart popup with `graphics`, or as unicode when `None`.
+    pub fn set_graphics(&mut self, graphics: Option<Graphics>) {
+        self.graphics = graphics;
+    }
+
+    pub fn set_listings(&mut self, listings: Listings) {
+        self.listings = Some(listings);
+    }
+
+    pub fn set_plugins(&mut self, plugins: Plugins) {
+        self.plugins = plugins;
+    }
+
+    pub fn set_control(&mut self, control: mpsc::UnboundedReceiver<ControlMessage>) {
+        self.control = Some(control);
+    }
+
+    /// Runs the commands received on the control socket since the last frame.
+    fn handle_control(&mut self) {
+        let mut messages = Vec::new();
+        if let Some(control) = &mut self.control {
+            while let Ok(message) = control.try_recv() {
+                messages.push(message);
+            }
+        }
+        for ControlMessage { request, reply } in messages {
+            let result = self.run_control(request);
+            let _ = reply.send(result);
+            self.dirty = true;
+        }
+    }
+
+    fn run_control(&mut self, request: ControlRequest) -> ControlReply {
+        match request {
+            ControlRequest::Select { symbol } => {
+                let symbol = symbol.to_uppercase();
+                if !self.visible_symbols.contains(&symbol) {
+                    return Err(format!("{} is not listed in the table", symbol));
+                }
+                self.run_command(Command::Symbol(symbol));
+            }
+            ControlRequest::AddAlert { name, symbol, when } => {
+                Expr::parse(&when).map_err(|err| format!("{} at {}", err.message, err.offset))?;
+                self.config.alerts.push(AlertRule {
+                    name,
+                    symbol: symbol.map(|symbol| symbol.to_uppercase()),
+                    when: Some(when),
+                    above: None,
+                    below: None,
+                    moves: None,
+                    within_mins: None,
+                    drops_from_high: None,
+                    volume_spike: None,
+                    trades_spike: None,
+                    cooldown_secs: 0,
+                    once: false,
+                    sinks: Vec::new(),
+                });
+                self.save_alerts();
+            }
+            ControlRequest::View { view } => {
+                self.workspace = 0;
+                self.view = view;
+                self.ensure_focus_visible();
+            }
+            ControlRequest::Workspace { number } => {
+                if !(1..=self.config.workspaces.len() + 1).contains(&number) {
+                    return Err(format!("there is no workspace {}", number));
+                }
+                self.select_workspace(number - 1);
+            }
+            ControlRequest::Export => {
+                return self
+                    .export_table()
+                    .map(|path| Some(path.display().to_string()))
+                    .ok_or_else(|| "export failed".to_string());
+            }
+            ControlRequest::Quit => self.mode = Mode::Quit,
+        }
+        Ok(None)
+    }
+
+    /// Announces the symbols listed since the last call, pinning them when configured.
+    pub fn check_listings(&mut self) {
+        let Some(listings) = &mut self.listings else {
+            return;
+        };
+        if !self.config.listings.enabled {
+            return;
+        }
+        let listed = listings.observe(self.exchange, &self.live.lock().unwrap(), Instant::now());
+        if listed.is_empty() {
+            return;
+        }
+        self.listed.extend(listed.iter().cloned());
+        for symbol in &listed {
+            self.notify(
+                Level::Warning,
+                format!("New listing on {}: {}", self.exchange.name(), symbol),
+            );
+        }
+        if self.config.listings.auto_pin {
+            for symbol in listed {
+                if !self.is_pinned(&symbol) {
+                    self.config.pinned.push(symbol);
+                }
+            }
+            self.track_indicators();
+            self.sync_selection();
+            if let Err(err) = self.config.save() {
+                error::report(format!("saving the config: {}", err));
+            }
+        }
+    }
+
+    /// Shows the image the chart popup drew over its plot once it changed, and removes the
+    /// shown one once the popup no longer draws one.
+    fn sync_chart_image(&mut self, terminal: &mut Terminal<impl Backend>) -> Result<(), Error> {
+        let Some(graphics) = self.graphics else {
+            return Ok(());
+        };
+        let image = self.chart_image.take();
+        if image == self.shown_image {
+            return Ok(());
+        }
+        if image.is_none() && graphics.protocol.in_cells() {
+            // Inline images are part of the cells, which only a full redraw overwrites.
+            terminal.clear().map_err(Error::Terminal)?;
+        }
+        graphics::show(graphics.protocol, image.as_ref())?;
+        self.shown_image = image;
+        Ok(())
+    }
+
+    /// Overrides the color support detected from the terminal or set in the config.
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    pub fn set_colors(&mut self) {
+        let palette = &self.palettes[self.color_index];
+        self.colors = TableColors::new(palette);
+        if self.config.accessibility.enabled {
+            let HexColor(r, g, b) = palette.background;
+            let dark = (r as u32 + g as u32 + b as u32) < 3 * 128;
+            let colors = &mut self.colors;
+            for color in [
+                &mut colors.header_fg,
+                &mut colors.row_fg,
+                &mut colors.selected_style_fg,
+                &mut colors.pinned_fg,
+                &mut colors.focused_border_color,
+                &mut colors.footer_border_color,
+                &mut colors.up,
+                &mut colors.down,
+            ] {
+                *color = accessibility::contrasted(*color, dark);
+            }
+        }
+    }
+
+    /// Columns shown in the table, in order.
+    pub(crate) fn columns(&self) -> Vec<SortColumn> {
+        let mut columns = vec![
+            SortColumn::Symbol,
+            SortColumn::Last,
+            SortColumn::PercentChange,
+            SortColumn::Open,
+            SortColumn::High,
+            SortColumn::Low,
+            SortColumn::Volume,
+            SortColumn::Change1m,
+            SortColumn::Change5m,
+            SortColumn::Change15m,
+        ];
+        if self.config.coingecko.enabled {
+            columns.extend([SortColumn::Rank, SortColumn::MarketCap]);
+        }
+        if self.config.indicators.enabled {
+            columns.extend([SortColumn::Rsi, SortColumn::EmaTrend, SortColumn::Vwap]);
+        }
+        if self.show_liquidations {
+            columns.push(SortColumn::Liquidations);
+        }
+        if self.config.updated_column {
+            columns.push(SortColumn::Updated);
+        }
+        if self
+            .alerts
+            .lock()
+            .unwrap()
+            .script
+            .as_ref()
+            .is_some_and(Script::has_column)
+        {
+            columns.push(SortColumn::Script);
+        }
+        columns
+    }
+
+    /// Columns that fit in `width`: Symbol stays in place and the rest scroll horizontally.
+    /// Clamps `column_offset` so the last column is never scrolled past.
+    pub(crate) fn visible_columns(&mut self, width: u16) -> Vec<SortColumn> {
+        let columns = self.columns();
+        // Each column after the first is preceded by one space of column spacing.
+        let fit = ((width + 1) / (COLUMN_WIDTH + 1)).max(1) as usize;
+        let scrollable = columns.len() - 1;
+        let shown = (fit - 1).min(scrollable);
+        self.column_offset = self.column_offset.min(scrollable - shown);
+        let mut visible = vec![columns[0]];
+        visible.extend(&columns[1 + self.column_offset..1 + self.column_offset + shown]);
+        visible
+    }
+
+    pub(crate) fn market_data<'a>(
+        &self,
+        cache: &'a HashMap<String, MarketData>,
+        symbol: &str,
+    ) -> Option<&'a MarketData> {
+        let instrument = self.exchange.instrument(symbol)?;
+        cache.get(coingecko::coin_symbol(&instrument.base))
+    }
+
+    pub(crate) fn cell_text(
+        &self,
+        column: SortColumn,
+        ticker: &HrTicker,
+        market: Option<&MarketData>,
+    ) -> String {
+        if column == SortColumn::Script {
+            return self
+                .script_cell(&ticker.s)
+                .unwrap_or_else(|| "-".to_string());
+        }
+        let liquidations = match column {
+            SortColumn::Liquidations => self.liquidation_count(&ticker.s),
+            _ => 0,
+        };
+        let indicators = self.indicators_for(&ticker.s);
+        let text = export::cell(
+            column,
+            ticker,
+            &self.config.format,
+            market,
+            liquidations,
+            indicators.as_ref(),
+        );
+        // Exports keep the plain text; only the screen is localized.
+        let format = &self.config.format;
+        match text.parse::<f64>() {
+            _ if column == SortColumn::Symbol => text,
+            Ok(value) => format.column(column, value, |_| text),
+            Err(_) => format.localize(&text),
+        }
+    }
+
+    fn script_cell(&self, symbol: &str) -> Option<String> {
+        let alerts = self.alerts.lock().unwrap();
+        alerts.script.as_ref()?.column(symbol).map(str::to_string)
+    }
+
+    pub(crate) fn indicators_for(&self, symbol: &str) -> Option<Indicators> {
+        self.indicators.lock().unwrap().values.get(symbol).copied()
+    }
+
+    /// Tells the indicator task which symbols to follow: the configured ones and the pinned.
+    fn track_indicators(&self) {
+        let mut tracked = self.config.indicators.symbols.clone();
+        for symbol in &self.config.pinned {
+            if !tracked.contains(symbol) {
+                tracked.push(symbol.clone());
+            }
+        }
+        self.indicators.lock().unwrap().tracked = tracked;
+    }
+
+    /// Writes the table as last rendered, in its current order, to a timestamped file, and
+    /// returns its path.
+    fn export_table(&mut self) -> Option<PathBuf> {
+        let store = Arc::clone(self.frozen.as_ref().unwrap_or(&self.live));
+        let tickers = store.lock().unwrap();
+        let market = Arc::clone(&self.market_data);
+        let market = market.lock().unwrap();
+        let columns = self.columns();
+        let rows: Vec<Vec<String>> = self
+            .visible_symbols
+            .iter()
+            .filter_map(|symbol| tickers.get(symbol))
+            .map(|ticker| {
+                let market = self.market_data(&market, &ticker.s);
+                columns
+                    .iter()
+                    .map(|column| self.cell_text(*column, ticker, market))
+                    .collect()
+            })
+            .collect();
+        let result = export::to_file(&self.config.export, &columns, &rows);
+        let (level, notice) = match &result {
+            Ok(path) => (
+                Level::Info,
+                format!("Exported {} rows to {}", rows.len(), path.display()),
+            ),
+            Err(err) => (Level::Error, format!("Export failed: {}", err)),
+        };
+        drop(tickers);
+        self.notify(level, notice);
+        result.ok()
+    }
+
+    /// The selected row's cells as `Y` copies them, tab-separated.
+    pub fn selected_row(&self) -> Option<String> {
+        let symbol = self.selected_symbol()?;
+        let store = Arc::clone(self.frozen.as_ref().unwrap_or(&self.live));
+        let tickers = store.lock().unwrap();
+        let ticker = tickers.get(symbol)?;
+        let market = self.market_data.lock().unwrap();
+        let market = self.market_data(&market, symbol);
+        let cells: Vec<String> = self
+            .columns()
+            .iter()
+            .map(|column| self.cell_text(*column, ticker, market))
+            .collect();
+        Some(clipboard::tsv_row(&cells))
+    }
+
+    fn copy(&mut self, text: &str, what: &str) {
+        let (level, notice) = match self.clipboard.copy(text) {
+            Ok(()) => (Level::Info, format!("Copied {}", what)),
+            Err(err) => (Level::Error, format!("Copy failed: {}", err)),
+        };
+        self.notify(level, notice);
+    }
+
+    fn liquidation_count(&self, symbol: &str) -> u32 {
+        self.liquidations
+            .lock()
+            .unwrap()
+            .counts
+            .get(symbol)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn compare(
+        &self,
+        column: SortColumn,
+        market: &HashMap<String, MarketData>,
+        a: &HrTicker,
+        b: &HrTicker,
+    ) -> Ordering {
+        match column {
+            SortColumn::Symbol => a.s.cmp(&b.s),
+            SortColumn::Last => a.c.total_cmp(&b.c),
+            // Symbols without a change on the basis yet sort as unchanged.
+            SortColumn::PercentChange => {
+                let change = |t| self.change_basis.change(t).unwrap_or(0.0);
+                change(a).total_cmp(&change(b))
+            }
+            SortColumn::Open => a.o.total_cmp(&b.o),
+            SortColumn::High => a.h.total_cmp(&b.h),
+            SortColumn::Low => a.l.total_cmp(&b.l),
+            SortColumn::Volume => {
+                let volume = |t: &HrTicker| t.v.parse::<f64>().unwrap_or(0.0);
+                volume(a).total_cmp(&volume(b))
+            }
+            // Unranked coins sort after ranked ones.
+            SortColumn::Rank => {
+                let rank = |t: &HrTicker| {
+                    self.market_data(market, &t.s)
+                        .and_then(|m| m.rank)
+                        .unwrap_or(u32::MAX)
+                };
+                rank(a).cmp(&rank(b))
+            }
+            SortColumn::MarketCap => {
+                let cap = |t: &HrTicker| {
+                    self.market_data(market, &t.s)
+                        .and_then(|m| m.market_cap)
+                        .unwrap_or(0.0)
+                };
+                cap(a).total_cmp(&cap(b))
+            }
+            SortColumn::Liquidations => self
+                .liquidation_count(&a.s)
+                .cmp(&self.liquidation_count(&b.s)),
+            // Symbols without indicators sort below those with them.
+            SortColumn::Rsi => {
+                let rsi = |t: &HrTicker| self.indicators_for(&t.s).and_then(|i| i.rsi);
+                rsi(a).unwrap_or(-1.0).total_cmp(&rsi(b).unwrap_or(-1.0))
+            }
+            SortColumn::EmaTrend => {
+                let trend = |t: &HrTicker| self.indicators_for(&t.s).and_then(|i| i.trend);
+                trend(a).cmp(&trend(b))
+            }
+            SortColumn::Vwap => {
+                let vwap = |t: &HrTicker| self.indicators_for(&t.s).and_then(|i| i.vwap);
+                vwap(a).unwrap_or(-1.0).total_cmp(&vwap(b).unwrap_or(-1.0))
+            }
+            // Ascending, symbols without a value come first, then text, then numbers by value.
+            SortColumn::Script => {
+                let value = |t: &HrTicker| {
+                    self.script_cell(&t.s)
+                        .map(|text| text.parse::<f64>().map_err(|_| text))
+                };
+                match (value(a), value(b)) {
+                    (Some(Ok(a)), Some(Ok(b))) => a.total_cmp(&b),
+                    (Some(Err(a)), Some(Err(b))) => a.cmp(&b),
+                    (a, b) => {
+                        let rank = |v: &Option<Result<f64, String>>| match v {
+                            Some(Ok(_)) => 2,
+                            Some(Err(_)) => 1,
+                            None => 0,
+                        };
+                        rank(&a).cmp(&rank(&b))
+                    }
+                }
+            }
+            // Rows that never ticked are the oldest.
+            SortColumn::Updated => {
+                let age = |t: &HrTicker| t.last_updated.map_or(Duration::MAX, |at| at.elapsed());
+                age(a).cmp(&age(b))
+            }
+            // Symbols without enough history yet count as unchanged.
+            SortColumn::Change1m | SortColumn::Change5m | SortColumn::Change15m => {
+                let window = column.window_ms().unwrap_or(0);
+                let change = |t: &HrTicker| t.rolling_change(window).unwrap_or(0.0);
+                change(a).total_cmp(&change(b))
+            }
+        }
+    }
+
+    /// Sorts `tickers` for the table. The order is cached and reused while the store, the sort
+    /// settings and the list itself are unchanged.
+    pub fn sort_tickers(&mut self, tickers: &mut [&HrTicker]) {
+        let key = self.sort_key();
+        let list = {
+            let mut hasher = DefaultHasher::new();
+            for ticker in tickers.iter() {
+                ticker.s.hash(&mut hasher);
+            }
+            hasher.finish()
+        };
+        if key.is_some() && self.sort_cache.key == key {
+            if let Some(order) = self.sort_cache.orders.get(&list) {
+                if order.len() == tickers.len() {
+                    let sorted: Vec<&HrTicker> = order.iter().map(|&i| tickers[i]).collect();
+                    tickers.copy_from_slice(&sorted);
+                    return;
+                }
+            }
+        }
+
+        let market = Arc::clone(&self.market_data);
+        let market = market.lock().unwrap();
+        let mut order: Vec<usize> = (0..tickers.len()).collect();
+        // Ties fall back to the secondary column and then the symbol, always ascending, so rows
+        // with equal values keep their place between frames.
+        order.sort_by(|&a, &b| {
+            let (a, b) = (tickers[a], tickers[b]);
+            let primary = self.compare(self.sort_column, &market, a, b);
+            let primary = match self.sort_order(self.sort_column) {
+                SortOrder::Ascending => primary,
+                SortOrder::Descending => primary.reverse(),
+            };
+            primary
+                .then_with(|| self.compare(self.config.secondary_sort, &market, a, b))
+                .then_with(|| a.s.cmp(&b.s))
+        });
+        // Stable, so the pinned section and the remainder both keep the sort order above.
+        order.sort_by_key(|&i| !self.is_pinned(&tickers[i].s));
+        drop(market);
+
+        let sorted: Vec<&HrTicker> = order.iter().map(|&i| tickers[i]).collect();
+        tickers.copy_from_slice(&sorted);
+        if key.is_none() {
+            return;
+        }
+        if self.sort_cache.key != key || self.sort_cache.orders.len() >= SORT_CACHE_LISTS {
+            self.sort_cache = SortCache {
+                key,
+                orders: HashMap::new(),
+            };
+        }
+        self.sort_cache.orders.insert(list, order);
+    }
+
+    /// What the current order depends on, or `None` when it depends on more than the ticker
+    /// store, such as market data, indicators or the time since each update.
+    fn sort_key(&self) -> Option<SortKey> {
+        let from_tickers = |column: SortColumn| {
+            matches!(
+                column,
+                SortColumn::Symbol
+                    | SortColumn::Last
+                    | SortColumn::Open
+                    | SortColumn::High
+                    | SortColumn::Low
+                    | SortColumn::Volume
+            ) || (column == SortColumn::PercentChange && self.change_basis == ChangeBasis::Day)
+        };
+        (from_tickers(self.sort_column) && from_tickers(self.config.secondary_sort)).then(|| {
+            SortKey {
+                column: self.sort_column,
+                order: self.sort_order(self.sort_column),
+                secondary: self.config.secondary_sort,
+                pinned: self.config.pinned.clone(),
+                generation: model::generation(),
+                paused: self.paused,
+            }
+        })
+    }
+
+    pub fn toggle_account(&mut self) {
+        self.view = match self.view {
+            View::Account => View::Table,
+            _ => View::Account,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub fn toggle_paper(&mut self) {
+        self.view = match self.view {
+            View::Paper => View::Table,
+            _ => View::Paper,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub fn toggle_portfolio(&mut self) {
+        self.view = match self.view {
+            View::Portfolio => View::Table,
+            _ => View::Portfolio,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub fn toggle_grid(&mut self) {
+        self.view = match self.view {
+            View::Grid => View::Table,
+            _ => View::Grid,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub fn toggle_heatmap(&mut self) {
+        self.view = match self.view {
+            View::Heatmap => View::Table,
+            _ => View::Heatmap,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub fn toggle_movers(&mut self) {
+        self.view = match self.view {
+            View::Movers => View::Table,
+            _ => View::Movers,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub fn toggle_comparison(&mut self) {
+        self.view = match self.view {
+            View::Comparison => View::Table,
+            _ => View::Comparison,
+        };
+        self.ensure_focus_visible();
+    }
+
+    pub(crate) fn is_pinned(&self, symbol: &str) -> bool {
+        self.config.pinned.iter().any(|s| s == symbol)
+    }
+
+    pub(crate) fn selected_symbol(&self) -> Option<&str> {
+        self.state
+            .selected()
+            .and_then(|i| self.visible_symbols.get(i))
+            .map(String::as_str)
+    }
+
+    pub fn toggle_pin(&mut self) {
+        let Some(symbol) = self.selected_symbol().map(str::to_owned) else {
+            return;
+        };
+        if self.is_pinned(&symbol) {
+            self.config.pinned.retain(|s| *s != symbol);
+        } else {
+            self.config.pinned.push(symbol);
+        }
+        self.track_indicators();
+        self.sync_selection();
+        if let Err(err) = self.config.save() {
+            error::report(format!("saving the config: {}", err));
+        }
+    }
+
+    /// Converts prices into the chosen denomination at the prices in `tickers`, or `None` when
+    /// prices are shown in their own quote currency.
+    pub(crate) fn converter(&self, tickers: &TickerMap) -> Option<Converter> {
+        Converter::new(
+            self.denomination,
+            self.exchange,
+            tickers,
+            self.config.denomination.fiat.as_deref(),
+            &self.fx_rates.lock().unwrap(),
+        )
+    }
+
+    /// Expands or collapses the selected group row.
+    pub fn toggle_group(&mut self) {
+        let Some(Some(key)) = self
+            .state
+            .selected()
+            .and_then(|i| self.visible_groups.get(i))
+        else {
+            return;
+        };
+        if !self.expanded_groups.remove(key) {
+            self.expanded_groups.insert(key.clone());
+        }
+    }
+
+    pub fn toggle_watchlist_only(&mut self) {
+        self.watchlist_only = !self.watchlist_only;
+        self.sync_selection();
+    }
+
+    /// Points the live stream at the pinned symbols in watchlist-only mode, and back at the
+    /// command line's selection otherwise.
+    fn sync_selection(&self) {
+        let wanted = if self.watchlist_only {
+            Selection::Symbols(self.config.pinned.clone())
+        } else {
+            self.streamed.clone()
+        };
+        self.selection.send_if_modified(|selection| {
+            let changed = *selection != wanted;
+            *selection = wanted;
+            changed
+        });
+    }
+
+    /// The shown watchlist tab, unless it is the one listing every symbol.
+    pub(crate) fn active_watchlist(&self) -> Option<&WatchlistConfig> {
+        self.config.watchlists.get(self.watchlist.checked_sub(1)?)
+    }
+
+    /// Adds `import` as a watchlist tab, replacing one of the same name, and saves the config.
+    /// Entries that name no market, and symbols not known to be listed on the exchange, are
+    /// reported.
+    pub fn import_watchlist(&mut self, import: Import) {
+        let name = import.watchlist.name.clone();
+        if !import.unmatched.is_empty() {
+            self.notify(
+                Level::Warning,
+                format!(
+                    "Watchlist {}: no market in {}",
+                    name,
+                    import.unmatched.join(", ")
+                ),
+            );
+        }
+        if let Some(listings) = &self.listings {
+            let unlisted: Vec<&str> = import
+                .watchlist
+                .symbols
+                .iter()
+                .filter(|symbol| listings.is_known(self.exchange, symbol) == Some(false))
+                .map(String::as_str)
+                .collect();
+            if !unlisted.is_empty() {
+                let message = format!(
+                    "Watchlist {}: not listed on {}: {}",
+                    name,
+                    self.exchange.name(),
+                    unlisted.join(", ")
+                );
+                self.notify(Level::Warning, message);
+            }
+        }
+        match self.config.watchlists.iter().position(|w| w.name == name) {
+            Some(i) => self.config.watchlists[i] = import.watchlist,
+            None => {
+                self.config.watchlists.push(import.watchlist);
+                self.tabs.push(TabState::new(SortColumn::Symbol));
+            }
+        }
+        if let Err(err) = self.config.save() {
+            error::report(format!("saving the config: {}", err));
+        }
+    }
+
+    /// Shows watchlist tab `i`, restoring the sort column, filter and selection it was left
+    /// with; 0 lists every symbol.
+    pub fn select_watchlist(&mut self, i: usize) {
+        if i == self.watchlist || i >= self.tabs.len() {
+            return;
+        }
+        self.tabs[self.watchlist] = TabState {
+            sort_column: self.sort_column,
+            tag_filter: self.tag_filter.take(),
+            selected: self.selected_symbol().map(str::to_owned),
+        };
+        let tab = self.tabs[i].clone();
+        self.watchlist = i;
+        self.sort_column = tab.sort_column;
+        self.tag_filter = tab.tag_filter;
+        self.pending_selection = tab.selected;
+        self.state = TableState::default().with_selected(Some(0));
+        self.column_offset = 0;
+        self.focus = Pane::Table;
+        if !self.visible_panes().contains(&Pane::Table) {
+            self.workspace = 0;
+            self.view = View::Table;
+        }
+    }
+
+    /// Shows the next watchlist tab, or the previous one with `back`, wrapping around.
+    pub fn cycle_watchlist(&mut self, back: bool) {
+        let count = self.tabs.len();
+        if count > 1 {
+            let step = if back { count - 1 } else { 1 };
+            self.select_watchlist((self.watchlist + step) % count);
+        }
+    }
+
+    /// Selects the symbol a tab was left with, once the table lists its rows again.
+    pub(crate) fn restore_selection(&mut self) {
+        let Some(symbol) = &self.pending_selection else {
+            return;
+        };
+        match self.visible_symbols.iter().position(|s| s == symbol) {
+            Some(i) => {
+                self.state.select(Some(i));
+                if let Some(row) = self.pending_row.take() {
+                    *self.state.offset_mut() = i.saturating_sub(row);
+                }
+                self.pending_selection = None;
+            }
+            // A restored symbol may only be listed once its first tickers arrive.
+            None if self.pending_row.is_some() => {}
+            None => self.pending_selection = None,
+        }
+    }
+
+    /// The panes of the table screen, whether each is open, and the action toggling it.
+    fn table_panes(&self) -> [(PaneKind, bool, Action); 8] {
+        [
+            (PaneKind::Detail, self.show_detail, Action::ToggleDetail),
+            (
+                PaneKind::Liquidations,
+                self.show_liquidations,
+                Action::ToggleLiquidations,
+            ),
+            (PaneKind::Alerts, self.show_alerts, Action::ToggleAlerts),
+            (
+                PaneKind::Notifications,
+                self.show_notifications,
+                Action::ToggleNotifications,
+            ),
+            (
+                PaneKind::AlertHistory,
+                self.show_alert_history,
+                Action::ToggleAlertHistory,
+            ),
+            (PaneKind::News, self.show_news, Action::ToggleNews),
+            (PaneKind::Plugins, self.show_plugins, Action::TogglePlugins),
+            (PaneKind::Status, self.show_status, Action::ToggleStatus),
+        ]
+    }
+
+    /// The state of the UI, to be restored by the next start.
+    pub fn session(&self) -> Session {
+        Session {
+            selected: self.selected_symbol().map(str::to_owned),
+            selected_row: self
+                .state
+                .selected()
+                .map_or(0, |i| i.saturating_sub(self.state.offset())),
+            column_offset: self.column_offset,
+            sort_column: Some(self.sort_column),
+            theme: Some(self.palettes[self.color_index].name.clone()),
+            view: self.view,
+            workspace: self.workspace,
+            panes: self
+                .table_panes()
+                .into_iter()
+                .filter(|(_, shown, _)| *shown)
+                .map(|(pane, _, _)| pane)
+                .collect(),
+            watchlist: self.active_watchlist().map(|w| w.name.clone()),
+            tag_filter: self.tag_filter.clone(),
+            watchlist_only: self.watchlist_only,
+            group_by_base: self.group_by_base,
+        }
+    }
+
+    /// Reopens the UI as `session` left it. Watchlists and themes that are no longer
+    /// configured are skipped, and the selection is restored once its symbol is listed.
+    pub fn restore_session(&mut self, session: Session) {
+        if let Some(i) = session
+            .theme
+            .and_then(|name| self.palettes.iter().position(|p| p.name == name))
+        {
+            self.color_index = i;
+            self.set_colors();
+        }
+        if let Some(i) = session
+            .watchlist
+            .and_then(|name| self.config.watchlists.iter().position(|w| w.name == name))
+        {
+            self.select_watchlist(i + 1);
+        }
+        if let Some(column) = session.sort_column {
+            self.sort_column = column;
+        }
+        self.tag_filter = session.tag_filter;
+        if session.watchlist_only != self.watchlist_only {
+            self.toggle_watchlist_only();
+        }
+        self.group_by_base = session.group_by_base;
+        for (pane, shown, action) in self.table_panes() {
+            if !shown && session.panes.contains(&pane) {
+                self.apply_action(action);
+            }
+        }
+        let view = match session.view {
+            View::Table => None,
+            View::Comparison => Some(Action::ToggleComparison),
+            View::Portfolio => Some(Action::TogglePortfolio),
+            View::Paper => Some(Action::TogglePaper),
+            View::Account => Some(Action::ToggleAccount),
+            View::Grid => Some(Action::ToggleGrid),
+            View::Heatmap => Some(Action::ToggleHeatmap),
+            View::Movers => Some(Action::ToggleMovers),
+        };
+        if let Some(action) = view {
+            self.apply_action(action);
+        }
+        self.select_workspace(session.workspace);
+        self.column_offset = session.column_offset;
+        if session.selected.is_some() {
+            self.pending_selection = session.selected;
+            self.pending_row = Some(session.selected_row);
+        }
+    }
+
+    /// Restores the session saved at `path`, and saves the session there on quit.
+    pub fn set_session_path(&mut self, path: PathBuf) {
+        self.restore_session(Session::load(&path));
+        self.session_path = Some(path);
+    }
+
+    fn save_session(&self) {
+        if let Some(path) = &self.session_path {
+            if let Err(err) = self.session().save(path) {
+                error::report(format!("saving the session: {}", err));
+            }
+        }
+    }
+
+    /// Whether the table lists `symbol`.
+    pub(crate) fn shows(&self, symbol: &str) -> bool {
+        (!self.watchlist_only || self.is_pinned(symbol))
+            && (self.config.quotes.is_empty()
+                || self.exchange.instrument(symbol).is_some_and(|instrument| {
+                    self.config
+                        .quotes
+                        .iter()
+                        .any(|quote| quote.eq_ignore_ascii_case(&instrument.quote))
+                }))
+            && self.active_watchlist().is_none_or(|watchlist| {
+                watchlist.contains(symbol, self.config.notes.get(symbol), &self.listed)
+            })
+            && self.tag_filter.as_ref().is_none_or(|tag| {
+                self.config
+                    .notes
+                    .get(symbol)
+                    .is_some_and(|note| note.has_tag(tag))
+            })
+    }
+
+    pub fn next_sort_column(&mut self) {
+        let columns = self.columns();
+        let i = columns
+            .iter()
+            .position(|c| *c == self.sort_column)
+            .unwrap_or(0);
+        self.sort_column = columns[(i + 1) % columns.len()];
+    }
+
+    /// The direction `column` sorts in: the one it was last reversed to, or its default.
+    pub(crate) fn sort_order(&self, column: SortColumn) -> SortOrder {
+        self.config
+            .sort_orders
+            .get(&column)
+            .copied()
+            .unwrap_or_else(|| column.default_order())
+    }
+
+    /// Reverses the sort column's direction and saves it as the column's preference.
+    pub fn reverse_sort(&mut self) {
+        let order = self.sort_order(self.sort_column).reversed();
+        if order == self.sort_column.default_order() {
+            self.config.sort_orders.remove(&self.sort_column);
+        } else {
+            self.config.sort_orders.insert(self.sort_column, order);
+        }
+        if let Err(err) = self.config.save() {
+            error::report(format!("saving the config: {}", err));
+        }
+    }
+
+    /// Sorts by `column` if it is shown, or reverses the sort if it already is the sort column.
+    pub fn sort_by(&mut self, column: SortColumn) {
+        if column == self.sort_column {
+            self.reverse_sort();
+        } else if self.columns().contains(&column) {
+            self.sort_column = column;
+        }
+    }
+
+    /// Waits up to `timeout` for terminal input and applies it. Any input, including a resize,
+    /// marks the screen for redrawing.
+    async fn handle_events(&mut self, timeout: Duration) -> Result<(), Error> {
+        let event = tokio::task::block_in_place(|| term::next_event(timeout))?;
+        match event {
+            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                self.handle_key_press(key).await
+            }
+            Some(_) => {}
+            None => return Ok(()),
+        }
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Whether the next frame has to be drawn: after input, new tickers or notifications, while
+    /// rows are flashing or charts are loading, and once a second for clocks, ages and toasts
+    /// that expire.
+    fn needs_redraw(&mut self) -> bool {
+        let generation = model::generation();
+        if generation != self.drawn_generation {
+            self.drawn_generation = generation;
+            self.data_changed_at = Some(Instant::now());
+            return true;
+        }
+        let flash = Duration::from_millis(self.config.theme.flash_ms);
+        let flashing = !self.config.accessibility.enabled
+            && self.data_changed_at.is_some_and(|at| at.elapsed() < flash);
+        let loading = self
+            .chart
+            .iter()
+            .chain(&self.chart_pane)
+            .chain(self.compare_charts.iter().flatten())
+            .chain(&self.grid)
+            .any(|chart| chart.is_loading() || chart.is_loading_older())
+            || self.news.is_loading()
+            || self.plugins.is_loading()
+            || self.maintenance.is_loading();
+        self.dirty || flashing || loading || self.drawn_at.elapsed() >= HEARTBEAT
+    }
+
+    /// Applies one key press, as read from the terminal by the run loop.
+    pub async fn handle_key_press(&mut self, key: KeyEvent) {
+        // Raw mode swallows the interrupt, so Ctrl+C quits from anywhere whatever the keymap says.
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.mode = Mode::Quit;
+            return;
+        }
+        // The rule editor takes raw text input, so it bypasses the keymap.
+        if self.rule_editor.is_some() {
+            self.handle_rule_editor_key(key);
+            return;
+        }
+        if self.order_ticket.is_some() {
+            self.handle_order_ticket_key(key);
+            return;
+        }
+        if self.command_palette.is_some() {
+            self.handle_command_palette_key(key);
+            return;
+        }
+        if self.exchange_switcher.is_some() {
+            self.handle_exchange_switcher_key(key);
+            return;
+        }
+        if self.note_editor.is_some() {
+            self.handle_note_editor_key(key);
+            return;
+        }
+        let pending = self.pending_key.take().map(|(first, _)| first);
+        let action = match self.keymap.lookup(pending, &key) {
+            Lookup::Action(action) => Some(action),
+            Lookup::Pending(first) => {
+                self.pending_key = Some((first, Instant::now()));
+                return;
+            }
+            // A key that does not complete a chord only cancels it.
+            Lookup::None if pending.is_some() => return,
+            Lookup::None => None,
+        };
+        if self.chart.is_some() {
+            if let Some(action) = action {
+                self.handle_chart_action(action);
+            }
+            return;
+        }
+        if self.compare_charts.is_some() {
+            if let Some(Action::Quit | Action::CompareCharts) = action {
+                self.compare_charts = None;
+            }
+            return;
+        }
+        let Some(action) = action else {
+            // Unbound digits switch workspaces, and unbound function keys sort by the table's
+            // columns in order.
+            match key.code {
+                KeyCode::Char(digit @ '1'..='9') => {
+                    self.select_workspace(digit as usize - '1' as usize)
+                }
+                KeyCode::F(n) => {
+                    let columns = self.columns();
+                    if let Some(column) = (n as usize).checked_sub(1).and_then(|i| columns.get(i)) {
+                        self.sort_by(*column);
+                    }
+                }
+                _ => {}
+            }
+            return;
+        };
+        self.apply_action(action);
+    }
+
+    /// Drops the first key of a chord once it has waited `CHORD_TIMEOUT` for the second.
+    pub fn expire_pending_key(&mut self) {
+        if self
+            .pending_key
+            .is_some_and(|(_, pressed)| pressed.elapsed() >= CHORD_TIMEOUT)
+        {
+            self.pending_key = None;
+            self.dirty = true;
+        }
+    }
+
+    /// Adds a notification from the app itself to the history, showing it as a toast.
+    fn notify(&mut self, level: Level, message: impl std::fmt::Display) {
+        self.push_notification(Notification::new(level, message));
+    }
+
+    fn push_notification(&mut self, notification: Notification) {
+        if self.notifications.len() == NOTIFICATION_HISTORY {
+            self.notifications.pop_front();
+        }
+        self.notifications.push_back(notification);
+    }
+
+    /// Moves notifications posted by background tasks since the last call into the history.
+    pub fn collect_notifications(&mut self) {
+        for notification in notify::take_posted() {
+            self.push_notification(notification);
+            self.dirty = true;
+        }
+    }
+
+    /// Notifications still shown as toasts, newest first.
+    pub(crate) fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        self.notifications.iter().rev().filter(|n| n.is_toast())
+    }
+
+    /// Snoozes the rule and symbol of the newest alert still shown as a toast.
+    fn snooze_alert(&mut self) {
+        let Some(alert) = self.toasts().find_map(|toast| toast.alert.clone()) else {
+            return;
+        };
+        self.alerts.lock().unwrap().snooze(&alert, ALERT_SNOOZE);
+        self.notify(
+            Level::Info,
+            format!("Snoozed {} on {} for an hour", alert.rule, alert.symbol),
+        );
+    }
+
+    /// Runs `action` as bound to a key, or as chosen in the command palette.
+    fn apply_action(&mut self, action: Action) {
+        if matches!(
+            action,
+            Action::ToggleDetail
+                | Action::ToggleComparison
+                | Action::TogglePortfolio
+                | Action::TogglePaper
+                | Action::ToggleAccount
+                | Action::ToggleLiquidations
+                | Action::ToggleAlerts
+                | Action::ToggleNotifications
+                | Action::ToggleNews
+                | Action::TogglePlugins
+                | Action::ToggleStatus
+                | Action::ToggleGrid
+                | Action::ToggleHeatmap
+                | Action::ToggleMovers
+        ) {
+            // These toggle parts of the table screen, so they bring it up.
+            self.workspace = 0;
+        }
+        match action {
+            Action::Quit => self.mode = Mode::Quit,
+            Action::NextColor => self.next_color(),
+            Action::PreviousColor => self.previous_color(),
+            Action::FocusNextPane => self.focus_next_pane(),
+            Action::ToggleDetail => {
+                self.show_detail = !self.show_detail;
+                self.detail_scroll = 0;
+                self.ensure_focus_visible();
+            }
+            Action::ToggleComparison => self.toggle_comparison(),
+            Action::TogglePortfolio => self.toggle_portfolio(),
+            Action::TogglePaper => self.toggle_paper(),
+            Action::ToggleAccount => self.toggle_account(),
+            Action::ToggleGrid => self.toggle_grid(),
+            Action::ToggleHeatmap => self.toggle_heatmap(),
+            Action::ToggleMovers => self.toggle_movers(),
+            Action::Buy => self.open_order_ticket(Side::Buy),
+            Action::Sell => self.open_order_ticket(Side::Sell),
+            Action::Pause => self.paused = !self.paused,
+            Action::WatchlistOnly => self.toggle_watchlist_only(),
+            Action::NextWatchlist => self.cycle_watchlist(false),
+            Action::PreviousWatchlist => self.cycle_watchlist(true),
+            Action::Export => {
+                self.export_table();
+            }
+            Action::CommandPalette => self.open_command_palette(),
+            Action::CopySymbol => {
+                if let Some(symbol) = self.selected_symbol().map(str::to_owned) {
+                    self.copy(&symbol, &symbol);
+                }
+            }
+            Action::CopyRow => {
+                if let (Some(row), Some(symbol)) = (self.selected_row(), self.selected_symbol()) {
+                    let what = format!("the {} row", symbol);
+                    self.copy(&row, &what);
+                }
+            }
+            Action::EditNote => {
+                if let Some(symbol) = self.selected_symbol().map(str::to_owned) {
+                    let input = self
+                        .config
+                        .notes
+                        .get(&symbol)
+                        .map_or(String::new(), SymbolNote::input);
+                    self.note_editor = Some(NoteEditor { symbol, input });
+                }
+            }
+            Action::SwitchExchange => {
+                if self.switch_exchange.is_some() {
+                    self.exchange_switcher = Some(ExchangeSwitcher::new(self.exchange));
+                } else {
+                    self.notify(
+                        Level::Warning,
+                        "the exchange cannot be switched while replaying a recording",
+                    );
+                }
+            }
+            Action::ToggleLiquidations => {
+                self.show_liquidations = !self.show_liquidations;
+                self.liquidations_scroll = 0;
+                self.ensure_focus_visible();
+            }
+            Action::ToggleAlerts => {
+                self.show_alerts = !self.show_alerts;
+                self.ensure_focus_visible();
+            }
+            Action::ToggleNotifications => {
+                self.show_notifications = !self.show_notifications;
+                self.notifications_scroll = 0;
+                self.ensure_focus_visible();
+            }
+            Action::SnoozeAlert => self.snooze_alert(),
+            Action::ToggleNews => {
+                self.show_news = !self.show_news;
+                self.news_state.select(None);
+                self.ensure_focus_visible();
+            }
+            Action::TogglePlugins => {
+                self.show_plugins = !self.show_plugins;
+                self.ensure_focus_visible();
+            }
+            Action::ToggleStatus => {
+                self.show_status = !self.show_status;
+                self.ensure_focus_visible();
+            }
+            Action::ToggleAlertHistory => {
+                self.show_alert_history = !self.show_alert_history;
+                self.alert_history_scroll = 0;
+                self.ensure_focus_visible();
+            }
+            _ => match self.focus {
+                Pane::Table => self.handle_table_action(action),
+                Pane::Detail => self.handle_detail_action(action),
+                Pane::Chart => self.handle_chart_pane_action(action),
+                Pane::Comparison => self.handle_comparison_action(action),
+                Pane::Liquidations => self.handle_liquidations_action(action),
+                Pane::Alerts => self.handle_alerts_action(action),
+                Pane::Notifications => self.handle_notifications_action(action),
+                Pane::AlertHistory => self.handle_alert_history_action(action),
+                Pane::News => self.handle_news_action(action),
+                Pane::Portfolio => self.handle_portfolio_action(action),
+                Pane::Paper => self.handle_paper_action(action),
+                Pane::Heatmap => self.handle_heatmap_action(action),
+                Pane::Gainers | Pane::Losers => self.handle_movers_action(action),
+                Pane::Account | Pane::Grid | Pane::Plugins | Pane::Status => {}
+            },
+        };
+    }
+
+    fn open_command_palette(&mut self) {
+        let mut commands: Vec<Command> = Action::ALL
+            .iter()
+            .filter(|action| **action != Action::CommandPalette)
+            .map(|action| Command::Action(*action))
+            .collect();
+        commands.extend(
+            self.palettes
+                .iter()
+                .map(|palette| Command::Theme(palette.name.clone())),
+        );
+        let workspaces = std::iter::once("Table")
+            .chain(self.config.workspaces.iter().map(|w| w.name.as_str()))
+            .enumerate()
+            .map(|(i, name)| Command::Workspace(i, name.to_string()));
+        commands.extend(workspaces);
+        if !self.config.watchlists.is_empty() {
+            let watchlists = std::iter::once("All")
+                .chain(self.config.watchlists.iter().map(|w| w.name.as_str()))
+                .enumerate()
+                .map(|(i, name)| Command::Watchlist(i, name.to_string()));
+            commands.extend(watchlists);
+        }
+        let tags: BTreeSet<&String> = self
+            .config
+            .notes
+            .values()
+            .flat_map(|note| &note.tags)
+            .collect();
+        commands.extend(tags.into_iter().map(|tag| Command::Tag(tag.clone())));
+        let mut symbols: Vec<String> = self.live.lock().unwrap().keys().cloned().collect();
+        symbols.sort();
+        commands.extend(symbols.into_iter().map(Command::Symbol));
+        self.command_palette = Some(CommandPalette::new(commands));
+    }
+
+    fn handle_command_palette_key(&mut self, key: KeyEvent) {
+        let Some(palette) = &mut self.command_palette else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.command_palette = None,
+            KeyCode::Enter => {
+                let command = palette.chosen().cloned();
+                self.command_palette = None;
+                if let Some(command) = command {
+                    self.run_command(command);
+                }
+            }
+            KeyCode::Down => palette.next(),
+            KeyCode::Up => palette.previous(),
+            _ => {
+                if edit_text(&mut palette.input, &key) {
+                    palette.filter();
+                }
+            }
+        }
+    }
+
+    fn handle_note_editor_key(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.note_editor else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.note_editor = None,
+            KeyCode::Enter => {
+                let note = SymbolNote::parse(&editor.input);
+                let symbol = editor.symbol.clone();
+                self.note_editor = None;
+                if note.is_empty() {
+                    self.config.notes.remove(&symbol);
+                } else {
+                    self.config.notes.insert(symbol, note);
+                }
+                if let Err(err) = self.config.save() {
+                    error::report(format!("saving the config: {}", err));
+                }
+            }
+            _ => {
+                edit_text(&mut editor.input, &key);
+            }
+        }
+    }
+
+    fn handle_exchange_switcher_key(&mut self, key: KeyEvent) {
+        let Some(switcher) = &mut self.exchange_switcher else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.exchange_switcher = None,
+            KeyCode::Enter => {
+                let exchange = switcher.chosen();
+                self.exchange_switcher = None;
+                self.switch_to(exchange);
+            }
+            KeyCode::Down | KeyCode::Char('j') => switcher.next(),
+            KeyCode::Up | KeyCode::Char('k') => switcher.previous(),
+            _ => {}
+        }
+    }
+
+    /// Reconnects the live stream to `exchange` and starts the table over: the old exchange's
+    /// rows, selection, charts and paused snapshot are dropped.
+    pub fn switch_to(&mut self, exchange: Exchange) {
+        let Some(switch) = &self.switch_exchange else {
+            return;
+        };
+        if exchange == self.exchange {
+            return;
+        }
+        switch.send_replace(exchange);
+        self.exchange = exchange;
+        self.live.lock().unwrap().clear();
+        *self.status.lock().unwrap() = ConnectionStatus::default();
+        self.state = TableState::default();
+        self.ticker_length = 0;
+        self.visible_symbols.clear();
+        self.visible_groups.clear();
+        self.expanded_groups.clear();
+        self.heatmap_symbols.clear();
+        self.gainers.clear();
+        self.losers.clear();
+        self.paused = false;
+        self.frozen = None;
+        self.resume_diff.clear();
+        self.chart = None;
+        self.chart_pane = None;
+        self.compare_charts = None;
+        self.compare_mark = None;
+        self.grid.clear();
+        self.notify(Level::Info, format!("Switched to {}", exchange.name()));
+    }
+
+    fn run_command(&mut self, command: Command) {
+        match command {
+            Command::Action(action) => self.apply_action(action),
+            Command::Theme(name) => {
+                if let Some(i) = self.palettes.iter().position(|p| p.name == name) {
+                    self.color_index = i;
+                    self.show_theme();
+                }
+            }
+            Command::Workspace(i, _) => self.select_workspace(i),
+            Command::Watchlist(i, _) => self.select_watchlist(i),
+            Command::Tag(tag) => {
+                self.tag_filter = match self.tag_filter.take() {
+                    Some(active) if active == tag => None,
+                    _ => Some(tag),
+                };
+            }
+            Command::Symbol(symbol) => {
+                if !self.visible_panes().contains(&Pane::Table) {
+                    self.workspace = 0;
+                    self.view = View::Table;
+                }
+                self.focus = Pane::Table;
+                if let Some(i) = self.visible_symbols.iter().position(|s| *s == symbol) {
+                    self.select_row(i);
+                }
+            }
+        }
+    }
+
+    fn open_chart(&mut self, source: ChartSource) {
+        if let Some(symbol) = self.selected_symbol().map(str::to_owned) {
+            self.open_chart_for(symbol, source);
+        }
+    }
+
+    fn open_chart_for(&mut self, symbol: String, source: ChartSource) {
+        self.chart = Some(ChartView::open(
+            symbol,
+            source,
+            self.exchange,
+            &self.config.recorder,
+        ));
+    }
+
+    /// Marks the selected symbol for comparison, or compares the marked symbol with it on one
+    /// chart.
+    fn compare_selected(&mut self) {
+        let Some(symbol) = self.selected_symbol().map(str::to_owned) else {
+            return;
+        };
+        match self.compare_mark.take() {
+            Some(marked) if marked == symbol => {
+                self.notify(Level::Info, format!("Stopped comparing {}", symbol));
+            }
+            Some(marked) => {
+                // Exchanges without klines compare the recorded history instead.
+                let source = if self.exchange.has_klines() {
+                    ChartSource::Live
+                } else {
+                    ChartSource::History
+                };
+                let open =
+                    |symbol| ChartView::open(symbol, source, self.exchange, &self.config.recorder);
+                self.compare_charts = Some([open(marked), open(symbol)]);
+            }
+            None => {
+                let keys = self.keymap.keys_for(Action::CompareCharts);
+                self.notify(
+                    Level::Info,
+                    format!("Comparing {}: press {} on a second symbol", symbol, keys),
+                );
+                self.compare_mark = Some(symbol);
+            }
+        }
+    }
+
+    fn toggle_overlay(&mut self, action: Action) {
+        let overlay = match action {
+            Action::ToggleSma => Overlay::Sma,
+            Action::ToggleEma => Overlay::Ema,
+            Action::ToggleBollinger => Overlay::Bollinger,
+            Action::ToggleVwap => Overlay::Vwap,
+            Action::ToggleVolumeProfile => {
+                self.volume_profile = !self.volume_profile;
+                return;
+            }
+            Action::CycleChartStyle => {
+                self.chart_style = self.chart_style.next();
+                return;
+            }
+            Action::ToggleLogScale => {
+                self.log_scale = !self.log_scale;
+                return;
+            }
+            _ => return,
+        };
+        if let Some(i) = self.chart_overlays.iter().position(|o| *o == overlay) {
+            self.chart_overlays.remove(i);
+        } else {
+            self.chart_overlays.push(overlay);
+        }
+    }
+
+    /// Moves the crosshair with ←/→, pans with Shift+←/→, zooms, cycles the range shown and
+    /// returns to the latest candles with End. Returns whether `action` was one of these.
+    fn navigate_chart(chart: &mut ChartView, action: Action) -> bool {
+        match action {
+            Action::PreviousColor => chart.move_cursor(-1),
+            Action::NextColor => chart.move_cursor(1),
+            Action::ScrollLeft => chart.pan(-(chart.span() as isize / 2).max(1)),
+            Action::ScrollRight => chart.pan((chart.span() as isize / 2).max(1)),
+            Action::ZoomIn => chart.zoom_in(),
+            Action::ZoomOut => chart.zoom_out(),
+            Action::Last => chart.reset_view(),
+            Action::CycleChartRange => chart.cycle_range(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// The chart popup is modal: it only responds to closing, switching its source, moving
+    /// around the chart and toggling overlays.
+    fn handle_chart_action(&mut self, action: Action) {
+        let Some(chart) = &mut self.chart else {
+            return;
+        };
+        if Self::navigate_chart(chart, action) {
+            return;
+        }
+        match action {
+            Action::Quit | Action::ToggleChart => self.chart = None,
+            Action::ToggleHistory => {
+                let source = match chart.source {
+                    ChartSource::Live => ChartSource::History,
+                    ChartSource::History => ChartSource::Live,
+                };
+                let symbol = chart.symbol.clone();
+                self.open_chart_for(symbol, source);
+            }
+            _ => self.toggle_overlay(action),
+        }
+    }
+
+    fn handle_chart_pane_action(&mut self, action: Action) {
+        let Some(chart) = &mut self.chart_pane else {
+            return;
+        };
+        if Self::navigate_chart(chart, action) {
+            return;
+        }
+        if action == Action::ToggleHistory {
+            let source = match chart.source {
+                ChartSource::Live => ChartSource::History,
+                ChartSource::History => ChartSource::Live,
+            };
+            let symbol = chart.symbol.clone();
+            self.chart_pane = Some(ChartView::open(
+                symbol,
+                source,
+                self.exchange,
+                &self.config.recorder,
+            ));
+        } else {
+            self.toggle_overlay(action);
+        }
+    }
+
+    fn handle_table_action(&mut self, action: Action) {
+        // Moving around the table before a restored selection is listed keeps it where it is.
+        self.pending_selection = None;
+        self.pending_row = None;
+        if let Some(column) = action.sort_column() {
+            // Columns that are not shown cannot be sorted by.
+            if self.columns().contains(&column) {
+                self.sort_by(column);
+            }
+            return;
+        }
+        match action {
+            Action::Next => self.next(),
+            Action::Previous => self.previous(),
+            Action::PageDown => self.page_down(),
+            Action::PageUp => self.page_up(),
+            Action::First => self.select_row(0),
+            Action::Last => self.select_row(usize::MAX),
+            Action::ScrollLeft => self.column_offset = self.column_offset.saturating_sub(1),
+            Action::ScrollRight => self.column_offset += 1,
+            Action::SortNext => self.next_sort_column(),
+            Action::Pin => self.toggle_pin(),
+            Action::ReverseSort => self.reverse_sort(),
+            Action::GroupByBase => self.group_by_base = !self.group_by_base,
+            Action::CycleDenomination => {
+                let has_fiat = self.config.denomination.fiat.is_some();
+                self.denomination = self.denomination.next(has_fiat);
+            }
+            Action::CycleChangeBasis => self.change_basis = self.change_basis.next(),
+            Action::EditItem => self.toggle_group(),
+            Action::ToggleChart => self.open_chart(ChartSource::Live),
+            Action::ToggleHistory => self.open_chart(ChartSource::History),
+            Action::CompareCharts => self.compare_selected(),
+            _ => {}
+        };
+    }
+
+    fn handle_detail_action(&mut self, action: Action) {
+        match action {
+            Action::Next => self.detail_scroll = self.detail_scroll.saturating_add(1),
+            Action::Previous => self.detail_scroll = self.detail_scroll.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    fn handle_comparison_action(&mut self, action: Action) {
+        let selected = self.comparison_state.selected();
+        match action {
+            Action::Next => self
+                .comparison_state
+                .select(Some(selected.map_or(0, |i| i + 1))),
+            Action::Previous => self
+                .comparison_state
+                .select(Some(selected.map_or(0, |i| i.saturating_sub(1)))),
+            _ => {}
+        }
+    }
+
+    fn handle_heatmap_action(&mut self, action: Action) {
+        let last = self.heatmap_symbols.len().saturating_sub(1);
+        match action {
+            Action::Next => self.heatmap_selected = (self.heatmap_selected + 1).min(last),
+            Action::Previous => self.heatmap_selected = self.heatmap_selected.saturating_sub(1),
+            Action::First => self.heatmap_selected = 0,
+            Action::Last => self.heatmap_selected = last,
+            Action::ToggleChart | Action::ToggleHistory => {
+                if let Some(symbol) = self.heatmap_symbols.get(self.heatmap_selected) {
+                    let source = if action == Action::ToggleChart {
+                        ChartSource::Live
+                    } else {
+                        ChartSource::History
+                    };
+                    self.open_chart_for(symbol.clone(), source);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves the selection of the focused list; Tab switches both lists between 24h and 5m
+    /// change.
+    fn handle_movers_action(&mut self, action: Action) {
+        let (state, symbols) = if self.focus == Pane::Gainers {
+            (&mut self.gainers_state, &self.gainers)
+        } else {
+            (&mut self.losers_state, &self.losers)
+        };
+        let last = symbols.len().saturating_sub(1);
+        let selected = state.selected();
+        match action {
+            Action::Next => state.select(Some(selected.map_or(0, |i| (i + 1).min(last)))),
+            Action::Previous => state.select(Some(selected.map_or(0, |i| i.saturating_sub(1)))),
+            Action::First => state.select(Some(0)),
+            Action::Last => state.select(Some(last)),
+            Action::SortNext => self.movers_basis = self.movers_basis.toggled(),
+            Action::ToggleChart | Action::ToggleHistory => {
+                if let Some(symbol) = selected.and_then(|i| symbols.get(i)) {
+                    let source = if action == Action::ToggleChart {
+                        ChartSource::Live
+                    } else {
+                        ChartSource::History
+                    };
+                    self.open_chart_for(symbol.clone(), source);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_liquidations_action(&mut self, action: Action) {
+        match action {
+            Action::Next => self.liquidations_scroll += 1,
+            Action::Previous => {
+                self.liquidations_scroll = self.liquidations_scroll.saturating_sub(1)
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_notifications_action(&mut self, action: Action) {
+        match action {
+            Action::Next => self.notifications_scroll += 1,
+            Action::Previous => {
+                self.notifications_scroll = self.notifications_scroll.saturating_sub(1)
+            }
+            Action::First => self.notifications_scroll = 0,
+            _ => {}
+        }
+    }
+
+    /// Enter filters the history to the table's selected symbol, or clears the filter.
+    fn handle_alert_history_action(&mut self, action: Action) {
+        match action {
+            Action::Next => self.alert_history_scroll += 1,
+            Action::Previous => {
+                self.alert_history_scroll = self.alert_history_scroll.saturating_sub(1)
+            }
+            Action::First => self.alert_history_scroll = 0,
+            Action::EditItem => {
+                self.alert_history_symbol = match self.alert_history_symbol {
+                    Some(_) => None,
+                    None => self.selected_symbol().map(str::to_owned),
+                };
+                self.alert_history_scroll = 0;
+            }
+            _ => {}
+        }
+    }
+
+    /// Enter opens the selected headline's article in the browser.
+    fn handle_news_action(&mut self, action: Action) {
+        let selected = self.news_state.selected();
+        let last = self.news.headlines.len().saturating_sub(1);
+        match action {
+            Action::Next => self
+                .news_state
+                .select(Some(selected.map_or(0, |i| (i + 1).min(last)))),
+            Action::Previous => self
+                .news_state
+                .select(Some(selected.map_or(0, |i| i.saturating_sub(1)))),
+            Action::First => self.news_state.select(Some(0)),
+            Action::Last => self.news_state.select(Some(last)),
+            Action::EditItem => {
+                let Some(headline) = selected.and_then(|i| self.news.headlines.get(i)) else {
+                    return;
+                };
+                if let Err(err) = open::that_detached(&headline.url) {
+                    self.notify(
+                        Level::Error,
+                        format!("Could not open {}: {}", headline.url, err),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_portfolio_action(&mut self, action: Action) {
+        let selected = self.portfolio_state.selected();
+        let last = self.portfolio.holdings.len().saturating_sub(1);
+        match action {
+            Action::Next => self
+                .portfolio_state
+                .select(Some(selected.map_or(0, |i| (i + 1).min(last)))),
+            Action::Previous => self
+                .portfolio_state
+                .select(Some(selected.map_or(0, |i| i.saturating_sub(1)))),
+            _ => {}
+        }
+    }
+
+    fn handle_paper_action(&mut self, action: Action) {
+        let selected = self.paper_state.selected();
+        let last = self.paper.positions.len().saturating_sub(1);
+        match action {
+            Action::Next => self
+                .paper_state
+                .select(Some(selected.map_or(0, |i| (i + 1).min(last)))),
+            Action::Previous => self
+                .paper_state
+                .select(Some(selected.map_or(0, |i| i.saturating_sub(1)))),
+            _ => {}
+        }
+    }
+
+    fn handle_alerts_action(&mut self, action: Action) {
+        let count = self.alerts.lock().unwrap().rules().len();
+        let selected = self.alerts_state.selected().filter(|i| *i < count);
+        match action {
+            Action::Next => self.alerts_state.select(Some(
+                selected.map_or(0, |i| (i + 1).min(count.saturating_sub(1))),
+            )),
+            Action::Previous => self
+                .alerts_state
+                .select(Some(selected.map_or(0, |i| i.saturating_sub(1)))),
+            Action::NewItem => {
+                self.rule_editor = Some(RuleEditor {
+                    index: None,
+                    input: self
+                        .selected_symbol()
+                        .map_or(String::new(), |symbol| format!("{}: ", symbol)),
+                    error: None,
+                })
+            }
+            Action::EditItem => {
+                if let Some(i) = selected {
+                    let rule = self.alerts.lock().unwrap().rules()[i].clone();
+                    let input = match &rule.symbol {
+                        Some(symbol) => format!("{}: {}", symbol, rule.expression()),
+                        None => rule.expression(),
+                    };
+                    self.rule_editor = Some(RuleEditor {
+                        index: Some(i),
+                        input,
+                        error: None,
+                    });
+                }
+            }
+            Action::DeleteItem => {
+                if let Some(i) = selected {
+                    self.config.alerts.remove(i);
+                    self.save_alerts();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_rule_editor_key(&mut self, key: KeyEvent) {
+        let Some(editor) = &mut self.rule_editor else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.rule_editor = None,
+            KeyCode::Enter => self.submit_rule(),
+            _ => {
+                if edit_text(&mut editor.input, &key) {
+                    editor.error = None;
+                }
+            }
+        }
+    }
+
+    fn handle_order_ticket_key(&mut self, key: KeyEvent) {
+        let Some(ticket) = &mut self.order_ticket else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.order_ticket = None,
+            KeyCode::Enter => self.submit_order(),
+            _ => {
+                if edit_text(&mut ticket.input, &key) {
+                    ticket.error = None;
+                }
+            }
+        }
+    }
+
+    /// Opens an order ticket for the selected symbol: the selected position in the paper view,
+    /// otherwise the selected table row.
+    fn open_order_ticket(&mut self, side: Side) {
+        let symbol = match self.view {
+            View::Paper => self
+                .paper_state
+                .selected()
+                .and_then(|i| self.paper.positions.keys().nth(i))
+                .cloned(),
+            _ => self.selected_symbol().map(str::to_owned),
+        };
+        if let Some(symbol) = symbol {
+            self.order_ticket = Some(OrderTicket {
+                side,
+                symbol,
+                input: String::new(),
+                error: None,
+            });
+        }
+    }
+
+    fn submit_order(&mut self) {
+        let Some(ticket) = &mut self.order_ticket else {
+            return;
+        };
+        let result = match ticket.input.trim().parse::<f64>() {
+            Ok(quantity) => {
+                let tickers = self.live.lock().unwrap();
+                self.paper.execute(
+                    &self.config.paper,
+                    &tickers,
+                    &ticket.symbol,
+                    ticket.side,
+                    quantity,
+                )
+            }
+            Err(_) => Err(format!("invalid quantity {:?}", ticket.input.trim())),
+        };
+        match result {
+            Ok(trade) => {
+                self.paper_status = Some(format!(
+                    "{} {} {} @ {}",
+                    trade.side.name(),
+                    trade.quantity,
+                    trade.symbol,
+                    trade.price
+                ));
+                self.order_ticket = None;
+            }
+            Err(err) => ticket.error = Some(err),
+        }
+    }
+
+    /// Parses the editor input and, if valid, stores it as a `when` rule.
+    fn submit_rule(&mut self) {
+        let Some(editor) = &mut self.rule_editor else {
+            return;
+        };
+        let (symbol, source) = match editor.input.split_once(':') {
+            Some((symbol, source)) => (Some(symbol.trim().to_ascii_uppercase()), source),
+            None => (None, editor.input.as_str()),
+        };
+        let source = source.trim_start();
+        let start = editor.input.len() - source.len();
+        if let Err(err) = Expr::parse(source) {
+            editor.error = Some((start + err.offset, err.message));
+            return;
+        }
+        let symbol = symbol.filter(|s| !s.is_empty());
+        let when = source.trim().to_string();
+        match editor.index.and_then(|i| self.config.alerts.get_mut(i)) {
+            Some(rule) => {
+                rule.symbol = symbol;
+                rule.when = Some(when);
+                rule.above = None;
+                rule.below = None;
+            }
+            None => self.config.alerts.push(AlertRule {
+                name: editor.input.trim().to_string(),
+                symbol,
+                when: Some(when),
+                above: None,
+                below: None,
+                moves: None,
+                within_mins: None,
+                drops_from_high: None,
+                volume_spike: None,
+                trades_spike: None,
+                cooldown_secs: 0,
+                once: false,
+                sinks: Vec::new(),
+            }),
+        }
+        self.rule_editor = None;
+        self.save_alerts();
+    }
+
+    /// Applies the edited rules to the running engine and writes them to the config file.
+    fn save_alerts(&mut self) {
+        self.alerts
+            .lock()
+            .unwrap()
+            .set_rules(self.config.alerts.clone());
+        if let Err(err) = self.config.save() {
+            error::report(format!("saving the config: {}", err));
+        }
+    }
+
+    /// Panes currently on screen, in focus order.
+    /// Layout of the shown workspace, or `None` on the table screen.
+    pub(crate) fn workspace_layout(&self) -> Option<&Slot> {
+        let i = self.workspace.checked_sub(1)?;
+        self.config
+            .workspaces
+            .get(i)
+            .map(|workspace| &workspace.layout)
+    }
+
+    /// Shows workspace `i` if it exists; 0 is the table screen.
+    pub fn select_workspace(&mut self, i: usize) {
+        if i <= self.config.workspaces.len() {
+            self.workspace = i;
+            self.ensure_focus_visible();
+        }
+    }
+
+    pub(crate) fn visible_panes(&self) -> Vec<Pane> {
+        if let Some(layout) = self.workspace_layout() {
+            let mut panes: Vec<Pane> = Vec::new();
+            for kind in layout.panes() {
+                let pane = match kind {
+                    PaneKind::Table => Pane::Table,
+                    PaneKind::Detail => Pane::Detail,
+                    PaneKind::Chart => Pane::Chart,
+                    PaneKind::Comparison => Pane::Comparison,
+                    PaneKind::Portfolio => Pane::Portfolio,
+                    PaneKind::Paper => Pane::Paper,
+                    PaneKind::Account => Pane::Account,
+                    PaneKind::Liquidations => Pane::Liquidations,
+                    PaneKind::Alerts => Pane::Alerts,
+                    PaneKind::Notifications => Pane::Notifications,
+                    PaneKind::AlertHistory => Pane::AlertHistory,
+                    PaneKind::News => Pane::News,
+                    PaneKind::Plugins => Pane::Plugins,
+                    PaneKind::Status => Pane::Status,
+                    PaneKind::Grid => Pane::Grid,
+                    PaneKind::Heatmap => Pane::Heatmap,
+                    PaneKind::Gainers => Pane::Gainers,
+                    PaneKind::Losers => Pane::Losers,
+                };
+                if !panes.contains(&pane) {
+                    panes.push(pane);
+                }
+            }
+            if !panes.is_empty() {
+                return panes;
+            }
+        }
+        let mut panes = match self.view {
+            View::Table if self.show_detail => vec![Pane::Table, Pane::Detail],
+            View::Table => vec![Pane::Table],
+            View::Comparison => vec![Pane::Comparison],
+            View::Portfolio => vec![Pane::Portfolio],
+            View::Paper => vec![Pane::Paper],
+            View::Account => vec![Pane::Account],
+            View::Grid => vec![Pane::Grid],
+            View::Heatmap => vec![Pane::Heatmap],
+            View::Movers => vec![Pane::Gainers, Pane::Losers],
+        };
+        if self.view == View::Table && self.show_liquidations {
+            panes.push(Pane::Liquidations);
+        }
+        if self.view == View::Table && self.show_alerts {
+            panes.push(Pane::Alerts);
+        }
+        if self.view == View::Table && self.show_notifications {
+            panes.push(Pane::Notifications);
+        }
+        if self.view == View::Table && self.show_alert_history {
+            panes.push(Pane::AlertHistory);
+        }
+        if self.view == View::Table && self.show_news {
+            panes.push(Pane::News);
+        }
+        if self.view == View::Table && self.show_plugins {
+            panes.push(Pane::Plugins);
+        }
+        if self.view == View::Table && self.show_status {
+            panes.push(Pane::Status);
+        }
+        panes
+    }
+
+    pub fn focus_next_pane(&mut self) {
+        let panes = self.visible_panes();
+        let i = panes.iter().position(|p| *p == self.focus).unwrap_or(0);
+        self.focus = panes[(i + 1) % panes.len()];
+    }
+
+    fn ensure_focus_visible(&mut self) {
+        let panes = self.visible_panes();
+        if !panes.contains(&self.focus) {
+            self.focus = panes[0];
+        }
+    }
+
+    /// Takes or releases the frozen snapshot after `paused` was toggled.
+    fn sync_pause(&mut self, live: &Arc<Mutex<TickerMap>>) {
+        match (self.paused, &self.frozen) {
+            (true, None) => {
+                let snapshot = live.lock().unwrap().clone();
+                self.frozen = Some(Arc::new(Mutex::new(snapshot)));
+            }
+            (false, Some(frozen)) => {
+                let frozen = frozen.lock().unwrap();
+                let live = live.lock().unwrap();
+                self.resume_diff = live
+                    .values()
+                    .filter(|t| frozen.get(&t.s).is_none_or(|old| old.c != t.c))
+                    .map(|t| t.s.clone())
+                    .collect();
+                drop(frozen);
+                self.frozen = None;
+                self.resumed_at = Some(Instant::now());
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether `ticker` has gone without updates for longer than the configured window. The frozen
+    /// snapshot is never stale while paused.
+    pub(crate) fn is_stale(&self, ticker: &HrTicker) -> bool {
+        let window = Duration::from_secs(self.config.stale_secs);
+        !self.paused
+            && !window.is_zero()
+            && ticker.last_updated.is_some_and(|at| at.elapsed() >= window)
+    }
+
+    /// Row background for `ticker`, fading from the tick color back to `base` after a change.
+    pub(crate) fn flash(&self, ticker: &HrTicker, base: Color) -> Color {
+        if self.config.accessibility.enabled {
+            return base;
+        }
+        let duration = Duration::from_millis(self.config.theme.flash_ms);
+        let Some(elapsed) = ticker.last_changed.map(|at| at.elapsed()) else {
+            return base;
+        };
+        if elapsed >= duration {
+            return base;
+        }
+        let tick = if ticker.c >= ticker.previous_price {
+            self.colors.flash_up
+        } else {
+            self.colors.flash_down
+        };
+        let remaining = 1.0 - elapsed.as_secs_f32() / duration.as_secs_f32();
+        theme::blend(base, tick, remaining)
+    }
+
+    pub(crate) fn changed_while_paused(&self, symbol: &str) -> bool {
+        self.resumed_at
+            .is_some_and(|at| at.elapsed() < RESUME_HIGHLIGHT)
+            && self.resume_diff.contains(symbol)
+    }
+
+    /// Picks up loaded candles and keeps a live chart's last candle at the streamed price.
+    pub fn update_chart(&mut self) {
+        self.sync_chart_pane();
+        self.sync_grid();
+        let live = self.live.lock().unwrap();
+        let charts = self.chart.iter_mut().chain(self.chart_pane.iter_mut());
+        let compared = self.compare_charts.iter_mut().flatten();
+        for chart in charts.chain(compared).chain(self.grid.iter_mut()) {
+            chart.poll();
+            if let Some(ticker) = live.get(&chart.symbol) {
+                chart.update(ticker.c as f64, ticker.E);
+            }
+        }
+    }
+
+    /// Picks up fetched headlines and fetches them again every `refresh_secs` while the news
+    /// pane is shown.
+    fn update_news(&mut self) {
+        if self.visible_panes().contains(&Pane::News) {
+            self.news.poll(&self.config.news);
+        }
+    }
+
+    /// Picks up the exchange's status and fetches it again every `refresh_secs`. A replay has
+    /// no live exchange to report on.
+    fn update_maintenance(&mut self) {
+        if self.switch_exchange.is_some() {
+            self.maintenance
+                .poll(&self.config.maintenance, self.exchange);
+        }
+    }
+
+    /// Banner lines: maintenance of the exchange and delistings of the symbols listed.
+    pub(crate) fn maintenance_warnings(&self) -> Vec<String> {
+        let status = &self.maintenance.status;
+        if !status.system_maintenance && status.announcements.is_empty() {
+            return Vec::new();
+        }
+        let mut symbols = HashSet::new();
+        for symbol in &self.visible_symbols {
+            let market = symbol.split('_').next().unwrap_or(symbol);
+            if let Some(instrument) = self.exchange.instrument(market) {
+                symbols.insert(instrument.base);
+            }
+            symbols.insert(market.to_string());
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        status.warnings(&symbols, now)
+    }
+
+    /// Merges the tickers of the plugins' last refresh into the store, and refreshes them every
+    /// `refresh_secs`.
+    fn update_plugins(&mut self) {
+        let tickers = self.plugins.poll(self.config.plugins.refresh_secs);
+        if !tickers.is_empty() {
+            model::update_tickers(tickers, self.live.clone());
+        }
+    }
+
+    /// Keeps a chart for each of the top symbols in the table while the grid is shown, and
+    /// reloads their candles every `refresh_secs`.
+    fn sync_grid(&mut self) {
+        if !self.visible_panes().contains(&Pane::Grid) {
+            self.grid.clear();
+            return;
+        }
+        let count = (self.config.grid.rows * self.config.grid.columns) as usize;
+        let refresh = Duration::from_secs(self.config.grid.refresh_secs.max(10));
+        let reload = self.grid_loaded_at.elapsed() >= refresh;
+        if reload {
+            self.grid_loaded_at = Instant::now();
+        }
+        // Exchanges without klines chart the recorded history instead.
+        let source = if self.exchange.has_klines() {
+            ChartSource::Live
+        } else {
+            ChartSource::History
+        };
+        let mut previous = std::mem::take(&mut self.grid);
+        for symbol in self.visible_symbols.iter().take(count) {
+            let chart = match previous.iter().position(|chart| chart.symbol == *symbol) {
+                Some(i) => {
+                    let mut chart = previous.swap_remove(i);
+                    if reload {
+                        chart.reload();
+                    }
+                    chart
+                }
+                None => {
+                    ChartView::open(symbol.clone(), source, self.exchange, &self.config.recorder)
+                }
+            };
+            self.grid.push(chart);
+        }
+    }
+
+    /// Keeps the chart pane on the selected symbol while a workspace shows one.
+    fn sync_chart_pane(&mut self) {
+        if !self.visible_panes().contains(&Pane::Chart) {
+            self.chart_pane = None;
+            return;
+        }
+        let Some(symbol) = self.selected_symbol() else {
+            return;
+        };
+        if self
+            .chart_pane
+            .as_ref()
+            .is_some_and(|chart| chart.symbol == symbol)
+        {
+            return;
+        }
+        let source = self
+            .chart_pane
+            .as_ref()
+            .map_or(ChartSource::Live, |chart| chart.source);
+        self.chart_pane = Some(ChartView::open(
+            symbol.to_owned(),
+            source,
+            self.exchange,
+            &self.config.recorder,
+        ));
+    }
+}
+
+/// Applies stream updates to the store. With a non-zero `update_hz`, updates are coalesced so
+/// each symbol is merged at most once per period, keeping only its latest values.
+fn record_status(status: &Mutex<ConnectionStatus>, results: &[HrTicker], stream: &TickerStream) {
+    let mut status = status.lock().unwrap();
+    status.record(results);
+    status.polling = stream.is_polling();
+}
+
+pub async fn apply_updates(
+    mut stream: TickerStream,
+    exchange: Exchange,
+    update_hz: u32,
+    tickers: Arc<Mutex<TickerMap>>,
+    comparison: Arc<Mutex<Comparison>>,
+    status: Arc<Mutex<ConnectionStatus>>,
+    engine: Arc<Mutex<AlertEngine>>,
+) {
+    let client = net::client();
+    let check_alerts = |results: &[HrTicker]| {
+        let mut engine = engine.lock().unwrap();
+        if engine.is_empty() {
+            return;
+        }
+        for (alert, sinks) in engine.evaluate(results) {
+            if let Err(err) = engine.history.record(&alert) {
+                error::report(format!("alert log: {}", err));
+            }
+            notify::post_alert(alert.clone());
+            tokio::spawn(alerts::sink::deliver_all(client.clone(), alert, sinks));
+        }
+    };
+
+    if update_hz == 0 {
+        while let Some(results) = stream.next().await {
+            metrics::increment(&metrics::MESSAGES);
+            record_status(&status, &results, &stream);
+            check_alerts(&results);
+            comparison.lock().unwrap().update(exchange, &results);
+            update_tickers(results, tickers.clone());
+        }
+        status.lock().unwrap().closed = true;
+        return;
+    }
+
+    let mut pending = TickerMap::new();
+    let mut flush = tokio::time::interval(Duration::from_secs(1) / update_hz);
+    loop {
+        tokio::select! {
+            results = stream.next() => match results {
+                Some(results) => {
+                    metrics::increment(&metrics::MESSAGES);
+                    record_status(&status, &results, &stream);
+                    check_alerts(&results);
+                    comparison.lock().unwrap().update(exchange, &results);
+                    pending.extend(results.into_iter().map(|t| (t.s.clone(), t)));
+                }
+                None => {
+                    update_tickers(pending.drain(..).map(|(_, t)| t), tickers.clone());
+                    status.lock().unwrap().closed = true;
+                    break;
+                }
+            },
+            _ = flush.tick() => {
+                if !pending.is_empty() {
+                    update_tickers(pending.drain(..).map(|(_, t)| t), tickers.clone());
+                }
+            }
+        }
+    }
+}
+
+pub async fn run_app(
+    mut app: App,
+    terminal: &mut Terminal<impl Backend>,
+    tickers: Arc<Mutex<TickerMap>>,
+) -> Result<(), Error> {
+    let frame = Duration::from_secs(1) / app.config.fps.clamp(1, MAX_FPS);
+    loop {
+        // Input is applied as soon as it arrives, but drawn at most once a frame.
+        let next_frame = app.drawn_at + frame;
+        app.handle_events(next_frame.saturating_duration_since(Instant::now()))
+            .await?;
+        app.handle_control();
+        if !app.is_running() {
+            break;
+        }
+        if Instant::now() < next_frame {
+            continue;
+        }
+
+        app.update_chart();
+        app.update_news();
+        app.update_maintenance();
+        app.update_plugins();
+
+        app.sync_pause(&tickers);
+        app.check_listings();
+        app.collect_notifications();
+        app.expire_pending_key();
+
+        if !app.needs_redraw() {
+            app.drawn_at = Instant::now();
+            continue;
+        }
+
+        // Draw the UI
+        let started = Instant::now();
+        terminal
+            .draw(|f| {
+                let tickers_clone = Arc::clone(app.frozen.as_ref().unwrap_or(&tickers));
+                app.ticker_length = tickers_clone.lock().unwrap().len();
+                ui(f, &mut app, tickers_clone);
+                theme::downgrade(f.buffer_mut(), app.color_mode);
+                if app.ascii {
+                    ascii::downgrade(f.buffer_mut());
+                }
+            })
+            .map_err(Error::Terminal)?;
+        app.sync_chart_image(terminal)?;
+        metrics::record_render(started.elapsed());
+        app.drawn_at = Instant::now();
+        app.dirty = false;
+    }
+
+    app.save_session();
+    Ok(())
+}
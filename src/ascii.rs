@@ -0,0 +1,55 @@
+//! ASCII-only output for terminals and consoles without unicode, and for screen readers: every
+//! symbol of a drawn frame is replaced by its closest ASCII look-alike.
+
+use ratatui::buffer::Buffer;
+
+/// `c` in ASCII: box drawing as `+-|`, braille and block elements by how much of the cell
+/// they fill, markers by a similar character, and anything else as `?`.
+pub fn to_ascii(c: char) -> char {
+    match c {
+        c if c.is_ascii() => c,
+        '─' | '━' | '═' | '┈' | '┄' | '╌' | '—' | '–' => '-',
+        '│' | '┃' | '║' | '┊' | '┆' | '╎' | '▏' | '▕' => '|',
+        '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' | '╔' | '╗' | '╚' | '╝' | '╭' | '╮'
+        | '╰' | '╯' | '┏' | '┓' | '┗' | '┛' | '╠' | '╣' | '╦' | '╩' | '╬' => {
+            '+'
+        }
+        '▁' | '▂' => '_',
+        '▃' | '▄' => '.',
+        '▅' | '▆' => '=',
+        '▇' | '█' | '▉' | '▊' | '▋' | '▌' | '▍' | '▎' | '▐' => '#',
+        '░' | '▒' | '▓' => ':',
+        '•' | '●' | '★' | '×' => '*',
+        '◌' | '○' | '◍' | 'Ⓢ' => 'o',
+        '▲' | '↑' => '^',
+        '▼' | '↓' | '▾' => 'v',
+        '▶' | '▸' | '→' | '»' => '>',
+        '◀' | '◂' | '←' | '«' => '<',
+        '…' => '.',
+        '\u{2800}' => ' ',
+        '\u{2801}'..='\u{28ff}' => {
+            // Dots 1, 2, 4 and 5 are the upper half of a braille cell.
+            let dots = c as u32 - 0x2800;
+            match (dots & 0b0001_1011 != 0, dots & 0b1110_0100 != 0) {
+                (true, true) => ':',
+                (true, false) => '\'',
+                _ => '.',
+            }
+        }
+        _ => '?',
+    }
+}
+
+/// Replaces the non-ASCII symbols of a drawn frame.
+pub fn downgrade(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        if !cell.symbol().is_ascii() {
+            let mut chars = cell.symbol().chars();
+            let ascii = match (chars.next(), chars.next()) {
+                (Some(c), None) => to_ascii(c),
+                _ => '?',
+            };
+            cell.set_char(ascii);
+        }
+    }
+}
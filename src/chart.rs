@@ -0,0 +1,1280 @@
+//! Candlestick chart popup for the selected symbol, fed either by the exchange's klines or by
+//! the local history database.
+
+use crate::{
+    error::{self, Error},
+    exchange::Exchange,
+    format::{self, NumberFormat},
+    graphics::{self, Canvas, Graphics, Image, Renderer},
+    recorder::Recorder,
+    recorder::RecorderConfig,
+};
+use futures::FutureExt;
+use plotters::{
+    element::{DashedPathElement, PathElement, Rectangle},
+    style::Color as _,
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    ops::Range,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+
+/// Candle width, matching the interval the history database aggregates to.
+pub const INTERVAL: &str = "1m";
+pub const INTERVAL_MS: u64 = 60_000;
+/// Candles requested from the exchange or read from the database per chart.
+pub const CANDLE_LIMIT: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Candle {
+    /// Milliseconds since the epoch at the start of the interval.
+    pub open_time: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Base volume traded in the interval; 0 for candles rebuilt from recorded ticks.
+    pub volume: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartSource {
+    /// Klines fetched from the exchange, kept current with the live price.
+    Live,
+    /// Candles read from the local SQLite database, available offline.
+    History,
+}
+
+impl ChartSource {
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartSource::Live => "Live",
+            ChartSource::History => "History",
+        }
+    }
+}
+
+/// A line the chart can draw over its candles.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Overlay {
+    Sma,
+    Ema,
+    Bollinger,
+    Vwap,
+}
+
+impl Overlay {
+    pub const ALL: [Overlay; 4] = [
+        Overlay::Sma,
+        Overlay::Ema,
+        Overlay::Bollinger,
+        Overlay::Vwap,
+    ];
+}
+
+/// How charts draw their candles.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChartStyle {
+    #[default]
+    Candles,
+    /// A braille line through the closes.
+    Line,
+    /// The line with the area below it shaded.
+    Area,
+}
+
+impl ChartStyle {
+    pub fn next(self) -> Self {
+        match self {
+            ChartStyle::Candles => ChartStyle::Line,
+            ChartStyle::Line => ChartStyle::Area,
+            ChartStyle::Area => ChartStyle::Candles,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartStyle::Candles => "candles",
+            ChartStyle::Line => "line",
+            ChartStyle::Area => "area",
+        }
+    }
+}
+
+/// `[chart]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ChartConfig {
+    /// Overlays shown when a chart opens; the overlay keys toggle them while it is open.
+    pub overlays: Vec<Overlay>,
+    /// How the chart popup is drawn: as an image on terminals that can show one by default.
+    pub renderer: Renderer,
+    /// Style charts open with; the chart style key cycles it while one is open.
+    pub style: ChartStyle,
+    /// How long fetched klines are reused for a symbol before only the newer candles are
+    /// fetched; 0 always fetches them all.
+    pub kline_cache_secs: u64,
+    /// Whether charts open with the volume profile shown.
+    pub volume_profile: bool,
+    /// Whether charts open with a logarithmic price axis.
+    pub log_scale: bool,
+}
+
+impl Default for ChartConfig {
+    fn default() -> Self {
+        Self {
+            overlays: vec![Overlay::Ema, Overlay::Vwap],
+            renderer: Renderer::Auto,
+            style: ChartStyle::Candles,
+            kline_cache_secs: 60,
+            volume_profile: false,
+            log_scale: false,
+        }
+    }
+}
+
+/// How the grid draws each chart.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GridStyle {
+    #[default]
+    Candles,
+    /// A line through the closes, fitting twice as many candles.
+    Line,
+}
+
+/// `[grid]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct GridConfig {
+    pub rows: u16,
+    pub columns: u16,
+    pub style: GridStyle,
+    /// How often the grid's candles are loaded again; the live price is folded in between.
+    pub refresh_secs: u64,
+}
+
+impl Default for GridConfig {
+    fn default() -> Self {
+        Self {
+            rows: 2,
+            columns: 2,
+            style: GridStyle::Candles,
+            refresh_secs: 60,
+        }
+    }
+}
+
+type Pending = JoinHandle<Result<Vec<Candle>, String>>;
+
+/// The latest klines fetched per exchange, symbol and interval, shared by every chart so
+/// reopening one does not download its history again.
+struct KlineCache {
+    ttl: Duration,
+    entries: BTreeMap<(Exchange, String, &'static str), CachedKlines>,
+}
+
+struct CachedKlines {
+    candles: Vec<Candle>,
+    fetched_at: Instant,
+}
+
+static KLINE_CACHE: Mutex<KlineCache> = Mutex::new(KlineCache {
+    ttl: Duration::from_secs(60),
+    entries: BTreeMap::new(),
+});
+
+/// Sets how long fetched klines are reused, from the `kline_cache_secs` config key; zero
+/// turns the cache off.
+pub fn set_kline_cache_ttl(ttl: Duration) {
+    let mut cache = KLINE_CACHE.lock().unwrap();
+    cache.ttl = ttl;
+    if ttl.is_zero() {
+        cache.entries.clear();
+    }
+}
+
+/// `cached` candles followed by `fresh` ones, which replace any cached candle from the first
+/// fresh interval on; the oldest are dropped beyond `CANDLE_LIMIT`.
+pub fn merge_klines(cached: &[Candle], fresh: Vec<Candle>) -> Vec<Candle> {
+    let start = fresh.first().map_or(u64::MAX, |candle| candle.open_time);
+    let mut candles: Vec<Candle> = cached
+        .iter()
+        .filter(|candle| candle.open_time < start)
+        .copied()
+        .collect();
+    candles.extend(fresh);
+    let excess = candles.len().saturating_sub(CANDLE_LIMIT);
+    candles.drain(..excess);
+    candles
+}
+
+/// The latest klines of `symbol`: cached ones while they are fresh, otherwise the cached ones
+/// topped up with the candles from the last cached interval on. Returns them with the klines
+/// that were fetched.
+async fn latest_klines(
+    exchange: Exchange,
+    symbol: &str,
+) -> Result<(Vec<Candle>, Vec<Candle>), Error> {
+    let key = (exchange, symbol.to_string(), INTERVAL);
+    let (ttl, cached) = {
+        let cache = KLINE_CACHE.lock().unwrap();
+        let cached = cache.entries.get(&key).map(|entry| {
+            (
+                entry.fetched_at.elapsed() < cache.ttl,
+                entry.candles.clone(),
+            )
+        });
+        (cache.ttl, cached)
+    };
+    if let Some((true, candles)) = cached {
+        return Ok((candles, Vec::new()));
+    }
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    // The last cached candle is fetched again, as it was likely still open.
+    let limit = cached
+        .as_ref()
+        .and_then(|(_, candles)| candles.last())
+        .map_or(CANDLE_LIMIT, |last| {
+            (now.saturating_sub(last.open_time) / INTERVAL_MS) as usize + 1
+        })
+        .min(CANDLE_LIMIT);
+    let fresh = exchange.klines(symbol, limit, None).await?;
+    let candles = match cached {
+        Some((_, cached)) if limit < CANDLE_LIMIT => merge_klines(&cached, fresh.clone()),
+        _ => fresh.clone(),
+    };
+    if !ttl.is_zero() {
+        let entry = CachedKlines {
+            candles: candles.clone(),
+            fetched_at: Instant::now(),
+        };
+        KLINE_CACHE.lock().unwrap().entries.insert(key, entry);
+    }
+    Ok((candles, fresh))
+}
+
+/// Fewest candles the chart can be zoomed in to.
+const MIN_ZOOM: usize = 8;
+
+/// A span of time the chart can be set to show in full, with the klines it needs fetched a
+/// page at a time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChartRange {
+    Day,
+    Week,
+    Month,
+}
+
+impl ChartRange {
+    /// Intervals the range covers.
+    pub fn candles(self) -> usize {
+        let days = match self {
+            ChartRange::Day => 1,
+            ChartRange::Week => 7,
+            ChartRange::Month => 30,
+        };
+        days * 24 * 60 * 60_000 / INTERVAL_MS as usize
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ChartRange::Day => "last day",
+            ChartRange::Week => "last 7 days",
+            ChartRange::Month => "last 30 days",
+        }
+    }
+
+    /// The next longer range, then back to the latest klines.
+    pub fn next(range: Option<Self>) -> Option<Self> {
+        match range {
+            None => Some(ChartRange::Day),
+            Some(ChartRange::Day) => Some(ChartRange::Week),
+            Some(ChartRange::Week) => Some(ChartRange::Month),
+            Some(ChartRange::Month) => None,
+        }
+    }
+}
+
+pub struct ChartView {
+    pub symbol: String,
+    pub source: ChartSource,
+    pub candles: Vec<Candle>,
+    /// Why the candles could not be loaded, if they could not.
+    pub error: Option<String>,
+    /// Candles shown across the chart; `None` fits one per column.
+    pub zoom: Option<usize>,
+    /// Candles hidden to the right of the view; 0 follows the latest candle.
+    pub offset: usize,
+    /// Index into `candles` of the candle under the crosshair.
+    pub cursor: Option<usize>,
+    /// Range shown in full, instead of the latest candles at one per column.
+    pub range: Option<ChartRange>,
+    /// Candles the chart fits at one per column, as last drawn.
+    width: Cell<usize>,
+    exchange: Exchange,
+    /// The history database, and whether live klines are written to it.
+    path: Option<PathBuf>,
+    store: bool,
+    pending: Option<Pending>,
+    /// Older klines being fetched after panning past the first loaded candle.
+    older: Option<Pending>,
+    /// Set once the exchange has no klines before the first loaded candle.
+    exhausted: bool,
+}
+
+impl ChartView {
+    /// Starts loading `symbol`'s candles from `source` in the background. Live klines are also
+    /// written to the history database when the recorder is enabled.
+    pub fn open(
+        symbol: String,
+        source: ChartSource,
+        exchange: Exchange,
+        recorder: &RecorderConfig,
+    ) -> Self {
+        let mut chart = Self {
+            symbol,
+            source,
+            candles: Vec::new(),
+            error: None,
+            zoom: None,
+            offset: 0,
+            cursor: None,
+            range: None,
+            width: Cell::new(0),
+            exchange,
+            path: recorder.path(),
+            store: recorder.enabled,
+            pending: None,
+            older: None,
+            // History charts read everything the database returns up front.
+            exhausted: source == ChartSource::History,
+        };
+        chart.reload();
+        chart
+    }
+
+    /// Loads the candles again in the background, keeping the current ones until they arrive.
+    pub fn reload(&mut self) {
+        if let Some(pending) = self.pending.take() {
+            pending.abort();
+        }
+        let symbol = self.symbol.clone();
+        self.pending = Some(match self.source {
+            ChartSource::Live => fetch_klines(self.exchange, symbol, None, None, self.store_path()),
+            ChartSource::History => {
+                let path = self.path.clone();
+                tokio::task::spawn_blocking(move || {
+                    let path = path.ok_or("no data directory available")?;
+                    if !path.exists() {
+                        return Err(format!(
+                            "no history at {}; enable the recorder to collect it",
+                            path.display()
+                        ));
+                    }
+                    Recorder::open(&path)
+                        .map_err(|err| err.to_string())?
+                        .candles(&symbol, CANDLE_LIMIT)
+                        .map_err(|err| err.to_string())
+                })
+            }
+        });
+    }
+
+    /// The history database live klines are written to, when the recorder is enabled.
+    fn store_path(&self) -> Option<PathBuf> {
+        self.path.clone().filter(|_| self.store)
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    pub fn is_loading_older(&self) -> bool {
+        self.older.is_some()
+    }
+
+    /// Takes the loaded candles once the background task has finished, without blocking.
+    pub fn poll(&mut self) {
+        if let Some(result) = self.pending.as_mut().and_then(|p| p.now_or_never()) {
+            self.pending = None;
+            match result {
+                Ok(Ok(candles)) => {
+                    self.candles = candles;
+                    self.error = None;
+                    self.load_range();
+                }
+                Ok(Err(err)) => self.error = Some(err),
+                Err(err) => self.error = Some(err.to_string()),
+            }
+        }
+        if let Some(result) = self.older.as_mut().and_then(|p| p.now_or_never()) {
+            self.older = None;
+            match result.unwrap_or_else(|err| Err(err.to_string())) {
+                Ok(older) => {
+                    let first = self.candles.first().map_or(u64::MAX, |c| c.open_time);
+                    let older: Vec<Candle> =
+                        older.into_iter().filter(|c| c.open_time < first).collect();
+                    self.exhausted = older.is_empty();
+                    self.cursor = self.cursor.map(|i| i + older.len());
+                    self.candles.splice(0..0, older);
+                }
+                Err(err) => {
+                    error::report(format!("older klines for {}: {}", self.symbol, err));
+                    self.exhausted = true;
+                }
+            }
+        }
+    }
+
+    /// Folds the latest streamed price into the last candle, opening a new one when the
+    /// interval has rolled over. History charts stay as recorded, and a panned view stays on
+    /// the candles it shows.
+    pub fn update(&mut self, price: f64, time: u64) {
+        if self.source == ChartSource::Live {
+            let before = self.candles.len();
+            fold_price(&mut self.candles, price, time);
+            if self.offset > 0 {
+                self.offset += self.candles.len() - before;
+            }
+        }
+    }
+
+    /// Records how many candles the chart fits at one per column, which the view steps by
+    /// until it is zoomed.
+    pub fn fit(&self, width: usize) {
+        self.width.set(width);
+    }
+
+    /// Number of candles in view.
+    pub fn span(&self) -> usize {
+        self.zoom.unwrap_or(self.width.get()).max(1)
+    }
+
+    /// The candles in view, as a range of `candles`.
+    pub fn window(&self) -> Range<usize> {
+        let end = self.candles.len().saturating_sub(self.offset);
+        end.saturating_sub(self.span())..end
+    }
+
+    pub fn zoom_in(&mut self) {
+        self.zoom = Some((self.span() / 2).max(MIN_ZOOM));
+        self.follow_cursor();
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.zoom = Some((self.span() * 2).min((CANDLE_LIMIT * 4).max(self.span())));
+        self.clamp_offset();
+        self.load_older();
+    }
+
+    /// Moves the view by `candles`, towards older candles when negative.
+    pub fn pan(&mut self, candles: isize) {
+        self.offset = self.offset.saturating_add_signed(-candles);
+        self.clamp_offset();
+        if let Some(cursor) = self.cursor {
+            let window = self.window();
+            self.cursor = Some(cursor.clamp(window.start, window.end.saturating_sub(1)));
+        }
+        self.load_older();
+    }
+
+    /// Moves the crosshair by `candles`, showing it at the latest candle in view first and
+    /// panning when it leaves the view.
+    pub fn move_cursor(&mut self, candles: isize) {
+        if self.candles.is_empty() {
+            return;
+        }
+        let cursor = match self.cursor {
+            Some(cursor) => cursor.saturating_add_signed(candles),
+            None => self.window().end.saturating_sub(1),
+        };
+        self.cursor = Some(cursor.min(self.candles.len() - 1));
+        self.follow_cursor();
+    }
+
+    /// Shows the next longer range in full, or the latest candles again after the longest.
+    pub fn cycle_range(&mut self) {
+        self.range = ChartRange::next(self.range);
+        self.zoom = self.range.map(ChartRange::candles);
+        self.offset = 0;
+        self.cursor = None;
+        self.load_range();
+    }
+
+    /// Returns to the latest candles at the default zoom, without a crosshair.
+    pub fn reset_view(&mut self) {
+        self.range = None;
+        self.zoom = None;
+        self.offset = 0;
+        self.cursor = None;
+    }
+
+    fn follow_cursor(&mut self) {
+        if let Some(cursor) = self.cursor {
+            let window = self.window();
+            if cursor < window.start {
+                self.offset = (self.candles.len() - cursor).saturating_sub(self.span());
+            } else if cursor >= window.end {
+                self.offset = self.candles.len() - 1 - cursor;
+            }
+        }
+        self.clamp_offset();
+        self.load_older();
+    }
+
+    fn clamp_offset(&mut self) {
+        let max = self.candles.len().saturating_sub(self.span());
+        self.offset = self.offset.min(max);
+    }
+
+    /// Fetches the klines before the first loaded candle once the view reaches it.
+    fn load_older(&mut self) {
+        if self.exhausted || self.older.is_some() || self.window().start > 0 {
+            return;
+        }
+        let Some(first) = self.candles.first() else {
+            return;
+        };
+        self.older = Some(fetch_klines(
+            self.exchange,
+            self.symbol.clone(),
+            first.open_time.checked_sub(1),
+            None,
+            self.store_path(),
+        ));
+    }
+
+    /// Fetches the klines the range needs before the first loaded candle, replacing a fetch of
+    /// a single older page.
+    fn load_range(&mut self) {
+        let (Some(range), Some(first), Some(last)) =
+            (self.range, self.candles.first(), self.candles.last())
+        else {
+            return;
+        };
+        let start = last
+            .open_time
+            .saturating_sub((range.candles() as u64 - 1) * INTERVAL_MS);
+        if self.exhausted || first.open_time <= start {
+            return;
+        }
+        let end_time = first.open_time.checked_sub(1);
+        if let Some(older) = self.older.take() {
+            older.abort();
+        }
+        self.older = Some(fetch_klines(
+            self.exchange,
+            self.symbol.clone(),
+            end_time,
+            Some(start),
+            self.store_path(),
+        ));
+    }
+}
+
+/// Klines ending at `end_time`, a page of `CANDLE_LIMIT` or, with `start_time`, as many pages
+/// as it takes to reach it, oldest first.
+async fn klines_before(
+    exchange: Exchange,
+    symbol: &str,
+    end_time: u64,
+    start_time: Option<u64>,
+) -> Result<Vec<Candle>, Error> {
+    let mut pages = Vec::new();
+    let mut end = end_time;
+    loop {
+        let page = exchange.klines(symbol, CANDLE_LIMIT, Some(end)).await?;
+        // A page that does not reach further back would be fetched again and again.
+        let Some(first) = page
+            .first()
+            .map(|c| c.open_time)
+            .filter(|first| *first <= end)
+        else {
+            break;
+        };
+        pages.push(page);
+        match (start_time, first.checked_sub(1)) {
+            (Some(start), Some(before)) if first > start => end = before,
+            _ => break,
+        }
+    }
+    let start = start_time.unwrap_or(0);
+    Ok(pages
+        .into_iter()
+        .rev()
+        .flatten()
+        .filter(|c| c.open_time >= start)
+        .collect())
+}
+
+/// Fetches klines in the background, ending at `end_time` or the latest and going back to
+/// `start_time` when set, and writes the ones fetched to the history database at `store`. The
+/// latest go through the kline cache.
+fn fetch_klines(
+    exchange: Exchange,
+    symbol: String,
+    end_time: Option<u64>,
+    start_time: Option<u64>,
+    store: Option<PathBuf>,
+) -> Pending {
+    tokio::spawn(async move {
+        let (candles, fetched) = match end_time {
+            Some(end_time) => {
+                let candles = klines_before(exchange, &symbol, end_time, start_time).await;
+                let candles = candles.map_err(|err| err.to_string())?;
+                (candles.clone(), candles)
+            }
+            None => latest_klines(exchange, &symbol)
+                .await
+                .map_err(|err| err.to_string())?,
+        };
+        if let Some(path) = store.filter(|_| !fetched.is_empty()) {
+            let stored = fetched;
+            let result = tokio::task::spawn_blocking(move || {
+                Recorder::open(&path)
+                    .map_err(|err| err.to_string())?
+                    .store_klines(&symbol, INTERVAL, &stored)
+                    .map_err(|err| err.to_string())
+            })
+            .await;
+            if let Ok(Err(err)) = result {
+                error::report(format!("storing klines: {}", err));
+            }
+        }
+        Ok(candles)
+    })
+}
+
+/// Folds a streamed price at `time` into the last candle, opening a new one when the interval
+/// has rolled over.
+pub fn fold_price(candles: &mut Vec<Candle>, price: f64, time: u64) {
+    if price <= 0.0 {
+        return;
+    }
+    let Some(last) = candles.last_mut() else {
+        return;
+    };
+    if time < last.open_time {
+        return;
+    }
+    if time < last.open_time + INTERVAL_MS {
+        last.high = last.high.max(price);
+        last.low = last.low.min(price);
+        last.close = price;
+    } else {
+        let open = last.close;
+        candles.push(Candle {
+            open_time: time - time % INTERVAL_MS,
+            open,
+            high: price.max(open),
+            low: price.min(open),
+            close: price,
+            volume: 0.0,
+        });
+    }
+}
+
+/// The closes of the last `count` intervals both `a` and `b` have a candle for, each as its
+/// percent change from the first of them, and numbered from 0 along the x axis.
+pub fn relative_closes(a: &[Candle], b: &[Candle], count: usize) -> [Vec<(f64, f64)>; 2] {
+    let (mut i, mut j) = (0, 0);
+    let mut common = Vec::new();
+    while i < a.len() && j < b.len() {
+        match a[i].open_time.cmp(&b[j].open_time) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                common.push((a[i].close, b[j].close));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let common = &common[common.len().saturating_sub(count)..];
+    let Some(&(base_a, base_b)) = common.first() else {
+        return [Vec::new(), Vec::new()];
+    };
+    let relative = |close: f64, base: f64| (close - base) / base * 100.0;
+    let points = |close: fn(&(f64, f64)) -> f64, base: f64| {
+        common
+            .iter()
+            .enumerate()
+            .map(|(x, closes)| (x as f64, relative(close(closes), base)))
+            .collect()
+    };
+    [points(|c| c.0, base_a), points(|c| c.1, base_b)]
+}
+
+impl Drop for ChartView {
+    fn drop(&mut self) {
+        for pending in self.pending.iter().chain(&self.older) {
+            pending.abort();
+        }
+    }
+}
+
+/// Candlestick plot with a price axis on the left and a time axis along the bottom. The last
+/// `span` candles are shown, one column per candle by default; zoomed in, candles widen to
+/// fill the plot, and zoomed out, neighbouring candles are merged into one column.
+pub struct CandleChart<'a> {
+    candles: &'a [Candle],
+    up: Color,
+    down: Color,
+    axis: Color,
+    /// Lines drawn over the candles, one value per candle.
+    overlays: Vec<(Vec<Option<f64>>, Color)>,
+    span: Option<usize>,
+    /// Index into `candles` of the candle under the crosshair.
+    cursor: Option<usize>,
+    /// Separators of the price labels; plain when unset.
+    number_format: Option<&'a NumberFormat>,
+    /// When set, the plot is drawn as an image into the slot and its cells are left blank.
+    raster: Option<(Graphics, &'a RefCell<Option<Image>>)>,
+    /// Columns right of the plot given to the volume profile; none when 0.
+    profile: u16,
+    style: ChartStyle,
+    /// Whether the price axis is logarithmic.
+    log: bool,
+}
+
+impl<'a> CandleChart<'a> {
+    pub fn new(candles: &'a [Candle]) -> Self {
+        Self {
+            candles,
+            up: Color::Green,
+            down: Color::Red,
+            axis: Color::DarkGray,
+            overlays: Vec::new(),
+            span: None,
+            cursor: None,
+            number_format: None,
+            raster: None,
+            profile: 0,
+            style: ChartStyle::Candles,
+            log: false,
+        }
+    }
+
+    /// Spaces prices logarithmically, so a move of the same percentage is as tall anywhere.
+    pub fn log_scale(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Draws a line, or a shaded area, through the closes instead of candles.
+    pub fn style(mut self, style: ChartStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Adds a line through `values`, which are aligned with the candles.
+    pub fn overlay(mut self, values: Vec<Option<f64>>, color: Color) -> Self {
+        self.overlays.push((values, color));
+        self
+    }
+
+    pub fn colors(mut self, up: Color, down: Color, axis: Color) -> Self {
+        self.up = up;
+        self.down = down;
+        self.axis = axis;
+        self
+    }
+
+    pub fn number_format(mut self, format: &'a NumberFormat) -> Self {
+        self.number_format = Some(format);
+        self
+    }
+
+    fn label(&self, price: f64) -> String {
+        let label = price_label(price);
+        match self.number_format {
+            Some(format) => format.localize(&label),
+            None => label,
+        }
+    }
+
+    /// Color of a line through `visible`, by whether it ends above where it started.
+    fn trend_color(&self, visible: &[Candle]) -> Color {
+        if visible[visible.len() - 1].close >= visible[0].open {
+            self.up
+        } else {
+            self.down
+        }
+    }
+
+    /// Number of candles laid out across the plot; by default as many as it has columns.
+    pub fn span(mut self, span: Option<usize>) -> Self {
+        self.span = span;
+        self
+    }
+
+    pub fn cursor(mut self, cursor: Option<usize>) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// Draws the volume traded at each price row of the shown candles as a histogram `width`
+    /// columns wide to the right of the plot.
+    pub fn volume_profile(mut self, width: u16) -> Self {
+        self.profile = width;
+        self
+    }
+
+    /// Draws the plot as an image for `graphics` into `image` instead of into the buffer.
+    pub fn raster(mut self, graphics: Graphics, image: &'a RefCell<Option<Image>>) -> Self {
+        self.raster = Some((graphics, image));
+        self
+    }
+}
+
+const AXIS_WIDTH: u16 = 12;
+
+/// Braille dots over a grid of cells, two across and four down per cell.
+struct Dots {
+    columns: usize,
+    cells: Vec<u8>,
+}
+
+/// Bit of each dot of a braille cell, by row and column.
+const BRAILLE: [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+impl Dots {
+    fn new(columns: usize, rows: usize) -> Self {
+        Self {
+            columns,
+            cells: vec![0; columns * rows],
+        }
+    }
+
+    fn set(&mut self, x: usize, y: usize) {
+        if x / 2 < self.columns {
+            if let Some(cell) = self.cells.get_mut(y / 4 * self.columns + x / 2) {
+                *cell |= BRAILLE[y % 4][x % 2];
+            }
+        }
+    }
+
+    fn get(&self, column: usize, row: usize) -> u8 {
+        self.cells[row * self.columns + column]
+    }
+}
+
+/// Traces a line through `points`, in dots, and with `area` shades the dots below it down to
+/// `bottom`. Returns the line and the shading.
+fn trace(points: &[(usize, usize)], area: bool, columns: usize, bottom: usize) -> (Dots, Dots) {
+    let rows = bottom.div_ceil(4);
+    let (mut line, mut shade) = (Dots::new(columns, rows), Dots::new(columns, rows));
+    let Some(&(_, first)) = points.first() else {
+        return (line, shade);
+    };
+    let mut previous = first;
+    let segments = points.windows(2).map(|pair| (pair[0], pair[1]));
+    let last = points[points.len() - 1];
+    for ((x0, y0), (x1, y1)) in segments.chain([(last, last)]) {
+        for x in x0..=x1.max(x0) {
+            let y = if x1 > x0 {
+                let t = (x - x0) as f64 / (x1 - x0) as f64;
+                (y0 as f64 + (y1 as f64 - y0 as f64) * t).round() as usize
+            } else {
+                y1
+            };
+            // Steep moves are joined with a vertical run, so the line has no gaps.
+            for y in previous.min(y)..=previous.max(y) {
+                line.set(x, y);
+            }
+            if area {
+                for y in y + 1..bottom {
+                    shade.set(x, y);
+                }
+            }
+            previous = y;
+        }
+    }
+    (line, shade)
+}
+
+/// Fewest plot columns kept when a volume profile is drawn beside the candles.
+const MIN_PROFILE_PLOT: u16 = 20;
+
+/// Eighths of a cell, for the ends of the volume profile's bars.
+const EIGHTHS: [&str; 8] = [" ", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+/// Width of the volume profile beside a chart drawn in `area`, or 0 when the chart is too
+/// narrow for one.
+pub fn profile_width(area: Rect) -> u16 {
+    let width = (area.width / 6).clamp(8, 24);
+    if area.width >= AXIS_WIDTH + MIN_PROFILE_PLOT + width {
+        width
+    } else {
+        0
+    }
+}
+
+/// Volume traded at each of `levels` price levels from `high` down to `low`, each candle's
+/// volume spread evenly over the levels its range covers. Prices are rounded to the nearest
+/// level, as the chart rounds them to rows.
+pub fn volume_profile(candles: &[Candle], high: f64, low: f64, levels: usize) -> Vec<f64> {
+    let mut volumes = vec![0.0; levels];
+    if levels == 0 {
+        return volumes;
+    }
+    let range = (high - low).max(f64::EPSILON);
+    let level = |price: f64| {
+        ((high - price) / range * (levels - 1) as f64)
+            .round()
+            .clamp(0.0, (levels - 1) as f64) as usize
+    };
+    for candle in candles {
+        let (top, bottom) = (level(candle.high), level(candle.low));
+        let share = candle.volume / (bottom - top + 1) as f64;
+        for volume in &mut volumes[top..=bottom] {
+            *volume += share;
+        }
+    }
+    volumes
+}
+
+/// Candles a chart drawn in `area` fits at one per column.
+pub fn plot_width(area: Rect) -> usize {
+    area.width.saturating_sub(AXIS_WIDTH) as usize
+}
+
+/// One candle spanning all of `candles`.
+fn merge(candles: &[Candle]) -> Candle {
+    Candle {
+        open_time: candles[0].open_time,
+        open: candles[0].open,
+        high: candles.iter().map(|c| c.high).fold(f64::MIN, f64::max),
+        low: candles.iter().map(|c| c.low).fold(f64::MAX, f64::min),
+        close: candles[candles.len() - 1].close,
+        volume: candles.iter().map(|c| c.volume).sum(),
+    }
+}
+
+impl Widget for CandleChart<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width <= AXIS_WIDTH + 1 || area.height < 3 || self.candles.is_empty() {
+            return;
+        }
+        // The profile is left out when it would squeeze the candles.
+        let profile = if area.width >= AXIS_WIDTH + MIN_PROFILE_PLOT + self.profile {
+            self.profile
+        } else {
+            0
+        };
+        let plot = Rect {
+            x: area.x + AXIS_WIDTH,
+            y: area.y,
+            width: area.width - AXIS_WIDTH - profile,
+            height: area.height - 1,
+        };
+        let width = plot.width as usize;
+        let span = self.span.unwrap_or(width).max(1);
+        // Candles merged into each column when zoomed out, columns per candle when zoomed in.
+        let per_column = span.div_ceil(width);
+        let column_width = (width / span).max(1);
+        let skipped = self.candles.len().saturating_sub(span);
+        let shown = &self.candles[skipped..];
+        let visible: Vec<Candle> = shown.chunks(per_column).map(merge).collect();
+        let high = visible.iter().map(|c| c.high).fold(f64::MIN, f64::max);
+        let low = visible.iter().map(|c| c.low).fold(f64::MAX, f64::min);
+        // On a log scale equal ratios, rather than equal differences, take equal heights.
+        let scale = |price: f64| {
+            if self.log {
+                price.max(f64::MIN_POSITIVE).ln()
+            } else {
+                price
+            }
+        };
+        let range = (scale(high) - scale(low)).max(f64::EPSILON);
+        // How far down from the top of the plot `price` is, from 0 to 1.
+        let depth = |price: f64| (scale(high) - scale(price)) / range;
+        let rows = plot.height - 1;
+        let row = |price: f64| plot.y + (depth(price) * rows as f64).round() as u16;
+        // Leftmost column of each candle and the column of its wick.
+        let body_width = column_width.saturating_sub(1).max(1);
+        let left = |i: usize| plot.x + (i * column_width) as u16;
+        let wick = |i: usize| left(i) + (body_width / 2) as u16;
+
+        let axis_style = Style::default().fg(self.axis);
+        let middle = if self.log {
+            ((scale(high) + scale(low)) / 2.0).exp()
+        } else {
+            (high + low) / 2.0
+        };
+        for (y, price) in [
+            (plot.y, high),
+            (plot.y + rows / 2, middle),
+            (plot.y + rows, low),
+        ] {
+            buf.set_stringn(
+                area.x,
+                y,
+                format!("{:>11}", self.label(price)),
+                AXIS_WIDTH as usize - 1,
+                axis_style,
+            );
+        }
+
+        let cursor = self
+            .cursor
+            .and_then(|i| i.checked_sub(skipped))
+            .map(|i| i / per_column)
+            .filter(|i| *i < visible.len());
+        if let Some(i) = cursor {
+            buf.set_stringn(
+                area.x,
+                row(visible[i].close),
+                format!("{:>11}", self.label(visible[i].close)),
+                AXIS_WIDTH as usize - 1,
+                axis_style.add_modifier(Modifier::REVERSED),
+            );
+        }
+
+        if profile > 1 {
+            // One blank column between the candles and the bars, and the level of the most
+            // volume, the point of control, highlighted.
+            let scaled: Vec<Candle> = shown
+                .iter()
+                .map(|candle| Candle {
+                    high: scale(candle.high),
+                    low: scale(candle.low),
+                    ..*candle
+                })
+                .collect();
+            let volumes = volume_profile(&scaled, scale(high), scale(low), plot.height as usize);
+            let most = volumes.iter().copied().fold(0.0, f64::max);
+            let x = plot.x + plot.width + 1;
+            let width = (profile - 1) as usize;
+            for (i, volume) in volumes.iter().enumerate().filter(|_| most > 0.0) {
+                let eighths = (volume / most * (width * 8) as f64).round() as usize;
+                let mut bar = "█".repeat(eighths / 8);
+                let partial = eighths % 8;
+                if partial > 0 {
+                    bar.push_str(EIGHTHS[partial]);
+                }
+                let style = if *volume == most {
+                    Style::default().fg(self.up).add_modifier(Modifier::BOLD)
+                } else {
+                    axis_style
+                };
+                buf.set_stringn(x, plot.y + i as u16, bar, width, style);
+            }
+        }
+
+        let axis_y = plot.y + plot.height;
+        let first = format::timestamp(visible[0].open_time);
+        let last = format::timestamp(visible[visible.len() - 1].open_time);
+        buf.set_stringn(plot.x, axis_y, &first, plot.width as usize, axis_style);
+        if plot.width as usize >= first.len() + last.len() + 2 {
+            let x = plot.x + plot.width - last.len() as u16;
+            buf.set_string(x, axis_y, &last, axis_style);
+        }
+
+        if let Some((graphics, image)) = self.raster {
+            let (cell_width, cell_height) = (graphics.cell.0 as i32, graphics.cell.1 as i32);
+            let mut canvas = Canvas::new(
+                plot.width as u32 * cell_width as u32,
+                plot.height as u32 * cell_height as u32,
+            );
+            // Prices span from the middle of the top row to the middle of the bottom row, level
+            // with their axis labels.
+            let y = |price: f64| {
+                cell_height / 2 + (depth(price) * (rows as i32 * cell_height) as f64) as i32
+            };
+            let slot = column_width as i32 * cell_width;
+            let center = |i: usize| i as i32 * slot + slot / 2;
+            let (right, bottom) = (canvas.width as i32 - 1, canvas.height as i32 - 1);
+            let drawn = canvas.draw(|area| {
+                let axis = graphics::rgb(self.axis);
+                if let Some(i) = cursor {
+                    let price = y(visible[i].close);
+                    area.draw(&DashedPathElement::new(
+                        [(0, price), (right, price)],
+                        3,
+                        3,
+                        axis,
+                    ))?;
+                    let x = center(i);
+                    area.draw(&DashedPathElement::new([(x, 0), (x, bottom)], 3, 3, axis))?;
+                }
+                if self.style != ChartStyle::Candles {
+                    let color = graphics::rgb(self.trend_color(&visible));
+                    let points: Vec<(i32, i32)> = (0..visible.len())
+                        .map(|i| (center(i), y(visible[i].close)))
+                        .collect();
+                    if self.style == ChartStyle::Area {
+                        // A hatch below the line, lighter than the line itself.
+                        for pair in points.windows(2) {
+                            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                            for x in x0..x1 {
+                                let top = y0 + (y1 - y0) * (x - x0) / (x1 - x0);
+                                area.draw(&DashedPathElement::new(
+                                    [(x, top), (x, bottom)],
+                                    1,
+                                    1,
+                                    color,
+                                ))?;
+                            }
+                        }
+                    }
+                    area.draw(&PathElement::new(points, color))?;
+                }
+                let half_body = (slot * 7 / 20).max(1);
+                for (i, candle) in visible
+                    .iter()
+                    .enumerate()
+                    .filter(|_| self.style == ChartStyle::Candles)
+                {
+                    let color = graphics::rgb(if candle.close >= candle.open {
+                        self.up
+                    } else {
+                        self.down
+                    });
+                    let x = center(i);
+                    area.draw(&PathElement::new(
+                        [(x, y(candle.high)), (x, y(candle.low))],
+                        color,
+                    ))?;
+                    // The corners of a filled rectangle are its first pixel and the one past its
+                    // last.
+                    area.draw(&Rectangle::new(
+                        [
+                            (x - half_body, y(candle.open.max(candle.close))),
+                            (x + half_body, y(candle.open.min(candle.close)) + 1),
+                        ],
+                        color.filled(),
+                    ))?;
+                }
+                for (values, color) in &self.overlays {
+                    let values = values.get(skipped..).unwrap_or_default();
+                    let points: Vec<(i32, i32)> = values
+                        .chunks(per_column)
+                        .enumerate()
+                        .take(visible.len())
+                        .filter_map(|(i, chunk)| Some((center(i), y(chunk[chunk.len() - 1]?))))
+                        .collect();
+                    area.draw(&PathElement::new(points, graphics::rgb(*color)))?;
+                }
+                Ok(())
+            });
+            // Plotters only fails on a broken backend; the unicode chart is drawn then instead.
+            if drawn.is_ok() {
+                *image.borrow_mut() = Some(Image { area: plot, canvas });
+                return;
+            }
+        }
+
+        if self.style != ChartStyle::Candles {
+            // Closes are placed in dots level with the middle of their rows.
+            let dot = |price: f64| (depth(price) * (rows * 4) as f64 + 1.5).round();
+            let points: Vec<(usize, usize)> = visible
+                .iter()
+                .enumerate()
+                .map(|(i, candle)| ((wick(i) - plot.x) as usize * 2, dot(candle.close) as usize))
+                .collect();
+            let (line, shade) = trace(
+                &points,
+                self.style == ChartStyle::Area,
+                plot.width as usize,
+                plot.height as usize * 4,
+            );
+            let color = self.trend_color(&visible);
+            for row in 0..plot.height {
+                for column in 0..plot.width {
+                    let (dots, shaded) = (
+                        line.get(column as usize, row as usize),
+                        shade.get(column as usize, row as usize),
+                    );
+                    if dots | shaded == 0 {
+                        continue;
+                    }
+                    let style = if dots == 0 {
+                        Style::default().fg(color).add_modifier(Modifier::DIM)
+                    } else {
+                        Style::default().fg(color)
+                    };
+                    let symbol = char::from_u32(0x2800 + (dots | shaded) as u32).unwrap_or(' ');
+                    buf.get_mut(plot.x + column, plot.y + row)
+                        .set_char(symbol)
+                        .set_style(style);
+                }
+            }
+        }
+
+        for (i, candle) in visible
+            .iter()
+            .enumerate()
+            .filter(|_| self.style == ChartStyle::Candles)
+        {
+            let style = Style::default().fg(if candle.close >= candle.open {
+                self.up
+            } else {
+                self.down
+            });
+            for y in row(candle.high)..=row(candle.low) {
+                buf.get_mut(wick(i), y).set_symbol("│").set_style(style);
+            }
+            let body_top = row(candle.open.max(candle.close));
+            let body_bottom = row(candle.open.min(candle.close));
+            for x in left(i)..left(i) + body_width as u16 {
+                for y in body_top..=body_bottom {
+                    buf.get_mut(x, y).set_symbol("┃").set_style(style);
+                }
+            }
+        }
+
+        for (values, color) in &self.overlays {
+            let style = Style::default().fg(*color);
+            let values = values.get(skipped..).unwrap_or_default();
+            for (i, chunk) in values.chunks(per_column).enumerate().take(visible.len()) {
+                if let Some(value) = chunk[chunk.len() - 1].filter(|v| (low..=high).contains(v)) {
+                    buf.get_mut(wick(i), row(value))
+                        .set_symbol("•")
+                        .set_style(style);
+                }
+            }
+        }
+
+        // The crosshair only fills empty cells, so it never hides a candle or an overlay.
+        if let Some(i) = cursor {
+            let y = row(visible[i].close);
+            for x in plot.x..plot.x + plot.width {
+                let cell = buf.get_mut(x, y);
+                if cell.symbol() == " " {
+                    cell.set_symbol("┈").set_style(axis_style);
+                }
+            }
+            for y in plot.y..plot.y + plot.height {
+                let cell = buf.get_mut(wick(i), y);
+                if cell.symbol() == " " {
+                    cell.set_symbol("┊").set_style(axis_style);
+                }
+            }
+        }
+    }
+}
+
+/// Axis labels with enough precision for both BTC- and sub-cent-priced symbols.
+pub fn price_label(price: f64) -> String {
+    let magnitude = price.abs().max(f64::MIN_POSITIVE).log10().floor() as i32;
+    let decimals = (4 - magnitude).clamp(0, 8) as usize;
+    format!("{:.*}", decimals, price)
+}
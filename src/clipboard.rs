@@ -0,0 +1,31 @@
+//! Copying to the system clipboard: `y` copies the selected symbol and `Y` its row.
+
+use crate::error::Error;
+
+/// Connects on the first copy and then stays open: on X11 and Wayland the copied text is
+/// served by this process, so it is only pasteable while the connection lives.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn copy(&mut self, text: &str) -> Result<(), Error> {
+        let clipboard = match &mut self.inner {
+            Some(clipboard) => clipboard,
+            None => self.inner.insert(arboard::Clipboard::new()?),
+        };
+        clipboard.set_text(text)?;
+        Ok(())
+    }
+}
+
+/// One row as tab-separated cells. Tabs and line breaks inside a cell become spaces, so the
+/// row pastes into a single spreadsheet row.
+pub fn tsv_row(cells: &[String]) -> String {
+    cells
+        .iter()
+        .map(|cell| cell.replace(['\t', '\n', '\r'], " "))
+        .collect::<Vec<_>>()
+        .join("\t")
+}
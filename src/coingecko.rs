@@ -0,0 +1,194 @@
+use crate::{error, exchange::Exchange, model::TickerMap, net};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::watch;
+
+const MARKETS_URL: &str = "https://api.coingecko.com/api/v3/coins/markets";
+const GLOBAL_URL: &str = "https://api.coingecko.com/api/v3/global";
+/// The public API allows roughly 30 calls a minute; stay well below it.
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(3);
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(60);
+const PAGE_SIZE: u32 = 250;
+
+/// `[coingecko]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct CoinGeckoConfig {
+    pub enabled: bool,
+    /// Optional demo API key, sent as `x-cg-demo-api-key`.
+    pub api_key: Option<String>,
+    pub refresh_secs: u64,
+    /// Upper bound on market pages (250 coins each) fetched per refresh.
+    pub max_pages: u32,
+}
+
+impl Default for CoinGeckoConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            api_key: None,
+            refresh_secs: 300,
+            max_pages: 4,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct MarketData {
+    pub market_cap: Option<f64>,
+    #[serde(rename = "market_cap_rank")]
+    pub rank: Option<u32>,
+    pub circulating_supply: Option<f64>,
+}
+
+#[derive(Deserialize)]
+struct Coin {
+    symbol: String,
+    #[serde(flatten)]
+    data: MarketData,
+}
+
+/// Market data by upper-case coin symbol, kept across refreshes.
+pub type MarketDataCache = Arc<Mutex<HashMap<String, MarketData>>>;
+
+/// Figures of the whole crypto market.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlobalData {
+    /// In USD.
+    pub total_market_cap: f64,
+    /// BTC's share of the total market cap, in percent.
+    pub btc_dominance: f64,
+}
+
+/// The latest global figures, once fetched.
+pub type GlobalCache = Arc<Mutex<Option<GlobalData>>>;
+
+#[derive(Deserialize)]
+struct Global {
+    data: GlobalFields,
+}
+
+#[derive(Deserialize)]
+struct GlobalFields {
+    total_market_cap: HashMap<String, f64>,
+    market_cap_percentage: HashMap<String, f64>,
+}
+
+/// CoinGecko symbol for an exchange base asset, without the `1000` multiplier prefixes futures
+/// exchanges use for low-priced coins.
+pub fn coin_symbol(base: &str) -> &str {
+    base.strip_prefix("1000000")
+        .or_else(|| base.strip_prefix("1000"))
+        .filter(|rest| !rest.is_empty())
+        .unwrap_or(base)
+}
+
+async fn fetch_page(
+    client: &reqwest::Client,
+    config: &CoinGeckoConfig,
+    page: u32,
+) -> Result<Vec<Coin>, reqwest::Error> {
+    let mut request = client
+        .get(MARKETS_URL)
+        .header("User-Agent", "crypto_tui_ticker")
+        .query(&[
+            ("vs_currency", "usd".to_string()),
+            ("order", "market_cap_desc".to_string()),
+            ("per_page", PAGE_SIZE.to_string()),
+            ("page", page.to_string()),
+        ]);
+    if let Some(key) = &config.api_key {
+        request = request.header("x-cg-demo-api-key", key);
+    }
+    request.send().await?.error_for_status()?.json().await
+}
+
+async fn fetch_global(
+    client: &reqwest::Client,
+    config: &CoinGeckoConfig,
+) -> Result<Option<GlobalData>, reqwest::Error> {
+    let mut request = client
+        .get(GLOBAL_URL)
+        .header("User-Agent", "crypto_tui_ticker");
+    if let Some(key) = &config.api_key {
+        request = request.header("x-cg-demo-api-key", key);
+    }
+    let global: Global = request.send().await?.error_for_status()?.json().await?;
+    let fields = global.data;
+    Ok(fields
+        .total_market_cap
+        .get("usd")
+        .zip(fields.market_cap_percentage.get("btc"))
+        .map(|(cap, dominance)| GlobalData {
+            total_market_cap: *cap,
+            btc_dominance: *dominance,
+        }))
+}
+
+/// Periodically fetches market data for the base assets of `tickers` into `cache`, and the
+/// global market figures into `global`.
+pub async fn run(
+    config: CoinGeckoConfig,
+    exchange: watch::Receiver<Exchange>,
+    tickers: Arc<Mutex<TickerMap>>,
+    cache: MarketDataCache,
+    global: GlobalCache,
+) {
+    let client = net::client();
+    loop {
+        let exchange = *exchange.borrow();
+        let wanted: HashSet<String> = tickers
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|symbol| exchange.instrument(symbol))
+            .map(|i| coin_symbol(&i.base).to_string())
+            .collect();
+
+        let mut found: HashMap<String, MarketData> = HashMap::new();
+        for page in 1..=config.max_pages {
+            if page > 1 {
+                tokio::time::sleep(MIN_REQUEST_INTERVAL).await;
+            }
+            match fetch_page(&client, &config, page).await {
+                Ok(coins) if coins.is_empty() => break,
+                Ok(coins) => {
+                    // Pages are ordered by market cap, so the largest coin keeps a shared symbol.
+                    for coin in coins {
+                        found
+                            .entry(coin.symbol.to_ascii_uppercase())
+                            .or_insert(coin.data);
+                    }
+                }
+                Err(err) if err.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {
+                    tokio::time::sleep(RATE_LIMIT_BACKOFF).await;
+                    break;
+                }
+                Err(err) => {
+                    error::report(format!("CoinGecko: {}", err));
+                    break;
+                }
+            }
+            if !wanted.is_empty() && wanted.iter().all(|s| found.contains_key(s)) {
+                break;
+            }
+        }
+
+        found.retain(|symbol, _| wanted.contains(symbol));
+        cache.lock().unwrap().extend(found);
+
+        tokio::time::sleep(MIN_REQUEST_INTERVAL).await;
+        match fetch_global(&client, &config).await {
+            Ok(Some(data)) => *global.lock().unwrap() = Some(data),
+            Ok(None) => {}
+            Err(err) if err.status() == Some(StatusCode::TOO_MANY_REQUESTS) => {}
+            Err(err) => error::report(format!("CoinGecko: {}", err)),
+        }
+        tokio::time::sleep(Duration::from_secs(config.refresh_secs.max(60))).await;
+    }
+}
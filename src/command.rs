@@ -0,0 +1,116 @@
+//! Command palette: fuzzy search over symbols, keymap actions, themes and workspaces.
+
+use crate::keymap::Action;
+use ratatui::widgets::ListState;
+
+/// Something the palette can run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Select the symbol in the table.
+    Symbol(String),
+    /// Run an action as if its key was pressed.
+    Action(Action),
+    /// Switch to the named theme.
+    Theme(String),
+    /// Show the workspace with this index and name.
+    Workspace(usize, String),
+    /// Show the watchlist tab with this index and name.
+    Watchlist(usize, String),
+    /// Show only the symbols with this tag, or everything again if the table already does.
+    Tag(String),
+}
+
+impl Command {
+    pub fn label(&self) -> String {
+        match self {
+            Command::Symbol(symbol) => symbol.clone(),
+            Command::Action(action) => action.description().to_string(),
+            Command::Theme(name) => format!("theme: {}", name),
+            Command::Workspace(i, name) => format!("workspace {}: {}", i + 1, name),
+            Command::Watchlist(_, name) => format!("watchlist: {}", name),
+            Command::Tag(tag) => format!("#{}", tag),
+        }
+    }
+}
+
+pub struct CommandPalette {
+    pub input: String,
+    /// Every command, in the order shown for an empty query.
+    commands: Vec<Command>,
+    /// Commands matching `input`, best first.
+    pub matches: Vec<Command>,
+    pub state: ListState,
+}
+
+impl CommandPalette {
+    pub fn new(commands: Vec<Command>) -> Self {
+        let mut palette = Self {
+            input: String::new(),
+            matches: Vec::new(),
+            commands,
+            state: ListState::default(),
+        };
+        palette.filter();
+        palette
+    }
+
+    /// Recomputes the matches after `input` changed and selects the best one.
+    pub fn filter(&mut self) {
+        let mut scored: Vec<(u32, &Command)> = self
+            .commands
+            .iter()
+            .filter_map(|command| {
+                fuzzy_score(&self.input, &command.label()).map(|score| (score, command))
+            })
+            .collect();
+        // Stable, so equal scores keep the empty-query order.
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, c)| c.clone()).collect();
+        self.state.select((!self.matches.is_empty()).then_some(0));
+    }
+
+    pub fn next(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state.select(Some((i + 1).min(self.matches.len() - 1)));
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if let Some(i) = self.state.selected() {
+            self.state.select(Some(i.saturating_sub(1)));
+        }
+    }
+
+    pub fn chosen(&self) -> Option<&Command> {
+        self.state.selected().and_then(|i| self.matches.get(i))
+    }
+}
+
+/// Scores `text` against `query` when the query's characters appear in it in order, ignoring
+/// case. Consecutive matches and matches at the start of a word score higher, so `btc` ranks
+/// `BTCUSDT` above `BATCHUSDT`. An empty query matches everything equally.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<u32> {
+    let mut score = 0;
+    let mut chars = text.chars().enumerate();
+    let mut before: Option<char> = None;
+    let mut last_match: Option<usize> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_ascii_lowercase();
+        let (i, c) = loop {
+            let (i, c) = chars.next()?;
+            if c.to_ascii_lowercase() == wanted {
+                break (i, c);
+            }
+            before = Some(c);
+        };
+        score += 1;
+        if before.is_none_or(|before| !before.is_alphanumeric()) {
+            score += 8;
+        } else if last_match.is_some_and(|j| j + 1 == i) {
+            score += 5;
+        }
+        before = Some(c);
+        last_match = Some(i);
+    }
+    Some(score)
+}
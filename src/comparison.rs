@@ -0,0 +1,58 @@
+use crate::{exchange::Exchange, model::HrTicker};
+use std::collections::{BTreeMap, HashMap};
+
+/// Last prices of the same market across exchanges, keyed by `Instrument::comparison_key`.
+#[derive(Debug, Default)]
+pub struct Comparison {
+    prices: HashMap<String, BTreeMap<Exchange, f32>>,
+}
+
+pub struct ComparisonRow<'a> {
+    pub key: &'a str,
+    pub prices: &'a BTreeMap<Exchange, f32>,
+    /// Spread between the highest and lowest price in basis points of the lowest.
+    pub spread_bps: Option<f64>,
+}
+
+impl Comparison {
+    pub fn update(&mut self, exchange: Exchange, tickers: &[HrTicker]) {
+        for ticker in tickers {
+            if let Some(instrument) = exchange.instrument(&ticker.s) {
+                self.prices
+                    .entry(instrument.comparison_key())
+                    .or_default()
+                    .insert(exchange, ticker.c);
+            }
+        }
+    }
+
+    /// Markets quoted on at least `min_exchanges` exchanges, widest spread first.
+    pub fn rows(&self, min_exchanges: usize) -> Vec<ComparisonRow<'_>> {
+        let mut rows: Vec<ComparisonRow> = self
+            .prices
+            .iter()
+            .filter(|(_, prices)| prices.len() >= min_exchanges)
+            .map(|(key, prices)| ComparisonRow {
+                key,
+                prices,
+                spread_bps: spread_bps(prices),
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            b.spread_bps
+                .unwrap_or(0.0)
+                .total_cmp(&a.spread_bps.unwrap_or(0.0))
+                .then_with(|| a.key.cmp(b.key))
+        });
+        rows
+    }
+}
+
+fn spread_bps(prices: &BTreeMap<Exchange, f32>) -> Option<f64> {
+    if prices.len() < 2 {
+        return None;
+    }
+    let min = prices.values().copied().fold(f32::INFINITY, f32::min) as f64;
+    let max = prices.values().copied().fold(f32::NEG_INFINITY, f32::max) as f64;
+    (min > 0.0).then(|| (max - min) / min * 10_000.0)
+}
@@ -0,0 +1,196 @@
+use crate::{
+    accessibility::AccessibilityConfig,
+    alerts::AlertRule,
+    chart::{ChartConfig, GridConfig},
+    coingecko::CoinGeckoConfig,
+    control::ControlConfig,
+    denomination::DenominationConfig,
+    exchange::Exchange,
+    export::ExportConfig,
+    format::NumberFormat,
+    heatmap::HeatmapConfig,
+    indicators::IndicatorsConfig,
+    keymap::Action,
+    liquidations::LiquidationsConfig,
+    listings::ListingsConfig,
+    maintenance::MaintenanceConfig,
+    model::{ChangeBasis, SortColumn, SortOrder},
+    movers::MoversConfig,
+    news::NewsConfig,
+    notes::SymbolNote,
+    paper::PaperConfig,
+    plugins::PluginsConfig,
+    recorder::RecorderConfig,
+    script::ScriptConfig,
+    summary::SummaryConfig,
+    theme::ThemeConfig,
+    watchlist::WatchlistConfig,
+    workspace::{self, WorkspaceConfig},
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+const APP_DIR: &str = "crypto_tui_ticker";
+const CONFIG_FILE: &str = "config.toml";
+
+/// User settings persisted in `<config dir>/crypto_tui_ticker/config.toml`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// Exchange whose tickers fill the table; `--exchange` takes precedence.
+    pub exchange: Exchange,
+    /// Quote assets the table is limited to, e.g. `["USDT"]`; every market is listed when
+    /// empty.
+    pub quotes: Vec<String>,
+    /// Symbols pinned to the top of the table, in the order they were pinned.
+    pub pinned: Vec<String>,
+    /// Note and tags per symbol, edited with `#`.
+    pub notes: BTreeMap<String, SymbolNote>,
+    /// Rounding applied to derived metrics.
+    pub format: NumberFormat,
+    /// When set, a memory-mapped snapshot of the tickers is kept at this path.
+    pub shm_path: Option<PathBuf>,
+    pub recorder: RecorderConfig,
+    /// Extra exchanges streamed for the comparison view.
+    pub compare_exchanges: Vec<Exchange>,
+    pub coingecko: CoinGeckoConfig,
+    pub indicators: IndicatorsConfig,
+    pub chart: ChartConfig,
+    pub grid: GridConfig,
+    pub heatmap: HeatmapConfig,
+    pub movers: MoversConfig,
+    pub denomination: DenominationConfig,
+    pub liquidations: LiquidationsConfig,
+    /// How often coalesced stream updates are applied to the table; 0 applies them immediately.
+    pub update_hz: u32,
+    /// Rows without an update for this many seconds are dimmed as stale; 0 disables the check.
+    pub stale_secs: u64,
+    /// Most frames drawn per second; frames are only drawn when something changed.
+    pub fps: u32,
+    /// Adds a column with the time since each row last ticked.
+    pub updated_column: bool,
+    /// While the Binance websocket cannot connect, its REST tickers are polled this often; 0
+    /// only retries the websocket.
+    pub poll_secs: u64,
+    pub theme: ThemeConfig,
+    pub accessibility: AccessibilityConfig,
+    /// Column that orders rows whose sort column values are equal.
+    pub secondary_sort: SortColumn,
+    /// What the Percent Change column measures against at startup; `%` cycles it.
+    pub change_basis: ChangeBasis,
+    /// Sort direction per column, saved whenever a column's sort is reversed; columns that are
+    /// not listed use their default.
+    pub sort_orders: BTreeMap<SortColumn, SortOrder>,
+    /// Price alerts, evaluated against every stream update.
+    pub alerts: Vec<AlertRule>,
+    pub paper: PaperConfig,
+    pub export: ExportConfig,
+    pub listings: ListingsConfig,
+    pub summary: SummaryConfig,
+    pub news: NewsConfig,
+    pub maintenance: MaintenanceConfig,
+    pub script: ScriptConfig,
+    pub plugins: PluginsConfig,
+    pub control: ControlConfig,
+    /// Key bindings per action, replacing the defaults of the actions listed.
+    pub keys: BTreeMap<Action, Vec<String>>,
+    /// Workspaces 2 and up, in number key order.
+    pub workspaces: Vec<WorkspaceConfig>,
+    /// Watchlist tabs after the tab listing every symbol, in `]` order.
+    pub watchlists: Vec<WatchlistConfig>,
+    /// Proxy for every exchange and REST connection, e.g. `socks5://127.0.0.1:1080`; `--proxy`
+    /// takes precedence.
+    pub proxy: Option<String>,
+    /// Reopen with the selection, sort, theme, panes and filters the app was quit with.
+    pub restore_session: bool,
+    /// Directory the config is saved to, with the holdings and trade log beside it; the user's
+    /// config directory when `None`.
+    #[serde(skip)]
+    pub home: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            exchange: Exchange::Binance,
+            quotes: Vec::new(),
+            pinned: Vec::new(),
+            notes: BTreeMap::new(),
+            format: NumberFormat::default(),
+            shm_path: None,
+            recorder: RecorderConfig::default(),
+            compare_exchanges: Vec::new(),
+            coingecko: CoinGeckoConfig::default(),
+            indicators: IndicatorsConfig::default(),
+            chart: ChartConfig::default(),
+            grid: GridConfig::default(),
+            heatmap: HeatmapConfig::default(),
+            movers: MoversConfig::default(),
+            denomination: DenominationConfig::default(),
+            liquidations: LiquidationsConfig::default(),
+            update_hz: 2,
+            stale_secs: 60,
+            fps: 10,
+            updated_column: false,
+            poll_secs: 5,
+            theme: ThemeConfig::default(),
+            accessibility: AccessibilityConfig::default(),
+            secondary_sort: SortColumn::Symbol,
+            change_basis: ChangeBasis::Day,
+            sort_orders: BTreeMap::new(),
+            alerts: Vec::new(),
+            paper: PaperConfig::default(),
+            export: ExportConfig::default(),
+            listings: ListingsConfig::default(),
+            summary: SummaryConfig::default(),
+            news: NewsConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            script: ScriptConfig::default(),
+            plugins: PluginsConfig::default(),
+            control: ControlConfig::default(),
+            keys: BTreeMap::new(),
+            workspaces: workspace::defaults(),
+            watchlists: Vec::new(),
+            proxy: None,
+            restore_session: true,
+            home: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn dir() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR))
+    }
+
+    pub fn path() -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join(CONFIG_FILE))
+    }
+
+    /// [`Config::home`], or the user's config directory.
+    pub fn home_dir(&self) -> Option<PathBuf> {
+        self.home.clone().or_else(Self::dir)
+    }
+
+    /// Loads the config file, falling back to defaults when it is missing or unreadable.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|contents| toml::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let path = self
+            .home_dir()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "no config directory available")
+            })?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = toml::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(path, contents)
+    }
+}
@@ -0,0 +1,224 @@
+//! Control socket: a Unix socket taking one JSON command per line, so window-manager scripts
+//! and other local tools can drive a running instance.
+//!
+//! ```text
+//! {"command": "select", "symbol": "BTCUSDT"}
+//! {"command": "add_alert", "name": "BTC 70k", "symbol": "BTCUSDT", "when": "c crosses 70000"}
+//! {"command": "view", "view": "heatmap"}
+//! {"command": "workspace", "number": 2}
+//! {"command": "export"}
+//! {"command": "quit"}
+//! ```
+//!
+//! Each command is answered with one line, `{"ok": true}` with an optional `result`, or
+//! `{"ok": false, "error": "..."}`.
+
+use crate::app::View;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::{mpsc, oneshot};
+
+/// `[control]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ControlConfig {
+    pub enabled: bool,
+    /// Socket path; `crypto_tui_ticker.sock` in `$XDG_RUNTIME_DIR`, or in a directory of the
+    /// user's own under the temporary directory, by default.
+    pub path: Option<PathBuf>,
+}
+
+impl Default for ControlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: None,
+        }
+    }
+}
+
+impl ControlConfig {
+    pub fn path(&self) -> PathBuf {
+        self.path.clone().unwrap_or_else(|| {
+            std::env::var_os("XDG_RUNTIME_DIR")
+                .map_or_else(user_temp_dir, PathBuf::from)
+                .join("crypto_tui_ticker.sock")
+        })
+    }
+}
+
+/// A directory under the shared temporary directory named after the user, so users do not
+/// pick the same socket; `serve` creates it private to the user.
+#[cfg(unix)]
+fn user_temp_dir() -> PathBuf {
+    // SAFETY: getuid has no preconditions and cannot fail.
+    let uid = unsafe { libc::getuid() };
+    std::env::temp_dir().join(format!("crypto_tui_ticker-{}", uid))
+}
+
+#[cfg(not(unix))]
+fn user_temp_dir() -> PathBuf {
+    std::env::temp_dir().join("crypto_tui_ticker")
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum ControlRequest {
+    /// Select the symbol in the table.
+    Select {
+        symbol: String,
+    },
+    /// Add an alert rule, saved to the config file like one added in the alerts pane.
+    AddAlert {
+        name: String,
+        #[serde(default)]
+        symbol: Option<String>,
+        when: String,
+    },
+    /// Show a view of the table screen.
+    View {
+        view: View,
+    },
+    /// Show workspace `number`, counting from 1.
+    Workspace {
+        number: usize,
+    },
+    /// Export the table as shown; the result is the file's path.
+    Export,
+    Quit,
+}
+
+/// What a command returns on success, or why it failed.
+pub type ControlReply = Result<Option<String>, String>;
+
+/// A command for the app, which answers on `reply`.
+pub struct ControlMessage {
+    pub request: ControlRequest,
+    pub reply: oneshot::Sender<ControlReply>,
+}
+
+#[derive(Serialize)]
+struct Response {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Answers one line of a client, passing valid commands to the app.
+async fn handle_line(line: &str, commands: &mpsc::UnboundedSender<ControlMessage>) -> String {
+    let reply = match serde_json::from_str::<ControlRequest>(line) {
+        Ok(request) => {
+            let (reply, answer) = oneshot::channel();
+            if commands.send(ControlMessage { request, reply }).is_err() {
+                Err("the app is shutting down".to_string())
+            } else {
+                answer
+                    .await
+                    .unwrap_or_else(|_| Err("the app is shutting down".to_string()))
+            }
+        }
+        Err(err) => Err(format!("invalid command: {}", err)),
+    };
+    let response = match reply {
+        Ok(result) => Response {
+            ok: true,
+            result,
+            error: None,
+        },
+        Err(error) => Response {
+            ok: false,
+            result: None,
+            error: Some(error),
+        },
+    };
+    serde_json::to_string(&response).unwrap_or_default()
+}
+
+/// Removes the socket file once the listener is dropped.
+#[cfg(unix)]
+struct SocketFile(PathBuf);
+
+#[cfg(unix)]
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Creates `dir` with access for the user only, or checks that an existing one belongs to the
+/// user and that nobody else can write to it, so no one can put a socket of theirs in its place.
+#[cfg(unix)]
+fn private_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    let metadata = std::fs::metadata(dir)?;
+    // SAFETY: getuid has no preconditions and cannot fail.
+    if metadata.uid() != unsafe { libc::getuid() } || metadata.mode() & 0o022 != 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{} can be written by other users", dir.display()),
+        ));
+    }
+    Ok(())
+}
+
+/// Listens on `path` until the task is dropped, which removes the socket file. A socket file
+/// left behind by an instance that is gone is replaced; one that still answers is an error, and
+/// so is a directory other users can write to.
+#[cfg(unix)]
+pub async fn serve(
+    path: PathBuf,
+    commands: mpsc::UnboundedSender<ControlMessage>,
+) -> std::io::Result<()> {
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::{UnixListener, UnixStream},
+    };
+
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    private_dir(dir)?;
+    if path.exists() {
+        if UnixStream::connect(&path).await.is_ok() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AddrInUse,
+                format!("{} is used by another instance", path.display()),
+            ));
+        }
+        std::fs::remove_file(&path)?;
+    }
+    // Only the user may drive the app, wherever the socket is. The socket is created without
+    // access for others rather than restricted after binding, which would leave a window open.
+    // SAFETY: umask has no preconditions and cannot fail.
+    let umask = unsafe { libc::umask(0o177) };
+    let listener = UnixListener::bind(&path);
+    unsafe { libc::umask(umask) };
+    let listener = listener?;
+    let _file = SocketFile(path.clone());
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let commands = commands.clone();
+        tokio::spawn(async move {
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let mut response = handle_line(&line, &commands).await;
+                response.push('\n');
+                if writer.write_all(response.as_bytes()).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+}
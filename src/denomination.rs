@@ -0,0 +1,170 @@
+//! Prices shown in another unit than their market's quote: BTC, satoshis, ETH or a fiat
+//! currency, converted through the USD prices in the ticker store.
+
+use crate::{
+    error,
+    exchange::{Exchange, Instrument},
+    model::TickerMap,
+    net,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+const SATS_PER_BTC: f64 = 100_000_000.0;
+
+/// `[denomination]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct DenominationConfig {
+    /// Fiat currency offered after BTC, sats and ETH, e.g. `EUR`.
+    pub fiat: Option<String>,
+    /// Returns `{"rates": {"EUR": 0.92, ...}}` in units per USD, for currencies the exchange
+    /// has no USD stablecoin market of.
+    pub fx_url: String,
+    pub refresh_secs: u64,
+}
+
+impl Default for DenominationConfig {
+    fn default() -> Self {
+        Self {
+            fiat: None,
+            fx_url: "https://api.frankfurter.app/latest?from=USD".to_string(),
+            refresh_secs: 3600,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Denomination {
+    /// Each market's own quote currency.
+    #[default]
+    Quote,
+    Btc,
+    Sats,
+    Eth,
+    Fiat,
+}
+
+impl Denomination {
+    /// The next denomination in toggle order; `Fiat` is skipped without a fiat currency.
+    pub fn next(self, has_fiat: bool) -> Self {
+        match self {
+            Denomination::Quote => Denomination::Btc,
+            Denomination::Btc => Denomination::Sats,
+            Denomination::Sats => Denomination::Eth,
+            Denomination::Eth if has_fiat => Denomination::Fiat,
+            Denomination::Eth | Denomination::Fiat => Denomination::Quote,
+        }
+    }
+
+    pub fn name(self, fiat: Option<&str>) -> String {
+        match self {
+            Denomination::Quote => "quote".to_string(),
+            Denomination::Btc => "BTC".to_string(),
+            Denomination::Sats => "sats".to_string(),
+            Denomination::Eth => "ETH".to_string(),
+            Denomination::Fiat => fiat.unwrap_or("fiat").to_ascii_uppercase(),
+        }
+    }
+}
+
+/// Units of each currency per USD, by upper-case currency code.
+pub type FxRates = Arc<Mutex<HashMap<String, f64>>>;
+
+#[derive(Deserialize)]
+struct FxReply {
+    rates: HashMap<String, f64>,
+}
+
+/// Keeps `rates` up to date from the configured FX source.
+pub async fn refresh_fx(config: DenominationConfig, rates: FxRates) {
+    let client = net::client();
+    loop {
+        let reply = async {
+            client
+                .get(&config.fx_url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<FxReply>()
+                .await
+        };
+        match reply.await {
+            Ok(reply) => rates.lock().unwrap().extend(
+                reply
+                    .rates
+                    .into_iter()
+                    .map(|(code, rate)| (code.to_ascii_uppercase(), rate)),
+            ),
+            Err(err) => error::report(format!("FX rates: {}", err)),
+        }
+        tokio::time::sleep(Duration::from_secs(config.refresh_secs.max(60))).await;
+    }
+}
+
+/// Converts prices into one denomination at the current prices.
+pub struct Converter {
+    exchange: Exchange,
+    /// USD price of each base asset with a USD-quoted market.
+    usd: HashMap<String, f64>,
+    /// Units of the denomination per USD.
+    per_usd: f64,
+}
+
+impl Converter {
+    /// `None` for `Denomination::Quote`, or while the price the denomination needs is unknown.
+    pub fn new(
+        denomination: Denomination,
+        exchange: Exchange,
+        tickers: &TickerMap,
+        fiat: Option<&str>,
+        fx: &HashMap<String, f64>,
+    ) -> Option<Self> {
+        let mut usd = HashMap::new();
+        for ticker in tickers.values() {
+            if let Some(instrument) = exchange.instrument(&ticker.s) {
+                if instrument.is_usd_quoted() && ticker.c > 0.0 {
+                    usd.entry(instrument.base).or_insert(ticker.c as f64);
+                }
+            }
+        }
+        let per_usd = match denomination {
+            Denomination::Quote => return None,
+            Denomination::Btc => 1.0 / usd.get("BTC")?,
+            Denomination::Sats => SATS_PER_BTC / usd.get("BTC")?,
+            Denomination::Eth => 1.0 / usd.get("ETH")?,
+            Denomination::Fiat => {
+                // A USD stablecoin market of the currency, such as EURUSDT, gives its rate live.
+                let fiat = fiat?.to_ascii_uppercase();
+                match usd.get(&fiat) {
+                    Some(price) => 1.0 / price,
+                    None => *fx.get(&fiat)?,
+                }
+            }
+        };
+        Some(Self {
+            exchange,
+            usd,
+            per_usd,
+        })
+    }
+
+    /// `price` of `symbol`'s market in the denomination, if its quote's USD price is known.
+    /// Group keys such as `BTC/USD` are understood too.
+    pub fn convert(&self, symbol: &str, price: f32) -> Option<f64> {
+        let instrument = self
+            .exchange
+            .instrument(symbol.split('_').next().unwrap_or(symbol))
+            .or_else(|| Instrument::from_separated(symbol, '/'))?;
+        let quote_usd = if instrument.is_usd_quoted() {
+            1.0
+        } else {
+            *self.usd.get(&instrument.quote)?
+        };
+        Some(price as f64 * quote_usd * self.per_usd)
+    }
+}
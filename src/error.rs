@@ -0,0 +1,61 @@
+//! The crate's error type, and reporting of errors the app carries on after.
+
+use crate::notify::{self, Level};
+use std::{fmt, io};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// Boxed, as it is several times larger than the other variants.
+    #[error("websocket: {0}")]
+    WebSocket(Box<tokio_tungstenite::tungstenite::Error>),
+    /// A payload from an exchange or a file that is not in the expected format.
+    #[error("unexpected data: {0}")]
+    Parse(String),
+    #[error("HTTP: {0}")]
+    Http(#[from] reqwest::Error),
+    /// Setting up, reading from or drawing to the terminal.
+    #[error("terminal: {0}")]
+    Terminal(io::Error),
+    /// A setting, command line option or configuration file that cannot be used.
+    #[error("config: {0}")]
+    Config(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("database: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("clipboard: {0}")]
+    Clipboard(#[from] arboard::Error),
+    /// A WASM plugin that cannot be loaded or returned something unusable.
+    #[error("plugin {0}")]
+    Plugin(String),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<tokio_tungstenite::tungstenite::Error> for Error {
+    fn from(err: tokio_tungstenite::tungstenite::Error) -> Self {
+        Error::WebSocket(Box::new(err))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(err: quick_xml::Error) -> Self {
+        Error::Parse(err.to_string())
+    }
+}
+
+/// Reports an error the app carries on after: shown by the UI while it is running, written to
+/// stderr otherwise.
+pub fn report(message: impl fmt::Display) {
+    if notify::is_interactive() {
+        notify::post(Level::Error, message);
+    } else {
+        eprintln!("Error: {}", message);
+    }
+}
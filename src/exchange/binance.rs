@@ -0,0 +1,372 @@
+use crate::{
+    chart::Candle,
+    error::{self, Error},
+    exchange::Market,
+    metrics,
+    model::HrTicker,
+    net::{self, Socket},
+    notify::{self, Level},
+    ratelimit,
+    stream::Selection,
+};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+const FUTURES_URL: &str = "wss://fstream.binance.com/ws/!ticker@arr";
+/// Base endpoint whose streams are chosen with SUBSCRIBE and UNSUBSCRIBE requests.
+pub const STREAM_URL: &str = "wss://fstream.binance.com/ws";
+const ALL_TICKERS: &str = "!ticker@arr";
+const KLINES_URL: &str = "https://fapi.binance.com/fapi/v1/klines";
+/// 24 hour statistics of every symbol, polled while the websocket cannot connect.
+pub const TICKERS_URL: &str = "https://fapi.binance.com/fapi/v1/ticker/24hr";
+/// Spot counterparts of the futures endpoints above, which send the same payloads.
+const SPOT_URL: &str = "wss://stream.binance.com:9443/ws/!ticker@arr";
+pub const SPOT_STREAM_URL: &str = "wss://stream.binance.com:9443/ws";
+const SPOT_KLINES_URL: &str = "https://api.binance.com/api/v3/klines";
+pub const SPOT_TICKERS_URL: &str = "https://api.binance.com/api/v3/ticker/24hr";
+/// Binance closes connections after 24 hours, so they are replaced a little before that.
+const MAX_CONNECTION_AGE: Duration = Duration::from_secs(23 * 60 * 60 + 30 * 60);
+/// Binance pings every few minutes and tickers arrive every second, so a connection silent
+/// for this long is dead.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// Wait before connecting again after a connection attempt failed.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+pub async fn subscribe(market: Market, tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+    match market {
+        Market::Futures => subscribe_url(FUTURES_URL, tx).await,
+        Market::Spot => subscribe_url(SPOT_URL, tx).await,
+    }
+}
+
+/// Base stream endpoint of `market`, for `subscribe_selected`.
+pub fn stream_url(market: Market) -> &'static str {
+    match market {
+        Market::Futures => STREAM_URL,
+        Market::Spot => SPOT_STREAM_URL,
+    }
+}
+
+/// 24 hour statistics endpoint of `market`, for `rest_tickers`.
+pub fn tickers_url(market: Market) -> &'static str {
+    match market {
+        Market::Futures => TICKERS_URL,
+        Market::Spot => SPOT_TICKERS_URL,
+    }
+}
+
+/// Streams `!ticker@arr` payloads from `url`, e.g. a local server in tests.
+pub async fn subscribe_url(url: &str, tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+    stream(url, None, tx).await
+}
+
+/// Streams the tickers in `selection` from `url`, subscribing to the streams it needs and
+/// sending SUBSCRIBE and UNSUBSCRIBE requests whenever it changes.
+pub async fn subscribe_selected(
+    url: &str,
+    selection: watch::Receiver<Selection>,
+    tx: mpsc::Sender<Vec<HrTicker>>,
+) -> Result<(), Error> {
+    stream(url, Some(selection), tx).await
+}
+
+/// Stream names carrying `selection`: the all-market array, or one `<symbol>@ticker` stream
+/// per symbol.
+pub fn streams(selection: &Selection) -> Vec<String> {
+    match selection {
+        Selection::All => vec![ALL_TICKERS.to_string()],
+        Selection::Symbols(symbols) => symbols
+            .iter()
+            .map(|symbol| format!("{}@ticker", symbol.to_lowercase()))
+            .collect(),
+    }
+}
+
+fn request(method: &str, streams: &[String], id: u64) -> Message {
+    Message::Text(json!({ "method": method, "params": streams, "id": id }).to_string())
+}
+
+/// Tickers in a frame from either kind of stream. Replies to subscription requests carry none.
+fn parse(text: &str) -> Option<Vec<HrTicker>> {
+    if let Ok(batch) = serde_json::from_str::<Vec<HrTicker>>(text) {
+        return Some(batch);
+    }
+    if let Ok(ticker) = serde_json::from_str::<HrTicker>(text) {
+        return Some(vec![ticker]);
+    }
+    let is_reply = serde_json::from_str::<Value>(text).is_ok_and(|value| value.get("id").is_some());
+    if !is_reply {
+        metrics::increment(&metrics::PARSE_ERRORS);
+    }
+    None
+}
+
+/// Why a connection was given up.
+enum Ended {
+    /// Closed by the server, failed, or silent for too long.
+    Dropped,
+    /// Replaced ahead of Binance's 24 hour limit.
+    Expired,
+    /// Nothing is reading the tickers any more.
+    Unused,
+}
+
+/// Connects to `url` and keeps streaming from it in the background: server pings are answered,
+/// and the connection is replaced before it reaches Binance's 24 hour limit or when it drops,
+/// subscribing again to the current selection. Only the first connection's failure is returned.
+async fn stream(
+    url: &str,
+    mut selection: Option<watch::Receiver<Selection>>,
+    tx: mpsc::Sender<Vec<HrTicker>>,
+) -> Result<(), Error> {
+    let (socket, _) = net::connect(url).await?;
+    let url = url.to_string();
+    tokio::spawn(async move {
+        let mut socket = Some(socket);
+        // Only the first failure of an outage is reported; the footer shows the rest.
+        let (mut failing, mut dropped) = (false, false);
+        while !tx.is_closed() {
+            let connected = match socket.take() {
+                Some(socket) => socket,
+                None => match net::connect(&url).await {
+                    Ok((socket, _)) => {
+                        if dropped {
+                            notify::post(Level::Info, "Binance: reconnected");
+                        }
+                        (failing, dropped) = (false, false);
+                        socket
+                    }
+                    Err(err) => {
+                        if !failing {
+                            error::report(format!("Binance: {}", err));
+                        }
+                        failing = true;
+                        metrics::increment(&metrics::RECONNECTS);
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                        continue;
+                    }
+                },
+            };
+            match session(connected, selection.as_mut(), &tx).await {
+                Ended::Unused => break,
+                Ended::Expired => {}
+                Ended::Dropped => {
+                    if !dropped {
+                        notify::post(Level::Warning, "Binance: connection lost, reconnecting");
+                    }
+                    dropped = true;
+                    metrics::increment(&metrics::RECONNECTS);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Streams from one connection until it ends.
+async fn session(
+    socket: Socket,
+    mut selection: Option<&mut watch::Receiver<Selection>>,
+    tx: &mpsc::Sender<Vec<HrTicker>>,
+) -> Ended {
+    let (mut write, mut read) = socket.split();
+    let expires = tokio::time::sleep(MAX_CONNECTION_AGE);
+    tokio::pin!(expires);
+    let mut current = match selection.as_mut() {
+        Some(selection) => streams(&selection.borrow_and_update()),
+        None => Vec::new(),
+    };
+    let mut id = 1;
+    if !current.is_empty()
+        && write
+            .send(request("SUBSCRIBE", &current, id))
+            .await
+            .is_err()
+    {
+        return Ended::Dropped;
+    }
+
+    loop {
+        tokio::select! {
+            msg = tokio::time::timeout(IDLE_TIMEOUT, read.next()) => {
+                match msg {
+                    Ok(Some(Ok(Message::Text(text)))) => {
+                        if let Some(batch) = parse(&text) {
+                            if tx.send(batch).await.is_err() {
+                                return Ended::Unused;
+                            }
+                        }
+                    }
+                    Ok(Some(Ok(Message::Ping(payload)))) => {
+                        if write.send(Message::Pong(payload)).await.is_err() {
+                            return Ended::Dropped;
+                        }
+                    }
+                    Ok(Some(Ok(Message::Close(_)))) | Ok(Some(Err(_))) | Ok(None) => {
+                        return Ended::Dropped;
+                    }
+                    Ok(Some(Ok(_))) => {}
+                    Err(_) => {
+                        error::report(format!("Binance: no data for {}s", IDLE_TIMEOUT.as_secs()));
+                        return Ended::Dropped;
+                    }
+                }
+            }
+            changed = async { selection.as_mut().unwrap().changed().await }, if selection.is_some() => {
+                if changed.is_err() {
+                    // Nothing can change the selection any more.
+                    selection = None;
+                    continue;
+                }
+                let wanted = streams(&selection.as_mut().unwrap().borrow_and_update());
+                let removed: Vec<String> =
+                    current.iter().filter(|s| !wanted.contains(s)).cloned().collect();
+                let added: Vec<String> =
+                    wanted.iter().filter(|s| !current.contains(s)).cloned().collect();
+                for (method, streams) in [("UNSUBSCRIBE", removed), ("SUBSCRIBE", added)] {
+                    if streams.is_empty() {
+                        continue;
+                    }
+                    id += 1;
+                    if write.send(request(method, &streams, id)).await.is_err() {
+                        return Ended::Dropped;
+                    }
+                }
+                current = wanted;
+            }
+            () = &mut expires => {
+                let _ = write.send(Message::Close(None)).await;
+                return Ended::Expired;
+            }
+            () = tx.closed() => {
+                let _ = write.send(Message::Close(None)).await;
+                return Ended::Unused;
+            }
+        }
+    }
+}
+
+/// Fetches the most recent `limit` klines of `interval` (e.g. `1m`) for `symbol` in `market`,
+/// or the last ones opening at or before `end_time`.
+pub async fn klines(
+    market: Market,
+    symbol: &str,
+    interval: &str,
+    limit: usize,
+    end_time: Option<u64>,
+) -> Result<Vec<Candle>, Error> {
+    let mut query = vec![
+        ("symbol", symbol.to_string()),
+        ("interval", interval.to_string()),
+        ("limit", limit.to_string()),
+    ];
+    if let Some(end_time) = end_time {
+        query.push(("endTime", end_time.to_string()));
+    }
+    let (url, weight) = match market {
+        Market::Futures => (KLINES_URL, futures_klines_weight(limit)),
+        Market::Spot => (SPOT_KLINES_URL, 2),
+    };
+    let rows: Vec<Vec<Value>> = ratelimit::send(net::client().get(url).query(&query), weight)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    // Each row is [open time, "open", "high", "low", "close", "volume", close time, ...].
+    let number = |value: &Value| value.as_str().and_then(|s| s.parse::<f64>().ok());
+    rows.iter()
+        .map(|row| {
+            Some(Candle {
+                open_time: row.first()?.as_u64()?,
+                open: number(row.get(1)?)?,
+                high: number(row.get(2)?)?,
+                low: number(row.get(3)?)?,
+                close: number(row.get(4)?)?,
+                volume: number(row.get(5)?)?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| Error::Parse("unexpected kline format".to_string()))
+}
+
+/// One entry of the 24hr ticker REST endpoint: the statistics of a `24hrTicker` stream event
+/// under longer names.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RestTicker {
+    symbol: String,
+    price_change: String,
+    price_change_percent: String,
+    weighted_avg_price: String,
+    last_price: String,
+    last_qty: String,
+    open_price: String,
+    high_price: String,
+    low_price: String,
+    volume: String,
+    quote_volume: String,
+    open_time: u64,
+    close_time: u64,
+    first_id: i64,
+    last_id: i64,
+    count: u64,
+}
+
+impl RestTicker {
+    fn into_ticker(self) -> HrTicker {
+        let number = |s: &str| s.parse::<f32>().unwrap_or(0.0);
+        HrTicker {
+            e: "24hrTicker".to_string(),
+            E: self.close_time,
+            p: number(&self.price_change),
+            P: number(&self.price_change_percent),
+            w: number(&self.weighted_avg_price),
+            c: number(&self.last_price),
+            Q: number(&self.last_qty),
+            o: number(&self.open_price),
+            h: number(&self.high_price),
+            l: number(&self.low_price),
+            v: self.volume,
+            q: self.quote_volume,
+            O: self.open_time,
+            C: self.close_time,
+            F: self.first_id.max(0) as u64,
+            L: self.last_id.max(0) as u64,
+            n: self.count,
+            funding_rate: None,
+            previous_price: 0.0,
+            last_changed: None,
+            last_updated: None,
+            changes: Default::default(),
+            history: Default::default(),
+            basis_prices: Default::default(),
+            s: self.symbol,
+        }
+    }
+}
+
+/// Request weight of a futures klines request for `limit` candles; spot klines weigh 2.
+fn futures_klines_weight(limit: usize) -> u32 {
+    match limit {
+        0..=99 => 1,
+        100..=499 => 2,
+        500..=1000 => 5,
+        _ => 10,
+    }
+}
+
+/// Fetches every symbol's 24 hour statistics from the REST endpoint at `url`, as one batch.
+pub async fn rest_tickers(url: &str) -> Result<Vec<HrTicker>, Error> {
+    // The statistics of all symbols weigh 40 on futures and 80 on spot.
+    let weight = if url == SPOT_TICKERS_URL { 80 } else { 40 };
+    let tickers: Vec<RestTicker> = ratelimit::send(net::client().get(url), weight)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(tickers.into_iter().map(RestTicker::into_ticker).collect())
+}
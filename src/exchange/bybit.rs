@@ -0,0 +1,130 @@
+use super::normalized_ticker;
+use crate::{error::Error, model::HrTicker, net};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+const WS_URL: &str = "wss://stream.bybit.com/v5/public/linear";
+const INSTRUMENTS_URL: &str =
+    "https://api.bybit.com/v5/market/instruments-info?category=linear&limit=1000";
+/// Topics per subscribe request.
+const SUBSCRIBE_BATCH: usize = 10;
+
+#[derive(Deserialize)]
+struct InstrumentsResponse {
+    result: InstrumentsResult,
+}
+
+#[derive(Deserialize)]
+struct InstrumentsResult {
+    list: Vec<InstrumentInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstrumentInfo {
+    symbol: String,
+    status: String,
+    contract_type: String,
+}
+
+#[derive(Deserialize)]
+struct TickerMessage {
+    topic: String,
+    #[serde(rename = "type")]
+    kind: String,
+    ts: u64,
+    data: Map<String, Value>,
+}
+
+async fn perpetual_symbols() -> Result<Vec<String>, Error> {
+    let response: InstrumentsResponse = net::client()
+        .get(INSTRUMENTS_URL)
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(response
+        .result
+        .list
+        .into_iter()
+        .filter(|i| i.status == "Trading" && i.contract_type == "LinearPerpetual")
+        .map(|i| i.symbol)
+        .collect())
+}
+
+fn number(data: &Map<String, Value>, key: &str) -> f64 {
+    data.get(key)
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0.0)
+}
+
+fn to_ticker(symbol: &str, data: &Map<String, Value>, event_time: u64) -> HrTicker {
+    let mut ticker = normalized_ticker(
+        symbol,
+        number(data, "lastPrice") as f32,
+        (number(data, "price24hPcnt") * 100.0) as f32,
+        number(data, "highPrice24h") as f32,
+        number(data, "lowPrice24h") as f32,
+        number(data, "volume24h"),
+        event_time,
+    );
+    let open = number(data, "prevPrice24h") as f32;
+    if open > 0.0 {
+        ticker.o = open;
+        ticker.p = ticker.c - open;
+    }
+    ticker.q = number(data, "turnover24h").to_string();
+    ticker.funding_rate = data
+        .get("fundingRate")
+        .and_then(Value::as_str)
+        .and_then(|s| s.parse().ok());
+    ticker
+}
+
+pub async fn subscribe(tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+    let symbols = perpetual_symbols().await?;
+    let (ws_stream, _) = net::connect(WS_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    for batch in symbols.chunks(SUBSCRIBE_BATCH) {
+        let topics: Vec<String> = batch.iter().map(|s| format!("tickers.{}", s)).collect();
+        let request = serde_json::json!({ "op": "subscribe", "args": topics });
+        write.send(Message::Text(request.to_string())).await?;
+    }
+
+    tokio::spawn(async move {
+        // Deltas only carry changed fields, so the latest full state is kept per symbol.
+        let mut state: HashMap<String, Map<String, Value>> = HashMap::new();
+        while let Some(msg) = read.next().await {
+            let Ok(Message::Text(text)) = msg else {
+                continue;
+            };
+            let Ok(message) = serde_json::from_str::<TickerMessage>(&text) else {
+                continue;
+            };
+            let Some(symbol) = message.topic.strip_prefix("tickers.") else {
+                continue;
+            };
+            let data = state.entry(symbol.to_string()).or_default();
+            if message.kind == "snapshot" {
+                *data = message.data;
+            } else {
+                data.extend(message.data);
+            }
+            if tx
+                .send(vec![to_ticker(symbol, data, message.ts)])
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use super::normalized_ticker;
+use crate::{error::Error, model::HrTicker, net};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+const WS_URL: &str = "wss://advanced-trade-ws.coinbase.com";
+const PRODUCTS_URL: &str = "https://api.exchange.coinbase.com/products";
+const QUOTES: [&str; 3] = ["USD", "USDT", "USDC"];
+
+#[derive(Deserialize)]
+struct Product {
+    id: String,
+    quote_currency: String,
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct TickerMessage {
+    channel: String,
+    timestamp: String,
+    #[serde(default)]
+    events: Vec<TickerEvent>,
+}
+
+#[derive(Deserialize)]
+struct TickerEvent {
+    #[serde(default)]
+    tickers: Vec<CoinbaseTicker>,
+}
+
+#[derive(Deserialize)]
+struct CoinbaseTicker {
+    product_id: String,
+    price: String,
+    volume_24_h: String,
+    low_24_h: String,
+    high_24_h: String,
+    price_percent_chg_24_h: String,
+}
+
+async fn online_products() -> Result<Vec<String>, Error> {
+    let products: Vec<Product> = net::client()
+        .get(PRODUCTS_URL)
+        .header("User-Agent", "crypto_tui_ticker")
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(products
+        .into_iter()
+        .filter(|p| p.status == "online" && QUOTES.contains(&p.quote_currency.as_str()))
+        .map(|p| p.id)
+        .collect())
+}
+
+fn parse(text: &str) -> Option<Vec<HrTicker>> {
+    let message: TickerMessage = serde_json::from_str(text).ok()?;
+    if message.channel != "ticker" {
+        return None;
+    }
+    let event_time = chrono::DateTime::parse_from_rfc3339(&message.timestamp)
+        .map(|t| t.timestamp_millis() as u64)
+        .unwrap_or(0);
+    let tickers = message
+        .events
+        .iter()
+        .flat_map(|event| &event.tickers)
+        .map(|t| {
+            normalized_ticker(
+                &t.product_id,
+                t.price.parse().unwrap_or(0.0),
+                t.price_percent_chg_24_h.parse().unwrap_or(0.0),
+                t.high_24_h.parse().unwrap_or(0.0),
+                t.low_24_h.parse().unwrap_or(0.0),
+                t.volume_24_h.parse().unwrap_or(0.0),
+                event_time,
+            )
+        })
+        .collect();
+    Some(tickers)
+}
+
+pub async fn subscribe(tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+    let products = online_products().await?;
+    let (ws_stream, _) = net::connect(WS_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    let request = serde_json::json!({
+        "type": "subscribe",
+        "product_ids": products,
+        "channel": "ticker",
+    });
+    write.send(Message::Text(request.to_string())).await?;
+
+    tokio::spawn(async move {
+        while let Some(msg) = read.next().await {
+            if let Ok(Message::Text(text)) = msg {
+                if let Some(parsed) = parse(&text) {
+                    if tx.send(parsed).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use super::normalized_ticker;
+use crate::{error::Error, model::HrTicker, net};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+const WS_URL: &str = "wss://ws.kraken.com";
+const ASSET_PAIRS_URL: &str = "https://api.kraken.com/0/public/AssetPairs";
+const QUOTES: [&str; 3] = ["USD", "USDT", "USDC"];
+/// Pairs per subscribe request, to keep frames well below Kraken's size limits.
+const SUBSCRIBE_BATCH: usize = 100;
+
+/// Kraken's legacy asset codes and their common names.
+pub(crate) const ASSET_ALIASES: [(&str, &str); 3] =
+    [("XBT", "BTC"), ("XDG", "DOGE"), ("XETC", "ETC")];
+
+#[derive(Deserialize)]
+struct AssetPairs {
+    result: HashMap<String, AssetPair>,
+}
+
+#[derive(Deserialize)]
+struct AssetPair {
+    wsname: Option<String>,
+    status: Option<String>,
+}
+
+/// Translates a websocket pair name like `XBT/USD` into a table symbol like `BTCUSD`.
+pub fn translate_pair(pair: &str) -> String {
+    pair.split('/')
+        .map(|asset| {
+            ASSET_ALIASES
+                .iter()
+                .find(|(kraken, _)| *kraken == asset)
+                .map_or(asset, |(_, common)| *common)
+        })
+        .collect()
+}
+
+async fn tradable_pairs() -> Result<Vec<String>, Error> {
+    let pairs: AssetPairs = net::client()
+        .get(ASSET_PAIRS_URL)
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(pairs
+        .result
+        .into_values()
+        .filter(|p| p.status.as_deref().unwrap_or("online") == "online")
+        .filter_map(|p| p.wsname)
+        .filter(|name| {
+            name.split_once('/')
+                .is_some_and(|(_, quote)| QUOTES.contains(&quote))
+        })
+        .collect())
+}
+
+fn field(data: &Value, key: &str, index: usize) -> f64 {
+    data.get(key)
+        .and_then(|v| v.get(index))
+        .and_then(|v| match v {
+            Value::String(s) => s.parse().ok(),
+            other => other.as_f64(),
+        })
+        .unwrap_or(0.0)
+}
+
+/// Parses a `[channelID, data, "ticker", pair]` frame; events such as heartbeats are skipped.
+fn parse(text: &str) -> Option<HrTicker> {
+    let frame: Vec<Value> = serde_json::from_str(text).ok()?;
+    if frame.len() < 4 || frame[2] != "ticker" {
+        return None;
+    }
+    let data = &frame[1];
+    let pair = frame[3].as_str()?;
+    let last = field(data, "c", 0) as f32;
+    let open = field(data, "o", 1) as f32;
+    let percent_change = if open > 0.0 {
+        (last - open) / open * 100.0
+    } else {
+        0.0
+    };
+    let event_time = chrono::Utc::now().timestamp_millis() as u64;
+    let mut ticker = normalized_ticker(
+        &translate_pair(pair),
+        last,
+        percent_change,
+        field(data, "h", 1) as f32,
+        field(data, "l", 1) as f32,
+        field(data, "v", 1),
+        event_time,
+    );
+    ticker.o = open;
+    ticker.p = last - open;
+    ticker.w = field(data, "p", 1) as f32;
+    ticker.Q = field(data, "c", 1) as f32;
+    ticker.n = field(data, "t", 1) as u64;
+    Some(ticker)
+}
+
+pub async fn subscribe(tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+    let pairs = tradable_pairs().await?;
+    let (ws_stream, _) = net::connect(WS_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    for batch in pairs.chunks(SUBSCRIBE_BATCH) {
+        let request = serde_json::json!({
+            "event": "subscribe",
+            "pair": batch,
+            "subscription": { "name": "ticker" },
+        });
+        write.send(Message::Text(request.to_string())).await?;
+    }
+
+    tokio::spawn(async move {
+        while let Some(msg) = read.next().await {
+            if let Ok(Message::Text(text)) = msg {
+                if let Some(ticker) = parse(&text) {
+                    if tx.send(vec![ticker]).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use crate::{chart::Candle, chart::INTERVAL, error::Error, model::HrTicker};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tokio::sync::mpsc;
+
+pub mod binance;
+pub mod bybit;
+pub mod coinbase;
+pub mod kraken;
+pub mod okx;
+
+const DAY_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Quote assets, longest first so `FDUSD` wins over `USD` when splitting concatenated symbols.
+const QUOTE_ASSETS: [&str; 10] = [
+    "FDUSD", "USDT", "USDC", "BUSD", "TUSD", "USD", "BTC", "ETH", "BNB", "EUR",
+];
+
+/// Quote assets treated as interchangeable with USD when comparing exchanges.
+const USD_EQUIVALENTS: [&str; 6] = ["USD", "USDT", "USDC", "BUSD", "FDUSD", "TUSD"];
+
+#[derive(
+    Deserialize, Serialize, ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Exchange {
+    Binance,
+    BinanceSpot,
+    Coinbase,
+    Kraken,
+    Bybit,
+    Okx,
+}
+
+/// Kind of market an exchange's stream carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Market {
+    /// Perpetual futures.
+    Futures,
+    Spot,
+}
+
+impl Market {
+    pub fn name(self) -> &'static str {
+        match self {
+            Market::Futures => "futures",
+            Market::Spot => "spot",
+        }
+    }
+}
+
+impl Exchange {
+    /// Every source, in the order the exchange switcher lists them.
+    pub const ALL: [Exchange; 6] = [
+        Exchange::Binance,
+        Exchange::BinanceSpot,
+        Exchange::Coinbase,
+        Exchange::Kraken,
+        Exchange::Bybit,
+        Exchange::Okx,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Exchange::Binance => "Binance",
+            Exchange::BinanceSpot => "Binance Spot",
+            Exchange::Coinbase => "Coinbase",
+            Exchange::Kraken => "Kraken",
+            Exchange::Bybit => "Bybit",
+            Exchange::Okx => "OKX",
+        }
+    }
+
+    pub fn market(self) -> Market {
+        match self {
+            Exchange::Binance | Exchange::Bybit | Exchange::Okx => Market::Futures,
+            Exchange::BinanceSpot | Exchange::Coinbase | Exchange::Kraken => Market::Spot,
+        }
+    }
+
+    /// Whether this is one of Binance's markets, which are streamed per symbol.
+    pub fn is_binance(self) -> bool {
+        matches!(self, Exchange::Binance | Exchange::BinanceSpot)
+    }
+
+    /// Streams this exchange's tickers, normalized into `HrTicker`s, into `tx`.
+    pub async fn subscribe(self, tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+        match self {
+            Exchange::Binance => binance::subscribe(Market::Futures, tx).await,
+            Exchange::BinanceSpot => binance::subscribe(Market::Spot, tx).await,
+            Exchange::Coinbase => coinbase::subscribe(tx).await,
+            Exchange::Kraken => kraken::subscribe(tx).await,
+            Exchange::Bybit => bybit::subscribe(tx).await,
+            Exchange::Okx => okx::subscribe(tx).await,
+        }
+    }
+
+    /// Whether `klines` can fetch candles from this exchange.
+    pub fn has_klines(self) -> bool {
+        self.is_binance()
+    }
+
+    /// Fetches the most recent `limit` candles of `symbol` at the chart interval, or the last
+    /// ones opening at or before `end_time`.
+    pub async fn klines(
+        self,
+        symbol: &str,
+        limit: usize,
+        end_time: Option<u64>,
+    ) -> Result<Vec<Candle>, Error> {
+        match self {
+            Exchange::Binance | Exchange::BinanceSpot => {
+                binance::klines(self.market(), symbol, INTERVAL, limit, end_time).await
+            }
+            _ => Err(Error::Other(format!(
+                "live charts are not available for {}; switch to the recorded history",
+                self.name()
+            ))),
+        }
+    }
+
+    /// Splits an exchange-native symbol into its base and quote assets.
+    pub fn instrument(self, symbol: &str) -> Option<Instrument> {
+        match self {
+            Exchange::Binance
+            | Exchange::BinanceSpot
+            | Exchange::Kraken
+            | Exchange::Bybit
+            | Exchange::Okx => Instrument::from_concatenated(symbol),
+            Exchange::Coinbase => Instrument::from_separated(symbol, '-'),
+        }
+    }
+
+    /// The table symbol of `instrument` on this exchange, e.g. `BTCUSDT` or `BTC-USD`.
+    pub fn symbol(self, instrument: &Instrument) -> String {
+        match self {
+            Exchange::Coinbase => format!("{}-{}", instrument.base, instrument.quote),
+            _ => format!("{}{}", instrument.base, instrument.quote),
+        }
+    }
+}
+
+impl fmt::Display for Exchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// An exchange-independent market, e.g. `BTCUSDT` on Binance or `BTC-USD` on Coinbase.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Instrument {
+    pub base: String,
+    pub quote: String,
+}
+
+impl Instrument {
+    pub fn new(base: &str, quote: &str) -> Self {
+        Self {
+            base: base.to_ascii_uppercase(),
+            quote: quote.to_ascii_uppercase(),
+        }
+    }
+
+    /// Parses symbols without a separator such as `BTCUSDT`.
+    pub fn from_concatenated(symbol: &str) -> Option<Self> {
+        let symbol = symbol.to_ascii_uppercase();
+        QUOTE_ASSETS.iter().find_map(|quote| {
+            symbol
+                .strip_suffix(quote)
+                .filter(|base| !base.is_empty())
+                .map(|base| Self::new(base, quote))
+        })
+    }
+
+    /// Parses symbols with a separator such as `BTC-USD`.
+    pub fn from_separated(symbol: &str, separator: char) -> Option<Self> {
+        let (base, quote) = symbol.split_once(separator)?;
+        (!base.is_empty() && !quote.is_empty()).then(|| Self::new(base, quote))
+    }
+
+    /// Whether the quote is USD or a USD stablecoin.
+    pub fn is_usd_quoted(&self) -> bool {
+        USD_EQUIVALENTS.contains(&self.quote.as_str())
+    }
+
+    /// Whether the base is a USD stablecoin, such as in `USDCUSDT`.
+    pub fn is_stablecoin(&self) -> bool {
+        USD_EQUIVALENTS.contains(&self.base.as_str())
+    }
+
+    /// Key under which the same market on different exchanges is grouped, with USD stablecoin
+    /// quotes collapsed into `USD`.
+    pub fn comparison_key(&self) -> String {
+        let quote = if self.is_usd_quoted() {
+            "USD"
+        } else {
+            &self.quote
+        };
+        format!("{}/{}", self.base, quote)
+    }
+}
+
+/// Builds an `HrTicker` from the 24h statistics every exchange reports, deriving the fields only
+/// Binance provides.
+pub(crate) fn normalized_ticker(
+    symbol: &str,
+    last: f32,
+    percent_change: f32,
+    high: f32,
+    low: f32,
+    base_volume: f64,
+    event_time: u64,
+) -> HrTicker {
+    let open = if percent_change > -100.0 {
+        last / (1.0 + percent_change / 100.0)
+    } else {
+        0.0
+    };
+    HrTicker {
+        e: "24hrTicker".to_string(),
+        E: event_time,
+        s: symbol.to_string(),
+        p: last - open,
+        P: percent_change,
+        w: last,
+        c: last,
+        Q: 0.0,
+        o: open,
+        h: high,
+        l: low,
+        v: base_volume.to_string(),
+        q: (base_volume * last as f64).to_string(),
+        O: event_time.saturating_sub(DAY_MS),
+        C: event_time,
+        F: 0,
+        L: 0,
+        n: 0,
+        funding_rate: None,
+        previous_price: 0.0,
+        last_changed: None,
+        last_updated: None,
+        changes: Default::default(),
+        history: Default::default(),
+        basis_prices: Default::default(),
+    }
+}
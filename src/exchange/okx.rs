@@ -0,0 +1,133 @@
+use super::normalized_ticker;
+use crate::{error::Error, model::HrTicker, net};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+const WS_URL: &str = "wss://ws.okx.com:8443/ws/v5/public";
+const INSTRUMENTS_URL: &str = "https://www.okx.com/api/v5/public/instruments?instType=SWAP";
+/// OKX drops connections that are silent for 30 seconds.
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+const SUBSCRIBE_BATCH: usize = 50;
+
+#[derive(Deserialize)]
+struct InstrumentsResponse {
+    data: Vec<InstrumentInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstrumentInfo {
+    inst_id: String,
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct TickerMessage {
+    data: Vec<OkxTicker>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OkxTicker {
+    inst_id: String,
+    last: String,
+    last_sz: String,
+    open24h: String,
+    high24h: String,
+    low24h: String,
+    vol_ccy24h: String,
+    ts: String,
+}
+
+/// Turns an instrument ID like `BTC-USDT-SWAP` into a table symbol like `BTCUSDT`.
+pub fn normalize_inst_id(inst_id: &str) -> String {
+    inst_id
+        .strip_suffix("-SWAP")
+        .unwrap_or(inst_id)
+        .replace('-', "")
+}
+
+async fn live_swaps() -> Result<Vec<String>, Error> {
+    let response: InstrumentsResponse = net::client()
+        .get(INSTRUMENTS_URL)
+        .send()
+        .await?
+        .json()
+        .await?;
+    Ok(response
+        .data
+        .into_iter()
+        .filter(|i| i.state == "live")
+        .map(|i| i.inst_id)
+        .collect())
+}
+
+fn to_ticker(t: &OkxTicker) -> HrTicker {
+    let last: f32 = t.last.parse().unwrap_or(0.0);
+    let open: f32 = t.open24h.parse().unwrap_or(0.0);
+    let percent_change = if open > 0.0 {
+        (last - open) / open * 100.0
+    } else {
+        0.0
+    };
+    let mut ticker = normalized_ticker(
+        &normalize_inst_id(&t.inst_id),
+        last,
+        percent_change,
+        t.high24h.parse().unwrap_or(0.0),
+        t.low24h.parse().unwrap_or(0.0),
+        t.vol_ccy24h.parse().unwrap_or(0.0),
+        t.ts.parse().unwrap_or(0),
+    );
+    ticker.o = open;
+    ticker.p = last - open;
+    ticker.Q = t.last_sz.parse().unwrap_or(0.0);
+    ticker
+}
+
+pub async fn subscribe(tx: mpsc::Sender<Vec<HrTicker>>) -> Result<(), Error> {
+    let swaps = live_swaps().await?;
+    let (ws_stream, _) = net::connect(WS_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    for batch in swaps.chunks(SUBSCRIBE_BATCH) {
+        let args: Vec<_> = batch
+            .iter()
+            .map(|id| serde_json::json!({ "channel": "tickers", "instId": id }))
+            .collect();
+        let request = serde_json::json!({ "op": "subscribe", "args": args });
+        write.send(Message::Text(request.to_string())).await?;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PING_INTERVAL);
+        loop {
+            interval.tick().await;
+            if write.send(Message::Text("ping".to_string())).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        while let Some(msg) = read.next().await {
+            let Ok(Message::Text(text)) = msg else {
+                continue;
+            };
+            if text == "pong" {
+                continue;
+            }
+            if let Ok(message) = serde_json::from_str::<TickerMessage>(&text) {
+                let parsed = message.data.iter().map(to_ticker).collect();
+                if tx.send(parsed).await.is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use crate::{
+    coingecko::MarketData,
+    format,
+    format::NumberFormat,
+    indicators::{Indicators, Trend},
+    model::{HrTicker, SortColumn},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// `[export]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ExportConfig {
+    pub format: ExportFormat,
+    /// Directory exports are written to; the working directory when unset.
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for ExportConfig {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Csv,
+            dir: None,
+        }
+    }
+}
+
+/// Plain-text value of one table cell, as shown in the table without styling.
+pub fn cell(
+    column: SortColumn,
+    ticker: &HrTicker,
+    number_format: &NumberFormat,
+    market: Option<&MarketData>,
+    liquidations: u32,
+    indicators: Option<&Indicators>,
+) -> String {
+    match column {
+        SortColumn::Symbol => ticker.s.clone(),
+        SortColumn::Last => ticker.c.to_string(),
+        SortColumn::PercentChange => number_format.derived(ticker.P as f64),
+        SortColumn::Open => ticker.o.to_string(),
+        SortColumn::High => ticker.h.to_string(),
+        SortColumn::Low => ticker.l.to_string(),
+        SortColumn::Volume => ticker.v.clone(),
+        SortColumn::Rank => market
+            .and_then(|m| m.rank)
+            .map_or("-".to_string(), |rank| rank.to_string()),
+        SortColumn::MarketCap => market
+            .and_then(|m| m.market_cap)
+            .map_or("-".to_string(), format::compact),
+        SortColumn::Liquidations => liquidations.to_string(),
+        SortColumn::Change1m | SortColumn::Change5m | SortColumn::Change15m => column
+            .window_ms()
+            .and_then(|window| ticker.rolling_change(window))
+            .map_or("-".to_string(), |change| {
+                number_format.derived(change as f64)
+            }),
+        SortColumn::Rsi => indicators
+            .and_then(|i| i.rsi)
+            .map_or("-".to_string(), |rsi| format!("{:.1}", rsi)),
+        SortColumn::EmaTrend => match indicators.and_then(|i| i.trend) {
+            Some(Trend::Bullish) => "▲ bull".to_string(),
+            Some(Trend::Bearish) => "▼ bear".to_string(),
+            None => "-".to_string(),
+        },
+        SortColumn::Vwap => indicators
+            .and_then(|i| i.vwap)
+            .map_or("-".to_string(), |vwap| number_format.derived(vwap)),
+        SortColumn::Updated => ticker
+            .last_updated
+            .map_or("-".to_string(), |at| format::age(at.elapsed())),
+        // Kept by the script, see `App::cell_text`.
+        SortColumn::Script => "-".to_string(),
+    }
+}
+
+/// Writes `rows` under the `columns` headers: CSV with a header line, or a JSON array of
+/// objects keyed by column title.
+pub fn write(
+    out: &mut impl Write,
+    export_format: ExportFormat,
+    columns: &[SortColumn],
+    rows: &[Vec<String>],
+) -> io::Result<()> {
+    match export_format {
+        ExportFormat::Csv => {
+            let header: Vec<String> = columns.iter().map(|c| csv_field(c.title())).collect();
+            writeln!(out, "{}", header.join(","))?;
+            for row in rows {
+                let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+                writeln!(out, "{}", fields.join(","))?;
+            }
+        }
+        ExportFormat::Json => {
+            let objects: Vec<serde_json::Map<String, serde_json::Value>> = rows
+                .iter()
+                .map(|row| {
+                    columns
+                        .iter()
+                        .zip(row)
+                        .map(|(column, value)| (column.title().to_string(), value.clone().into()))
+                        .collect()
+                })
+                .collect();
+            serde_json::to_writer_pretty(&mut *out, &objects)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Writes the table to a timestamped file such as `tickers-20240610-143000.csv` and returns
+/// its path.
+pub fn to_file(
+    config: &ExportConfig,
+    columns: &[SortColumn],
+    rows: &[Vec<String>],
+) -> io::Result<PathBuf> {
+    let name = format!(
+        "tickers-{}.{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        config.format.extension()
+    );
+    let path = match &config.dir {
+        Some(dir) => {
+            fs::create_dir_all(dir)?;
+            dir.join(name)
+        }
+        None => PathBuf::from(name),
+    };
+    let mut file = io::BufWriter::new(fs::File::create(&path)?);
+    write(&mut file, config.format, columns, rows)?;
+    file.flush()?;
+    Ok(path)
+}
@@ -0,0 +1,239 @@
+use crate::model::SortColumn;
+use chrono::{Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Duration};
+
+/// How a value is rounded once it has been scaled to the configured significant digits.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RoundingMode {
+    /// Round half away from zero.
+    #[default]
+    HalfUp,
+    /// Round half to even (banker's rounding).
+    HalfEven,
+    /// Truncate toward zero.
+    Down,
+    /// Round away from zero.
+    Up,
+    /// Round toward negative infinity.
+    Floor,
+    /// Round toward positive infinity.
+    Ceiling,
+}
+
+impl RoundingMode {
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            RoundingMode::HalfUp => value.round(),
+            RoundingMode::HalfEven => value.round_ties_even(),
+            RoundingMode::Down => value.trunc(),
+            RoundingMode::Up => value.signum() * value.abs().ceil(),
+            RoundingMode::Floor => value.floor(),
+            RoundingMode::Ceiling => value.ceil(),
+        }
+    }
+}
+
+/// Hours of the footer clock and of the timestamps shown on screen.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Clock {
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+    /// 24-hour timestamps and no footer clock.
+    #[serde(rename = "off")]
+    Off,
+}
+
+impl Clock {
+    fn time_format(self) -> &'static str {
+        match self {
+            Clock::H24 | Clock::Off => "%H:%M:%S",
+            Clock::H12 => "%I:%M:%S %p",
+        }
+    }
+}
+
+/// Formatting for values we compute ourselves (basis, spreads, P&L, ...), so every view
+/// renders them identically, and the separators and clock every number and time on screen
+/// is shown with.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct NumberFormat {
+    pub rounding: RoundingMode,
+    pub significant_digits: u32,
+    /// Put between groups of three integer digits, e.g. `,` or a space; none when empty.
+    pub thousands_separator: String,
+    pub decimal_separator: String,
+    /// Shows volumes with a K/M/B/T suffix.
+    pub si_suffixes: bool,
+    /// Fixed number of decimals for a table column, e.g. `{ last = 2 }`.
+    pub decimals: BTreeMap<SortColumn, usize>,
+    pub clock: Clock,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            rounding: RoundingMode::HalfUp,
+            significant_digits: 6,
+            thousands_separator: String::new(),
+            decimal_separator: ".".to_string(),
+            si_suffixes: false,
+            decimals: BTreeMap::new(),
+            clock: Clock::H24,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Rounds `value` to the configured number of significant digits.
+    pub fn round(&self, value: f64) -> f64 {
+        if value == 0.0 || !value.is_finite() {
+            return value;
+        }
+        let scale = 10f64.powi(self.decimals_exponent(value));
+        self.rounding.apply(value * scale) / scale
+    }
+
+    /// Rounds and renders `value`, dropping trailing fractional zeros.
+    pub fn derived(&self, value: f64) -> String {
+        let rounded = self.round(value);
+        if rounded == 0.0 {
+            return "0".to_string();
+        }
+        if !rounded.is_finite() {
+            return rounded.to_string();
+        }
+        let decimals = self.decimals_exponent(rounded).max(0) as usize;
+        let text = format!("{:.*}", decimals, rounded);
+        if text.contains('.') {
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            text
+        }
+    }
+
+    /// Renders `value` of a table column: with the column's fixed decimals if it has them,
+    /// otherwise with `default`, and then with the configured separators.
+    pub fn column(
+        &self,
+        column: SortColumn,
+        value: f64,
+        default: impl FnOnce(f64) -> String,
+    ) -> String {
+        let text = match self.decimals.get(&column) {
+            Some(decimals) if value.is_finite() => format!("{:.*}", decimals, value),
+            _ if self.si_suffixes && column == SortColumn::Volume => compact(value),
+            _ => default(value),
+        };
+        self.localize(&text)
+    }
+
+    /// Applies the configured separators to a number rendered with a `.` decimal point and no
+    /// grouping, such as `-1234.5`, `1.23B` or `4.5%`; anything else is returned unchanged.
+    pub fn localize(&self, text: &str) -> String {
+        if self.thousands_separator.is_empty() && self.decimal_separator == "." {
+            return text.to_string();
+        }
+        let (sign, unsigned) = match text.strip_prefix(['-', '+']) {
+            Some(rest) => (&text[..1], rest),
+            None => ("", text),
+        };
+        let end = unsigned
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(unsigned.len());
+        let (number, suffix) = unsigned.split_at(end);
+        let (integer, fraction) = match number.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (number, None),
+        };
+        if integer.is_empty()
+            || fraction.is_some_and(|f| f.contains('.'))
+            || !["", "K", "M", "B", "T", "%"].contains(&suffix)
+        {
+            return text.to_string();
+        }
+        let mut grouped = String::new();
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push_str(&self.thousands_separator);
+            }
+            grouped.push(digit);
+        }
+        match fraction {
+            Some(fraction) => format!(
+                "{}{}{}{}{}",
+                sign, grouped, self.decimal_separator, fraction, suffix
+            ),
+            None => format!("{}{}{}", sign, grouped, suffix),
+        }
+    }
+
+    /// An exchange millisecond timestamp in local time, on the configured clock.
+    pub fn timestamp(&self, ms: u64) -> String {
+        match Local.timestamp_millis_opt(ms as i64).single() {
+            Some(time) => time
+                .format(&format!("%Y-%m-%d {}", self.clock.time_format()))
+                .to_string(),
+            None => "-".to_string(),
+        }
+    }
+
+    /// The current local time for the footer, unless the clock is off.
+    pub fn clock(&self) -> Option<String> {
+        (self.clock != Clock::Off)
+            .then(|| Local::now().format(self.clock.time_format()).to_string())
+    }
+
+    fn decimals_exponent(&self, value: f64) -> i32 {
+        let magnitude = value.abs().log10().floor() as i32;
+        self.significant_digits.max(1) as i32 - 1 - magnitude
+    }
+}
+
+/// Renders an exchange millisecond timestamp in local time.
+pub fn timestamp(ms: u64) -> String {
+    match Local.timestamp_millis_opt(ms as i64).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => "-".to_string(),
+    }
+}
+
+/// How long ago something happened, in its largest whole unit, e.g. `42s ago` or `3h ago`.
+pub fn age(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
+/// `value` rounded to `digits` significant digits, without trailing zeros, e.g. `0.0000234`.
+pub fn significant(value: f64, digits: i32) -> String {
+    if value == 0.0 || !value.is_finite() {
+        return value.to_string();
+    }
+    let decimals = (digits - 1 - value.abs().log10().floor() as i32).max(0) as usize;
+    let text = format!("{:.*}", decimals, value);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// Renders large values with a K/M/B/T suffix, e.g. `1.23B`.
+pub fn compact(value: f64) -> String {
+    const SUFFIXES: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
+    SUFFIXES
+        .iter()
+        .find(|(scale, _)| value.abs() >= *scale)
+        .map(|(scale, suffix)| format!("{:.2}{}", value / scale, suffix))
+        .unwrap_or_else(|| format!("{:.2}", value))
+}
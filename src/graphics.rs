@@ -0,0 +1,336 @@
+//! Raster charts for terminals that show images: the kitty graphics protocol, iTerm2's
+//! inline images and sixel. Charts are drawn with plotters into an RGBA canvas, encoded as PNG
+//! or sixel and placed over the cells of the chart plot.
+
+use crate::error::Error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crossterm::{cursor, QueueableCommand};
+use plotters::{
+    backend::DrawingBackend,
+    coord::Shift,
+    prelude::{BitMapBackend, DrawingArea, DrawingAreaErrorKind, IntoDrawingArea, RGBColor},
+};
+use ratatui::{layout::Rect, style::Color};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    env,
+    fmt::Write as _,
+    io::{self, Write},
+};
+
+/// Image id of the chart, so each frame replaces the last one.
+const IMAGE_ID: u32 = 1;
+/// Largest base64 payload of one kitty escape.
+const KITTY_CHUNK: usize = 4096;
+/// Cell size assumed when the terminal does not report its size in pixels.
+const DEFAULT_CELL: (u16, u16) = (8, 16);
+
+/// Colors a sixel image can use.
+const SIXEL_COLORS: usize = 256;
+/// Color the bitmap backend, which has no alpha, is cleared to; what is left of it is transparent.
+const BLANK: [u8; 3] = [0x01, 0x02, 0x03];
+
+/// A canvas as plotters draws on it, in pixels from the top left corner.
+pub type Area<'a> = DrawingArea<BitMapBackend<'a>, Shift>;
+pub type DrawError = DrawingAreaErrorKind<<BitMapBackend<'static> as DrawingBackend>::ErrorType>;
+
+/// How the chart popup is drawn; `auto` uses images where the terminal supports them.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Renderer {
+    #[default]
+    Auto,
+    Kitty,
+    Iterm2,
+    Sixel,
+    /// Always the unicode chart.
+    #[serde(alias = "off")]
+    Unicode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Protocol {
+    Kitty,
+    Iterm2,
+    Sixel,
+}
+
+impl Protocol {
+    /// The image protocol of the terminal the environment describes, if it has one.
+    pub fn detect() -> Option<Self> {
+        let var = |name: &str| env::var(name).unwrap_or_default();
+        if env::var_os("KITTY_WINDOW_ID").is_some()
+            || var("TERM").contains("kitty")
+            || var("TERM_PROGRAM") == "ghostty"
+        {
+            Some(Protocol::Kitty)
+        } else if var("TERM_PROGRAM") == "iTerm.app"
+            || var("LC_TERMINAL") == "iTerm2"
+            || var("TERM_PROGRAM") == "WezTerm"
+        {
+            Some(Protocol::Iterm2)
+        } else if ["foot", "mlterm", "contour"]
+            .iter()
+            .any(|name| var("TERM").starts_with(name))
+        {
+            // xterm only shows sixel when built and started with it, which its environment
+            // does not tell.
+            Some(Protocol::Sixel)
+        } else {
+            None
+        }
+    }
+
+    /// Whether the image is part of the cells it covers, which text then has to overwrite.
+    pub fn in_cells(self) -> bool {
+        self != Protocol::Kitty
+    }
+}
+
+/// The protocol charts are drawn with and the pixel size of a terminal cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Graphics {
+    pub protocol: Protocol,
+    pub cell: (u16, u16),
+}
+
+impl Graphics {
+    /// The graphics `renderer` asks for, or `None` for unicode charts.
+    pub fn new(renderer: Renderer) -> Option<Self> {
+        let protocol = match renderer {
+            Renderer::Auto => Protocol::detect()?,
+            Renderer::Kitty => Protocol::Kitty,
+            Renderer::Iterm2 => Protocol::Iterm2,
+            Renderer::Sixel => Protocol::Sixel,
+            Renderer::Unicode => return None,
+        };
+        let cell = match crossterm::terminal::window_size() {
+            Ok(size) if size.width > 0 && size.columns > 0 && size.rows > 0 => {
+                (size.width / size.columns, size.height / size.rows)
+            }
+            _ => DEFAULT_CELL,
+        };
+        Some(Self { protocol, cell })
+    }
+}
+
+/// An RGBA image, transparent until drawn on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * 4],
+        }
+    }
+
+    /// The pixel at `(x, y)`, transparent black when nothing was drawn there.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+
+    /// Draws on the canvas with plotters. Pixels drawn in exactly [`BLANK`] stay transparent.
+    pub fn draw(
+        &mut self,
+        draw: impl FnOnce(&Area) -> Result<(), DrawError>,
+    ) -> Result<(), DrawError> {
+        let mut rgb: Vec<u8> = self
+            .pixels
+            .chunks(4)
+            .flat_map(|p| if p[3] == 0 { BLANK } else { [p[0], p[1], p[2]] })
+            .collect();
+        {
+            let area =
+                BitMapBackend::with_buffer(&mut rgb, (self.width, self.height)).into_drawing_area();
+            draw(&area)?;
+            area.present()?;
+        }
+        for (pixel, rgb) in self.pixels.chunks_mut(4).zip(rgb.chunks(3)) {
+            if rgb != BLANK {
+                pixel.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 0xff]);
+            }
+        }
+        Ok(())
+    }
+
+    /// The image as PNG; empty when it has no pixels, which PNG cannot encode.
+    pub fn png(&self) -> Vec<u8> {
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let written = encoder.write_header().and_then(|mut writer| {
+            writer.write_image_data(&self.pixels)?;
+            writer.finish()
+        });
+        if written.is_err() {
+            png.clear();
+        }
+        png
+    }
+
+    /// The image as sixel: transparent where nothing was drawn, with one color register per
+    /// color, or the 6x6x6 color cube when there are more colors than registers.
+    pub fn sixel(&self) -> String {
+        let mut registers: HashMap<[u8; 3], usize> = HashMap::new();
+        for pixel in self.pixels.chunks(4).filter(|p| p[3] > 0) {
+            let next = registers.len();
+            registers
+                .entry([pixel[0], pixel[1], pixel[2]])
+                .or_insert(next);
+        }
+        let cube = registers.len() > SIXEL_COLORS;
+        let level = |v: u8| (v as usize * 5 + 127) / 255;
+        let register = |pixel: &[u8]| -> Option<usize> {
+            if pixel[3] == 0 {
+                None
+            } else if cube {
+                Some(36 * level(pixel[0]) + 6 * level(pixel[1]) + level(pixel[2]))
+            } else {
+                registers.get(&[pixel[0], pixel[1], pixel[2]]).copied()
+            }
+        };
+        let mut palette: Vec<(usize, [u8; 3])> = if cube {
+            (0..216)
+                .map(|i| (i, [i / 36, i / 6 % 6, i % 6].map(|l| (l * 255 / 5) as u8)))
+                .collect()
+        } else {
+            registers.iter().map(|(rgb, i)| (*i, *rgb)).collect()
+        };
+        palette.sort();
+
+        // Transparent background, square pixels, and the image size.
+        let mut out = format!("\x1bP0;1;0q\"1;1;{};{}", self.width, self.height);
+        let percent = |v: u8| v as u32 * 100 / 255;
+        for (i, [r, g, b]) in &palette {
+            let _ = write!(
+                out,
+                "#{};2;{};{};{}",
+                i,
+                percent(*r),
+                percent(*g),
+                percent(*b)
+            );
+        }
+        let width = self.width as usize;
+        for band in (0..self.height as usize).step_by(6) {
+            let rows = band..(band + 6).min(self.height as usize);
+            // Six pixels high per sixel, one row of bits per color.
+            let mut bits: HashMap<usize, Vec<u8>> = HashMap::new();
+            for y in rows {
+                for x in 0..width {
+                    let i = (y * width + x) * 4;
+                    if let Some(register) = register(&self.pixels[i..i + 4]) {
+                        bits.entry(register).or_insert_with(|| vec![0; width])[x] |=
+                            1 << (y - band);
+                    }
+                }
+            }
+            let mut colors: Vec<_> = bits.into_iter().collect();
+            colors.sort();
+            for (register, sixels) in colors {
+                let _ = write!(out, "#{}", register);
+                for run in sixels.chunk_by(|a, b| a == b) {
+                    let sixel = (b'?' + run[0]) as char;
+                    match run.len() {
+                        1..=3 => out.extend(std::iter::repeat_n(sixel, run.len())),
+                        len => {
+                            let _ = write!(out, "!{}{}", len, sixel);
+                        }
+                    }
+                }
+                out.push('$');
+            }
+            out.push('-');
+        }
+        out.push_str("\x1b\\");
+        out
+    }
+}
+
+/// `color` as plotters draws it, grey when it has no RGB value such as `Color::Reset`.
+pub fn rgb(color: Color) -> RGBColor {
+    crate::theme::to_rgb(color).map_or(RGBColor(0x7f, 0x7f, 0x7f), |c| RGBColor(c.0, c.1, c.2))
+}
+
+/// A canvas to be shown over the cells of `area`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    pub area: Rect,
+    pub canvas: Canvas,
+}
+
+/// The escape sequence that shows `image` at the cursor, scaled to its area.
+pub fn escape(protocol: Protocol, image: &Image) -> String {
+    let png = STANDARD.encode(image.canvas.png());
+    let (columns, rows) = (image.area.width, image.area.height);
+    match protocol {
+        Protocol::Kitty => {
+            let chunks: Vec<&str> = png
+                .as_bytes()
+                .chunks(KITTY_CHUNK)
+                .map(|c| std::str::from_utf8(c).unwrap_or_default())
+                .collect();
+            let mut out = String::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let more = u8::from(i + 1 < chunks.len());
+                if i == 0 {
+                    out.push_str(&format!(
+                        "\x1b_Ga=T,f=100,i={},q=2,C=1,c={},r={},m={};{}\x1b\\",
+                        IMAGE_ID, columns, rows, more, chunk
+                    ));
+                } else {
+                    out.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+                }
+            }
+            out
+        }
+        Protocol::Iterm2 => format!(
+            "\x1b]1337;File=inline=1;width={};height={};preserveAspectRatio=0;doNotMoveCursor=1:{}\x07",
+            columns, rows, png
+        ),
+        Protocol::Sixel => image.canvas.sixel(),
+    }
+}
+
+/// Draws `image` over the terminal, or removes the one shown when `None`.
+pub fn show(protocol: Protocol, image: Option<&Image>) -> Result<(), Error> {
+    let mut out = io::stdout();
+    if protocol == Protocol::Kitty {
+        write!(out, "\x1b_Ga=d,d=I,i={},q=2\x1b\\", IMAGE_ID).map_err(Error::Terminal)?;
+    }
+    if let Some(image) = image {
+        out.queue(cursor::SavePosition).map_err(Error::Terminal)?;
+        if protocol == Protocol::Sixel {
+            // Sixel pixels left transparent keep what was drawn before, so the last image is
+            // blanked first.
+            let blank = " ".repeat(image.area.width as usize);
+            for y in image.area.top()..image.area.bottom() {
+                out.queue(cursor::MoveTo(image.area.x, y))
+                    .map_err(Error::Terminal)?;
+                out.write_all(blank.as_bytes()).map_err(Error::Terminal)?;
+            }
+        }
+        out.queue(cursor::MoveTo(image.area.x, image.area.y))
+            .map_err(Error::Terminal)?;
+        out.write_all(escape(protocol, image).as_bytes())
+            .map_err(Error::Terminal)?;
+        out.queue(cursor::RestorePosition)
+            .map_err(Error::Terminal)?;
+    }
+    out.flush().map_err(Error::Terminal)
+}
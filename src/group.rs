@@ -0,0 +1,96 @@
+//! Grouping of the table by base asset: every market of a base, such as `BTCUSDT`, `BTCUSDC`
+//! and `BTCUSD_PERP`, collapses into one row with aggregate figures.
+
+use crate::{exchange::Exchange, model::HrTicker};
+use std::collections::BTreeMap;
+
+/// The markets of one base asset, with their aggregate as a ticker of its own.
+pub struct Group<'a> {
+    /// `BASE/QUOTE`, with USD stablecoin quotes collapsed into `USD` so the markets' prices are
+    /// comparable, e.g. `BTC/USD`.
+    pub key: String,
+    /// Highest quote volume first.
+    pub members: Vec<&'a HrTicker>,
+    /// Summed volumes and trade counts, and prices and changes weighted by quote volume.
+    pub total: HrTicker,
+}
+
+/// Key of the group `symbol` belongs to; symbols that cannot be split into base and quote are
+/// groups of their own. Contract suffixes such as `_PERP` are ignored.
+pub fn key(exchange: Exchange, symbol: &str) -> String {
+    let market = symbol.split('_').next().unwrap_or(symbol);
+    exchange.instrument(market).map_or_else(
+        || symbol.to_string(),
+        |instrument| instrument.comparison_key(),
+    )
+}
+
+/// Groups `tickers` by base asset, in key order.
+pub fn group<'a>(
+    exchange: Exchange,
+    tickers: impl IntoIterator<Item = &'a HrTicker>,
+) -> Vec<Group<'a>> {
+    let mut groups: BTreeMap<String, Vec<&HrTicker>> = BTreeMap::new();
+    for ticker in tickers {
+        groups
+            .entry(key(exchange, &ticker.s))
+            .or_default()
+            .push(ticker);
+    }
+    groups
+        .into_iter()
+        .map(|(key, mut members)| {
+            members.sort_by(|a, b| quote_volume(b).total_cmp(&quote_volume(a)));
+            let total = aggregate(&key, &members);
+            Group {
+                key,
+                members,
+                total,
+            }
+        })
+        .collect()
+}
+
+fn quote_volume(ticker: &HrTicker) -> f64 {
+    ticker.q.parse().unwrap_or(0.0)
+}
+
+/// A ticker named `key` standing for all of `members`, which must not be empty.
+pub fn aggregate(key: &str, members: &[&HrTicker]) -> HrTicker {
+    let volumes: Vec<f64> = members.iter().map(|t| quote_volume(t)).collect();
+    let total_volume: f64 = volumes.iter().sum();
+    // Markets without volume yet count equally.
+    let weights: Vec<f64> = if total_volume > 0.0 {
+        volumes.iter().map(|v| v / total_volume).collect()
+    } else {
+        vec![1.0 / members.len() as f64; members.len()]
+    };
+    let weighted = |field: fn(&HrTicker) -> f32| -> f32 {
+        members
+            .iter()
+            .zip(&weights)
+            .map(|(t, w)| field(t) as f64 * w)
+            .sum::<f64>() as f32
+    };
+    let base_volume: f64 = members.iter().map(|t| t.v.parse().unwrap_or(0.0)).sum();
+
+    let mut total = members[0].clone();
+    total.s = key.to_string();
+    total.E = members.iter().map(|t| t.E).max().unwrap_or_default();
+    total.p = weighted(|t| t.p);
+    total.P = weighted(|t| t.P);
+    total.w = weighted(|t| t.w);
+    total.c = weighted(|t| t.c);
+    total.o = weighted(|t| t.o);
+    total.h = weighted(|t| t.h);
+    total.l = weighted(|t| t.l);
+    total.previous_price = weighted(|t| t.previous_price);
+    total.v = base_volume.to_string();
+    total.q = total_volume.to_string();
+    total.n = members.iter().map(|t| t.n).sum();
+    total.funding_rate = None;
+    total.last_changed = members.iter().filter_map(|t| t.last_changed).max();
+    total.last_updated = members.iter().filter_map(|t| t.last_updated).max();
+    total.history = Default::default();
+    total
+}
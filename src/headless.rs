@@ -0,0 +1,26 @@
+//! `--headless` mode: normalized ticker updates as JSON lines on stdout, for shell pipelines.
+
+use crate::stream::TickerStream;
+use std::io::{self, Write};
+
+/// Writes every ticker from `stream` to stdout as one JSON object per line, keeping only `symbols`
+/// when the list is non-empty. Returns once the stream ends or stdout is closed.
+pub async fn run(mut stream: TickerStream, symbols: &[String]) -> io::Result<()> {
+    let symbols: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+    while let Some(tickers) = stream.next().await {
+        let mut out = io::stdout().lock();
+        for ticker in tickers
+            .iter()
+            .filter(|t| symbols.is_empty() || symbols.contains(&t.s.to_uppercase()))
+        {
+            serde_json::to_writer(&mut out, ticker)?;
+            out.write_all(b"\n")?;
+        }
+        // Flushed per batch so readers such as `jq` see updates as they arrive.
+        match out.flush() {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Market heatmap: a treemap of the most traded symbols, sized by 24h quote volume.
+
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+
+/// `[heatmap]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct HeatmapConfig {
+    /// How many of the highest-volume symbols get a block.
+    pub max_symbols: usize,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self { max_symbols: 40 }
+    }
+}
+
+/// A rectangle in fractional cells, with the height doubled so that terminal cells, about
+/// twice as tall as they are wide, come out square.
+#[derive(Clone, Copy, Debug)]
+struct Area {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Area {
+    fn short_side(&self) -> f64 {
+        self.width.min(self.height)
+    }
+
+    /// Snaps to whole cells. Edges are rounded rather than sizes, so neighbours share an edge
+    /// without gaps or overlaps.
+    fn cells(&self, origin: Rect) -> Rect {
+        let left = self.x.round() as u16;
+        let right = (self.x + self.width).round() as u16;
+        let top = (self.y / 2.0).round() as u16;
+        let bottom = ((self.y + self.height) / 2.0).round() as u16;
+        Rect::new(
+            origin.x + left,
+            origin.y + top,
+            right.saturating_sub(left),
+            bottom.saturating_sub(top),
+        )
+    }
+}
+
+/// Lays out one block per weight in `area` with a squarified treemap, so each block's area is
+/// proportional to its weight and blocks stay close to square. Weights must be sorted largest
+/// first; blocks too small to fill a cell come out empty.
+pub fn treemap(weights: &[f64], area: Rect) -> Vec<Rect> {
+    let total: f64 = weights.iter().filter(|w| **w > 0.0).sum();
+    if total <= 0.0 || area.area() == 0 {
+        return vec![Rect::default(); weights.len()];
+    }
+    let mut free = Area {
+        x: 0.0,
+        y: 0.0,
+        width: area.width as f64,
+        height: area.height as f64 * 2.0,
+    };
+    let scale = free.width * free.height / total;
+    let sizes: Vec<f64> = weights.iter().map(|w| w.max(0.0) * scale).collect();
+
+    let mut blocks = Vec::with_capacity(sizes.len());
+    let mut row: Vec<f64> = Vec::new();
+    for &size in &sizes {
+        let side = free.short_side();
+        let mut longer = row.clone();
+        longer.push(size);
+        if !row.is_empty() && worst(&longer, side) > worst(&row, side) {
+            free = lay_row(&row, free, &mut blocks);
+            row.clear();
+        }
+        row.push(size);
+    }
+    lay_row(&row, free, &mut blocks);
+    blocks.into_iter().map(|block| block.cells(area)).collect()
+}
+
+/// The worst aspect ratio of a row of `sizes` laid along a side of length `side`.
+fn worst(sizes: &[f64], side: f64) -> f64 {
+    let sum: f64 = sizes.iter().sum();
+    let max = sizes.iter().copied().fold(f64::MIN, f64::max);
+    let min = sizes.iter().copied().fold(f64::MAX, f64::min);
+    if sum <= 0.0 || min <= 0.0 {
+        return f64::MAX;
+    }
+    let side = side * side;
+    (side * max / (sum * sum)).max(sum * sum / (side * min))
+}
+
+/// Lays `sizes` along the short side of `free` and returns what is left of it.
+fn lay_row(sizes: &[f64], free: Area, blocks: &mut Vec<Area>) -> Area {
+    let sum: f64 = sizes.iter().sum();
+    if free.width >= free.height {
+        // A column on the left.
+        let width = if free.height > 0.0 {
+            sum / free.height
+        } else {
+            0.0
+        };
+        let mut y = free.y;
+        for size in sizes {
+            let height = if width > 0.0 { size / width } else { 0.0 };
+            blocks.push(Area {
+                x: free.x,
+                y,
+                width,
+                height,
+            });
+            y += height;
+        }
+        Area {
+            x: free.x + width,
+            width: (free.width - width).max(0.0),
+            ..free
+        }
+    } else {
+        // A row along the top.
+        let height = if free.width > 0.0 {
+            sum / free.width
+        } else {
+            0.0
+        };
+        let mut x = free.x;
+        for size in sizes {
+            let width = if height > 0.0 { size / height } else { 0.0 };
+            blocks.push(Area {
+                x,
+                y: free.y,
+                width,
+                height,
+            });
+            x += width;
+        }
+        Area {
+            y: free.y + height,
+            height: (free.height - height).max(0.0),
+            ..free
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Technical indicators (RSI, EMA crossover and session VWAP) computed from 1m klines, kept
+//! current with the live stream for the tracked symbols.
+
+use crate::{
+    chart::{self, Candle},
+    error,
+    exchange::Exchange,
+    model::TickerMap,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::watch;
+
+pub const RSI_PERIOD: usize = 14;
+pub const FAST_EMA: usize = 9;
+pub const SLOW_EMA: usize = 21;
+pub const SMA_PERIOD: usize = 20;
+pub const BOLLINGER_PERIOD: usize = 20;
+/// Distance of the Bollinger bands from their middle, in standard deviations.
+pub const BOLLINGER_WIDTH: f64 = 2.0;
+/// Enough candles for a full UTC day, the longest VWAP session.
+const KLINE_LIMIT: usize = 1440;
+const DAY_MS: u64 = 86_400_000;
+/// How often the live price is folded into the candles and the indicators recomputed.
+const LIVE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// `[indicators]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct IndicatorsConfig {
+    /// Shows the RSI, EMA and VWAP columns. Klines are only available from Binance.
+    pub enabled: bool,
+    /// Symbols to compute indicators for, in addition to the pinned ones.
+    pub symbols: Vec<String>,
+    /// How often the klines are fetched again.
+    pub refresh_secs: u64,
+}
+
+impl Default for IndicatorsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            symbols: Vec::new(),
+            refresh_secs: 300,
+        }
+    }
+}
+
+/// Whether the fast EMA is above the slow one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trend {
+    Bearish,
+    Bullish,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Indicators {
+    pub rsi: Option<f64>,
+    pub trend: Option<Trend>,
+    pub vwap: Option<f64>,
+}
+
+impl Indicators {
+    pub fn compute(candles: &[Candle]) -> Self {
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        let fast = ema(&closes, FAST_EMA).last().copied().flatten();
+        let slow = ema(&closes, SLOW_EMA).last().copied().flatten();
+        Self {
+            rsi: rsi(&closes, RSI_PERIOD),
+            trend: fast.zip(slow).map(|(fast, slow)| {
+                if fast >= slow {
+                    Trend::Bullish
+                } else {
+                    Trend::Bearish
+                }
+            }),
+            vwap: vwap(candles).last().copied().flatten(),
+        }
+    }
+}
+
+/// Exponential moving average of `values`, aligned with them. Seeded with the simple average
+/// of the first `period` values, so the first `period - 1` entries are `None`.
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 || values.len() < period {
+        return out;
+    }
+    let k = 2.0 / (period as f64 + 1.0);
+    let mut average = values[..period].iter().sum::<f64>() / period as f64;
+    out[period - 1] = Some(average);
+    for (i, value) in values.iter().enumerate().skip(period) {
+        average += (value - average) * k;
+        out[i] = Some(average);
+    }
+    out
+}
+
+/// Simple moving average of `values`, aligned with them; the first `period - 1` entries are
+/// `None`.
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    let mut out = vec![None; values.len()];
+    if period == 0 {
+        return out;
+    }
+    for (i, window) in values.windows(period).enumerate() {
+        out[i + period - 1] = Some(window.iter().sum::<f64>() / period as f64);
+    }
+    out
+}
+
+/// Lower and upper Bollinger bands: the simple moving average minus and plus `width` standard
+/// deviations of the same window.
+pub fn bollinger(
+    values: &[f64],
+    period: usize,
+    width: f64,
+) -> (Vec<Option<f64>>, Vec<Option<f64>>) {
+    let mut lower = vec![None; values.len()];
+    let mut upper = vec![None; values.len()];
+    if period == 0 {
+        return (lower, upper);
+    }
+    for (i, window) in values.windows(period).enumerate() {
+        let mean = window.iter().sum::<f64>() / period as f64;
+        let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / period as f64;
+        let spread = width * variance.sqrt();
+        lower[i + period - 1] = Some(mean - spread);
+        upper[i + period - 1] = Some(mean + spread);
+    }
+    (lower, upper)
+}
+
+/// Wilder's relative strength index of the last close, or `None` with fewer than `period + 1`
+/// closes.
+pub fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || closes.len() <= period {
+        return None;
+    }
+    let changes: Vec<f64> = closes.windows(2).map(|w| w[1] - w[0]).collect();
+    let (mut gain, mut loss) = changes[..period]
+        .iter()
+        .fold((0.0, 0.0), |(gain, loss), change| {
+            (gain + change.max(0.0), loss + (-change).max(0.0))
+        });
+    gain /= period as f64;
+    loss /= period as f64;
+    for change in &changes[period..] {
+        gain = (gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+    }
+    Some(if loss == 0.0 {
+        100.0
+    } else {
+        100.0 - 100.0 / (1.0 + gain / loss)
+    })
+}
+
+/// Volume-weighted average of the typical price, restarting at each UTC midnight. `None` until
+/// the session has traded volume.
+pub fn vwap(candles: &[Candle]) -> Vec<Option<f64>> {
+    let mut session = None;
+    let (mut weighted, mut volume) = (0.0, 0.0);
+    candles
+        .iter()
+        .map(|candle| {
+            let day = candle.open_time / DAY_MS;
+            if session != Some(day) {
+                session = Some(day);
+                (weighted, volume) = (0.0, 0.0);
+            }
+            let typical = (candle.high + candle.low + candle.close) / 3.0;
+            weighted += typical * candle.volume;
+            volume += candle.volume;
+            (volume > 0.0).then(|| weighted / volume)
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+pub struct IndicatorStore {
+    /// Symbols the background task keeps indicators for.
+    pub tracked: Vec<String>,
+    pub values: HashMap<String, Indicators>,
+}
+
+pub type IndicatorCache = Arc<Mutex<IndicatorStore>>;
+
+/// Keeps `cache` current for its tracked symbols: klines are fetched every `refresh_secs` and
+/// the live price is folded into the last candle in between. Candles are dropped whenever the
+/// exchange is switched, and none are fetched from exchanges without klines.
+pub async fn run(
+    config: IndicatorsConfig,
+    mut exchange: watch::Receiver<Exchange>,
+    tickers: Arc<Mutex<TickerMap>>,
+    cache: IndicatorCache,
+) {
+    let refresh = Duration::from_secs(config.refresh_secs.max(60));
+    let mut candles: HashMap<String, Vec<Candle>> = HashMap::new();
+    let mut fetched_at: HashMap<String, Instant> = HashMap::new();
+    loop {
+        if exchange.has_changed().unwrap_or(false) {
+            candles.clear();
+            fetched_at.clear();
+        }
+        let exchange = *exchange.borrow_and_update();
+        let tracked = if exchange.has_klines() {
+            cache.lock().unwrap().tracked.clone()
+        } else {
+            Vec::new()
+        };
+        candles.retain(|symbol, _| tracked.contains(symbol));
+        fetched_at.retain(|symbol, _| tracked.contains(symbol));
+        for symbol in &tracked {
+            if fetched_at
+                .get(symbol)
+                .is_some_and(|at| at.elapsed() < refresh)
+            {
+                continue;
+            }
+            fetched_at.insert(symbol.clone(), Instant::now());
+            match exchange.klines(symbol, KLINE_LIMIT, None).await {
+                Ok(fetched) => {
+                    candles.insert(symbol.clone(), fetched);
+                }
+                Err(err) => error::report(format!("klines for {}: {}", symbol, err)),
+            }
+        }
+
+        let values: HashMap<String, Indicators> = {
+            let live = tickers.lock().unwrap();
+            candles
+                .iter_mut()
+                .map(|(symbol, candles)| {
+                    if let Some(ticker) = live.get(symbol) {
+                        chart::fold_price(candles, ticker.c as f64, ticker.E);
+                    }
+                    (symbol.clone(), Indicators::compute(candles))
+                })
+                .collect()
+        };
+        cache.lock().unwrap().values = values;
+        tokio::time::sleep(LIVE_INTERVAL).await;
+    }
+}
@@ -0,0 +1,635 @@
+use crate::model::SortColumn;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Everything a key can be bound to.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Quit,
+    Next,
+    Previous,
+    PageDown,
+    PageUp,
+    First,
+    Last,
+    ScrollLeft,
+    ScrollRight,
+    NextColor,
+    PreviousColor,
+    FocusNextPane,
+    SortNext,
+    ReverseSort,
+    SortBySymbol,
+    SortByLast,
+    SortByPercentChange,
+    SortByOpen,
+    SortByHigh,
+    SortByLow,
+    SortByVolume,
+    SortByRank,
+    SortByMarketCap,
+    SortByLiquidations,
+    SortByChange1m,
+    SortByChange5m,
+    SortByChange15m,
+    SortByRsi,
+    SortByEmaTrend,
+    SortByVwap,
+    SortByUpdated,
+    SortByScript,
+    GroupByBase,
+    CycleDenomination,
+    CycleChangeBasis,
+    Pin,
+    ToggleDetail,
+    ToggleComparison,
+    TogglePortfolio,
+    TogglePaper,
+    ToggleAccount,
+    Buy,
+    Sell,
+    ToggleLiquidations,
+    ToggleAlerts,
+    ToggleNotifications,
+    ToggleAlertHistory,
+    SnoozeAlert,
+    ToggleNews,
+    TogglePlugins,
+    ToggleStatus,
+    NewItem,
+    EditItem,
+    DeleteItem,
+    ToggleChart,
+    ToggleHistory,
+    CompareCharts,
+    ToggleSma,
+    ToggleEma,
+    ToggleBollinger,
+    ToggleVwap,
+    ToggleVolumeProfile,
+    CycleChartStyle,
+    ToggleLogScale,
+    CycleChartRange,
+    ToggleGrid,
+    ToggleHeatmap,
+    ToggleMovers,
+    ZoomIn,
+    ZoomOut,
+    Export,
+    CommandPalette,
+    WatchlistOnly,
+    NextWatchlist,
+    PreviousWatchlist,
+    Pause,
+    SwitchExchange,
+    EditNote,
+    CopySymbol,
+    CopyRow,
+}
+
+impl Action {
+    pub const ALL: [Action; 80] = [
+        Action::Quit,
+        Action::Previous,
+        Action::Next,
+        Action::PageUp,
+        Action::PageDown,
+        Action::First,
+        Action::Last,
+        Action::ScrollLeft,
+        Action::ScrollRight,
+        Action::NextColor,
+        Action::PreviousColor,
+        Action::SortNext,
+        Action::ReverseSort,
+        Action::SortBySymbol,
+        Action::SortByLast,
+        Action::SortByPercentChange,
+        Action::SortByOpen,
+        Action::SortByHigh,
+        Action::SortByLow,
+        Action::SortByVolume,
+        Action::SortByRank,
+        Action::SortByMarketCap,
+        Action::SortByLiquidations,
+        Action::SortByChange1m,
+        Action::SortByChange5m,
+        Action::SortByChange15m,
+        Action::SortByRsi,
+        Action::SortByEmaTrend,
+        Action::SortByVwap,
+        Action::SortByUpdated,
+        Action::SortByScript,
+        Action::GroupByBase,
+        Action::CycleDenomination,
+        Action::CycleChangeBasis,
+        Action::Pin,
+        Action::ToggleDetail,
+        Action::ToggleComparison,
+        Action::TogglePortfolio,
+        Action::TogglePaper,
+        Action::ToggleAccount,
+        Action::Buy,
+        Action::Sell,
+        Action::FocusNextPane,
+        Action::ToggleLiquidations,
+        Action::ToggleAlerts,
+        Action::ToggleNotifications,
+        Action::ToggleAlertHistory,
+        Action::SnoozeAlert,
+        Action::ToggleNews,
+        Action::TogglePlugins,
+        Action::ToggleStatus,
+        Action::NewItem,
+        Action::EditItem,
+        Action::DeleteItem,
+        Action::ToggleChart,
+        Action::ToggleHistory,
+        Action::CompareCharts,
+        Action::ToggleSma,
+        Action::ToggleEma,
+        Action::ToggleBollinger,
+        Action::ToggleVwap,
+        Action::ToggleVolumeProfile,
+        Action::CycleChartStyle,
+        Action::ToggleLogScale,
+        Action::CycleChartRange,
+        Action::ToggleGrid,
+        Action::ToggleHeatmap,
+        Action::ToggleMovers,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::Export,
+        Action::CommandPalette,
+        Action::WatchlistOnly,
+        Action::NextWatchlist,
+        Action::PreviousWatchlist,
+        Action::Pause,
+        Action::SwitchExchange,
+        Action::EditNote,
+        Action::CopySymbol,
+        Action::CopyRow,
+    ];
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::Next => "down",
+            Action::Previous => "up",
+            Action::PageDown => "page down",
+            Action::PageUp => "page up",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::ScrollLeft => "scroll left",
+            Action::ScrollRight => "scroll right",
+            Action::NextColor => "next color",
+            Action::PreviousColor => "previous color",
+            Action::FocusNextPane => "focus pane",
+            Action::SortNext => "sort next column",
+            Action::ReverseSort => "reverse sort",
+            Action::SortBySymbol => "sort by symbol",
+            Action::SortByLast => "sort by last",
+            Action::SortByPercentChange => "sort by percent change",
+            Action::SortByOpen => "sort by open",
+            Action::SortByHigh => "sort by high",
+            Action::SortByLow => "sort by low",
+            Action::SortByVolume => "sort by volume",
+            Action::SortByRank => "sort by rank",
+            Action::SortByMarketCap => "sort by market cap",
+            Action::SortByLiquidations => "sort by liquidations",
+            Action::SortByChange1m => "sort by 1m change",
+            Action::SortByChange5m => "sort by 5m change",
+            Action::SortByChange15m => "sort by 15m change",
+            Action::SortByRsi => "sort by RSI",
+            Action::SortByEmaTrend => "sort by EMA trend",
+            Action::SortByVwap => "sort by VWAP",
+            Action::SortByUpdated => "sort by update time",
+            Action::SortByScript => "sort by script",
+            Action::GroupByBase => "group by base asset",
+            Action::CycleDenomination => "price denomination",
+            Action::CycleChangeBasis => "change basis",
+            Action::Pin => "pin",
+            Action::ToggleDetail => "details",
+            Action::ToggleComparison => "compare exchanges",
+            Action::TogglePortfolio => "portfolio",
+            Action::TogglePaper => "paper trading",
+            Action::ToggleAccount => "account",
+            Action::Buy => "paper buy",
+            Action::Sell => "paper sell",
+            Action::ToggleLiquidations => "liquidations",
+            Action::ToggleAlerts => "alerts",
+            Action::ToggleNotifications => "notifications",
+            Action::ToggleAlertHistory => "alert history",
+            Action::SnoozeAlert => "snooze alert",
+            Action::ToggleNews => "news",
+            Action::TogglePlugins => "plugins",
+            Action::ToggleStatus => "request budget",
+            Action::NewItem => "new",
+            Action::EditItem => "edit",
+            Action::DeleteItem => "delete",
+            Action::ToggleChart => "chart",
+            Action::ToggleHistory => "history chart",
+            Action::CompareCharts => "compare charts",
+            Action::ToggleSma => "chart SMA",
+            Action::ToggleEma => "chart EMA",
+            Action::ToggleBollinger => "chart Bollinger bands",
+            Action::ToggleVwap => "chart VWAP",
+            Action::ToggleVolumeProfile => "volume profile",
+            Action::CycleChartStyle => "chart style",
+            Action::ToggleLogScale => "log scale",
+            Action::CycleChartRange => "chart range",
+            Action::ToggleGrid => "chart grid",
+            Action::ToggleHeatmap => "heatmap",
+            Action::ToggleMovers => "top gainers and losers",
+            Action::ZoomIn => "zoom in",
+            Action::ZoomOut => "zoom out",
+            Action::Export => "export",
+            Action::CommandPalette => "command palette",
+            Action::WatchlistOnly => "watchlist only",
+            Action::NextWatchlist => "next watchlist",
+            Action::PreviousWatchlist => "previous watchlist",
+            Action::Pause => "pause",
+            Action::SwitchExchange => "switch exchange",
+            Action::EditNote => "note and tags",
+            Action::CopySymbol => "copy symbol",
+            Action::CopyRow => "copy row",
+        }
+    }
+
+    /// The column a sort-by action sorts by.
+    pub fn sort_column(self) -> Option<SortColumn> {
+        let column = match self {
+            Action::SortBySymbol => SortColumn::Symbol,
+            Action::SortByLast => SortColumn::Last,
+            Action::SortByPercentChange => SortColumn::PercentChange,
+            Action::SortByOpen => SortColumn::Open,
+            Action::SortByHigh => SortColumn::High,
+            Action::SortByLow => SortColumn::Low,
+            Action::SortByVolume => SortColumn::Volume,
+            Action::SortByRank => SortColumn::Rank,
+            Action::SortByMarketCap => SortColumn::MarketCap,
+            Action::SortByLiquidations => SortColumn::Liquidations,
+            Action::SortByChange1m => SortColumn::Change1m,
+            Action::SortByChange5m => SortColumn::Change5m,
+            Action::SortByChange15m => SortColumn::Change15m,
+            Action::SortByRsi => SortColumn::Rsi,
+            Action::SortByEmaTrend => SortColumn::EmaTrend,
+            Action::SortByVwap => SortColumn::Vwap,
+            Action::SortByUpdated => SortColumn::Updated,
+            Action::SortByScript => SortColumn::Script,
+            _ => return None,
+        };
+        Some(column)
+    }
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["Esc", "q"],
+            Action::Next => &["Down", "j"],
+            Action::Previous => &["Up", "k"],
+            Action::PageDown => &["PageDown"],
+            Action::PageUp => &["PageUp"],
+            Action::First => &["Home", "g g"],
+            Action::Last => &["End", "G"],
+            Action::ScrollLeft => &["Shift+Left"],
+            Action::ScrollRight => &["Shift+Right"],
+            Action::NextColor => &["Right", "l"],
+            Action::PreviousColor => &["Left", "h"],
+            Action::FocusNextPane => &["Shift+Tab"],
+            Action::SortNext => &["Tab"],
+            Action::ReverseSort => &["r"],
+            Action::SortBySymbol => &["S s"],
+            Action::SortByLast => &["S l"],
+            Action::SortByPercentChange => &["S p"],
+            Action::SortByOpen => &["S o"],
+            Action::SortByHigh => &["S h"],
+            Action::SortByLow => &["S L"],
+            Action::SortByVolume => &["S v"],
+            Action::SortByRank => &["S r"],
+            Action::SortByMarketCap => &["S m"],
+            Action::SortByLiquidations => &["S q"],
+            Action::SortByChange1m => &["S 1"],
+            Action::SortByChange5m => &["S 5"],
+            Action::SortByChange15m => &["S f"],
+            Action::SortByRsi => &["S i"],
+            Action::SortByEmaTrend => &["S e"],
+            Action::SortByVwap => &["S w"],
+            Action::SortByUpdated => &["S u"],
+            Action::SortByScript => &["S x"],
+            Action::GroupByBase => &["Ctrl+g"],
+            Action::CycleDenomination => &["D"],
+            Action::CycleChangeBasis => &["%"],
+            Action::Pin => &["p"],
+            Action::ToggleDetail => &["d"],
+            Action::ToggleComparison => &["c"],
+            Action::TogglePortfolio => &["P"],
+            Action::TogglePaper => &["T"],
+            Action::ToggleAccount => &["A"],
+            Action::Buy => &["b"],
+            Action::Sell => &["s"],
+            Action::ToggleLiquidations => &["L"],
+            Action::ToggleAlerts => &["a"],
+            Action::ToggleNotifications => &["N"],
+            Action::ToggleAlertHistory => &["Ctrl+a"],
+            Action::SnoozeAlert => &["z"],
+            Action::ToggleNews => &["u"],
+            Action::TogglePlugins => &["I"],
+            Action::ToggleStatus => &["F12"],
+            Action::NewItem => &["n"],
+            Action::EditItem => &["Enter"],
+            Action::DeleteItem => &["Delete"],
+            Action::ToggleChart => &["C"],
+            Action::ToggleHistory => &["H"],
+            Action::CompareCharts => &["v"],
+            Action::ToggleSma => &["m"],
+            Action::ToggleEma => &["E"],
+            Action::ToggleBollinger => &["B"],
+            Action::ToggleVwap => &["V"],
+            Action::ToggleVolumeProfile => &["f"],
+            Action::CycleChartStyle => &["K"],
+            Action::ToggleLogScale => &["o"],
+            Action::CycleChartRange => &["R"],
+            Action::ToggleGrid => &["M"],
+            Action::ToggleHeatmap => &["O"],
+            Action::ToggleMovers => &["t"],
+            Action::ZoomIn => &["+", "="],
+            Action::ZoomOut => &["-"],
+            Action::Export => &["e"],
+            Action::CommandPalette => &["Ctrl+p"],
+            Action::WatchlistOnly => &["w"],
+            Action::NextWatchlist => &["]"],
+            Action::PreviousWatchlist => &["["],
+            Action::Pause => &["Space"],
+            Action::SwitchExchange => &["x"],
+            Action::EditNote => &["#"],
+            Action::CopySymbol => &["y"],
+            Action::CopyRow => &["Y"],
+        }
+    }
+}
+
+/// A key with the modifiers that matter for matching. Shift is folded into the character for
+/// printable keys, so `L` and `Shift+l` are the same binding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => Self {
+                code: KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            KeyCode::BackTab => Self {
+                code: KeyCode::BackTab,
+                modifiers: modifiers - KeyModifiers::SHIFT,
+            },
+            _ => Self { code, modifiers },
+        }
+    }
+
+    /// Parses bindings such as `q`, `Esc`, `Ctrl+p`, `Shift+Tab`, `PageDown` or `F5`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut parts: Vec<&str> = text.split('+').collect();
+        // A trailing empty part means the key itself is `+`.
+        let key = match parts.pop() {
+            Some("") if text.ends_with('+') => {
+                parts.pop();
+                "+"
+            }
+            Some(key) => key,
+            None => return Err(format!("empty key binding {:?}", text)),
+        };
+        for modifier in parts {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier {:?} in {:?}", modifier, text)),
+            };
+        }
+        let code = match key.to_ascii_lowercase().as_str() {
+            "esc" | "escape" => KeyCode::Esc,
+            "enter" | "return" => KeyCode::Enter,
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            lower if lower.len() > 1 && lower.starts_with('f') => lower[1..]
+                .parse()
+                .map(KeyCode::F)
+                .map_err(|_| format!("unknown key {:?}", text))?,
+            _ => {
+                let mut chars = key.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(format!("unknown key {:?}", text)),
+                }
+            }
+        };
+        Ok(Self::new(code, modifiers))
+    }
+
+    /// Parses a binding of one key, or a chord of two keys pressed one after the other and
+    /// separated by a space, such as `g g` or `Ctrl+w l`.
+    pub fn parse_sequence(text: &str) -> Result<Vec<Self>, String> {
+        let keys = text
+            .split_whitespace()
+            .map(Self::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        match keys.len() {
+            0 => Err(format!("empty key binding {:?}", text)),
+            1 | 2 => Ok(keys),
+            _ => Err(format!("chord {:?} has more than two keys", text)),
+        }
+    }
+
+    pub fn from_event(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+}
+
+/// What a key press does, given the first key of a chord pressed before it, if any.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lookup {
+    Action(Action),
+    /// The key starts a chord; the next key completes it.
+    Pending(KeyBinding),
+    None,
+}
+
+pub struct Keymap {
+    bindings: HashMap<KeyBinding, Action>,
+    /// Two-key chords by their first and second key.
+    chords: HashMap<(KeyBinding, KeyBinding), Action>,
+    /// First keys of the chords, which wait for a second key instead of acting alone.
+    prefixes: HashSet<KeyBinding>,
+    /// Key labels per action as configured, for the footer.
+    labels: BTreeMap<Action, Vec<String>>,
+}
+
+impl Keymap {
+    /// Builds the keymap from the defaults, with `overrides` replacing the keys of the actions
+    /// they list. Invalid bindings are returned as errors and skipped, as are keys bound alone
+    /// that also start a chord, which the chord takes.
+    pub fn new(overrides: &BTreeMap<Action, Vec<String>>) -> (Self, Vec<String>) {
+        let mut labels: BTreeMap<Action, Vec<String>> = Action::ALL
+            .iter()
+            .map(|action| {
+                let keys = action.default_keys().iter().map(|k| k.to_string());
+                (*action, keys.collect())
+            })
+            .collect();
+        labels.extend(overrides.clone());
+
+        let mut errors = Vec::new();
+        let mut bindings = HashMap::new();
+        let mut chords = HashMap::new();
+        // Defaults first, so an overridden key takes precedence over a default using it.
+        let (overridden, defaults): (Vec<_>, Vec<_>) = labels
+            .iter()
+            .partition(|(action, _)| overrides.contains_key(action));
+        for (action, keys) in defaults.into_iter().chain(overridden) {
+            for key in keys {
+                match KeyBinding::parse_sequence(key).as_deref() {
+                    Ok([binding]) => {
+                        bindings.insert(*binding, *action);
+                    }
+                    Ok([first, second]) => {
+                        chords.insert((*first, *second), *action);
+                    }
+                    Ok(_) => {}
+                    Err(err) => errors.push(err.clone()),
+                }
+            }
+        }
+        let prefixes: HashSet<KeyBinding> = chords.keys().map(|(first, _)| *first).collect();
+        for (action, keys) in &labels {
+            for key in keys {
+                if let Ok([binding]) = KeyBinding::parse_sequence(key).as_deref() {
+                    if prefixes.contains(binding) {
+                        errors.push(format!(
+                            "{:?} for {} also starts a chord, which takes it",
+                            key,
+                            action.description()
+                        ));
+                    }
+                }
+            }
+        }
+
+        let keymap = Self {
+            bindings,
+            chords,
+            prefixes,
+            labels,
+        };
+        (keymap, errors)
+    }
+
+    /// What `key` does, `pending` being the first key of a chord pressed before it. A key that
+    /// does not complete the pending chord does nothing.
+    pub fn lookup(&self, pending: Option<KeyBinding>, key: &KeyEvent) -> Lookup {
+        let binding = KeyBinding::from_event(key);
+        let action = match pending {
+            Some(first) => self.chords.get(&(first, binding)),
+            None if self.prefixes.contains(&binding) => return Lookup::Pending(binding),
+            None => self.bindings.get(&binding),
+        };
+        action.map_or(Lookup::None, |action| Lookup::Action(*action))
+    }
+
+    /// Footer hint while `first` waits for the second key of a chord, e.g. `g… (g) first`.
+    pub fn pending_hint(&self, first: KeyBinding) -> String {
+        let mut prefix = None;
+        let mut chords = Vec::new();
+        for (action, keys) in &self.labels {
+            for key in keys {
+                let mut parts = key.split_whitespace();
+                let (Some(label), Some(second), None) = (parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                let bound = KeyBinding::parse(label)
+                    .ok()
+                    .zip(KeyBinding::parse(second).ok());
+                if bound
+                    .is_some_and(|pair| pair.0 == first && self.chords.get(&pair) == Some(action))
+                {
+                    prefix.get_or_insert(label);
+                    chords.push(format!("({}) {}", second, action.description()));
+                }
+            }
+        }
+        format!("{}… {}", prefix.unwrap_or_default(), chords.join(" | "))
+    }
+
+    /// Keys that currently trigger `action`, comma-separated, e.g. `Esc,q`.
+    pub fn keys_for(&self, action: Action) -> String {
+        self.bound_keys(action).join(",")
+    }
+
+    fn bound_keys(&self, action: Action) -> Vec<&str> {
+        self.labels.get(&action).map_or(Vec::new(), |keys| {
+            keys.iter()
+                .filter(|k| match KeyBinding::parse_sequence(k).as_deref() {
+                    Ok([binding]) => {
+                        !self.prefixes.contains(binding)
+                            && self.bindings.get(binding) == Some(&action)
+                    }
+                    Ok([first, second]) => self.chords.get(&(*first, *second)) == Some(&action),
+                    _ => false,
+                })
+                .map(String::as_str)
+                .collect()
+        })
+    }
+
+    /// Footer text listing every action with its keys. The sort-by chords are listed once by
+    /// their first key, e.g. `(S…) sort by column`.
+    pub fn help_text(&self) -> String {
+        let mut prefixes: Vec<&str> = Vec::new();
+        for action in Action::ALL.iter().filter(|a| a.sort_column().is_some()) {
+            for key in self.bound_keys(*action) {
+                let mut parts = key.split_whitespace();
+                if let (Some(first), Some(_)) = (parts.next(), parts.next()) {
+                    if !prefixes.contains(&first) {
+                        prefixes.push(first);
+                    }
+                }
+            }
+        }
+        let mut entries = Vec::new();
+        for action in Action::ALL {
+            let mut bound = self.bound_keys(action);
+            if action.sort_column().is_some() {
+                if !prefixes.is_empty() {
+                    entries.push(format!("({}…) sort by column", prefixes.join(",")));
+                    prefixes.clear();
+                }
+                bound.retain(|key| key.split_whitespace().count() == 1);
+            }
+            if !bound.is_empty() {
+                entries.push(format!("({}) {}", bound.join(","), action.description()));
+            }
+        }
+        entries.join(" | ")
+    }
+}
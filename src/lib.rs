@@ -0,0 +1,67 @@
+//! Live cryptocurrency tickers from several exchanges, as a terminal UI and as a library.
+//!
+//! [`TickerStream`] yields normalized [`HrTicker`] batches from an exchange or a recording and
+//! can be used without the TUI; [`App`] and [`app::run_app`] drive the terminal interface.
+
+pub mod accessibility;
+pub mod account;
+pub mod alerts;
+pub mod api;
+pub mod app;
+pub mod ascii;
+pub mod chart;
+pub mod clipboard;
+pub mod coingecko;
+pub mod command;
+pub mod comparison;
+pub mod config;
+pub mod control;
+pub mod denomination;
+pub mod error;
+pub mod exchange;
+pub mod export;
+pub mod format;
+pub mod graphics;
+pub mod group;
+pub mod headless;
+pub mod heatmap;
+pub mod indicators;
+pub mod keymap;
+pub mod liquidations;
+pub mod listings;
+pub mod maintenance;
+pub mod metrics;
+pub mod model;
+pub mod movers;
+pub mod net;
+pub mod news;
+pub mod notes;
+pub mod notify;
+pub mod oneline;
+pub mod paper;
+pub mod plain;
+pub mod plugins;
+pub mod portfolio;
+pub mod ratelimit;
+pub mod recorder;
+pub mod replay;
+pub mod script;
+pub mod session;
+pub mod setup;
+pub mod shm;
+pub mod status;
+pub mod stream;
+pub mod summary;
+pub mod supervisor;
+pub mod switcher;
+pub mod term;
+pub mod theme;
+pub mod ui;
+pub mod validate;
+pub mod watchlist;
+pub mod workspace;
+
+pub use app::App;
+pub use error::Error;
+pub use model::{HrTicker, TickerMap};
+pub use stream::TickerStream;
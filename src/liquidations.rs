@@ -0,0 +1,114 @@
+use crate::{error::Error, net};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+use tokio_tungstenite::tungstenite::protocol::Message;
+use tokio_util::sync::CancellationToken;
+
+const FORCE_ORDER_URL: &str = "wss://fstream.binance.com/ws/!forceOrder@arr";
+/// Liquidations kept for the pane; older ones only survive in the per-symbol counts.
+const HISTORY_LEN: usize = 500;
+
+/// `[liquidations]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct LiquidationsConfig {
+    /// Liquidations at or above this notional value (in quote currency) are highlighted.
+    pub large_notional: f64,
+}
+
+impl Default for LiquidationsConfig {
+    fn default() -> Self {
+        Self {
+            large_notional: 100_000.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Liquidation {
+    pub symbol: String,
+    /// Side of the liquidation order: `SELL` closes a long, `BUY` closes a short.
+    pub side: String,
+    pub quantity: f64,
+    pub price: f64,
+    pub time: u64,
+}
+
+impl Liquidation {
+    pub fn notional(&self) -> f64 {
+        self.quantity * self.price
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Liquidations {
+    pub recent: VecDeque<Liquidation>,
+    pub counts: HashMap<String, u32>,
+}
+
+impl Liquidations {
+    pub fn push(&mut self, liquidation: Liquidation) {
+        *self.counts.entry(liquidation.symbol.clone()).or_default() += 1;
+        self.recent.push_front(liquidation);
+        self.recent.truncate(HISTORY_LEN);
+    }
+}
+
+#[derive(Deserialize)]
+struct ForceOrderEvent {
+    o: ForceOrder,
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize)]
+struct ForceOrder {
+    s: String,  // Symbol
+    S: String,  // Side
+    z: String,  // Order filled accumulated quantity
+    ap: String, // Average price
+    T: u64,     // Order trade time
+}
+
+/// Streams forced orders into `liquidations` until `token` is cancelled, when the connection is
+/// closed.
+pub async fn subscribe(
+    liquidations: Arc<Mutex<Liquidations>>,
+    token: CancellationToken,
+) -> Result<(), Error> {
+    let (ws_stream, _) = net::connect(FORCE_ORDER_URL).await?;
+    let (mut write, mut read) = ws_stream.split();
+
+    tokio::spawn(async move {
+        loop {
+            let msg = tokio::select! {
+                msg = read.next() => msg,
+                () = token.cancelled() => {
+                    let _ = write.send(Message::Close(None)).await;
+                    break;
+                }
+            };
+            let Some(msg) = msg else {
+                break;
+            };
+            let Ok(Message::Text(text)) = msg else {
+                continue;
+            };
+            if let Ok(event) = serde_json::from_str::<ForceOrderEvent>(&text) {
+                let order = event.o;
+                liquidations.lock().unwrap().push(Liquidation {
+                    symbol: order.s,
+                    side: order.S,
+                    quantity: order.z.parse().unwrap_or(0.0),
+                    price: order.ap.parse().unwrap_or(0.0),
+                    time: order.T,
+                });
+            }
+        }
+    });
+
+    Ok(())
+}
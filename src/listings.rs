@@ -0,0 +1,127 @@
+//! New-listing detection: symbols that appear in the stream without having been listed before
+//! are announced, and pinned when configured.
+
+use crate::{error, exchange::Exchange, model::TickerMap};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+/// How long after the first tickers of a session new symbols are still taken as listed
+/// before: streams such as `!ticker@arr` only carry the symbols that traded, so quiet ones
+/// trickle in over the first seconds.
+const STARTUP_GRACE: Duration = Duration::from_secs(60);
+
+/// `[listings]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ListingsConfig {
+    /// Announce symbols listed while the app is running.
+    pub enabled: bool,
+    /// Pin them as well.
+    pub auto_pin: bool,
+}
+
+impl Default for ListingsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auto_pin: false,
+        }
+    }
+}
+
+/// The symbols known to be listed on each exchange, remembered across sessions so a symbol
+/// that rarely trades is not taken for a new listing when it first shows up.
+#[derive(Default)]
+pub struct Listings {
+    /// File the known symbols are kept in; without one they are only kept for the session.
+    path: Option<PathBuf>,
+    known: BTreeMap<Exchange, BTreeSet<String>>,
+    /// Symbols of the store already looked at. The store only grows, in insertion order, until
+    /// it is cleared.
+    observed: usize,
+    /// When the store's first tickers were seen.
+    started: Option<Instant>,
+    /// Whether the known symbols were saved since the startup grace ended.
+    saved: bool,
+}
+
+impl Listings {
+    /// `symbols.json` in the platform data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("crypto_tui_ticker").join("symbols.json"))
+    }
+
+    /// Opens the known symbols at `path`; a missing or unreadable file knows none.
+    pub fn open(path: PathBuf) -> Self {
+        let known = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            known,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the symbols of `tickers`, the store of `exchange`, that are listed for the first
+    /// time.
+    pub fn observe(
+        &mut self,
+        exchange: Exchange,
+        tickers: &TickerMap,
+        now: Instant,
+    ) -> Vec<String> {
+        if tickers.len() < self.observed {
+            // Cleared, e.g. after switching exchange: the next tickers start a session.
+            self.observed = 0;
+            self.started = None;
+            self.saved = false;
+        }
+        if tickers.is_empty() {
+            return Vec::new();
+        }
+        let started = *self.started.get_or_insert(now);
+        let in_grace = now < started + STARTUP_GRACE;
+        let known = self.known.entry(exchange).or_default();
+        let listed: Vec<String> = tickers
+            .keys()
+            .skip(self.observed)
+            .filter(|symbol| known.insert(symbol.to_string()) && !in_grace)
+            .cloned()
+            .collect();
+        self.observed = tickers.len();
+        if !listed.is_empty() || (!in_grace && !self.saved) {
+            self.save();
+        }
+        listed
+    }
+
+    /// Whether `symbol` is known to be listed on `exchange`; `None` before any of its symbols
+    /// are known, e.g. on the first run.
+    pub fn is_known(&self, exchange: Exchange, symbol: &str) -> Option<bool> {
+        self.known
+            .get(&exchange)
+            .filter(|known| !known.is_empty())
+            .map(|known| known.contains(symbol))
+    }
+
+    fn save(&mut self) {
+        self.saved = true;
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(path, serde_json::to_string(&self.known)?));
+        if let Err(err) = result {
+            error::report(format!("listings: {}", err));
+        }
+    }
+}
@@ -1,681 +1,507 @@
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
-use crossterm::{
-    event::{DisableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind},
-    execute,
-    terminal::LeaveAlternateScreen,
+use clap::Parser;
+use cli::{Cli, Command};
+use crypto_tui_ticker::{
+    account::{self, Account, Credentials},
+    alerts::{history::AlertHistory, AlertEngine},
+    api,
+    app::{apply_updates, run_app, App, Stores},
+    chart,
+    coingecko::{self, GlobalCache, MarketDataCache},
+    comparison::Comparison,
+    config::Config,
+    control,
+    denomination::{self, FxRates},
+    error::{self, Error},
+    exchange::{self, Exchange},
+    export::{self, ExportFormat},
+    format::NumberFormat,
+    graphics::Graphics,
+    headless,
+    indicators::{self, IndicatorCache},
+    liquidations::{self, Liquidations},
+    listings::Listings,
+    metrics,
+    model::{update_tickers, HrTicker, SortColumn, TickerMap, Tickers},
+    net::{self, Proxy},
+    notify, oneline, plain,
+    plugins::{self, Plugins},
+    recorder::{self, Recorder, RecorderConfig},
+    script::Script,
+    session::Session,
+    setup, shm,
+    status::ConnectionStatus,
+    stream::{Selection, TickerStream},
+    supervisor::Supervisor,
+    term, validate, watchlist,
 };
-use futures::StreamExt;
-use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    layout::{Constraint, Layout, Margin, Rect},
-    style::{palette::tailwind, Color, Modifier, Style},
-    symbols,
-    text::{Line, Text},
-    widgets::{
-        Block, BorderType, Borders, Cell, Clear, HighlightSpacing, LineGauge, Paragraph, Row,
-        Scrollbar, ScrollbarOrientation, ScrollbarState, Table, TableState,
-    },
-    Frame, Terminal,
-};
-use serde::{Deserialize, Serialize};
-use std::error::Error;
-use std::io;
 use std::{
-    collections::HashMap,
+    io::{self, IsTerminal},
+    process::ExitCode,
     sync::{Arc, Mutex},
     time::Duration,
 };
-use tokio::sync::mpsc;
-use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
-mod term;
-
-#[derive(Deserialize, Serialize, Clone, Debug)]
-pub struct HrTicker {
-    pub e: String, // Event type
-    pub E: u64,    // Event time
-    pub s: String, // Symbol
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub p: f32, // Price change
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub P: f32, // Price change percent
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub w: f32, // Weighted average price
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub c: f32, // Last price
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub Q: f32, // Last quantity
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub o: f32, // Open price
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub h: f32, // High price
-    #[serde(deserialize_with = "deserialize_f32_from_string")]
-    pub l: f32, // Low price
-    pub v: String, // Total traded base asset volume
-    pub q: String, // Total traded quote asset volume
-    pub O: u64,    // Statistics open time
-    pub C: u64,    // Statistics close time
-    pub F: u64,    // First trade ID
-    pub L: u64,    // Last trade ID
-    pub n: u64,    // Total number of trades
-    #[serde(default = "default_previous_price")]
-    pub previous_price: f32,
-}
-
-fn default_previous_price() -> f32 {
-    0.0
-}
-
-#[derive(Clone, Debug)]
-pub struct Tickers {
-    pub tickers: Arc<Mutex<Vec<HrTicker>>>,
-}
-
-#[derive(PartialEq, Eq)]
-enum SortOrder {
-    Ascending,
-    Descending,
-}
-
-#[derive(PartialEq, Eq)]
-enum SortColumn {
-    Symbol,
-    Last,
-    PercentChange,
-    Open,
-    High,
-    Low,
-    Volume,
-}
-
-impl Tickers {
-    pub fn new() -> Self {
-        Self {
-            tickers: Arc::new(Mutex::new(Vec::new())),
-        }
-    }
+use tokio::sync::{mpsc, watch};
+
+mod cli;
+
+fn prune(config: &RecorderConfig) -> Result<(), Error> {
+    let path = config
+        .path()
+        .ok_or_else(|| Error::Config("no data directory available".to_string()))?;
+    let mut recorder = Recorder::open(&path)?;
+    let stats = recorder.compact(config)?;
+    recorder.vacuum()?;
+    println!(
+        "{}: compacted {} ticks into 1m bars, deleted {} expired bars and {} expired klines",
+        path.display(),
+        stats.ticks_compacted,
+        stats.bars_deleted,
+        stats.klines_deleted
+    );
+    Ok(())
 }
 
-impl std::fmt::Display for HrTicker {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "HrTicker {{ s: {} }}", self.s)
+/// How long `--snapshot` keeps merging batches after the first one arrives, so exchanges that
+/// spread the market over several messages are complete.
+const SNAPSHOT_SETTLE: Duration = Duration::from_secs(2);
+/// How long background tasks get to finish after quitting, e.g. for the recorder's last write.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long `--snapshot` waits for the first batch before giving up.
+const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(15);
+
+async fn snapshot(
+    exchange: Exchange,
+    number_format: &NumberFormat,
+    export_format: ExportFormat,
+) -> Result<(), Error> {
+    let mut stream = TickerStream::connect(exchange);
+    let tickers = Arc::new(Mutex::new(TickerMap::new()));
+    let first = tokio::time::timeout(SNAPSHOT_TIMEOUT, stream.next())
+        .await
+        .map_err(|_| Error::Other("timed out waiting for tickers".to_string()))?
+        .ok_or_else(|| Error::Other("stream closed before any tickers arrived".to_string()))?;
+    update_tickers(first, tickers.clone());
+    let deadline = tokio::time::Instant::now() + SNAPSHOT_SETTLE;
+    while let Ok(Some(batch)) = tokio::time::timeout_at(deadline, stream.next()).await {
+        update_tickers(batch, tickers.clone());
     }
-}
 
-fn deserialize_f32_from_string<'de, D>(deserializer: D) -> Result<f32, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    let s: String = Deserialize::deserialize(deserializer)?;
-    s.parse::<f32>().map_err(serde::de::Error::custom)
+    let columns = [
+        SortColumn::Symbol,
+        SortColumn::Last,
+        SortColumn::PercentChange,
+        SortColumn::Open,
+        SortColumn::High,
+        SortColumn::Low,
+        SortColumn::Volume,
+    ];
+    let tickers = tickers.lock().unwrap();
+    let mut sorted: Vec<&HrTicker> = tickers.values().collect();
+    sorted.sort_by(|a, b| a.s.cmp(&b.s));
+    let rows: Vec<Vec<String>> = sorted
+        .into_iter()
+        .map(|ticker| {
+            columns
+                .iter()
+                .map(|column| export::cell(*column, ticker, number_format, None, 0, None))
+                .collect()
+        })
+        .collect();
+    let mut out = io::stdout().lock();
+    export::write(&mut out, export_format, &columns, &rows)?;
+    Ok(())
 }
 
-async fn subscribe_to_ticker(
-    tx: mpsc::Sender<Vec<HrTicker>>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let url = "wss://fstream.binance.com/ws/!ticker@arr";
-    let (ws_stream, _) = connect_async(url).await?;
-    let (_, mut read) = ws_stream.split();
-
-    tokio::spawn(async move {
-        while let Some(msg) = read.next().await {
-            if let Ok(Message::Text(text)) = msg {
-                let parsed: Vec<HrTicker> = serde_json::from_str(&text).unwrap();
-                tx.send(parsed).await.unwrap();
+/// Prints `symbols` on one line. Binance's are fetched once from its REST endpoint; other
+/// exchanges are streamed until every symbol has arrived or `SNAPSHOT_TIMEOUT` passes.
+async fn oneline(
+    exchange: Exchange,
+    symbols: &[String],
+    number_format: &NumberFormat,
+    ascii: bool,
+) -> Result<(), Error> {
+    let tickers = if exchange.is_binance() {
+        exchange::binance::rest_tickers(exchange::binance::tickers_url(exchange.market())).await?
+    } else {
+        let (_selection, selected) = watch::channel(Selection::of(symbols));
+        let mut stream = TickerStream::connect_selected(exchange, selected, None);
+        let mut tickers: Vec<HrTicker> = Vec::new();
+        let deadline = tokio::time::Instant::now() + SNAPSHOT_TIMEOUT;
+        while let Ok(Some(batch)) = tokio::time::timeout_at(deadline, stream.next()).await {
+            for ticker in batch {
+                tickers.retain(|t| t.s != ticker.s);
+                tickers.push(ticker);
+            }
+            if symbols
+                .iter()
+                .all(|symbol| tickers.iter().any(|t| t.s.eq_ignore_ascii_case(symbol)))
+            {
+                break;
             }
         }
-    });
-
+        tickers
+    };
+    println!("{}", oneline::line(symbols, &tickers, number_format, ascii));
     Ok(())
 }
 
-const PALETTES: [tailwind::Palette; 4] = [
-    tailwind::BLUE,
-    tailwind::EMERALD,
-    tailwind::INDIGO,
-    tailwind::RED,
-];
-
-struct TableColors {
-    buffer_bg: Color,
-    header_bg: Color,
-    header_fg: Color,
-    row_fg: Color,
-    selected_style_fg: Color,
-    normal_row_color: Color,
-    alt_row_color: Color,
-    footer_border_color: Color,
-}
-
-impl TableColors {
-    const fn new(color: &tailwind::Palette) -> Self {
-        Self {
-            buffer_bg: tailwind::SLATE.c950,
-            header_bg: color.c900,
-            header_fg: tailwind::SLATE.c200,
-            row_fg: tailwind::SLATE.c200,
-            selected_style_fg: color.c400,
-            normal_row_color: tailwind::SLATE.c950,
-            alt_row_color: tailwind::SLATE.c900,
-            footer_border_color: color.c400,
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("Error: {}", err);
+            ExitCode::FAILURE
         }
     }
 }
 
-const ITEM_HEIGHT: usize = 1;
-const INFO_TEXT: &str =
-    "(Esc) quit | (↑,k) up | (↓,j) down | (→,l) next color | (←,h) previous color | (Tab) sort next column | (r) reverse sort";
-
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
-enum Mode {
-    #[default]
-    Running,
-    Quit,
-}
-
-struct App {
-    mode: Mode,
-    state: TableState,
-    scroll_state: ScrollbarState,
-    scroll_position: usize,
-    colors: TableColors,
-    color_index: usize,
-    ticker_length: usize,
-    show_chart: bool,
-    chart_data: Option<tokio::task::JoinHandle<Result<String, Box<dyn Error + Send + Sync>>>>,
-    fetched_chart: Option<String>,
-    sort_order: SortOrder,
-    sort_column: SortColumn,
-    previous_prices: HashMap<String, f32>,
-}
-
-impl App {
-    fn new() -> Self {
-        Self {
-            mode: Mode::Running,
-            state: TableState::default(),
-            scroll_state: ScrollbarState::new(20),
-            scroll_position: 0,
-            colors: TableColors::new(&PALETTES[0]),
-            color_index: 2,
-            ticker_length: 25,
-            show_chart: false,
-            chart_data: None,
-            fetched_chart: None,
-            sort_column: SortColumn::Symbol,
-            sort_order: SortOrder::Ascending,
-            previous_prices: HashMap::new(),
+async fn run(cli: Cli) -> Result<(), Error> {
+    let interactive =
+        cli.oneline.is_none() && !cli.snapshot && !cli.headless && !cli.plain && !cli.check_config;
+    if let Some(path) = Config::path() {
+        if cli.command.is_none()
+            && interactive
+            && !path.exists()
+            && io::stdin().is_terminal()
+            && io::stdout().is_terminal()
+        {
+            setup::run(&path)?;
         }
     }
-
-    pub async fn run(
-        &mut self,
-        terminal: &mut Terminal<impl Backend>,
-        tickers: Arc<Mutex<Vec<HrTicker>>>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        while self.is_running() {
-            terminal.draw(|f| {
-                let tickers_clone = Arc::clone(&tickers);
-                self.ticker_length = tickers_clone.lock().unwrap().len();
-                ui(f, self, tickers_clone);
-            })?;
-            self.scroll_state = ScrollbarState::new(self.ticker_length * ITEM_HEIGHT)
-                .position(self.scroll_position);
-            self.handle_events().await.ok();
-        }
-        Ok(())
+    let path = Config::path();
+    let mut problems = Vec::new();
+    let mut config = match &path {
+        Some(path) => validate::load(path).unwrap_or_else(|found| {
+            problems = found;
+            Config::default()
+        }),
+        None => Config::default(),
+    };
+    problems.extend(validate::check_symbols("--symbols", &cli.symbols));
+    if let Some(symbols) = &cli.oneline {
+        problems.extend(validate::check_symbols("--oneline", symbols));
     }
-
-    fn is_running(&self) -> bool {
-        self.mode != Mode::Quit
+    for problem in &problems {
+        eprintln!("{}", problem);
     }
-
-    pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.ticker_length - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
-        self.scroll_position += ITEM_HEIGHT;
-        self.scroll_state = self.scroll_state.position(self.scroll_position);
+    match problems.len() {
+        0 if cli.check_config => {
+            let file = path.map_or_else(|| "config".to_string(), |p| p.display().to_string());
+            println!("{}: no problems found", file);
+            return Ok(());
+        }
+        0 => {}
+        1 => return Err(Error::Config("1 problem to fix".to_string())),
+        n => return Err(Error::Config(format!("{} problems to fix", n))),
     }
-
-    pub fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.ticker_length - 1
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
-        self.scroll_position -= ITEM_HEIGHT;
-        self.scroll_state = self.scroll_state.position(self.scroll_position);
+    config.accessibility.enabled |= cli.accessible;
+    if let Some(fps) = cli.fps {
+        config.fps = fps;
     }
-
-    pub fn next_color(&mut self) {
-        self.color_index = (self.color_index + 1) % PALETTES.len();
+    if let Some(Command::Prune) = cli.command {
+        return prune(&config.recorder);
     }
-
-    pub fn previous_color(&mut self) {
-        let count = PALETTES.len();
-        self.color_index = (self.color_index + count - 1) % count;
+    let proxy = match (&cli.proxy, &config.proxy) {
+        (Some(proxy), _) => Some(proxy.clone()),
+        (None, Some(url)) => Some(url.parse::<Proxy>().map_err(Error::Config)?),
+        (None, None) => None,
+    };
+    if let Some(proxy) = proxy {
+        net::set_proxy(proxy);
     }
-
-    pub fn set_colors(&mut self) {
-        self.colors = TableColors::new(&PALETTES[self.color_index]);
+    chart::set_kline_cache_ttl(Duration::from_secs(config.chart.kline_cache_secs));
+    let exchange = cli.exchange.unwrap_or(config.exchange);
+    if let Some(symbols) = &cli.oneline {
+        return oneline(exchange, symbols, &config.format, cli.ascii).await;
     }
-
-    pub fn sort_tickers(&mut self, tickers: &mut Vec<HrTicker>) {
-        match self.sort_column {
-            SortColumn::Symbol => {
-                tickers.sort_by(|a, b| a.s.cmp(&b.s));
-            }
-            SortColumn::Last => {
-                tickers.sort_by(|a, b| a.c.partial_cmp(&b.c).unwrap());
-            }
-            SortColumn::PercentChange => {
-                tickers.sort_by(|a, b| a.P.partial_cmp(&b.P).unwrap());
-            }
-            SortColumn::Open => {
-                tickers.sort_by(|a, b| a.o.partial_cmp(&b.o).unwrap());
-            }
-            SortColumn::High => {
-                tickers.sort_by(|a, b| a.h.partial_cmp(&b.h).unwrap());
-            }
-            SortColumn::Low => {
-                tickers.sort_by(|a, b| a.l.partial_cmp(&b.l).unwrap());
-            }
-            SortColumn::Volume => {
-                tickers.sort_by(|a, b| a.v.cmp(&b.v));
-            }
-        }
-        if self.sort_order == SortOrder::Descending {
-            tickers.reverse();
+    if cli.snapshot {
+        let export_format = cli.format.unwrap_or(config.export.format);
+        return snapshot(exchange, &config.format, export_format).await;
+    }
+    let (selection, selected) = watch::channel(Selection::of(&cli.symbols));
+    let poll = (config.poll_secs > 0).then(|| Duration::from_secs(config.poll_secs));
+    if cli.headless {
+        let stream = open_stream(&cli, exchange, selected, poll)?;
+        tokio::select! {
+            result = headless::run(stream, &cli.symbols) => result?,
+            // The stream is dropped, closing its connection; --record has flushed every batch.
+            _ = tokio::signal::ctrl_c() => {}
         }
+        return Ok(());
     }
-
-    pub fn next_sort_column(&mut self) {
-        self.sort_column = match self.sort_column {
-            SortColumn::Symbol => SortColumn::Last,
-            SortColumn::Last => SortColumn::PercentChange,
-            SortColumn::PercentChange => SortColumn::Open,
-            SortColumn::Open => SortColumn::High,
-            SortColumn::High => SortColumn::Low,
-            SortColumn::Low => SortColumn::Volume,
-            SortColumn::Volume => SortColumn::Symbol,
+    if cli.plain {
+        let stream = open_stream(&cli, exchange, selected, poll)?;
+        let color = plain::use_color();
+        tokio::select! {
+            result = plain::run(stream, &cli.symbols, &config.format, color) => result?,
+            _ = tokio::signal::ctrl_c() => {}
         }
+        return Ok(());
     }
 
-    async fn handle_events(&mut self) -> Result<(), Box<dyn std::error::Error>> {
-        let timeout = Duration::from_millis(0);
-        match term::next_event(timeout)? {
-            Some(Event::Key(key)) if key.kind == KeyEventKind::Press => {
-                self.handle_key_press(key).await
+    // From here on errors and other notifications are shown in the UI.
+    notify::set_interactive(true);
+    let mut tasks = Supervisor::new();
+    let tickers = Tickers::new();
+    if let Some(path) = config.shm_path.clone() {
+        let tickers = tickers.tickers.clone();
+        let name = format!("shared-memory export to {}", path.display());
+        tasks.spawn(name, async move { Ok(shm::run(&path, tickers).await?) });
+    }
+    if config.recorder.enabled {
+        let recorder = recorder::spawn(
+            config.recorder.clone(),
+            tickers.tickers.clone(),
+            tasks.token(),
+        );
+        tasks.spawn_graceful("recorder", async move {
+            match tokio::task::spawn_blocking(move || recorder.join()).await {
+                Ok(Ok(())) => Ok(()),
+                _ => Err(Error::Other("thread panicked".to_string())),
             }
-            _ => {}
+        });
+    }
+    // Changed by the exchange switcher; the live stream and the tasks that depend on the
+    // exchange follow it.
+    let (switch_exchange, switched) = watch::channel(exchange);
+    let comparison = Arc::new(Mutex::new(Comparison::default()));
+    for other in config.compare_exchanges.iter().copied() {
+        if other == exchange {
+            continue;
         }
-        Ok(())
+        let (tx, mut rx) = mpsc::channel::<Vec<HrTicker>>(100);
+        let comparison = comparison.clone();
+        tasks.spawn("comparison", async move {
+            while let Some(results) = rx.recv().await {
+                comparison.lock().unwrap().update(other, &results);
+            }
+            Ok(())
+        });
+        tasks.spawn(other.name(), other.subscribe(tx));
     }
 
-    async fn handle_key_press(&mut self, key: KeyEvent) {
-        match key.code {
-            KeyCode::Char('q') | KeyCode::Esc => self.mode = Mode::Quit,
-            KeyCode::Char('j') | KeyCode::Down => self.next(),
-            KeyCode::Char('k') | KeyCode::Up => self.previous(),
-            KeyCode::Char('l') | KeyCode::Right => self.next_color(),
-            KeyCode::Char('h') | KeyCode::Left => self.previous_color(),
-            KeyCode::Tab => self.next_sort_column(),
-            KeyCode::Char('r') => match self.sort_order {
-                SortOrder::Ascending => self.sort_order = SortOrder::Descending,
-                SortOrder::Descending => self.sort_order = SortOrder::Ascending,
-            },
-            // KeyCode::Enter => {
-            //     if !self.show_chart {
-            //         self.show_chart = true;
-            //         let chart_output = tokio::spawn(async { cschart::display_cs().await });
-            //         self.chart_data = Some(chart_output);
-            //     } else {
-            //         self.show_chart = false;
-            //     }
-            // }
-            _ => {}
-        };
+    let market_data = MarketDataCache::default();
+    let global = GlobalCache::default();
+    if config.coingecko.enabled {
+        let run = coingecko::run(
+            config.coingecko.clone(),
+            switched.clone(),
+            tickers.tickers.clone(),
+            market_data.clone(),
+            global.clone(),
+        );
+        tasks.spawn("CoinGecko", async move {
+            run.await;
+            Ok(())
+        });
     }
 
-    async fn get_chart_data(&mut self) {
-        if let Some(chart_future) = self.chart_data.take() {
-            match chart_future.await {
-                Ok(Ok(chart_output)) => {
-                    self.fetched_chart = Some(chart_output);
-                }
-                Ok(Err(err)) => {
-                    eprintln!("Error: {}", err);
-                    self.show_chart = false;
-                }
-                Err(join_err) => {
-                    eprintln!("Error: {}", join_err);
-                    self.show_chart = false;
-                }
-            }
-        }
+    let fx_rates = FxRates::default();
+    if config.denomination.fiat.is_some() {
+        let run = denomination::refresh_fx(config.denomination.clone(), fx_rates.clone());
+        tasks.spawn("FX rates", async move {
+            run.await;
+            Ok(())
+        });
     }
-}
 
-fn ui(f: &mut Frame, app: &mut App, tickers: Arc<Mutex<Vec<HrTicker>>>) {
-    if app.show_chart {
-        let area = centered_rect(80, 50, f.size());
-        f.render_widget(Clear, area);
+    let indicators = IndicatorCache::default();
+    if config.indicators.enabled {
+        let run = indicators::run(
+            config.indicators.clone(),
+            switched.clone(),
+            tickers.tickers.clone(),
+            indicators.clone(),
+        );
+        tasks.spawn("indicators", async move {
+            run.await;
+            Ok(())
+        });
+    }
 
-        app.get_chart_data();
+    let liquidations = Arc::new(Mutex::new(Liquidations::default()));
+    if exchange == Exchange::Binance {
+        let liquidations = liquidations.clone();
+        let token = tasks.token();
+        tasks.spawn("liquidations", async move {
+            liquidations::subscribe(liquidations, token).await
+        });
+    }
 
-        if let Some(chart_output) = &app.fetched_chart {
-            let chart_widget = Paragraph::new(Text::from(chart_output.clone())).block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("CHZ/USDT Chart")
-                    .border_type(BorderType::Double),
-            );
-            f.render_widget(chart_widget, area)
+    let account = Credentials::load().map(|credentials| {
+        let account = Arc::new(Mutex::new(Account::default()));
+        let run = account::run(credentials, account.clone());
+        tasks.spawn("account", async move {
+            run.await;
+            Ok(())
+        });
+        account
+    });
+
+    let config_update_hz = config.update_hz;
+    let mut engine = AlertEngine::new(config.alerts.clone());
+    if let Some(path) = AlertHistory::default_path() {
+        engine.history = AlertHistory::open(path);
+    }
+    for (i, rule) in engine.rules().iter().enumerate() {
+        if let Some(err) = engine.error(i) {
+            error::report(Error::Config(format!("alert {:?}: {}", rule.name, err)));
         }
+    }
+    match Script::load(&config.script) {
+        Ok(script) => engine.script = script,
+        Err(err) => error::report(err),
+    }
+    let engine = Arc::new(Mutex::new(engine));
+    let status = Arc::new(Mutex::new(ConnectionStatus::default()));
+    let stores = Stores {
+        tickers: tickers.tickers.clone(),
+        comparison: comparison.clone(),
+        market_data,
+        global,
+        fx_rates,
+        indicators,
+        liquidations,
+        status: status.clone(),
+        alerts: engine.clone(),
+        account,
+        selection,
+        // A recording plays back one exchange.
+        exchange: cli.replay.is_none().then_some(switch_exchange),
+    };
+    if let Some(addr) = cli.metrics_addr {
+        let tickers = tickers.tickers.clone();
+        tasks.spawn(format!("metrics endpoint {}", addr), async move {
+            Ok(metrics::serve(addr, exchange, tickers).await?)
+        });
+    }
+    if let Some(addr) = cli.api_addr {
+        let tickers = tickers.tickers.clone();
+        let alerts = engine.clone();
+        tasks.spawn(format!("API server {}", addr), async move {
+            Ok(api::serve(addr, tickers, alerts).await?)
+        });
+    }
+    // Images are not ASCII either.
+    let graphics = if cli.ascii {
+        None
     } else {
-        let rects = Layout::vertical([Constraint::Min(5), Constraint::Length(3)]).split(f.size());
-        app.set_colors();
-
-        // render_gauge(f, app, rects[0]);
-
-        render_table(f, app, rects[0], tickers);
-
-        render_scrollbar(f, app, rects[0]);
-
-        render_footer(f, app, rects[1]);
+        Graphics::new(cli.chart_renderer.unwrap_or(config.chart.renderer))
+    };
+    let plugins_config = config.plugins.clone();
+    let control_config = config.control.clone();
+    let restore_session = config.restore_session;
+    let mut app = App::new(config, exchange, stores);
+    // A recording's symbols were listed when it was made.
+    if cli.replay.is_none() {
+        app.set_listings(Listings::default_path().map_or_else(Listings::default, Listings::open));
     }
-}
-
-/// helper function to create a centered rect using up certain percentage of the available rect `r`
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::vertical([
-        Constraint::Percentage((100 - percent_y) / 2),
-        Constraint::Percentage(percent_y),
-        Constraint::Percentage((100 - percent_y) / 2),
-    ])
-    .split(r);
-
-    Layout::horizontal([
-        Constraint::Percentage((100 - percent_x) / 2),
-        Constraint::Percentage(percent_x),
-        Constraint::Percentage((100 - percent_x) / 2),
-    ])
-    .split(popup_layout[1])[1]
-}
-
-fn render_table(f: &mut Frame, app: &mut App, area: Rect, tickers: Arc<Mutex<Vec<HrTicker>>>) {
-    let mut tickers = tickers.lock().unwrap();
-    app.sort_tickers(&mut tickers);
-    let header_style = Style::default()
-        .fg(app.colors.header_fg)
-        .bg(app.colors.header_bg);
-
-    // Determine the style for the sorted column
-    let sort_column_style = Style::default()
-        .add_modifier(Modifier::BOLD)
-        .fg(Color::Yellow);
-
-    // Create the header with highlighting on the sorted column
-    let header = Row::new(vec![
-        Cell::from("Symbol").style(if app.sort_column == SortColumn::Symbol {
-            sort_column_style
-        } else {
-            header_style
-        }),
-        Cell::from("Last").style(if app.sort_column == SortColumn::Last {
-            sort_column_style
-        } else {
-            header_style
-        }),
-        Cell::from("Percent Change").style(if app.sort_column == SortColumn::PercentChange {
-            sort_column_style
-        } else {
-            header_style
-        }),
-        Cell::from("Open").style(if app.sort_column == SortColumn::Open {
-            sort_column_style
-        } else {
-            header_style
-        }),
-        Cell::from("High").style(if app.sort_column == SortColumn::High {
-            sort_column_style
-        } else {
-            header_style
-        }),
-        Cell::from("Low").style(if app.sort_column == SortColumn::Low {
-            sort_column_style
-        } else {
-            header_style
-        }),
-        Cell::from("Volume").style(if app.sort_column == SortColumn::Volume {
-            sort_column_style
-        } else {
-            header_style
-        }),
-    ])
-    .style(header_style)
-    .height(1);
-
-    let selected_style = Style::default()
-        .add_modifier(Modifier::REVERSED)
-        .fg(app.colors.selected_style_fg);
-
-    let rows = tickers
-        .iter()
-        .enumerate()
-        .map(|(i, ticker)| {
-            let color = if i % 2 == 0 {
-                app.colors.normal_row_color
-            } else {
-                app.colors.alt_row_color
-            };
-
-            let last_price_color = match ticker.previous_price {
-                previous_price => {
-                    if ticker.c > previous_price {
-                        Color::Green
-                    } else if ticker.c < previous_price {
-                        Color::Red
-                    } else {
-                        app.colors.row_fg
-                    }
-                }
-            };
-
-            Row::new(vec![
-                Cell::from(ticker.s.clone()),
-                Cell::from(ticker.c.to_string()).style(Style::default().fg(last_price_color)),
-                Cell::from(ticker.P.to_string()),
-                Cell::from(ticker.o.to_string()),
-                Cell::from(ticker.h.to_string()),
-                Cell::from(ticker.l.to_string()),
-                Cell::from(ticker.v.clone()),
-            ])
-            .style(Style::default().fg(app.colors.row_fg).bg(color))
-            .height(1)
-        })
-        .collect::<Vec<Row>>();
-
-    let table = Table::new(
-        rows,
-        [
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-            Constraint::Length(10),
-        ],
-    )
-    .header(header)
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Crypto Tickers"),
-    )
-    .highlight_style(selected_style)
-    .highlight_spacing(HighlightSpacing::default());
-
-    f.render_stateful_widget(table, area, &mut app.state);
-}
-
-fn render_gauge(f: &mut Frame, app: &mut App, area: Rect) {
-    f.render_widget(
-        LineGauge::default()
-            .block(Block::bordered().title("Progress"))
-            .filled_style(
-                Style::default()
-                    .fg(Color::White)
-                    .bg(Color::Black)
-                    .add_modifier(Modifier::BOLD),
-            )
-            .line_set(symbols::line::THICK)
-            .ratio(0.4),
-        area.inner(Margin {
-            vertical: 1,
-            horizontal: 1,
-        }),
-    );
-}
-
-fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
-    f.render_stateful_widget(
-        Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(None)
-            .end_symbol(None),
-        area.inner(Margin {
-            vertical: 1,
-            horizontal: 1,
-        }),
-        &mut app.scroll_state,
-    );
-}
-
-fn render_footer(f: &mut Frame, app: &App, area: Rect) {
-    let info_footer = Paragraph::new(Line::from(INFO_TEXT))
-        .style(
-            Style::default()
-                .fg(app.colors.row_fg)
-                .bg(app.colors.buffer_bg),
-        )
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Double)
-                .border_style(Style::default().fg(app.colors.footer_border_color)),
-        );
-    f.render_widget(info_footer, area);
-}
-
-fn update_tickers(new_tickers: Vec<HrTicker>, tickers: Arc<Mutex<Vec<HrTicker>>>) {
-    let mut tickers = tickers.lock().unwrap();
-
-    for new_ticker in new_tickers {
-        match tickers.iter_mut().find(|t| t.s == new_ticker.s) {
-            Some(existing_ticker) => {
-                // Update existing ticker
-                existing_ticker.previous_price = existing_ticker.c;
-                existing_ticker.p = new_ticker.p;
-                existing_ticker.P = new_ticker.P;
-                existing_ticker.w = new_ticker.w;
-                existing_ticker.c = new_ticker.c;
-                existing_ticker.Q = new_ticker.Q;
-                existing_ticker.o = new_ticker.o;
-                existing_ticker.h = new_ticker.h;
-                existing_ticker.l = new_ticker.l;
-                existing_ticker.v.clone_from(&new_ticker.v);
-                existing_ticker.q.clone_from(&new_ticker.q);
-                existing_ticker.O = new_ticker.O;
-                existing_ticker.C = new_ticker.C;
-                existing_ticker.F = new_ticker.F;
-                existing_ticker.L = new_ticker.L;
-                existing_ticker.n = new_ticker.n;
-            }
-            None => {
-                // Add new ticker
-                tickers.push(new_ticker);
-            }
+    // A replay starts from a clean slate and leaves the saved session alone.
+    if restore_session && cli.replay.is_none() {
+        if let Some(path) = Session::default_path() {
+            app.set_session_path(path);
         }
     }
-}
-async fn run_app(
-    mut app: App,
-    terminal: &mut Terminal<impl Backend>,
-    tickers: Arc<Mutex<Vec<HrTicker>>>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    loop {
-        // Handle events
-        if app.handle_events().await.is_err() {
-            break;
+    if let Some(path) = &cli.watchlist_file {
+        match watchlist::import(path, exchange) {
+            Ok(import) => app.import_watchlist(import),
+            Err(err) => error::report(err),
         }
-
-        // Check if we need to update the UI with chart data
-        app.get_chart_data().await;
-
-        // Draw the UI
-        terminal.draw(|f| {
-            let tickers_clone = Arc::clone(&tickers);
-            app.ticker_length = tickers_clone.lock().unwrap().len();
-            ui(f, &mut app, tickers_clone);
-        })?;
-
-        // Exit the loop if the app is quitting
-        if !app.is_running() {
-            break;
+    }
+    // Nor do plugins feed a replay.
+    if cli.replay.is_none() && plugins_config.enabled {
+        if let Some(dir) = plugins_config.dir() {
+            app.set_plugins(Plugins::new(plugins::load_dir(
+                &dir,
+                &plugins_config.grants,
+            )));
         }
     }
-
-    Ok(())
-}
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let tickers = Tickers::new();
-    let app = App::new();
-    let (tx, mut rx) = mpsc::channel::<Vec<HrTicker>>(100);
-    let tickers_clone = tickers.tickers.clone();
-    tokio::spawn(async move {
-        while let Some(results) = rx.recv().await {
-            update_tickers(results, tickers_clone.clone());
+    #[cfg(unix)]
+    if control_config.enabled {
+        let (tx, rx) = mpsc::unbounded_channel();
+        app.set_control(rx);
+        let path = control_config.path();
+        // A second instance runs without one rather than taking over the first's.
+        tasks.spawn("control socket", async move {
+            if let Err(err) = control::serve(path.clone(), tx).await {
+                error::report(format!("control socket {}: {}", path.display(), err));
+            }
+            Ok(())
+        });
+    }
+    app.set_graphics(graphics);
+    app.set_ascii(cli.ascii);
+    if let Some(mode) = cli.color_mode {
+        app.set_color_mode(mode);
+    }
+    let mut stream = open_stream(&cli, exchange, selected.clone(), poll)?;
+    let record = cli.record.clone();
+    let store = tickers.tickers.clone();
+    let mut switched = switched;
+    tasks.spawn("ticker updates", async move {
+        loop {
+            let exchange = *switched.borrow_and_update();
+            let updates = apply_updates(
+                stream,
+                exchange,
+                config_update_hz,
+                store.clone(),
+                comparison.clone(),
+                status.clone(),
+                engine.clone(),
+            );
+            tokio::select! {
+                () = updates => break,
+                Ok(()) = switched.changed() => {}
+            }
+            // The old stream is dropped with its updates; rows it merged after the app cleared
+            // the table go too.
+            store.lock().unwrap().clear();
+            let next = TickerStream::connect_selected(*switched.borrow(), selected.clone(), poll);
+            stream = match &record {
+                Some(path) => next.record(path)?,
+                None => next,
+            };
         }
+        Ok(())
     });
 
-    tokio::spawn(async move {
-        subscribe_to_ticker(tx).await.unwrap();
-    });
-
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    let backend = CrosstermBackend::new(&mut stdout);
-    let mut terminal = Terminal::new(backend)?;
+    let mut terminal = term::init()?;
 
     terminal.clear()?;
 
-    run_app(app, &mut terminal, tickers.tickers).await?;
+    let result = run_app(app, &mut terminal, tickers.tickers).await;
 
     terminal.clear()?;
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    term::restore()?;
     terminal.show_cursor()?;
+    notify::set_interactive(false);
 
+    for err in tasks.shutdown(SHUTDOWN_TIMEOUT).await {
+        eprintln!("Error: {}", err);
+    }
+    result?;
     Ok(())
 }
+
+/// The update source selected on the command line: the exchange, streaming the symbols in
+/// `selection` and polling every `poll` while its websocket is down, or a `--replay` file, teed
+/// into `--record` when set. Files are opened here so a bad path is reported before the TUI
+/// starts.
+fn open_stream(
+    cli: &Cli,
+    exchange: Exchange,
+    selection: watch::Receiver<Selection>,
+    poll: Option<Duration>,
+) -> Result<TickerStream, Error> {
+    let stream = match &cli.replay {
+        Some(path) => TickerStream::replay(path, cli.speed)?,
+        None => TickerStream::connect_selected(exchange, selection, poll),
+    };
+    Ok(match &cli.record {
+        Some(path) => stream.record(path)?,
+        None => stream,
+    })
+}
@@ -0,0 +1,215 @@
+//! Exchange maintenance and delisting warnings: Binance's system status and announcements are
+//! polled so a banner can tell an exchange outage or a delisting from a local connection
+//! problem.
+
+use crate::{
+    error::{self, Error},
+    exchange::Exchange,
+    net, ratelimit,
+};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+
+const SYSTEM_STATUS_URL: &str = "https://api.binance.com/sapi/v1/system/status";
+const ANNOUNCEMENTS_URL: &str =
+    "https://www.binance.com/bapi/composite/v1/public/cms/article/list/query";
+/// Announcement catalogs of Binance's support site.
+const DELISTING_CATALOG: u32 = 161;
+const MAINTENANCE_CATALOG: u32 = 157;
+/// How long after its release a delisting announcement is still shown.
+const DELISTING_MS: u64 = 14 * 24 * 60 * 60_000;
+/// How long after its release a maintenance announcement is still shown.
+const MAINTENANCE_MS: u64 = 2 * 24 * 60 * 60_000;
+
+/// `[maintenance]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    /// Poll Binance's system status and announcements.
+    pub enabled: bool,
+    pub refresh_secs: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            refresh_secs: 300,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnnouncementKind {
+    Maintenance,
+    Delisting,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Announcement {
+    pub kind: AnnouncementKind,
+    pub title: String,
+    /// Milliseconds since the epoch.
+    pub released: u64,
+}
+
+/// What the exchange last reported about itself.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExchangeStatus {
+    /// Set while the exchange reports system maintenance.
+    pub system_maintenance: bool,
+    pub announcements: Vec<Announcement>,
+}
+
+impl ExchangeStatus {
+    /// Lines for the banner: system maintenance, recent maintenance announcements, and recent
+    /// delistings whose title names one of `symbols` or their base assets.
+    pub fn warnings(&self, symbols: &HashSet<String>, now: u64) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.system_maintenance {
+            warnings.push("Binance reports system maintenance".to_string());
+        }
+        for announcement in &self.announcements {
+            let age = now.saturating_sub(announcement.released);
+            let shown = match announcement.kind {
+                AnnouncementKind::Maintenance => age < MAINTENANCE_MS,
+                AnnouncementKind::Delisting => {
+                    age < DELISTING_MS
+                        && announcement
+                            .title
+                            .split(|c: char| !c.is_ascii_alphanumeric())
+                            .any(|word| symbols.contains(word))
+                }
+            };
+            if shown {
+                warnings.push(announcement.title.clone());
+            }
+        }
+        warnings
+    }
+}
+
+#[derive(Deserialize)]
+struct SystemStatus {
+    /// 0 normal, 1 system maintenance.
+    status: u8,
+}
+
+#[derive(Deserialize)]
+struct Articles {
+    data: ArticleData,
+}
+
+#[derive(Deserialize)]
+struct ArticleData {
+    catalogs: Vec<Catalog>,
+}
+
+#[derive(Deserialize)]
+struct Catalog {
+    articles: Vec<Article>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Article {
+    title: String,
+    release_date: u64,
+}
+
+async fn fetch_announcements(
+    client: &reqwest::Client,
+    catalog: u32,
+    kind: AnnouncementKind,
+) -> Result<Vec<Announcement>, Error> {
+    let articles: Articles = client
+        .get(ANNOUNCEMENTS_URL)
+        .query(&[
+            ("type", "1".to_string()),
+            ("catalogId", catalog.to_string()),
+            ("pageNo", "1".to_string()),
+            ("pageSize", "20".to_string()),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(articles
+        .data
+        .catalogs
+        .into_iter()
+        .flat_map(|catalog| catalog.articles)
+        .map(|article| Announcement {
+            kind,
+            title: article.title,
+            released: article.release_date,
+        })
+        .collect())
+}
+
+async fn fetch_status() -> Result<ExchangeStatus, Error> {
+    let client = net::client();
+    let status: SystemStatus = ratelimit::send(client.get(SYSTEM_STATUS_URL), 1)
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    let mut announcements =
+        fetch_announcements(&client, MAINTENANCE_CATALOG, AnnouncementKind::Maintenance).await?;
+    announcements.extend(
+        fetch_announcements(&client, DELISTING_CATALOG, AnnouncementKind::Delisting).await?,
+    );
+    Ok(ExchangeStatus {
+        system_maintenance: status.status == 1,
+        announcements,
+    })
+}
+
+/// The exchange's status, fetched in the background.
+#[derive(Default)]
+pub struct Maintenance {
+    pub status: ExchangeStatus,
+    pending: Option<JoinHandle<Result<ExchangeStatus, Error>>>,
+    loaded_at: Option<Instant>,
+}
+
+impl Maintenance {
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Takes a fetched status without blocking, and fetches it again after `refresh_secs`.
+    /// Only Binance publishes its status; other exchanges have none.
+    pub fn poll(&mut self, config: &MaintenanceConfig, exchange: Exchange) {
+        if let Some(result) = self.pending.as_mut().and_then(|p| p.now_or_never()) {
+            self.pending = None;
+            match result.map_err(|err| Error::Other(err.to_string())) {
+                Ok(Ok(status)) => self.status = status,
+                Ok(Err(err)) | Err(err) => error::report(format!("exchange status: {}", err)),
+            }
+        }
+        if !config.enabled || !exchange.is_binance() {
+            self.status = ExchangeStatus::default();
+            return;
+        }
+        let refresh = Duration::from_secs(config.refresh_secs.max(60));
+        if self.pending.is_none() && self.loaded_at.is_none_or(|at| at.elapsed() >= refresh) {
+            self.loaded_at = Some(Instant::now());
+            self.pending = Some(tokio::spawn(fetch_status()));
+        }
+    }
+}
+
+impl Drop for Maintenance {
+    fn drop(&mut self) {
+        if let Some(pending) = &self.pending {
+            pending.abort();
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Optional Prometheus endpoint (`--metrics-addr`) exposing per-symbol gauges and a few
+//! internal counters in the text exposition format.
+
+use crate::{exchange::Exchange, model::TickerMap};
+use std::{
+    fmt::Write as _,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// Update batches received from the exchange stream.
+pub static MESSAGES: AtomicU64 = AtomicU64::new(0);
+/// Exchange frames that could not be parsed and were dropped.
+pub static PARSE_ERRORS: AtomicU64 = AtomicU64::new(0);
+/// Times the ticker stream had to be connected again after a failure.
+pub static RECONNECTS: AtomicU64 = AtomicU64::new(0);
+/// Duration of the last terminal draw, in microseconds.
+pub static RENDER_MICROS: AtomicU64 = AtomicU64::new(0);
+
+pub fn increment(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn record_render(duration: Duration) {
+    RENDER_MICROS.store(duration.as_micros() as u64, Ordering::Relaxed);
+}
+
+/// Serves `GET /metrics` on `addr` until the process exits.
+pub async fn serve(
+    addr: SocketAddr,
+    exchange: Exchange,
+    tickers: Arc<Mutex<TickerMap>>,
+) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let tickers = tickers.clone();
+        tokio::spawn(async move {
+            // A client hanging up mid-request is not worth reporting.
+            let _ = respond(stream, exchange, &tickers).await;
+        });
+    }
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    exchange: Exchange,
+    tickers: &Mutex<TickerMap>,
+) -> std::io::Result<()> {
+    let mut request = [0; 1024];
+    let read = stream.read(&mut request).await?;
+    let request = String::from_utf8_lossy(&request[..read]);
+    let path = request.split_whitespace().nth(1).unwrap_or("");
+    let (status, body) = if request.starts_with("GET ") && path == "/metrics" {
+        ("200 OK", render(exchange, tickers))
+    } else {
+        ("404 Not Found", "not found\n".to_string())
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn render(exchange: Exchange, tickers: &Mutex<TickerMap>) -> String {
+    let mut out = String::new();
+    let exchange = exchange.name();
+    let tickers = tickers.lock().unwrap();
+    let mut symbols: Vec<&String> = tickers.keys().collect();
+    symbols.sort();
+
+    let _ = writeln!(out, "# HELP crypto_ticker_last_price Last traded price.");
+    let _ = writeln!(out, "# TYPE crypto_ticker_last_price gauge");
+    for symbol in &symbols {
+        let _ = writeln!(
+            out,
+            "crypto_ticker_last_price{{exchange=\"{}\",symbol=\"{}\"}} {}",
+            exchange, symbol, tickers[*symbol].c
+        );
+    }
+    let _ = writeln!(
+        out,
+        "# HELP crypto_ticker_change_percent_24h 24h price change in percent."
+    );
+    let _ = writeln!(out, "# TYPE crypto_ticker_change_percent_24h gauge");
+    for symbol in &symbols {
+        let _ = writeln!(
+            out,
+            "crypto_ticker_change_percent_24h{{exchange=\"{}\",symbol=\"{}\"}} {}",
+            exchange, symbol, tickers[*symbol].P
+        );
+    }
+    drop(tickers);
+
+    for (name, help, counter) in [
+        (
+            "crypto_ticker_messages_total",
+            "Update batches received from the exchange.",
+            &MESSAGES,
+        ),
+        (
+            "crypto_ticker_parse_errors_total",
+            "Exchange frames dropped because they could not be parsed.",
+            &PARSE_ERRORS,
+        ),
+        (
+            "crypto_ticker_reconnects_total",
+            "Times the ticker stream was connected again after a failure.",
+            &RECONNECTS,
+        ),
+    ] {
+        let _ = writeln!(out, "# HELP {} {}", name, help);
+        let _ = writeln!(out, "# TYPE {} counter", name);
+        let _ = writeln!(out, "{} {}", name, counter.load(Ordering::Relaxed));
+    }
+    let _ = writeln!(
+        out,
+        "# HELP crypto_ticker_render_seconds Duration of the last frame render."
+    );
+    let _ = writeln!(out, "# TYPE crypto_ticker_render_seconds gauge");
+    let _ = writeln!(
+        out,
+        "crypto_ticker_render_seconds {}",
+        RENDER_MICROS.load(Ordering::Relaxed) as f64 / 1e6
+    );
+    out
+}
@@ -0,0 +1,505 @@
+//! Ticker data as normalized from every exchange, and the store it is merged into.
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+/// Bumped by every merge into a store that changed it, so readers can tell when there is
+/// something new.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Number of merges that changed any ticker store so far.
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::Relaxed)
+}
+
+#[allow(non_snake_case)]
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct HrTicker {
+    pub e: String, // Event type
+    pub E: u64,    // Event time
+    pub s: String, // Symbol
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub p: f32, // Price change
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub P: f32, // Price change percent
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub w: f32, // Weighted average price
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub c: f32, // Last price
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub Q: f32, // Last quantity
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub o: f32, // Open price
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub h: f32, // High price
+    #[serde(deserialize_with = "deserialize_f32_from_string")]
+    pub l: f32, // Low price
+    pub v: String, // Total traded base asset volume
+    pub q: String, // Total traded quote asset volume
+    pub O: u64,    // Statistics open time
+    pub C: u64,    // Statistics close time
+    pub F: u64,    // First trade ID
+    pub L: u64,    // Last trade ID
+    pub n: u64,    // Total number of trades
+    /// Current funding rate, for perpetual sources that stream it.
+    #[serde(default)]
+    pub funding_rate: Option<f32>,
+    #[serde(default = "default_previous_price")]
+    pub previous_price: f32,
+    /// When the last price last changed, for the tick flash.
+    #[serde(skip)]
+    pub last_changed: Option<Instant>,
+    /// When the symbol last received an update, for stale detection.
+    #[serde(skip)]
+    pub last_updated: Option<Instant>,
+    /// Fields the symbol's latest update changed; all of them for a new symbol.
+    #[serde(skip)]
+    pub changes: Changes,
+    /// Recent last prices, for the 1m/5m/15m change columns.
+    #[serde(skip)]
+    pub history: PriceHistory,
+    /// Older last prices, for the Percent Change column's 1h, 4h and UTC day bases.
+    #[serde(skip)]
+    pub basis_prices: BasisPrices,
+}
+
+impl HrTicker {
+    /// The fields in which `update` differs from this ticker.
+    pub fn diff(&self, update: &HrTicker) -> Changes {
+        let mut changes = Changes::NONE;
+        let mut mark = |changed: bool, fields: Changes| {
+            if changed {
+                changes = changes | fields;
+            }
+        };
+        mark(self.c != update.c || self.Q != update.Q, Changes::LAST);
+        mark(
+            self.p != update.p || self.P != update.P || self.w != update.w,
+            Changes::CHANGE,
+        );
+        mark(self.o != update.o, Changes::OPEN);
+        mark(self.h != update.h, Changes::HIGH);
+        mark(self.l != update.l, Changes::LOW);
+        mark(self.v != update.v || self.q != update.q, Changes::VOLUME);
+        mark(
+            self.n != update.n
+                || self.F != update.F
+                || self.L != update.L
+                || self.O != update.O
+                || self.C != update.C
+                || self.E != update.E,
+            Changes::TRADES,
+        );
+        mark(self.funding_rate != update.funding_rate, Changes::FUNDING);
+        changes
+    }
+
+    /// Percent change of the last price over the past `window_ms`, once enough history has
+    /// been seen.
+    pub fn rolling_change(&self, window_ms: u64) -> Option<f32> {
+        self.history.change(window_ms, self.c)
+    }
+
+    fn record_price(&mut self, time: u64, price: f32) {
+        self.history.record(time, price);
+        self.basis_prices.record(time, price);
+    }
+}
+
+const HOUR_MS: u64 = 60 * 60_000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+/// What the Percent Change column measures the last price against.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeBasis {
+    /// The exchange's rolling 24h change.
+    #[default]
+    Day,
+    /// The price an hour ago, as seen by the app.
+    Hour,
+    FourHours,
+    /// The price at 00:00 UTC, once the app has been running since then.
+    UtcDay,
+}
+
+impl ChangeBasis {
+    /// Header of the Percent Change column on this basis.
+    pub fn title(self) -> &'static str {
+        match self {
+            ChangeBasis::Day => SortColumn::PercentChange.title(),
+            ChangeBasis::Hour => "1h %",
+            ChangeBasis::FourHours => "4h %",
+            ChangeBasis::UtcDay => "UTC day %",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ChangeBasis::Day => ChangeBasis::Hour,
+            ChangeBasis::Hour => ChangeBasis::FourHours,
+            ChangeBasis::FourHours => ChangeBasis::UtcDay,
+            ChangeBasis::UtcDay => ChangeBasis::Day,
+        }
+    }
+
+    /// The ticker's percent change on this basis, if it is known yet.
+    pub fn change(self, ticker: &HrTicker) -> Option<f32> {
+        let prices = &ticker.basis_prices;
+        match self {
+            ChangeBasis::Day => Some(ticker.P),
+            ChangeBasis::Hour => prices.history.change(HOUR_MS, ticker.c),
+            ChangeBasis::FourHours => prices.history.change(4 * HOUR_MS, ticker.c),
+            ChangeBasis::UtcDay => prices.since_midnight(ticker.c),
+        }
+    }
+}
+
+/// Last prices of one symbol for the longer change bases: the past four hours, and the first
+/// price of the UTC day.
+#[derive(Clone, Debug)]
+pub struct BasisPrices {
+    history: PriceHistory,
+    /// UTC day, in days since the epoch, and the first price seen in it.
+    day_open: Option<(u64, f32)>,
+    /// Whether `day_open` is the price at midnight, the price before it being from the day
+    /// before.
+    from_midnight: bool,
+}
+
+impl Default for BasisPrices {
+    fn default() -> Self {
+        Self {
+            history: PriceHistory::with_reach(4 * HOUR_MS),
+            day_open: None,
+            from_midnight: false,
+        }
+    }
+}
+
+impl BasisPrices {
+    pub fn record(&mut self, time: u64, price: f32) {
+        self.history.record(time, price);
+        let day = time / DAY_MS;
+        match self.day_open {
+            Some((open_day, _)) if open_day >= day => {}
+            previous => {
+                self.from_midnight = previous.is_some_and(|(open_day, _)| open_day + 1 == day);
+                self.day_open = Some((day, price));
+            }
+        }
+    }
+
+    /// Percent change from the price at 00:00 UTC to `price`, or `None` when the first price
+    /// of the day was not seen.
+    pub fn since_midnight(&self, price: f32) -> Option<f32> {
+        let (_, open) = self.day_open.filter(|_| self.from_midnight)?;
+        (open > 0.0).then(|| (price - open) / open * 100.0)
+    }
+}
+
+/// Closest spacing of the samples kept in a `PriceHistory`.
+const SAMPLE_MS: u64 = 1_000;
+/// How far back a default `PriceHistory` reaches: the longest rolling change column.
+const HISTORY_MS: u64 = 15 * 60_000;
+/// Samples a `PriceHistory` keeps at most; longer reaches space them further apart.
+const MAX_SAMPLES: u64 = 900;
+
+/// Last prices of one symbol over the past 15 minutes by default, at most one per second,
+/// timed by the exchange's event time so replays at any speed give the same changes.
+#[derive(Clone, Debug)]
+pub struct PriceHistory {
+    /// (event time in ms, last price), oldest first.
+    samples: VecDeque<(u64, f32)>,
+    reach_ms: u64,
+    sample_ms: u64,
+}
+
+impl Default for PriceHistory {
+    fn default() -> Self {
+        Self::with_reach(HISTORY_MS)
+    }
+}
+
+impl PriceHistory {
+    /// A history reaching `reach_ms` back, with samples at least a second apart and no more
+    /// than 900 of them.
+    pub fn with_reach(reach_ms: u64) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            reach_ms,
+            sample_ms: (reach_ms / MAX_SAMPLES).max(SAMPLE_MS),
+        }
+    }
+
+    pub fn reach_ms(&self) -> u64 {
+        self.reach_ms
+    }
+
+    pub fn record(&mut self, time: u64, price: f32) {
+        if self
+            .samples
+            .back()
+            .is_some_and(|(last, _)| time < last + self.sample_ms)
+        {
+            return;
+        }
+        self.samples.push_back((time, price));
+        // Keep one sample at or before the start of the longest window.
+        let start = time.saturating_sub(self.reach_ms);
+        while self.samples.get(1).is_some_and(|(t, _)| *t <= start) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Percent change from the price `window_ms` before the latest sample to `price`, or
+    /// `None` when the history does not reach back that far.
+    pub fn change(&self, window_ms: u64, price: f32) -> Option<f32> {
+        let since = self.samples.back()?.0.checked_sub(window_ms)?;
+        let i = self.samples.partition_point(|(t, _)| *t <= since);
+        let (_, base) = self.samples.get(i.checked_sub(1)?)?;
+        (*base > 0.0).then(|| (price - base) / base * 100.0)
+    }
+}
+
+fn default_previous_price() -> f32 {
+    0.0
+}
+
+/// Set of ticker fields, such as the ones an update changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Changes(u8);
+
+impl Changes {
+    pub const NONE: Changes = Changes(0);
+    /// Last price and quantity.
+    pub const LAST: Changes = Changes(1);
+    /// 24h price change, percent change and weighted average price.
+    pub const CHANGE: Changes = Changes(1 << 1);
+    pub const OPEN: Changes = Changes(1 << 2);
+    pub const HIGH: Changes = Changes(1 << 3);
+    pub const LOW: Changes = Changes(1 << 4);
+    /// Base and quote volume.
+    pub const VOLUME: Changes = Changes(1 << 5);
+    /// Trade count, trade ids, statistics window and event time.
+    pub const TRADES: Changes = Changes(1 << 6);
+    pub const FUNDING: Changes = Changes(1 << 7);
+    pub const ALL: Changes = Changes(u8::MAX);
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Whether any of `fields` is in the set.
+    pub fn intersects(self, fields: Changes) -> bool {
+        self.0 & fields.0 != 0
+    }
+}
+
+impl std::ops::BitOr for Changes {
+    type Output = Changes;
+
+    fn bitor(self, other: Changes) -> Changes {
+        Changes(self.0 | other.0)
+    }
+}
+
+/// Latest ticker per symbol, in the order symbols were first seen.
+pub type TickerMap = IndexMap<String, HrTicker>;
+
+#[derive(Clone, Debug)]
+pub struct Tickers {
+    pub tickers: Arc<Mutex<TickerMap>>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    pub fn reversed(self) -> Self {
+        match self {
+            SortOrder::Ascending => SortOrder::Descending,
+            SortOrder::Descending => SortOrder::Ascending,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum SortColumn {
+    Symbol,
+    Last,
+    PercentChange,
+    Open,
+    High,
+    Low,
+    Volume,
+    Rank,
+    MarketCap,
+    Liquidations,
+    Change1m,
+    Change5m,
+    Change15m,
+    Rsi,
+    EmaTrend,
+    Vwap,
+    /// Time since the row last ticked.
+    Updated,
+    /// Filled by the script's `custom_column`.
+    Script,
+}
+
+impl SortColumn {
+    pub fn title(self) -> &'static str {
+        match self {
+            SortColumn::Symbol => "Symbol",
+            SortColumn::Last => "Last",
+            SortColumn::PercentChange => "Percent Change",
+            SortColumn::Open => "Open",
+            SortColumn::High => "High",
+            SortColumn::Low => "Low",
+            SortColumn::Volume => "Volume",
+            SortColumn::Rank => "Rank",
+            SortColumn::MarketCap => "Market Cap",
+            SortColumn::Liquidations => "Liqs",
+            SortColumn::Change1m => "1m %",
+            SortColumn::Change5m => "5m %",
+            SortColumn::Change15m => "15m %",
+            SortColumn::Rsi => "RSI 14",
+            SortColumn::EmaTrend => "EMA 9/21",
+            SortColumn::Vwap => "VWAP",
+            SortColumn::Updated => "Updated",
+            SortColumn::Script => "Script",
+        }
+    }
+
+    /// Direction the column sorts in until it is reversed: names, ranks and ages ascending,
+    /// values largest first.
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortColumn::Symbol | SortColumn::Rank | SortColumn::Updated => SortOrder::Ascending,
+            _ => SortOrder::Descending,
+        }
+    }
+
+    /// Window of the rolling change columns, in milliseconds.
+    pub fn window_ms(self) -> Option<u64> {
+        match self {
+            SortColumn::Change1m => Some(60_000),
+            SortColumn::Change5m => Some(5 * 60_000),
+            SortColumn::Change15m => Some(15 * 60_000),
+            _ => None,
+        }
+    }
+}
+
+impl Tickers {
+    pub fn new() -> Self {
+        Self {
+            tickers: Arc::new(Mutex::new(TickerMap::new())),
+        }
+    }
+}
+
+impl Default for Tickers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for HrTicker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HrTicker {{ s: {} }}", self.s)
+    }
+}
+
+fn deserialize_f32_from_string<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    // Exchanges send prices as strings; recordings written by `--record` hold plain numbers.
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(f32),
+    }
+    match Deserialize::deserialize(deserializer)? {
+        StringOrNumber::String(s) => s.parse::<f32>().map_err(serde::de::Error::custom),
+        StringOrNumber::Number(n) => Ok(n),
+    }
+}
+
+/// Merges a batch of updates into the store, keeping each symbol's previous price, the
+/// times of its last update and price change, and the fields the update changed. Returns the
+/// number of symbols that were added or changed; repeats of the same values change nothing
+/// but the update time.
+pub fn update_tickers(
+    new_tickers: impl IntoIterator<Item = HrTicker>,
+    tickers: Arc<Mutex<TickerMap>>,
+) -> usize {
+    let mut tickers = tickers.lock().unwrap();
+    let mut changed = 0;
+
+    for new_ticker in new_tickers {
+        match tickers.get_mut(&new_ticker.s) {
+            Some(existing_ticker) => {
+                // Update existing ticker
+                let changes = existing_ticker.diff(&new_ticker);
+                existing_ticker.changes = changes;
+                existing_ticker.previous_price = existing_ticker.c;
+                existing_ticker.last_updated = Some(Instant::now());
+                existing_ticker.record_price(new_ticker.E, new_ticker.c);
+                if changes.is_empty() {
+                    continue;
+                }
+                changed += 1;
+                if new_ticker.c != existing_ticker.c {
+                    existing_ticker.last_changed = Some(Instant::now());
+                }
+                existing_ticker.E = new_ticker.E;
+                existing_ticker.p = new_ticker.p;
+                existing_ticker.P = new_ticker.P;
+                existing_ticker.w = new_ticker.w;
+                existing_ticker.c = new_ticker.c;
+                existing_ticker.Q = new_ticker.Q;
+                existing_ticker.o = new_ticker.o;
+                existing_ticker.h = new_ticker.h;
+                existing_ticker.l = new_ticker.l;
+                existing_ticker.v.clone_from(&new_ticker.v);
+                existing_ticker.q.clone_from(&new_ticker.q);
+                existing_ticker.O = new_ticker.O;
+                existing_ticker.C = new_ticker.C;
+                existing_ticker.F = new_ticker.F;
+                existing_ticker.L = new_ticker.L;
+                existing_ticker.n = new_ticker.n;
+                existing_ticker.funding_rate = new_ticker.funding_rate;
+            }
+            None => {
+                // Add new ticker
+                let mut new_ticker = new_ticker;
+                new_ticker.last_updated = Some(Instant::now());
+                new_ticker.changes = Changes::ALL;
+                new_ticker.record_price(new_ticker.E, new_ticker.c);
+                tickers.insert(new_ticker.s.clone(), new_ticker);
+                changed += 1;
+            }
+        }
+    }
+    if changed > 0 {
+        GENERATION.fetch_add(1, Ordering::Relaxed);
+    }
+    changed
+}
@@ -0,0 +1,90 @@
+//! Top gainers and losers: the symbols that moved most up and down, side by side.
+
+use crate::model::{HrTicker, SortColumn};
+use serde::{Deserialize, Serialize};
+
+/// `[movers]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct MoversConfig {
+    /// How many symbols each list shows.
+    pub count: usize,
+    /// Change the lists are ranked by when the view opens.
+    pub basis: Basis,
+}
+
+impl Default for MoversConfig {
+    fn default() -> Self {
+        Self {
+            count: 15,
+            basis: Basis::Day,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Basis {
+    /// The exchange's 24h percent change.
+    Day,
+    /// Percent change over the last five minutes, computed from the stream.
+    FiveMinutes,
+}
+
+impl Basis {
+    pub fn title(self) -> &'static str {
+        match self {
+            Basis::Day => "24h",
+            Basis::FiveMinutes => "5m",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Basis::Day => Basis::FiveMinutes,
+            Basis::FiveMinutes => Basis::Day,
+        }
+    }
+
+    /// The ticker's change on this basis, if it is known yet.
+    pub fn change(self, ticker: &HrTicker) -> Option<f32> {
+        match self {
+            Basis::Day => Some(ticker.P),
+            Basis::FiveMinutes => {
+                ticker.rolling_change(SortColumn::Change5m.window_ms().unwrap_or_default())
+            }
+        }
+    }
+}
+
+/// Tickers with their change, largest move first.
+pub type Ranked<'a> = Vec<(&'a HrTicker, f32)>;
+
+/// Up to `count` symbols that rose the most, largest change first, and up to `count` that fell
+/// the most, largest fall first. Unchanged symbols are in neither list.
+pub fn rank<'a>(
+    tickers: impl IntoIterator<Item = &'a HrTicker>,
+    basis: Basis,
+    count: usize,
+) -> (Ranked<'a>, Ranked<'a>) {
+    let mut changes: Vec<(&HrTicker, f32)> = tickers
+        .into_iter()
+        .filter_map(|ticker| Some((ticker, basis.change(ticker)?)))
+        .filter(|(_, change)| change.is_finite())
+        .collect();
+    changes.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.s.cmp(&b.0.s)));
+    let gainers = changes
+        .iter()
+        .take_while(|(_, change)| *change > 0.0)
+        .take(count)
+        .copied()
+        .collect();
+    let losers = changes
+        .iter()
+        .rev()
+        .take_while(|(_, change)| *change < 0.0)
+        .take(count)
+        .copied()
+        .collect();
+    (gainers, losers)
+}
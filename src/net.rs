@@ -0,0 +1,182 @@
+//! Outgoing connections. Websockets and REST requests go through the proxy given with `--proxy`
+//! or the `proxy` config key when one is set, and connect directly otherwise.
+
+use crate::error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::percent_decode_str;
+use reqwest::Url;
+use std::{fmt, io, str::FromStr, sync::OnceLock};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tokio_socks::tcp::Socks5Stream;
+use tokio_tungstenite::{
+    client_async_tls, connect_async,
+    tungstenite::{
+        client::IntoClientRequest,
+        error::{Error as WsError, UrlError},
+        handshake::client::Response,
+    },
+    MaybeTlsStream, WebSocketStream,
+};
+
+/// An open websocket connection to an exchange.
+pub type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Longest proxy reply to a CONNECT request that is read before giving up on it.
+const MAX_CONNECT_REPLY: usize = 8192;
+
+static PROXY: OnceLock<Proxy> = OnceLock::new();
+
+/// An `http://`, `socks5://` or `socks5h://` proxy URL, with optional `user:password@`
+/// credentials. SOCKS5 proxies resolve the exchange host names either way.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proxy {
+    url: Url,
+}
+
+impl FromStr for Proxy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|err| format!("invalid proxy URL {:?}: {}", s, err))?;
+        if !matches!(url.scheme(), "http" | "socks5" | "socks5h") {
+            return Err(format!(
+                "unsupported proxy scheme {:?}; use http, socks5 or socks5h",
+                url.scheme()
+            ));
+        }
+        if url.host_str().is_none() {
+            return Err(format!("proxy URL {:?} has no host", s));
+        }
+        Ok(Self { url })
+    }
+}
+
+impl fmt::Display for Proxy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+impl Proxy {
+    fn is_socks(&self) -> bool {
+        self.url.scheme() != "http"
+    }
+
+    fn address(&self) -> (String, u16) {
+        let host = self.url.host_str().unwrap_or_default();
+        let port = self
+            .url
+            .port()
+            .unwrap_or(if self.is_socks() { 1080 } else { 8080 });
+        (host.trim_matches(['[', ']']).to_string(), port)
+    }
+
+    fn credentials(&self) -> Option<(String, String)> {
+        if self.url.username().is_empty() {
+            return None;
+        }
+        let decode = |s: &str| percent_decode_str(s).decode_utf8_lossy().into_owned();
+        Some((
+            decode(self.url.username()),
+            decode(self.url.password().unwrap_or_default()),
+        ))
+    }
+
+    /// Opens a TCP connection to `host:port` through the proxy.
+    pub async fn tunnel(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let proxy = self.address();
+        let proxy = (proxy.0.as_str(), proxy.1);
+        if self.is_socks() {
+            let target = (host, port);
+            let stream = match self.credentials() {
+                Some((user, password)) => {
+                    Socks5Stream::connect_with_password(proxy, target, &user, &password).await
+                }
+                None => Socks5Stream::connect(proxy, target).await,
+            };
+            return stream
+                .map(Socks5Stream::into_inner)
+                .map_err(io::Error::other);
+        }
+
+        let mut stream = TcpStream::connect(proxy).await?;
+        let mut request = format!(
+            "CONNECT {host}:{port} HTTP/1.1\r\nHost: {host}:{port}\r\n",
+            host = host,
+            port = port
+        );
+        if let Some((user, password)) = self.credentials() {
+            let token = STANDARD.encode(format!("{}:{}", user, password));
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // Read byte by byte so nothing after the reply's headers is taken from the tunnel.
+        let mut reply = Vec::new();
+        while !reply.ends_with(b"\r\n\r\n") {
+            if reply.len() >= MAX_CONNECT_REPLY {
+                return Err(io::Error::other("proxy reply to CONNECT is too long"));
+            }
+            reply.push(stream.read_u8().await?);
+        }
+        let reply = String::from_utf8_lossy(&reply);
+        let status = reply.lines().next().unwrap_or_default();
+        match status.split_whitespace().nth(1) {
+            Some(code) if code.starts_with('2') => Ok(stream),
+            _ => Err(io::Error::other(format!(
+                "proxy refused CONNECT to {}:{}: {}",
+                host, port, status
+            ))),
+        }
+    }
+}
+
+/// Routes every later connection through `proxy`. Only the first call has an effect.
+pub fn set_proxy(proxy: Proxy) {
+    let _ = PROXY.set(proxy);
+}
+
+pub fn proxy() -> Option<&'static Proxy> {
+    PROXY.get()
+}
+
+/// A REST client using the proxy, if any.
+pub fn client() -> reqwest::Client {
+    builder().build().unwrap_or_default()
+}
+
+/// A client builder with the proxy set, for clients that need their own policies.
+pub fn builder() -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = proxy() {
+        match reqwest::Proxy::all(proxy.url.clone()) {
+            Ok(proxy) => builder = builder.proxy(proxy),
+            Err(err) => error::report(format!("proxy {}: {}", proxy, err)),
+        }
+    }
+    builder
+}
+
+/// Opens a websocket to `url` like `connect_async`, through the proxy if one is set.
+pub async fn connect<R: IntoClientRequest + Unpin>(url: R) -> Result<(Socket, Response), WsError> {
+    let Some(proxy) = proxy() else {
+        return connect_async(url).await;
+    };
+    let request = url.into_client_request()?;
+    let uri = request.uri();
+    let host = uri
+        .host()
+        .ok_or(WsError::Url(UrlError::NoHostName))?
+        .trim_matches(['[', ']'])
+        .to_string();
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("wss") => 443,
+        _ => 80,
+    });
+    let stream = proxy.tunnel(&host, port).await?;
+    client_async_tls(request, stream).await
+}
@@ -0,0 +1,304 @@
+//! News headlines from RSS or Atom feeds and the CryptoPanic API, for the news pane.
+
+use crate::{
+    error::{self, Error},
+    net,
+};
+use chrono::DateTime;
+use futures::FutureExt;
+use quick_xml::{events::Event, Reader};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+
+const CRYPTOPANIC_URL: &str = "https://cryptopanic.com/api/v1/posts/";
+
+/// `[news]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct NewsConfig {
+    /// RSS or Atom feed URLs.
+    pub feeds: Vec<String>,
+    /// CryptoPanic API token; its latest public posts are added to the feeds when set.
+    pub cryptopanic_token: Option<String>,
+    pub refresh_secs: u64,
+    /// Headlines kept, newest first.
+    pub max_headlines: usize,
+}
+
+impl Default for NewsConfig {
+    fn default() -> Self {
+        Self {
+            feeds: Vec::new(),
+            cryptopanic_token: None,
+            refresh_secs: 300,
+            max_headlines: 100,
+        }
+    }
+}
+
+impl NewsConfig {
+    pub fn has_sources(&self) -> bool {
+        !self.feeds.is_empty() || self.cryptopanic_token.is_some()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Headline {
+    pub title: String,
+    pub url: String,
+    /// Title of the feed or site it came from.
+    pub source: String,
+    /// Milliseconds since the epoch, when the feed gives a date.
+    pub published: Option<u64>,
+}
+
+impl Headline {
+    /// Assets of `bases` the title mentions by ticker, as whole upper-case words such as `BTC`
+    /// or `$SOL`.
+    pub fn mentions<'a>(&self, bases: &'a HashSet<String>) -> Vec<&'a str> {
+        let mut mentioned = Vec::new();
+        for word in self.title.split(|c: char| !c.is_ascii_alphanumeric()) {
+            if let Some(base) = bases.get(word) {
+                if !mentioned.contains(&base.as_str()) {
+                    mentioned.push(base.as_str());
+                }
+            }
+        }
+        mentioned
+    }
+}
+
+/// Parses the items of an RSS feed or the entries of an Atom feed, titled with the feed's
+/// title.
+pub fn parse_feed(xml: &str) -> Result<Vec<Headline>, Error> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+    let mut feed_title = String::new();
+    let mut headlines = Vec::new();
+    // The headline being read, and the element whose text is being read.
+    let mut item: Option<Headline> = None;
+    let mut field: Option<String> = None;
+    let mut date = String::new();
+    loop {
+        let event = reader.read_event()?;
+        let empty = matches!(event, Event::Empty(_));
+        match event {
+            Event::Start(start) | Event::Empty(start) => {
+                // Qualified names, so that e.g. `media:title` is not taken for the title.
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                match name.as_str() {
+                    "item" | "entry" => {
+                        item = Some(Headline {
+                            title: String::new(),
+                            url: String::new(),
+                            source: feed_title.clone(),
+                            published: None,
+                        });
+                        date.clear();
+                    }
+                    // Atom links are attributes; prefer the alternate one.
+                    "link" => {
+                        let mut href = None;
+                        let mut alternate = true;
+                        for attribute in start.attributes().flatten() {
+                            let value = attribute.unescape_value()?;
+                            match attribute.key.as_ref() {
+                                b"href" => href = Some(value.into_owned()),
+                                b"rel" => alternate = value == "alternate",
+                                _ => {}
+                            }
+                        }
+                        if let (Some(item), Some(href)) = (&mut item, href) {
+                            if alternate || item.url.is_empty() {
+                                item.url = href;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                field = (!empty).then_some(name);
+            }
+            Event::Text(text) => {
+                let text = text.unescape()?;
+                read_field(
+                    field.as_deref(),
+                    &text,
+                    &mut item,
+                    &mut feed_title,
+                    &mut date,
+                );
+            }
+            Event::CData(text) => {
+                let text = String::from_utf8_lossy(&text).into_owned();
+                read_field(
+                    field.as_deref(),
+                    &text,
+                    &mut item,
+                    &mut feed_title,
+                    &mut date,
+                );
+            }
+            Event::End(end) => {
+                if matches!(end.name().as_ref(), b"item" | b"entry") {
+                    if let Some(mut headline) = item.take() {
+                        headline.published = parse_date(&date);
+                        if !headline.title.is_empty() {
+                            headlines.push(headline);
+                        }
+                    }
+                }
+                field = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(headlines)
+}
+
+fn read_field(
+    field: Option<&str>,
+    text: &str,
+    item: &mut Option<Headline>,
+    feed_title: &mut String,
+    date: &mut String,
+) {
+    match (field, item) {
+        (Some("title"), Some(item)) => item.title.push_str(text.trim()),
+        (Some("link"), Some(item)) => item.url.push_str(text.trim()),
+        (Some("pubDate" | "published" | "updated" | "dc:date"), Some(_)) if date.is_empty() => {
+            date.push_str(text.trim())
+        }
+        (Some("title"), None) if feed_title.is_empty() => feed_title.push_str(text.trim()),
+        _ => {}
+    }
+}
+
+/// RSS dates are RFC 2822 and Atom dates RFC 3339.
+fn parse_date(date: &str) -> Option<u64> {
+    DateTime::parse_from_rfc2822(date)
+        .or_else(|_| DateTime::parse_from_rfc3339(date))
+        .ok()
+        .and_then(|date| u64::try_from(date.timestamp_millis()).ok())
+}
+
+#[derive(Deserialize)]
+struct CryptoPanicPosts {
+    results: Vec<CryptoPanicPost>,
+}
+
+#[derive(Deserialize)]
+struct CryptoPanicPost {
+    title: String,
+    url: String,
+    published_at: Option<String>,
+    source: Option<CryptoPanicSource>,
+}
+
+#[derive(Deserialize)]
+struct CryptoPanicSource {
+    title: String,
+}
+
+async fn fetch_cryptopanic(client: &reqwest::Client, token: &str) -> Result<Vec<Headline>, Error> {
+    let posts: CryptoPanicPosts = client
+        .get(CRYPTOPANIC_URL)
+        .query(&[("auth_token", token), ("public", "true")])
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    Ok(posts
+        .results
+        .into_iter()
+        .map(|post| Headline {
+            title: post.title,
+            url: post.url,
+            source: post.source.map_or("CryptoPanic".to_string(), |s| s.title),
+            published: post.published_at.as_deref().and_then(parse_date),
+        })
+        .collect())
+}
+
+async fn fetch_feed(client: &reqwest::Client, url: &str) -> Result<Vec<Headline>, Error> {
+    let xml = client
+        .get(url)
+        .header("User-Agent", "crypto_tui_ticker")
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    parse_feed(&xml)
+}
+
+/// Fetches every source, newest first and without repeated links. Sources that fail are
+/// reported and skipped.
+async fn fetch_all(config: NewsConfig) -> Vec<Headline> {
+    let client = net::client();
+    let mut headlines = Vec::new();
+    for url in &config.feeds {
+        match fetch_feed(&client, url).await {
+            Ok(feed) => headlines.extend(feed),
+            Err(err) => error::report(format!("news feed {}: {}", url, err)),
+        }
+    }
+    if let Some(token) = &config.cryptopanic_token {
+        match fetch_cryptopanic(&client, token).await {
+            Ok(posts) => headlines.extend(posts),
+            Err(err) => error::report(format!("CryptoPanic: {}", err)),
+        }
+    }
+    headlines.sort_by_key(|headline| Reverse(headline.published));
+    let mut seen = HashSet::new();
+    headlines.retain(|headline| seen.insert(headline.url.clone()));
+    headlines.truncate(config.max_headlines);
+    headlines
+}
+
+/// Headlines for the news pane, fetched in the background while the pane is shown.
+#[derive(Default)]
+pub struct News {
+    pub headlines: Vec<Headline>,
+    pending: Option<JoinHandle<Vec<Headline>>>,
+    loaded_at: Option<Instant>,
+}
+
+impl News {
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Takes fetched headlines without blocking, and starts a fetch when the last one is
+    /// older than `refresh_secs`.
+    pub fn poll(&mut self, config: &NewsConfig) {
+        if let Some(result) = self.pending.as_mut().and_then(|p| p.now_or_never()) {
+            self.pending = None;
+            if let Ok(headlines) = result {
+                self.headlines = headlines;
+            }
+        }
+        let refresh = Duration::from_secs(config.refresh_secs.max(60));
+        if self.pending.is_none()
+            && config.has_sources()
+            && self.loaded_at.is_none_or(|at| at.elapsed() >= refresh)
+        {
+            self.loaded_at = Some(Instant::now());
+            self.pending = Some(tokio::spawn(fetch_all(config.clone())));
+        }
+    }
+}
+
+impl Drop for News {
+    fn drop(&mut self) {
+        if let Some(pending) = &self.pending {
+            pending.abort();
+        }
+    }
+}
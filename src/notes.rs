@@ -0,0 +1,44 @@
+//! Notes and tags attached to symbols, kept in the config file, e.g. a reminder of why a coin
+//! is being watched and tags such as `breakout watch` to filter the table by.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SymbolNote {
+    pub note: String,
+    pub tags: Vec<String>,
+}
+
+impl SymbolNote {
+    /// Parses the note editor's `tag, tag: note`; input without a colon is only a note.
+    pub fn parse(input: &str) -> Self {
+        let (tags, note) = input.split_once(':').unwrap_or(("", input));
+        Self {
+            note: note.trim().to_string(),
+            tags: tags
+                .split(',')
+                .map(|tag| tag.trim().trim_start_matches('#').trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect(),
+        }
+    }
+
+    /// The note as the editor shows it, the inverse of `parse`.
+    pub fn input(&self) -> String {
+        format!("{}: {}", self.tags.join(", "), self.note)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.note.is_empty() && self.tags.is_empty()
+    }
+
+    /// Whether a tag starts with `query`, ignoring case and a leading `#`, so `#breakout`
+    /// finds `breakout watch`.
+    pub fn has_tag(&self, query: &str) -> bool {
+        let query = query.trim_start_matches('#').to_lowercase();
+        self.tags
+            .iter()
+            .any(|tag| tag.to_lowercase().starts_with(&query))
+    }
+}
@@ -0,0 +1,102 @@
+//! Notifications for the user: posted from anywhere, shown by the UI as toasts and kept in its
+//! notification history.
+
+use crate::alerts::Alert;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Set while the TUI is on screen; notifications posted at other times are not shown.
+static INTERACTIVE: AtomicBool = AtomicBool::new(false);
+static POSTED: Mutex<VecDeque<Notification>> = Mutex::new(VecDeque::new());
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    /// How long a toast of this level stays on screen.
+    pub fn duration(self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(4),
+            Level::Warning => Duration::from_secs(6),
+            Level::Error => Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    /// Local time it was posted, in milliseconds since the epoch.
+    pub time: u64,
+    pub posted: Instant,
+    /// The alert it announces, which can be snoozed while the toast is shown.
+    pub alert: Option<Alert>,
+}
+
+impl Notification {
+    pub fn new(level: Level, message: impl fmt::Display) -> Self {
+        Self {
+            level,
+            message: message.to_string(),
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+            posted: Instant::now(),
+            alert: None,
+        }
+    }
+
+    /// Whether it is still shown as a toast.
+    pub fn is_toast(&self) -> bool {
+        self.posted.elapsed() < self.level.duration()
+    }
+}
+
+/// Queues notifications for the UI from now until it is called with `false`.
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::Relaxed);
+}
+
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
+/// Queues a notification for the UI. A message already waiting in the queue is not queued
+/// again, and nothing is queued while the UI is not running.
+pub fn post(level: Level, message: impl fmt::Display) {
+    queue(Notification::new(level, message));
+}
+
+/// Queues the notification of a triggered alert.
+pub fn post_alert(alert: Alert) {
+    let mut notification = Notification::new(Level::Warning, format!("Alert {}", alert.message()));
+    notification.alert = Some(alert);
+    queue(notification);
+}
+
+fn queue(notification: Notification) {
+    if !is_interactive() {
+        return;
+    }
+    let mut posted = POSTED.lock().unwrap();
+    if !posted.iter().any(|n| n.message == notification.message) {
+        posted.push_back(notification);
+    }
+}
+
+/// Takes the notifications posted since the last call, oldest first.
+pub fn take_posted() -> Vec<Notification> {
+    POSTED.lock().unwrap().drain(..).collect()
+}
@@ -0,0 +1,39 @@
+//! `--oneline` mode: a compact summary of a few symbols on one line, for tmux status bars,
+//! polybar and shell prompts.
+
+use crate::{
+    format::NumberFormat,
+    model::{HrTicker, SortColumn},
+};
+
+/// `symbols` in order, each as `BTCUSDT ▲64210.5 +1.23%`; a symbol without a ticker is shown as
+/// `BTCUSDT ?`. With `ascii` the arrows are `^` and `v`.
+pub fn line(
+    symbols: &[String],
+    tickers: &[HrTicker],
+    number_format: &NumberFormat,
+    ascii: bool,
+) -> String {
+    symbols
+        .iter()
+        .map(|symbol| {
+            let Some(ticker) = tickers.iter().find(|t| t.s.eq_ignore_ascii_case(symbol)) else {
+                return format!("{} ?", symbol.to_uppercase());
+            };
+            let arrow = match (ticker.P >= 0.0, ascii) {
+                (true, false) => "▲",
+                (false, false) => "▼",
+                (true, true) => "^",
+                (false, true) => "v",
+            };
+            let price =
+                number_format.column(SortColumn::Last, ticker.c as f64, |_| ticker.c.to_string());
+            let sign = if ticker.P > 0.0 { "+" } else { "" };
+            let change = number_format.column(SortColumn::PercentChange, ticker.P as f64, |p| {
+                format!("{:.2}", p)
+            });
+            format!("{} {}{} {}{}%", ticker.s, arrow, price, sign, change)
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
@@ -0,0 +1,197 @@
+use crate::model::TickerMap;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const TRADE_LOG_FILE: &str = "paper_trades.jsonl";
+
+/// `[paper]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct PaperConfig {
+    /// Simulated slippage against the last price, in basis points.
+    pub slippage_bps: f64,
+    /// Fee charged on each fill's notional, in basis points.
+    pub fee_bps: f64,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self {
+            slippage_bps: 1.0,
+            fee_bps: 4.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn name(self) -> &'static str {
+        match self {
+            Side::Buy => "Buy",
+            Side::Sell => "Sell",
+        }
+    }
+
+    fn sign(self) -> f64 {
+        match self {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        }
+    }
+}
+
+/// One simulated fill, as written to the trade log.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Trade {
+    /// Milliseconds since the epoch.
+    pub time: u64,
+    pub symbol: String,
+    pub side: Side,
+    pub quantity: f64,
+    pub price: f64,
+    pub fee: f64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PaperPosition {
+    /// Signed size: positive long, negative short.
+    pub quantity: f64,
+    pub average_price: f64,
+}
+
+impl PaperPosition {
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        self.quantity * (price - self.average_price)
+    }
+}
+
+/// Simulated account built by replaying the trade log.
+#[derive(Debug, Default)]
+pub struct PaperAccount {
+    pub positions: BTreeMap<String, PaperPosition>,
+    /// Realized P&L net of fees.
+    pub realized_pnl: f64,
+    /// All fills, oldest first.
+    pub trades: Vec<Trade>,
+    /// The trade log fills are appended to.
+    log: Option<PathBuf>,
+}
+
+impl PaperAccount {
+    /// Rebuilds the account from the trade log in the config directory `dir`; lines that fail
+    /// to parse are skipped.
+    pub fn load(dir: Option<&Path>) -> Self {
+        let mut account = Self {
+            log: dir.map(|dir| dir.join(TRADE_LOG_FILE)),
+            ..Self::default()
+        };
+        let Some(file) = account
+            .log
+            .as_ref()
+            .and_then(|path| fs::File::open(path).ok())
+        else {
+            return account;
+        };
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            if let Ok(trade) = serde_json::from_str::<Trade>(&line) {
+                account.apply(trade);
+            }
+        }
+        account
+    }
+
+    /// Fills a market order for `quantity` of `symbol` at the last streamed price, adjusted by
+    /// the configured slippage, and appends it to the trade log.
+    pub fn execute(
+        &mut self,
+        config: &PaperConfig,
+        tickers: &TickerMap,
+        symbol: &str,
+        side: Side,
+        quantity: f64,
+    ) -> Result<Trade, String> {
+        if !(quantity.is_finite() && quantity > 0.0) {
+            return Err("quantity must be a positive number".to_string());
+        }
+        let last = tickers
+            .get(symbol)
+            .map(|t| t.c as f64)
+            .filter(|price| *price > 0.0)
+            .ok_or_else(|| format!("no price for {} yet", symbol))?;
+        let price = last * (1.0 + side.sign() * config.slippage_bps / 10_000.0);
+        let trade = Trade {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            price,
+            fee: quantity * price * config.fee_bps / 10_000.0,
+        };
+        append(self.log.as_deref(), &trade).map_err(|err| format!("trade log: {}", err))?;
+        self.apply(trade.clone());
+        Ok(trade)
+    }
+
+    fn apply(&mut self, trade: Trade) {
+        let position = self.positions.entry(trade.symbol.clone()).or_default();
+        let delta = trade.side.sign() * trade.quantity;
+        if position.quantity == 0.0 || position.quantity.signum() == delta.signum() {
+            let size = position.quantity + delta;
+            position.average_price =
+                (position.quantity * position.average_price + delta * trade.price) / size;
+            position.quantity = size;
+        } else {
+            let closed = delta.abs().min(position.quantity.abs());
+            self.realized_pnl +=
+                closed * (trade.price - position.average_price) * position.quantity.signum();
+            position.quantity += delta;
+            // Flipping through zero opens the remainder at the fill price.
+            if position.quantity != 0.0 && position.quantity.signum() == delta.signum() {
+                position.average_price = trade.price;
+            }
+        }
+        if position.quantity.abs() < f64::EPSILON {
+            self.positions.remove(&trade.symbol);
+        }
+        self.realized_pnl -= trade.fee;
+        self.trades.push(trade);
+    }
+
+    pub fn unrealized_pnl(&self, tickers: &TickerMap) -> f64 {
+        self.positions
+            .iter()
+            .filter_map(|(symbol, position)| {
+                let price = tickers.get(symbol)?.c as f64;
+                Some(position.unrealized_pnl(price))
+            })
+            .sum()
+    }
+}
+
+fn append(path: Option<&Path>, trade: &Trade) -> io::Result<()> {
+    let path = path
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory available"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(
+        file,
+        "{}",
+        serde_json::to_string(trade).map_err(io::Error::other)?
+    )
+}
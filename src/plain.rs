@@ -0,0 +1,84 @@
+//! `--plain` mode: one formatted line per price change, written to the normal terminal buffer
+//! instead of the alternate screen, so it scrolls like `watch` output and can be logged.
+
+use crate::{
+    format::NumberFormat,
+    model::{HrTicker, SortColumn},
+    stream::TickerStream,
+};
+use std::{
+    collections::HashMap,
+    io::{self, IsTerminal, Write},
+};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const RESET: &str = "\x1b[0m";
+
+/// Whether stdout takes ANSI colors: a terminal other than `dumb`, with `NO_COLOR` unset.
+pub fn use_color() -> bool {
+    io::stdout().is_terminal()
+        && std::env::var_os("NO_COLOR").is_none()
+        && std::env::var("TERM").map_or(true, |term| term != "dumb")
+}
+
+/// `ticker` as a line such as `2024-06-10 12:00:01  BTCUSDT  64210.5  +1.23%`. With `color`,
+/// the price is green or red by its move from `previous` and the change by its sign.
+pub fn line(
+    ticker: &HrTicker,
+    previous: Option<f32>,
+    number_format: &NumberFormat,
+    color: bool,
+) -> String {
+    let paint = |text: String, up: Option<bool>| match (color, up) {
+        (true, Some(true)) => format!("{}{}{}", GREEN, text, RESET),
+        (true, Some(false)) => format!("{}{}{}", RED, text, RESET),
+        _ => text,
+    };
+    let price = number_format.column(SortColumn::Last, ticker.c as f64, |_| ticker.c.to_string());
+    let sign = if ticker.P > 0.0 { "+" } else { "" };
+    let change = number_format.column(SortColumn::PercentChange, ticker.P as f64, |p| {
+        format!("{:.2}", p)
+    });
+    let moved = previous
+        .filter(|previous| *previous != ticker.c)
+        .map(|previous| ticker.c > previous);
+    let signed = (ticker.P != 0.0).then_some(ticker.P > 0.0);
+    format!(
+        "{}  {:<12} {}  {}",
+        number_format.timestamp(ticker.E),
+        ticker.s,
+        paint(format!("{:>12}", price), moved),
+        paint(format!("{:>8}", format!("{}{}%", sign, change)), signed)
+    )
+}
+
+/// Writes a line for every price change from `stream`, keeping only `symbols` when the list is
+/// non-empty. Returns once the stream ends or stdout is closed.
+pub async fn run(
+    mut stream: TickerStream,
+    symbols: &[String],
+    number_format: &NumberFormat,
+    color: bool,
+) -> io::Result<()> {
+    let symbols: Vec<String> = symbols.iter().map(|s| s.to_uppercase()).collect();
+    let mut last: HashMap<String, f32> = HashMap::new();
+    while let Some(tickers) = stream.next().await {
+        let mut out = io::stdout().lock();
+        for ticker in tickers
+            .iter()
+            .filter(|t| symbols.is_empty() || symbols.contains(&t.s.to_uppercase()))
+        {
+            let previous = last.insert(ticker.s.clone(), ticker.c);
+            if previous == Some(ticker.c) {
+                continue;
+            }
+            writeln!(out, "{}", line(ticker, previous, number_format, color))?;
+        }
+        match out.flush() {
+            Err(err) if err.kind() == io::ErrorKind::BrokenPipe => return Ok(()),
+            result => result?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,587 @@
+//! WASM plugins: modules in the plugins directory that supply tickers or a pane, run in a
+//! sandbox that can only reach the network and storage their grant allows.
+//!
+//! A plugin exports its `memory` and `alloc(len: i32) -> i32`, which returns a buffer the host
+//! writes arguments and results into, and either or both of:
+//!
+//! - `tickers() -> i64`: a JSON array of `{"symbol", "price", "change", "high", "low", "volume"}`
+//!   objects, merged into the table on every refresh.
+//! - `pane() -> i64`: a JSON `{"title", "lines"}` object, shown in the plugins pane.
+//!
+//! Results are returned as `ptr << 32 | len` of a UTF-8 buffer in the plugin's memory. The plugin
+//! may import these functions from the `host` module:
+//!
+//! - `log(ptr, len)` reports a message.
+//! - `http_get(ptr, len) -> i64` fetches the URL and returns the body like an export returns
+//!   its result, or -1 when the URL's host is not granted or the request failed. Redirects are
+//!   followed only to granted hosts, and requests are bounded by `HTTP_TIMEOUT` and
+//!   `HTTP_BODY_BYTES`. A call into the plugin makes at most `HTTP_REQUESTS` of them, all
+//!   within `CALL_TIME`.
+//! - `storage_get(ptr, len) -> i64` returns the value stored under the key, or -1.
+//! - `storage_set(key_ptr, key_len, value_ptr, value_len) -> i32` stores a value, returning 0,
+//!   or -1 without the storage grant or when the values would outgrow `STORAGE_BYTES`.
+
+use crate::{
+    error::{self, Error},
+    exchange,
+    model::HrTicker,
+    net,
+};
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio::task::JoinHandle;
+use wasmi::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder,
+    TypedFunc,
+};
+
+/// Instructions a single call into a plugin may execute, so a runaway plugin cannot stall the
+/// app.
+const FUEL: u64 = 100_000_000;
+/// Linear memory a plugin may grow to.
+const MEMORY_BYTES: usize = 64 << 20;
+/// Longest an `http_get` request may take, so a slow server cannot hold a blocking thread.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Largest response body `http_get` returns.
+const HTTP_BODY_BYTES: usize = 4 << 20;
+/// Redirects `http_get` follows, each to a granted host.
+const HTTP_REDIRECTS: usize = 5;
+/// Requests `http_get` makes within a single call into a plugin.
+const HTTP_REQUESTS: usize = 4;
+/// Wall-clock time the host functions of a single call into a plugin may take. Fuel only
+/// meters the plugin's own instructions, so without it a plugin could wait on the network for
+/// as long as it has requests.
+const CALL_TIME: Duration = Duration::from_secs(20);
+/// Total size of the keys and values a plugin may store.
+const STORAGE_BYTES: usize = 1 << 20;
+
+/// `[plugins]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct PluginsConfig {
+    pub enabled: bool,
+    /// Directory the `.wasm` modules are loaded from; `plugins` in the platform config
+    /// directory by default.
+    pub dir: Option<PathBuf>,
+    pub refresh_secs: u64,
+    /// Capabilities per plugin, by file name without the extension. Plugins without a grant
+    /// get neither network nor storage.
+    pub grants: BTreeMap<String, Grant>,
+}
+
+impl Default for PluginsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            dir: None,
+            refresh_secs: 10,
+            grants: BTreeMap::new(),
+        }
+    }
+}
+
+impl PluginsConfig {
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir
+            .clone()
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("crypto_tui_ticker").join("plugins")))
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Grant {
+    /// Hosts `http_get` may fetch from.
+    pub http: Vec<String>,
+    /// Whether `storage_get` and `storage_set` keep values, in a file of the plugin's own.
+    pub storage: bool,
+}
+
+/// A ticker as a plugin reports it.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct PluginTicker {
+    pub symbol: String,
+    pub price: f32,
+    /// 24h percent change.
+    #[serde(default)]
+    pub change: f32,
+    #[serde(default)]
+    pub high: Option<f32>,
+    #[serde(default)]
+    pub low: Option<f32>,
+    /// 24h base volume.
+    #[serde(default)]
+    pub volume: f64,
+}
+
+impl PluginTicker {
+    fn into_ticker(self, time: u64) -> HrTicker {
+        exchange::normalized_ticker(
+            &self.symbol,
+            self.price,
+            self.change,
+            self.high.unwrap_or(self.price),
+            self.low.unwrap_or(self.price),
+            self.volume,
+            time,
+        )
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct PluginPane {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub lines: Vec<String>,
+}
+
+struct HostState {
+    name: String,
+    grant: Grant,
+    /// Client for `http_get`, following redirects only to granted hosts.
+    http: reqwest::Client,
+    /// File the stored values are kept in, with the storage grant.
+    storage_path: Option<PathBuf>,
+    storage: BTreeMap<String, String>,
+    limits: StoreLimits,
+    /// When the current call's `CALL_TIME` runs out, and the requests it has made.
+    deadline: Instant,
+    requests: usize,
+}
+
+impl HostState {
+    fn save_storage(&self) -> Result<(), Error> {
+        let Some(path) = &self.storage_path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let json =
+            serde_json::to_string(&self.storage).map_err(|err| Error::Plugin(err.to_string()))?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Fetches `url`, whose host must be granted, returning at most `HTTP_BODY_BYTES`.
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>, Error> {
+        let denied = || Error::Plugin(format!("{}: {} is not granted", self.name, url));
+        let url = reqwest::Url::parse(url).map_err(|_| denied())?;
+        if !may_fetch(&self.grant.http, &url) {
+            return Err(denied());
+        }
+        let mut response = self.http.get(url).send().await?.error_for_status()?;
+        let too_large = || Error::Plugin(format!("{}: response over the size limit", self.name));
+        if response
+            .content_length()
+            .is_some_and(|len| len > HTTP_BODY_BYTES as u64)
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > HTTP_BODY_BYTES {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
+
+/// Whether `url` is on one of the granted `hosts`.
+fn may_fetch(hosts: &[String], url: &reqwest::Url) -> bool {
+    matches!(url.scheme(), "http" | "https")
+        && url
+            .host_str()
+            .is_some_and(|host| hosts.iter().any(|allowed| allowed == host))
+}
+
+/// The `http_get` client of a plugin granted `hosts`: redirects are followed only while they
+/// stay on those hosts, so an open redirect on a granted host cannot reach any other.
+fn http_client(hosts: &[String]) -> reqwest::Client {
+    let hosts = hosts.to_vec();
+    let policy = reqwest::redirect::Policy::custom(move |attempt| {
+        if attempt.previous().len() >= HTTP_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if !may_fetch(&hosts, attempt.url()) {
+            attempt.error("redirect to a host that is not granted")
+        } else {
+            attempt.follow()
+        }
+    });
+    net::builder()
+        .redirect(policy)
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .unwrap_or_default()
+}
+
+/// A loaded plugin instance.
+pub struct Plugin {
+    pub name: String,
+    store: Store<HostState>,
+    memory: Memory,
+    tickers: Option<TypedFunc<(), i64>>,
+    pane: Option<TypedFunc<(), i64>>,
+}
+
+impl Plugin {
+    /// Instantiates `wasm` with the host functions of `grant`. Stored values are kept in
+    /// `storage_path` when the grant allows storage.
+    pub fn new(
+        name: &str,
+        wasm: &[u8],
+        grant: Grant,
+        storage_path: Option<PathBuf>,
+    ) -> Result<Self, Error> {
+        let error = |err: wasmi::Error| Error::Plugin(format!("{}: {}", name, err));
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm).map_err(error)?;
+        let storage_path = storage_path.filter(|_| grant.storage);
+        let storage = storage_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let state = HostState {
+            name: name.to_string(),
+            http: http_client(&grant.http),
+            grant,
+            storage_path,
+            storage,
+            limits: StoreLimitsBuilder::new().memory_size(MEMORY_BYTES).build(),
+            deadline: Instant::now() + CALL_TIME,
+            requests: 0,
+        };
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(FUEL).map_err(|err| error(err.into()))?;
+        let linker = host_functions(&engine).map_err(error)?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(error)?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| Error::Plugin(format!("{}: no memory export", name)))?;
+        instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(error)?;
+        let export = |name| instance.get_typed_func::<(), i64>(&store, name).ok();
+        Ok(Self {
+            name: name.to_string(),
+            tickers: export("tickers"),
+            pane: export("pane"),
+            store,
+            memory,
+        })
+    }
+
+    pub fn has_pane(&self) -> bool {
+        self.pane.is_some()
+    }
+
+    /// The plugin's tickers, or `None` when it is not a ticker source.
+    pub fn tickers(&mut self) -> Result<Option<Vec<PluginTicker>>, Error> {
+        let Some(tickers) = self.tickers else {
+            return Ok(None);
+        };
+        self.call(tickers).map(Some)
+    }
+
+    /// The plugin's pane, or `None` when it has none.
+    pub fn pane(&mut self) -> Result<Option<PluginPane>, Error> {
+        let Some(pane) = self.pane else {
+            return Ok(None);
+        };
+        self.call(pane).map(Some)
+    }
+
+    fn call<T: serde::de::DeserializeOwned>(
+        &mut self,
+        export: TypedFunc<(), i64>,
+    ) -> Result<T, Error> {
+        let error = |err: String| Error::Plugin(format!("{}: {}", self.name, err));
+        self.store
+            .set_fuel(FUEL)
+            .map_err(|err| error(err.to_string()))?;
+        let state = self.store.data_mut();
+        state.deadline = Instant::now() + CALL_TIME;
+        state.requests = 0;
+        let packed = export
+            .call(&mut self.store, ())
+            .map_err(|err| error(err.to_string()))?;
+        let bytes = read(&self.memory, &self.store, packed)
+            .ok_or_else(|| error("result out of bounds".to_string()))?;
+        serde_json::from_slice(&bytes).map_err(|err| error(err.to_string()))
+    }
+}
+
+/// Reads the buffer `packed` points at.
+fn read(memory: &Memory, store: impl wasmi::AsContext, packed: i64) -> Option<Vec<u8>> {
+    let (ptr, len) = ((packed >> 32) as u32 as usize, packed as u32 as usize);
+    let mut buffer = vec![0; len];
+    memory.read(store, ptr, &mut buffer).ok()?;
+    Some(buffer)
+}
+
+fn read_str(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Option<String> {
+    let memory = caller.get_export("memory").and_then(Extern::into_memory)?;
+    let packed = (i64::from(ptr as u32) << 32) | i64::from(len as u32);
+    String::from_utf8(read(&memory, caller, packed)?).ok()
+}
+
+/// Copies `bytes` into a buffer from the plugin's `alloc`, returning it packed, or -1.
+fn write(caller: &mut Caller<'_, HostState>, bytes: &[u8]) -> i64 {
+    let Ok(len) = i32::try_from(bytes.len()) else {
+        return -1;
+    };
+    let alloc = caller
+        .get_export("alloc")
+        .and_then(Extern::into_func)
+        .and_then(|alloc| alloc.typed::<i32, i32>(&*caller).ok());
+    let memory = caller.get_export("memory").and_then(Extern::into_memory);
+    let (Some(alloc), Some(memory)) = (alloc, memory) else {
+        return -1;
+    };
+    let Ok(ptr) = alloc.call(&mut *caller, len) else {
+        return -1;
+    };
+    if memory
+        .write(&mut *caller, ptr as u32 as usize, bytes)
+        .is_err()
+    {
+        return -1;
+    }
+    (i64::from(ptr as u32) << 32) | i64::from(len as u32)
+}
+
+fn host_functions(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::new(engine);
+    linker.func_wrap(
+        "host",
+        "log",
+        |caller: Caller<'_, HostState>, ptr: i32, len: i32| {
+            if let Some(message) = read_str(&caller, ptr, len) {
+                error::report(format!("plugin {}: {}", caller.data().name, message));
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "host",
+        "http_get",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+            let Some(url) = read_str(&caller, ptr, len) else {
+                return -1;
+            };
+            // Plugins run on a blocking thread of the runtime.
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                return -1;
+            };
+            let state = caller.data_mut();
+            let left = state.deadline.saturating_duration_since(Instant::now());
+            if state.requests >= HTTP_REQUESTS || left.is_zero() {
+                return -1;
+            }
+            state.requests += 1;
+            let body = runtime.block_on(tokio::time::timeout(left, caller.data().fetch(&url)));
+            match body {
+                Ok(Ok(body)) => write(&mut caller, &body),
+                _ => -1,
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "host",
+        "storage_get",
+        |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> i64 {
+            let value = read_str(&caller, ptr, len)
+                .filter(|_| caller.data().grant.storage)
+                .and_then(|key| caller.data().storage.get(&key).cloned());
+            match value {
+                Some(value) => write(&mut caller, value.as_bytes()),
+                None => -1,
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "host",
+        "storage_set",
+        |mut caller: Caller<'_, HostState>,
+         key_ptr: i32,
+         key_len: i32,
+         value_ptr: i32,
+         value_len: i32|
+         -> i32 {
+            if !caller.data().grant.storage {
+                return -1;
+            }
+            let key = read_str(&caller, key_ptr, key_len);
+            let value = read_str(&caller, value_ptr, value_len);
+            let (Some(key), Some(value)) = (key, value) else {
+                return -1;
+            };
+            let state = caller.data_mut();
+            let others: usize = state
+                .storage
+                .iter()
+                .filter(|(stored, _)| **stored != key)
+                .map(|(key, value)| key.len() + value.len())
+                .sum();
+            if others + key.len() + value.len() > STORAGE_BYTES {
+                error::report(format!(
+                    "plugin {} storage: over the size limit",
+                    state.name
+                ));
+                return -1;
+            }
+            state.storage.insert(key, value);
+            match state.save_storage() {
+                Ok(()) => 0,
+                Err(err) => {
+                    error::report(format!("plugin {} storage: {}", state.name, err));
+                    -1
+                }
+            }
+        },
+    )?;
+    Ok(linker)
+}
+
+/// Loads every `.wasm` module in `dir`, in file name order. Modules that fail to load are
+/// reported and skipped.
+pub fn load_dir(dir: &Path, grants: &BTreeMap<String, Grant>) -> Vec<Plugin> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    paths.sort();
+    let storage_dir = dirs::data_dir().map(|dir| dir.join("crypto_tui_ticker").join("plugins"));
+    let mut plugins = Vec::new();
+    for path in paths {
+        let Some(name) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+        let grant = grants.get(&name).cloned().unwrap_or_default();
+        let storage_path = storage_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.json", name)));
+        let plugin = fs::read(&path)
+            .map_err(Error::from)
+            .and_then(|wasm| Plugin::new(&name, &wasm, grant, storage_path));
+        match plugin {
+            Ok(plugin) => plugins.push(plugin),
+            Err(err) => error::report(err),
+        }
+    }
+    plugins
+}
+
+/// What one refresh of the plugins produced.
+#[derive(Default)]
+struct Output {
+    tickers: Vec<HrTicker>,
+    panes: Vec<(String, PluginPane)>,
+}
+
+fn refresh(plugins: &Mutex<Vec<Plugin>>) -> Output {
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64);
+    let mut output = Output::default();
+    for plugin in plugins.lock().unwrap().iter_mut() {
+        match plugin.tickers() {
+            Ok(tickers) => output.tickers.extend(
+                tickers
+                    .into_iter()
+                    .flatten()
+                    .map(|ticker| ticker.into_ticker(time)),
+            ),
+            Err(err) => error::report(err),
+        }
+        match plugin.pane() {
+            Ok(Some(pane)) => output.panes.push((plugin.name.clone(), pane)),
+            Ok(None) => {}
+            Err(err) => error::report(err),
+        }
+    }
+    output
+}
+
+/// The loaded plugins, refreshed on a blocking thread every `refresh_secs`.
+#[derive(Default)]
+pub struct Plugins {
+    /// Locked by the refresh while it runs.
+    instances: Arc<Mutex<Vec<Plugin>>>,
+    count: usize,
+    /// Latest pane of each plugin that has one, by plugin name.
+    pub panes: Vec<(String, PluginPane)>,
+    pending: Option<JoinHandle<Output>>,
+    loaded_at: Option<Instant>,
+}
+
+impl Plugins {
+    pub fn new(plugins: Vec<Plugin>) -> Self {
+        Self {
+            panes: plugins
+                .iter()
+                .filter(|plugin| plugin.has_pane())
+                .map(|plugin| (plugin.name.clone(), PluginPane::default()))
+                .collect(),
+            count: plugins.len(),
+            instances: Arc::new(Mutex::new(plugins)),
+            ..Self::default()
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Takes the results of a refresh without blocking, returning the plugins' tickers, and
+    /// starts a refresh when the last one is older than `refresh_secs`.
+    pub fn poll(&mut self, refresh_secs: u64) -> Vec<HrTicker> {
+        let mut tickers = Vec::new();
+        if let Some(result) = self.pending.as_mut().and_then(|p| p.now_or_never()) {
+            self.pending = None;
+            if let Ok(output) = result {
+                tickers = output.tickers;
+                self.panes = output.panes;
+            }
+        }
+        let refresh_every = Duration::from_secs(refresh_secs.max(1));
+        if self.pending.is_none()
+            && !self.is_empty()
+            && self
+                .loaded_at
+                .is_none_or(|at| at.elapsed() >= refresh_every)
+        {
+            self.loaded_at = Some(Instant::now());
+            let instances = self.instances.clone();
+            self.pending = Some(tokio::task::spawn_blocking(move || refresh(&instances)));
+        }
+        tickers
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{error, model::TickerMap};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+const PORTFOLIO_FILE: &str = "portfolio.toml";
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Holding {
+    pub symbol: String,
+    pub quantity: f64,
+    /// Average price paid per unit, in the symbol's quote currency.
+    pub cost_basis: f64,
+}
+
+/// Holdings kept in `<config dir>/crypto_tui_ticker/portfolio.toml`, separate from the settings.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct Portfolio {
+    pub holdings: Vec<Holding>,
+}
+
+pub struct Position<'a> {
+    pub holding: &'a Holding,
+    /// Last streamed price; `None` until the symbol has ticked.
+    pub price: Option<f64>,
+    pub market_value: f64,
+    pub unrealized_pnl: f64,
+    pub pnl_percent: f64,
+    /// Share of the portfolio's total market value, in percent.
+    pub allocation: f64,
+}
+
+pub struct Summary {
+    pub market_value: f64,
+    pub unrealized_pnl: f64,
+    pub pnl_percent: f64,
+}
+
+impl Portfolio {
+    /// Loads the holdings file in the config directory `dir`, falling back to an empty
+    /// portfolio when it is missing. A file that exists but cannot be parsed is reported rather
+    /// than silently ignored.
+    pub fn load(dir: Option<&Path>) -> Self {
+        let Some(path) = dir.map(|dir| dir.join(PORTFOLIO_FILE)) else {
+            return Self::default();
+        };
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        toml::from_str(&contents).unwrap_or_else(|err| {
+            error::report(format!("{}: {}", path.display(), err));
+            Self::default()
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.holdings.is_empty()
+    }
+
+    /// Values every holding at the latest prices. Holdings without a price yet are valued at
+    /// cost, so they neither move the P&L nor drop out of the allocation.
+    pub fn positions<'a>(&'a self, tickers: &TickerMap) -> Vec<Position<'a>> {
+        let mut positions: Vec<Position> = self
+            .holdings
+            .iter()
+            .map(|holding| {
+                let price = tickers.get(&holding.symbol).map(|t| t.c as f64);
+                let cost = holding.quantity * holding.cost_basis;
+                let market_value = price.map_or(cost, |price| holding.quantity * price);
+                let unrealized_pnl = market_value - cost;
+                Position {
+                    holding,
+                    price,
+                    market_value,
+                    unrealized_pnl,
+                    pnl_percent: percent(unrealized_pnl, cost),
+                    allocation: 0.0,
+                }
+            })
+            .collect();
+        let total: f64 = positions.iter().map(|p| p.market_value).sum();
+        for position in &mut positions {
+            position.allocation = percent(position.market_value, total);
+        }
+        positions
+    }
+
+    pub fn summary(&self, tickers: &TickerMap) -> Summary {
+        let positions = self.positions(tickers);
+        let market_value = positions.iter().map(|p| p.market_value).sum();
+        let cost = self
+            .holdings
+            .iter()
+            .map(|h| h.quantity * h.cost_basis)
+            .sum();
+        let unrealized_pnl = market_value - cost;
+        Summary {
+            market_value,
+            unrealized_pnl,
+            pnl_percent: percent(unrealized_pnl, cost),
+        }
+    }
+}
+
+fn percent(part: f64, whole: f64) -> f64 {
+    if whole == 0.0 {
+        0.0
+    } else {
+        part / whole * 100.0
+    }
+}
@@ -0,0 +1,184 @@
+//! Request weight budgets of the Binance REST APIs. Binance bans IPs that go over the weight
+//! allowed per minute, so every REST call to its APIs goes through [`send`], which queues a
+//! request until the current minute has room for it and keeps to the weight Binance reports.
+
+use crate::error::Error;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::{
+    sync::Mutex,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Header in which Binance reports the weight used by the IP in the current minute.
+const USED_WEIGHT_HEADER: &str = "x-mbx-used-weight-1m";
+
+/// Share of the limit requests are kept under, leaving room for other clients on the IP.
+const HEADROOM_PERCENT: u32 = 90;
+
+/// Wait after a 429 or 418 answer without a `Retry-After` header.
+const DEFAULT_BACKOFF: Duration = Duration::from_secs(60);
+
+const MINUTE_MS: u64 = 60_000;
+
+static BUDGETS: Mutex<[Budget; 2]> = Mutex::new([
+    Budget::new("Binance futures", "fapi.binance.com", 2400),
+    Budget::new("Binance spot", "api.binance.com", 6000),
+]);
+
+/// Weight used in the current minute of one API.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Budget {
+    pub name: &'static str,
+    pub host: &'static str,
+    /// Weight Binance allows per minute.
+    pub limit: u32,
+    pub used: u32,
+    /// Requests waiting for the next minute or the end of a backoff.
+    pub queued: usize,
+    /// Minute since the epoch `used` counts for.
+    minute: u64,
+    /// Time, in milliseconds since the epoch, until which Binance asked not to be sent requests.
+    retry_at: Option<u64>,
+}
+
+impl Budget {
+    pub const fn new(name: &'static str, host: &'static str, limit: u32) -> Self {
+        Self {
+            name,
+            host,
+            limit,
+            used: 0,
+            queued: 0,
+            minute: 0,
+            retry_at: None,
+        }
+    }
+
+    fn roll(&mut self, now_ms: u64) {
+        let minute = now_ms / MINUTE_MS;
+        if minute != self.minute {
+            self.minute = minute;
+            self.used = 0;
+        }
+    }
+
+    /// Takes `weight` from the budget at `now_ms`, or returns how long to wait before trying
+    /// again. A request heavier than the whole budget is let through at the start of a minute.
+    pub fn reserve(&mut self, weight: u32, now_ms: u64) -> Option<Duration> {
+        if let Some(wait) = self.backoff(now_ms) {
+            return Some(wait);
+        }
+        self.roll(now_ms);
+        let allowed = self.limit * HEADROOM_PERCENT / 100;
+        if self.used > 0 && self.used + weight > allowed {
+            return Some(Duration::from_millis(MINUTE_MS - now_ms % MINUTE_MS));
+        }
+        self.used += weight;
+        None
+    }
+
+    /// Takes in the weight Binance reported as used at `now_ms`, which counts other clients on
+    /// the IP too.
+    pub fn record_used(&mut self, used: u32, now_ms: u64) {
+        self.roll(now_ms);
+        self.used = self.used.max(used);
+    }
+
+    /// Stops requests for `wait` from `now_ms`, after Binance answered 429 or 418.
+    pub fn back_off(&mut self, wait: Duration, now_ms: u64) {
+        self.retry_at = Some(now_ms + wait.as_millis() as u64);
+    }
+
+    /// How long requests are still held back by a backoff at `now_ms`.
+    pub fn backoff(&self, now_ms: u64) -> Option<Duration> {
+        self.retry_at
+            .filter(|retry_at| *retry_at > now_ms)
+            .map(|retry_at| Duration::from_millis(retry_at - now_ms))
+    }
+
+    /// Weight left in the minute of `now_ms`.
+    pub fn remaining(&self, now_ms: u64) -> u32 {
+        if now_ms / MINUTE_MS == self.minute {
+            self.limit.saturating_sub(self.used)
+        } else {
+            self.limit
+        }
+    }
+}
+
+/// Milliseconds since the epoch, the clock budgets are kept by.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+/// The budgets of the Binance APIs, for the status pane.
+pub fn budgets() -> Vec<Budget> {
+    BUDGETS.lock().unwrap().to_vec()
+}
+
+fn with_budget<T>(host: &str, f: impl FnOnce(&mut Budget) -> T) -> Option<T> {
+    BUDGETS
+        .lock()
+        .unwrap()
+        .iter_mut()
+        .find(|budget| budget.host == host)
+        .map(f)
+}
+
+/// Counts a request in its budget's queue while it waits, including when it is dropped.
+struct Queued(String);
+
+impl Queued {
+    fn new(host: &str) -> Self {
+        with_budget(host, |budget| budget.queued += 1);
+        Self(host.to_string())
+    }
+}
+
+impl Drop for Queued {
+    fn drop(&mut self) {
+        with_budget(&self.0, |budget| budget.queued -= 1);
+    }
+}
+
+/// Sends `request`, of `weight` in Binance's request weights, once its API's budget has room
+/// for it. Requests to other hosts are sent straight away.
+pub async fn send(request: RequestBuilder, weight: u32) -> Result<Response, Error> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+    let mut queued = None;
+    while let Some(wait) = with_budget(&host, |budget| budget.reserve(weight, now_ms())).flatten() {
+        queued.get_or_insert_with(|| Queued::new(&host));
+        tokio::time::sleep(wait).await;
+    }
+    drop(queued);
+
+    let response = client.execute(request).await?;
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse::<u64>().ok())
+    };
+    let used = header(USED_WEIGHT_HEADER);
+    let retry_after = header(reqwest::header::RETRY_AFTER.as_str());
+    let banned = matches!(
+        response.status(),
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::IM_A_TEAPOT
+    );
+    with_budget(&host, |budget| {
+        let now = now_ms();
+        if let Some(used) = used {
+            budget.record_used(used as u32, now);
+        }
+        if banned {
+            let wait = retry_after.map_or(DEFAULT_BACKOFF, Duration::from_secs);
+            budget.back_off(wait, now);
+        }
+    });
+    Ok(response)
+}
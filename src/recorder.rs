@@ -0,0 +1,305 @@
+use crate::{
+    chart::Candle,
+    chart::INTERVAL,
+    error::{self, Error},
+    model::{HrTicker, TickerMap},
+};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tokio_util::sync::CancellationToken;
+
+const SCHEMA_VERSION: i32 = 2;
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// `[recorder]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct RecorderConfig {
+    pub enabled: bool,
+    /// Defaults to `recordings.sqlite` in the platform data directory.
+    pub path: Option<PathBuf>,
+    /// Seconds between snapshots of the ticker store.
+    pub interval_secs: u64,
+    /// Raw snapshots older than this are downsampled to 1 minute bars.
+    pub raw_retention_days: u32,
+    /// Bars and stored klines older than this are deleted.
+    pub bar_retention_days: u32,
+    /// Minutes between background compaction runs.
+    pub compaction_interval_mins: u64,
+}
+
+impl Default for RecorderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            interval_secs: 5,
+            raw_retention_days: 7,
+            bar_retention_days: 90,
+            compaction_interval_mins: 60,
+        }
+    }
+}
+
+impl RecorderConfig {
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.clone().or_else(|| {
+            dirs::data_dir().map(|dir| dir.join("crypto_tui_ticker").join("recordings.sqlite"))
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PruneStats {
+    pub ticks_compacted: usize,
+    pub bars_deleted: usize,
+    pub klines_deleted: usize,
+}
+
+pub struct Recorder {
+    conn: Connection,
+}
+
+impl Recorder {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        let recorder = Self { conn };
+        recorder.migrate()?;
+        Ok(recorder)
+    }
+
+    fn migrate(&self) -> rusqlite::Result<()> {
+        let version: i32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version < 1 {
+            self.conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS ticks (
+                    symbol TEXT NOT NULL,
+                    ts INTEGER NOT NULL,
+                    price REAL NOT NULL,
+                    volume REAL NOT NULL
+                );
+                CREATE INDEX IF NOT EXISTS ticks_ts ON ticks (ts);
+                CREATE INDEX IF NOT EXISTS ticks_symbol_ts ON ticks (symbol, ts);
+                CREATE TABLE IF NOT EXISTS bars_1m (
+                    symbol TEXT NOT NULL,
+                    ts INTEGER NOT NULL,
+                    open REAL NOT NULL,
+                    high REAL NOT NULL,
+                    low REAL NOT NULL,
+                    close REAL NOT NULL,
+                    samples INTEGER NOT NULL,
+                    PRIMARY KEY (symbol, ts)
+                );",
+            )?;
+        }
+        if version < 2 {
+            self.conn.execute_batch(
+                "CREATE TABLE IF NOT EXISTS klines (
+                    symbol TEXT NOT NULL,
+                    interval TEXT NOT NULL,
+                    open_time INTEGER NOT NULL,
+                    open REAL NOT NULL,
+                    high REAL NOT NULL,
+                    low REAL NOT NULL,
+                    close REAL NOT NULL,
+                    volume REAL NOT NULL,
+                    PRIMARY KEY (symbol, interval, open_time)
+                );",
+            )?;
+        }
+        self.conn
+            .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))
+    }
+
+    pub fn record(&mut self, tickers: &[HrTicker]) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT INTO ticks (symbol, ts, price, volume) VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            for ticker in tickers {
+                let volume = ticker.v.parse::<f64>().unwrap_or(0.0);
+                insert.execute(params![ticker.s, ticker.E as i64, ticker.c as f64, volume])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// Stores klines fetched from the exchange, replacing earlier copies of the same candles
+    /// (the most recent one is usually still open when fetched).
+    pub fn store_klines(
+        &mut self,
+        symbol: &str,
+        interval: &str,
+        candles: &[Candle],
+    ) -> rusqlite::Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut insert = tx.prepare_cached(
+                "INSERT OR REPLACE INTO klines
+                    (symbol, interval, open_time, open, high, low, close, volume)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for c in candles {
+                insert.execute(params![
+                    symbol,
+                    interval,
+                    c.open_time as i64,
+                    c.open,
+                    c.high,
+                    c.low,
+                    c.close,
+                    c.volume
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
+    /// The most recent `limit` one-minute candles of `symbol`, oldest first. Stored klines
+    /// take precedence over bars compacted from ticks, which take precedence over raw ticks
+    /// aggregated on the fly.
+    pub fn candles(&self, symbol: &str, limit: usize) -> rusqlite::Result<Vec<Candle>> {
+        // SQLite fills the bare columns from the row that produced MIN(priority).
+        let mut query = self.conn.prepare(
+            "SELECT ts, open, high, low, close, volume, MIN(priority) FROM (
+                SELECT open_time AS ts, open, high, low, close, volume, 0 AS priority
+                FROM klines WHERE symbol = ?1 AND interval = ?2
+                UNION ALL
+                SELECT ts, open, high, low, close, 0, 1 FROM bars_1m WHERE symbol = ?1
+                UNION ALL
+                SELECT g.bucket,
+                       (SELECT price FROM ticks t WHERE t.symbol = ?1 AND t.ts = g.first_ts LIMIT 1),
+                       g.high, g.low,
+                       (SELECT price FROM ticks t WHERE t.symbol = ?1 AND t.ts = g.last_ts LIMIT 1),
+                       0, 2
+                FROM (SELECT ts / 60000 * 60000 AS bucket, MIN(ts) AS first_ts,
+                             MAX(ts) AS last_ts, MAX(price) AS high, MIN(price) AS low
+                      FROM ticks WHERE symbol = ?1 GROUP BY bucket) g
+             )
+             GROUP BY ts ORDER BY ts DESC LIMIT ?3",
+        )?;
+        let mut candles = query
+            .query_map(params![symbol, INTERVAL, limit as i64], |row| {
+                Ok(Candle {
+                    open_time: row.get::<_, i64>(0)? as u64,
+                    open: row.get(1)?,
+                    high: row.get(2)?,
+                    low: row.get(3)?,
+                    close: row.get(4)?,
+                    volume: row.get(5)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        candles.reverse();
+        Ok(candles)
+    }
+
+    /// Downsamples raw ticks past the raw retention window into 1 minute bars and drops bars
+    /// and klines past the bar retention window.
+    pub fn compact(&mut self, config: &RecorderConfig) -> rusqlite::Result<PruneStats> {
+        let now = now_ms();
+        let raw_cutoff = now - config.raw_retention_days as i64 * DAY_MS;
+        let bar_cutoff = now - config.bar_retention_days as i64 * DAY_MS;
+
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT INTO bars_1m (symbol, ts, open, high, low, close, samples)
+             SELECT g.symbol, g.bucket,
+                    (SELECT price FROM ticks t WHERE t.symbol = g.symbol AND t.ts = g.first_ts LIMIT 1),
+                    g.high, g.low,
+                    (SELECT price FROM ticks t WHERE t.symbol = g.symbol AND t.ts = g.last_ts LIMIT 1),
+                    g.samples
+             FROM (SELECT symbol, ts / 60000 * 60000 AS bucket, MIN(ts) AS first_ts,
+                          MAX(ts) AS last_ts, MAX(price) AS high, MIN(price) AS low,
+                          COUNT(*) AS samples
+                   FROM ticks WHERE ts < ?1 GROUP BY symbol, bucket) g
+             WHERE true
+             ON CONFLICT (symbol, ts) DO UPDATE SET
+                high = max(high, excluded.high),
+                low = min(low, excluded.low),
+                close = excluded.close,
+                samples = samples + excluded.samples",
+            params![raw_cutoff],
+        )?;
+        let ticks_compacted = tx.execute("DELETE FROM ticks WHERE ts < ?1", params![raw_cutoff])?;
+        let bars_deleted = tx.execute("DELETE FROM bars_1m WHERE ts < ?1", params![bar_cutoff])?;
+        let klines_deleted = tx.execute(
+            "DELETE FROM klines WHERE open_time < ?1",
+            params![bar_cutoff],
+        )?;
+        tx.commit()?;
+
+        Ok(PruneStats {
+            ticks_compacted,
+            bars_deleted,
+            klines_deleted,
+        })
+    }
+
+    /// Returns freed pages to the file system.
+    pub fn vacuum(&self) -> rusqlite::Result<()> {
+        self.conn.execute_batch("VACUUM")
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Records snapshots of `tickers` on a background thread and compacts the database
+/// periodically. Once `token` is cancelled a last snapshot is written and the thread ends.
+pub fn spawn(
+    config: RecorderConfig,
+    tickers: Arc<Mutex<TickerMap>>,
+    token: CancellationToken,
+) -> thread::JoinHandle<()> {
+    let runtime = tokio::runtime::Handle::current();
+    thread::spawn(move || {
+        let Some(path) = config.path() else {
+            return;
+        };
+        let mut recorder = match Recorder::open(&path) {
+            Ok(recorder) => recorder,
+            Err(err) => {
+                error::report(format!("recorder {}: {}", path.display(), err));
+                return;
+            }
+        };
+        let interval = Duration::from_secs(config.interval_secs.max(1));
+        let compaction_interval = Duration::from_secs(config.compaction_interval_mins * 60);
+        let mut last_compaction = Instant::now();
+        loop {
+            let stopping = runtime
+                .block_on(tokio::time::timeout(interval, token.cancelled()))
+                .is_ok();
+            let snapshot: Vec<HrTicker> = tickers.lock().unwrap().values().cloned().collect();
+            if let Err(err) = recorder.record(&snapshot) {
+                error::report(format!("recorder: {}", err));
+            }
+            if stopping {
+                break;
+            }
+            if last_compaction.elapsed() >= compaction_interval {
+                if let Err(err) = recorder.compact(&config) {
+                    error::report(format!("recorder compaction: {}", err));
+                }
+                last_compaction = Instant::now();
+            }
+        }
+    })
+}
@@ -0,0 +1,83 @@
+//! Recording of the ticker stream to NDJSON and playback through the same update channel.
+//!
+//! Each line holds one batch as it was sent to the table, with the local receive time:
+//! `{"ts": 1718000000000, "tickers": [...]}`. Batches are stored after normalization, so
+//! recordings from any exchange replay the same way.
+
+use crate::{metrics, model::HrTicker};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc;
+
+#[derive(Deserialize, Serialize)]
+struct Frame {
+    /// Milliseconds since the epoch when the batch was received.
+    ts: u64,
+    tickers: Vec<HrTicker>,
+}
+
+/// Parses a replay speed such as `4x`, `0.5` or `2X`.
+pub fn parse_speed(value: &str) -> Result<f64, String> {
+    let number = value.trim_end_matches(['x', 'X']);
+    match number.parse::<f64>() {
+        Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(speed),
+        _ => Err(format!("invalid speed {:?}, expected e.g. 4x", value)),
+    }
+}
+
+/// Opens `path` for appending a recording.
+pub fn create(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Forwards batches from `rx` to `tx`, appending each one to `file`.
+pub async fn record(
+    file: File,
+    mut rx: mpsc::Receiver<Vec<HrTicker>>,
+    tx: mpsc::Sender<Vec<HrTicker>>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(file);
+    while let Some(tickers) = rx.recv().await {
+        let frame = Frame {
+            ts: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_millis() as u64),
+            tickers,
+        };
+        serde_json::to_writer(&mut writer, &frame)?;
+        writer.write_all(b"\n")?;
+        // Flushed per batch so an interrupted session still leaves a usable file.
+        writer.flush()?;
+        if tx.send(frame.tickers).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Sends the batches recorded in `file` to `tx`, keeping their original spacing divided by
+/// `speed`. Unreadable lines are skipped.
+pub async fn replay(file: File, speed: f64, tx: mpsc::Sender<Vec<HrTicker>>) -> io::Result<()> {
+    let reader = BufReader::new(file);
+    let mut previous_ts = None;
+    for line in reader.lines() {
+        let Ok(frame) = serde_json::from_str::<Frame>(&line?) else {
+            metrics::increment(&metrics::PARSE_ERRORS);
+            continue;
+        };
+        if let Some(previous) = previous_ts {
+            let gap = Duration::from_millis(frame.ts.saturating_sub(previous));
+            tokio::time::sleep(gap.div_f64(speed)).await;
+        }
+        previous_ts = Some(frame.ts);
+        if tx.send(frame.tickers).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
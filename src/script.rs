@@ -0,0 +1,202 @@
+//! Rhai scripting hooks: user scripts can raise their own alerts from ticker updates, veto the
+//! alerts of the rules, and fill a table column.
+//!
+//! A script defines any of these functions:
+//!
+//! - `on_ticker_update(ticker)` runs for every ticker update; returning a string raises an alert
+//!   with that name for the ticker's symbol. Like a rule, it fires once when the name is first
+//!   returned and re-arms when the hook returns anything else.
+//! - `on_alert(alert)` runs for every alert a rule fires; returning `false` drops it.
+//! - `custom_column(symbol)` fills the script column, refreshed on each update of the symbol.
+//!
+//! Functions share state through `this`, an object map kept for the session.
+
+use crate::{
+    alerts::{sink::Sink, Alert},
+    error::{self, Error},
+    model::HrTicker,
+};
+use rhai::{CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::PathBuf,
+};
+
+/// Operations a single hook call may take, so a runaway loop cannot stall the stream.
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+const ON_TICKER_UPDATE: &str = "on_ticker_update";
+const ON_ALERT: &str = "on_alert";
+const CUSTOM_COLUMN: &str = "custom_column";
+
+/// `[script]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ScriptConfig {
+    /// Rhai script to load at startup.
+    pub path: Option<PathBuf>,
+    /// Header of the column `custom_column` fills.
+    pub column_title: String,
+    /// Where alerts raised by `on_ticker_update` are delivered.
+    pub sinks: Vec<Sink>,
+}
+
+impl Default for ScriptConfig {
+    fn default() -> Self {
+        Self {
+            path: None,
+            column_title: "Script".to_string(),
+            sinks: Vec::new(),
+        }
+    }
+}
+
+/// A compiled script and the state its hooks keep.
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    /// Bound to `this` in every hook.
+    state: Dynamic,
+    /// Hooks the script defines and that have not failed.
+    hooks: HashSet<&'static str>,
+    /// Last `custom_column` value per symbol.
+    column: HashMap<String, String>,
+    /// Alert `on_ticker_update` last returned per symbol.
+    raised: HashMap<String, String>,
+    pub sinks: Vec<Sink>,
+}
+
+impl Script {
+    /// Loads the script of `config`, if one is set.
+    pub fn load(config: &ScriptConfig) -> Result<Option<Self>, Error> {
+        let Some(path) = &config.path else {
+            return Ok(None);
+        };
+        let source = fs::read_to_string(path)
+            .map_err(|err| Error::Config(format!("script {}: {}", path.display(), err)))?;
+        let mut script = Self::compile(&source)
+            .map_err(|err| Error::Config(format!("script {}: {}", path.display(), err)))?;
+        script.sinks = config.sinks.clone();
+        Ok(Some(script))
+    }
+
+    /// Compiles `source` and runs its top-level statements once.
+    pub fn compile(source: &str) -> Result<Self, Error> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let ast = engine
+            .compile(source)
+            .map_err(|err| Error::Other(err.to_string()))?;
+        engine
+            .run_ast(&ast)
+            .map_err(|err| Error::Other(err.to_string()))?;
+        let hooks = [ON_TICKER_UPDATE, ON_ALERT, CUSTOM_COLUMN]
+            .into_iter()
+            .filter(|hook| {
+                ast.iter_functions()
+                    .any(|f| f.name == *hook && f.params.len() == 1)
+            })
+            .collect();
+        Ok(Self {
+            engine,
+            ast,
+            state: Dynamic::from_map(Map::new()),
+            hooks,
+            column: HashMap::new(),
+            raised: HashMap::new(),
+            sinks: Vec::new(),
+        })
+    }
+
+    pub fn has_column(&self) -> bool {
+        self.hooks.contains(CUSTOM_COLUMN) || !self.column.is_empty()
+    }
+
+    /// The script column of `symbol`, once it has been updated.
+    pub fn column(&self, symbol: &str) -> Option<&str> {
+        self.column.get(symbol).map(String::as_str)
+    }
+
+    /// Runs `on_ticker_update`, then refreshes the symbol's column. Returns the name of the
+    /// alert the script newly raised, if any.
+    pub fn on_ticker_update(&mut self, ticker: &HrTicker) -> Option<String> {
+        let raised = if self.hooks.contains(ON_TICKER_UPDATE) {
+            self.call(ON_TICKER_UPDATE, Dynamic::from_map(ticker_map(ticker)))
+                .and_then(|value| value.into_string().ok())
+                .filter(|name| !name.is_empty())
+        } else {
+            None
+        };
+        if self.hooks.contains(CUSTOM_COLUMN) {
+            if let Some(value) = self.call(CUSTOM_COLUMN, Dynamic::from(ticker.s.clone())) {
+                if value.is_unit() {
+                    self.column.remove(&ticker.s);
+                } else {
+                    self.column.insert(ticker.s.clone(), value.to_string());
+                }
+            }
+        }
+        let Some(name) = raised else {
+            self.raised.remove(&ticker.s);
+            return None;
+        };
+        let previous = self.raised.insert(ticker.s.clone(), name.clone());
+        (previous.as_ref() != Some(&name)).then_some(name)
+    }
+
+    /// Whether the script lets `alert` through; scripts without `on_alert` keep every alert.
+    pub fn on_alert(&mut self, alert: &Alert) -> bool {
+        if !self.hooks.contains(ON_ALERT) {
+            return true;
+        }
+        let mut map = Map::new();
+        map.insert("rule".into(), alert.rule.clone().into());
+        map.insert("symbol".into(), alert.symbol.clone().into());
+        map.insert("price".into(), (alert.price as f64).into());
+        map.insert("time".into(), (alert.time as i64).into());
+        self.call(ON_ALERT, Dynamic::from_map(map))
+            .and_then(|keep| keep.as_bool().ok())
+            .unwrap_or(true)
+    }
+
+    /// Calls `hook`. A hook that fails is reported and not called again.
+    fn call(&mut self, hook: &'static str, arg: Dynamic) -> Option<Dynamic> {
+        let options = CallFnOptions::new()
+            .eval_ast(false)
+            .bind_this_ptr(&mut self.state);
+        let result = self.engine.call_fn_with_options::<Dynamic>(
+            options,
+            &mut Scope::new(),
+            &self.ast,
+            hook,
+            (arg,),
+        );
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.hooks.remove(hook);
+                error::report(format!("script {}: {}", hook, err));
+                None
+            }
+        }
+    }
+}
+
+/// The fields of `ticker` a script sees, as numbers where they are numeric.
+fn ticker_map(ticker: &HrTicker) -> Map {
+    let number = |text: &str| text.parse::<f64>().unwrap_or(0.0);
+    let mut map = Map::new();
+    map.insert("symbol".into(), ticker.s.clone().into());
+    map.insert("price".into(), (ticker.c as f64).into());
+    map.insert("change".into(), (ticker.P as f64).into());
+    map.insert("open".into(), (ticker.o as f64).into());
+    map.insert("high".into(), (ticker.h as f64).into());
+    map.insert("low".into(), (ticker.l as f64).into());
+    map.insert("volume".into(), number(&ticker.v).into());
+    map.insert("quote_volume".into(), number(&ticker.q).into());
+    map.insert("trades".into(), (ticker.n as i64).into());
+    map.insert("time".into(), (ticker.E as i64).into());
+    map
+}
@@ -0,0 +1,50 @@
+//! The state of the UI when the app was last quit, saved so the next start reopens it as it
+//! was left.
+
+use crate::{app::View, model::SortColumn, workspace::PaneKind};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path, path::PathBuf};
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Session {
+    /// Symbol selected in the table, and how many rows below the top of the table it was.
+    pub selected: Option<String>,
+    pub selected_row: usize,
+    /// Columns scrolled past to the right.
+    pub column_offset: usize,
+    pub sort_column: Option<SortColumn>,
+    pub theme: Option<String>,
+    pub view: View,
+    /// Workspace shown, 0 being the table screen.
+    pub workspace: usize,
+    /// Panes open on the table screen.
+    pub panes: Vec<PaneKind>,
+    /// Watchlist tab shown, by name; the tab of every symbol when unset.
+    pub watchlist: Option<String>,
+    pub tag_filter: Option<String>,
+    pub watchlist_only: bool,
+    pub group_by_base: bool,
+}
+
+impl Session {
+    /// `session.json` in the platform data directory.
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join("crypto_tui_ticker").join("session.json"))
+    }
+
+    /// Reads the session saved at `path`; a missing or unreadable file is the default session.
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
@@ -0,0 +1,199 @@
+//! First-run setup: a few questions asked on the terminal before the TUI starts, when there is
+//! no config file yet, answered into a commented `config.toml`.
+
+use crate::{error::Error, exchange::Exchange, theme::Palette, watchlist};
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+/// Name of the watchlist tab the symbols picked during setup are listed in.
+pub const WATCHLIST_NAME: &str = "Watchlist";
+
+/// What was picked during setup.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answers {
+    pub exchange: Exchange,
+    /// Quote assets the table is limited to; every market is listed when empty.
+    pub quotes: Vec<String>,
+    pub theme: String,
+    /// Symbols of the first watchlist tab, in the exchange's format.
+    pub watchlist: Vec<String>,
+}
+
+impl Default for Answers {
+    fn default() -> Self {
+        Self {
+            exchange: Exchange::Binance,
+            quotes: Vec::new(),
+            theme: crate::theme::ThemeConfig::default().name,
+            watchlist: Vec::new(),
+        }
+    }
+}
+
+/// Reads a line after writing `prompt`; `None` once `input` is closed.
+fn prompt(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+) -> io::Result<Option<String>> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        writeln!(output)?;
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+/// Asks for one of `choices`, by number or name, until one is given. An empty answer or a
+/// closed `input` picks the choice at `default`.
+fn choose(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    question: &str,
+    choices: &[String],
+    default: usize,
+) -> io::Result<usize> {
+    writeln!(output, "{}", question)?;
+    for (i, choice) in choices.iter().enumerate() {
+        writeln!(output, "  {}) {}", i + 1, choice)?;
+    }
+    loop {
+        let Some(answer) = prompt(input, output, &format!("Choice [{}]: ", default + 1))? else {
+            return Ok(default);
+        };
+        if answer.is_empty() {
+            return Ok(default);
+        }
+        let picked = match answer.parse::<usize>() {
+            Ok(n) => n.checked_sub(1).filter(|i| *i < choices.len()),
+            Err(_) => choices
+                .iter()
+                .position(|choice| choice.eq_ignore_ascii_case(&answer)),
+        };
+        match picked {
+            Some(i) => return Ok(i),
+            None => writeln!(output, "Pick a number from 1 to {}.", choices.len())?,
+        }
+    }
+}
+
+/// Asks the setup questions on `output`, reading the answers from `input`. Questions left
+/// unanswered keep their defaults.
+pub fn ask(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<Answers> {
+    let mut answers = Answers::default();
+    writeln!(
+        output,
+        "No config file yet; answer a few questions to write one, or press Enter to keep the \
+         defaults."
+    )?;
+    writeln!(output)?;
+
+    let exchanges: Vec<String> = Exchange::ALL
+        .iter()
+        .map(|exchange| format!("{} ({})", exchange.name(), exchange.market().name()))
+        .collect();
+    let default = Exchange::ALL.iter().position(|e| *e == answers.exchange);
+    let picked = choose(
+        input,
+        output,
+        "Exchange and market:",
+        &exchanges,
+        default.unwrap_or(0),
+    )?;
+    answers.exchange = Exchange::ALL[picked];
+
+    let quotes = prompt(
+        input,
+        output,
+        "Quote assets to list, comma-separated, e.g. USDT,USDC [all]: ",
+    )?;
+    answers.quotes = quotes
+        .unwrap_or_default()
+        .split(',')
+        .map(|quote| quote.trim().to_uppercase())
+        .filter(|quote| !quote.is_empty())
+        .collect();
+
+    let themes: Vec<String> = Palette::builtin()
+        .into_iter()
+        .map(|palette| palette.name)
+        .collect();
+    let default = themes.iter().position(|name| *name == answers.theme);
+    let picked = choose(input, output, "Theme:", &themes, default.unwrap_or(0))?;
+    answers.theme = themes[picked].clone();
+
+    let symbols = prompt(
+        input,
+        output,
+        "Symbols to watch, comma-separated, e.g. BTCUSDT,ETHUSDT [none]: ",
+    )?;
+    let (symbols, unmatched) = watchlist::parse(&symbols.unwrap_or_default(), answers.exchange);
+    if !unmatched.is_empty() {
+        writeln!(
+            output,
+            "Skipped {}: no known quote asset.",
+            unmatched.join(", ")
+        )?;
+    }
+    answers.watchlist = symbols;
+    Ok(answers)
+}
+
+/// `value` as a TOML value, e.g. a quoted string or an array.
+fn toml_value(value: impl serde::Serialize) -> String {
+    toml::Value::try_from(value).map_or_else(|_| "\"\"".to_string(), |value| value.to_string())
+}
+
+/// The config file for `answers`, with a comment on every key.
+pub fn config_toml(answers: &Answers) -> String {
+    let exchanges: Vec<String> = Exchange::ALL.iter().map(toml_value).collect();
+    let mut toml = format!(
+        "# crypto_tui_ticker settings, written by the first-run setup. Keys that are not listed\n\
+         # keep their defaults; the README describes every key.\n\
+         \n\
+         # Exchange whose tickers fill the table; --exchange takes precedence. One of\n\
+         # {}.\n\
+         exchange = {}\n\
+         \n\
+         # Quote assets the table is limited to, e.g. [\"USDT\", \"USDC\"]; every market is\n\
+         # listed when empty.\n\
+         quotes = {}\n\
+         \n\
+         [theme]\n\
+         # Palette to start with; h and l cycle through the others.\n\
+         name = {}\n",
+        exchanges.join(", "),
+        toml_value(answers.exchange),
+        toml_value(&answers.quotes),
+        toml_value(&answers.theme),
+    );
+    if !answers.watchlist.is_empty() {
+        toml.push_str(&format!(
+            "\n\
+             # Watchlist tabs after the tab listing every symbol, switched with [ and ].\n\
+             [[watchlists]]\n\
+             name = {}\n\
+             symbols = {}\n",
+            toml_value(WATCHLIST_NAME),
+            toml_value(&answers.watchlist),
+        ));
+    }
+    toml
+}
+
+/// Asks the setup questions on the terminal and writes the answers to `path`, creating its
+/// directory.
+pub fn run(path: &Path) -> Result<Answers, Error> {
+    let answers = ask(&mut io::stdin().lock(), &mut io::stdout())?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, config_toml(&answers))?;
+    println!("Wrote {}", path.display());
+    Ok(answers)
+}
@@ -0,0 +1,121 @@
+//! Read-only snapshot of the ticker store in a memory-mapped file.
+//!
+//! All integers and floats are little-endian. The file starts with a 64 byte header:
+//!
+//! | offset | type     | field                                   |
+//! |--------|----------|-----------------------------------------|
+//! | 0      | [u8; 8]  | magic `CTTSHM01`                        |
+//! | 8      | u64      | sequence (odd while a write is running) |
+//! | 16     | u32      | record size in bytes                    |
+//! | 20     | u32      | record count                            |
+//! | 24     | u32      | record capacity                         |
+//! | 32     | u64      | update time, ms since the Unix epoch    |
+//!
+//! followed by `capacity` records of 64 bytes each:
+//!
+//! | offset | type     | field                            |
+//! |--------|----------|----------------------------------|
+//! | 0      | [u8; 16] | symbol, NUL padded               |
+//! | 16     | f64      | last price                       |
+//! | 24     | f64      | 24h percent change               |
+//! | 32     | f64      | open price                       |
+//! | 40     | f64      | high price                       |
+//! | 48     | f64      | low price                        |
+//! | 56     | u64      | exchange event time, ms          |
+//!
+//! Readers copy the header and records, then re-read the sequence; if it is odd or has changed
+//! the copy raced a write and must be retried.
+
+use crate::model::{HrTicker, TickerMap};
+use memmap2::MmapMut;
+use std::{
+    fs::OpenOptions,
+    io,
+    path::Path,
+    sync::{
+        atomic::{fence, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+const MAGIC: &[u8; 8] = b"CTTSHM01";
+const HEADER_SIZE: usize = 64;
+const RECORD_SIZE: usize = 64;
+const SYMBOL_SIZE: usize = 16;
+const CAPACITY: usize = 2048;
+const SEQUENCE_OFFSET: usize = 8;
+
+pub struct ShmExport {
+    mmap: MmapMut,
+}
+
+impl ShmExport {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        file.set_len((HEADER_SIZE + CAPACITY * RECORD_SIZE) as u64)?;
+        // SAFETY: the mapping is only written through `self`; other processes are expected to
+        // map the file read-only and follow the sequence protocol described above.
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        mmap[..HEADER_SIZE].fill(0);
+        mmap[..8].copy_from_slice(MAGIC);
+        mmap[16..20].copy_from_slice(&(RECORD_SIZE as u32).to_le_bytes());
+        mmap[24..28].copy_from_slice(&(CAPACITY as u32).to_le_bytes());
+        Ok(Self { mmap })
+    }
+
+    fn sequence(&self) -> &AtomicU64 {
+        // SAFETY: the mapping is page aligned, so the 8 byte offset is aligned for a u64, and the
+        // atomic lives as long as the mapping borrowed by `self`.
+        unsafe { &*(self.mmap.as_ptr().add(SEQUENCE_OFFSET) as *const AtomicU64) }
+    }
+
+    pub fn write(&mut self, tickers: &[HrTicker]) {
+        let count = tickers.len().min(CAPACITY);
+        let updated_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let sequence = self.sequence().load(Ordering::Relaxed);
+        self.sequence()
+            .store(sequence.wrapping_add(1), Ordering::Relaxed);
+        fence(Ordering::Release);
+
+        self.mmap[20..24].copy_from_slice(&(count as u32).to_le_bytes());
+        self.mmap[32..40].copy_from_slice(&updated_ms.to_le_bytes());
+        for (i, ticker) in tickers.iter().take(count).enumerate() {
+            let start = HEADER_SIZE + i * RECORD_SIZE;
+            let record = &mut self.mmap[start..start + RECORD_SIZE];
+            let symbol = ticker.s.as_bytes();
+            let len = symbol.len().min(SYMBOL_SIZE);
+            record[..SYMBOL_SIZE].fill(0);
+            record[..len].copy_from_slice(&symbol[..len]);
+            let values = [ticker.c, ticker.P, ticker.o, ticker.h, ticker.l];
+            for (j, value) in values.iter().enumerate() {
+                let offset = SYMBOL_SIZE + j * 8;
+                record[offset..offset + 8].copy_from_slice(&(*value as f64).to_le_bytes());
+            }
+            record[56..64].copy_from_slice(&ticker.E.to_le_bytes());
+        }
+
+        self.sequence()
+            .store(sequence.wrapping_add(2), Ordering::Release);
+    }
+}
+
+/// Rewrites the export once per second until the process exits.
+pub async fn run(path: &Path, tickers: Arc<Mutex<TickerMap>>) -> io::Result<()> {
+    let mut export = ShmExport::create(path)?;
+    let mut interval = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        interval.tick().await;
+        let snapshot: Vec<HrTicker> = tickers.lock().unwrap().values().cloned().collect();
+        export.write(&snapshot);
+    }
+}
@@ -0,0 +1,58 @@
+use crate::model::HrTicker;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Without a message for this long, the stream is reported as stale.
+const STALE_AFTER: Duration = Duration::from_secs(5);
+
+/// Health of the primary exchange stream, shown in the footer.
+#[derive(Debug, Default)]
+pub struct ConnectionStatus {
+    /// Set once the stream has ended; the exchange task does not reconnect.
+    pub closed: bool,
+    pub last_message: Option<Instant>,
+    /// Local receive time minus the newest event time in the last message.
+    pub latency: Option<Duration>,
+    /// Set while the tickers come from REST polling because the websocket cannot connect.
+    pub polling: bool,
+}
+
+pub enum Health {
+    Connecting,
+    Connected {
+        latency: Option<Duration>,
+    },
+    Stale {
+        since: Duration,
+    },
+    /// Receiving tickers from REST polls instead of the websocket.
+    Polling,
+    Disconnected,
+}
+
+impl ConnectionStatus {
+    pub fn record(&mut self, tickers: &[HrTicker]) {
+        self.last_message = Some(Instant::now());
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        // Event times ahead of the local clock count as zero latency.
+        self.latency = tickers
+            .iter()
+            .map(|t| t.E)
+            .max()
+            .filter(|event_time| *event_time > 0)
+            .map(|event_time| Duration::from_millis(now_ms.saturating_sub(event_time)));
+    }
+
+    pub fn health(&self) -> Health {
+        match self.last_message.map(|at| at.elapsed()) {
+            _ if self.closed => Health::Disconnected,
+            None => Health::Connecting,
+            Some(_) if self.polling => Health::Polling,
+            Some(since) if since >= STALE_AFTER => Health::Stale { since },
+            Some(_) => Health::Connected {
+                latency: self.latency,
+            },
+        }
+    }
+}
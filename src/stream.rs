@@ -0,0 +1,232 @@
+//! Sources of normalized ticker updates: a live exchange connection or a `--replay` file,
+//! optionally teed into a `--record` file.
+
+use crate::{
+    error,
+    exchange::{binance, Exchange},
+    metrics,
+    model::HrTicker,
+    notify::{self, Level},
+    replay,
+};
+use std::{fs::File, io, path::Path, time::Duration};
+use tokio::sync::{mpsc, watch};
+
+/// Batches buffered between a source and its consumer.
+const CHANNEL_CAPACITY: usize = 100;
+/// Wait before connecting the ticker stream again after it failed to connect.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Connection attempts taking longer than this count as failed, so polling is not held up.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Which symbols a live stream carries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Selection {
+    #[default]
+    All,
+    Symbols(Vec<String>),
+}
+
+impl Selection {
+    /// Only `symbols`, or every symbol when there are none.
+    pub fn of(symbols: &[String]) -> Self {
+        if symbols.is_empty() {
+            Selection::All
+        } else {
+            Selection::Symbols(symbols.iter().map(|s| s.to_uppercase()).collect())
+        }
+    }
+
+    pub fn contains(&self, symbol: &str) -> bool {
+        match self {
+            Selection::All => true,
+            Selection::Symbols(symbols) => symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol)),
+        }
+    }
+}
+
+/// How a live stream currently receives its tickers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transport {
+    #[default]
+    Streaming,
+    /// Polling the REST endpoint because the websocket cannot connect.
+    Polling,
+}
+
+/// A stream of ticker batches, each holding the symbols that changed in one exchange message.
+/// Dropping it ends the connection behind it.
+pub struct TickerStream {
+    rx: mpsc::Receiver<Vec<HrTicker>>,
+    transport: watch::Receiver<Transport>,
+}
+
+impl TickerStream {
+    /// Streams `exchange`'s tickers, retrying the connection until it succeeds.
+    pub fn connect(exchange: Exchange) -> Self {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            // Only the first failure is reported; the footer shows the stream's state.
+            let mut failed = false;
+            loop {
+                match exchange.subscribe(tx.clone()).await {
+                    Ok(()) => break,
+                    Err(err) if !failed => error::report(format!("{}: {}", exchange.name(), err)),
+                    Err(_) => {}
+                }
+                failed = true;
+                metrics::increment(&metrics::RECONNECTS);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+        Self::streaming(rx)
+    }
+
+    fn streaming(rx: mpsc::Receiver<Vec<HrTicker>>) -> Self {
+        Self {
+            rx,
+            transport: watch::Sender::new(Transport::Streaming).subscribe(),
+        }
+    }
+
+    /// Streams `exchange`'s tickers for the symbols in `selection`, following changes to it.
+    /// Binance subscribes to each selected symbol's own stream, so unselected symbols are never
+    /// sent; the other exchanges stream everything and are filtered here.
+    ///
+    /// With a `poll` interval, Binance's REST tickers are polled at that interval while the
+    /// websocket cannot connect, trying the websocket again before each poll.
+    pub fn connect_selected(
+        exchange: Exchange,
+        selection: watch::Receiver<Selection>,
+        poll: Option<Duration>,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        if exchange.is_binance() {
+            let (transport_tx, transport) = watch::channel(Transport::Streaming);
+            tokio::spawn(async move {
+                // Only the first failure of each kind is reported; the footer shows the rest.
+                let (mut failed, mut poll_failed) = (false, false);
+                loop {
+                    let connect = binance::subscribe_selected(
+                        binance::stream_url(exchange.market()),
+                        selection.clone(),
+                        tx.clone(),
+                    );
+                    match tokio::time::timeout(CONNECT_TIMEOUT, connect).await {
+                        Ok(Ok(())) => {
+                            if failed {
+                                notify::post(
+                                    Level::Info,
+                                    format!("{}: streaming again", exchange.name()),
+                                );
+                            }
+                            transport_tx.send_replace(Transport::Streaming);
+                            break;
+                        }
+                        Ok(Err(err)) if !failed => {
+                            error::report(format!("{}: {}", exchange.name(), err))
+                        }
+                        Err(_) if !failed => {
+                            error::report(format!("{}: timed out connecting", exchange.name()))
+                        }
+                        _ => {}
+                    }
+                    failed = true;
+                    metrics::increment(&metrics::RECONNECTS);
+                    let Some(poll) = poll else {
+                        tokio::select! {
+                            () = tokio::time::sleep(RECONNECT_DELAY) => continue,
+                            () = tx.closed() => break,
+                        }
+                    };
+                    match binance::rest_tickers(binance::tickers_url(exchange.market())).await {
+                        Ok(batch) => {
+                            if transport_tx.send_replace(Transport::Polling) != Transport::Polling {
+                                notify::post(
+                                    Level::Warning,
+                                    format!("{}: websocket down, polling REST", exchange.name()),
+                                );
+                            }
+                            let batch: Vec<HrTicker> = {
+                                let selection = selection.borrow();
+                                batch
+                                    .into_iter()
+                                    .filter(|t| selection.contains(&t.s))
+                                    .collect()
+                            };
+                            if !batch.is_empty() && tx.send(batch).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(err) if !poll_failed => {
+                            poll_failed = true;
+                            error::report(format!("{} REST tickers: {}", exchange.name(), err));
+                        }
+                        Err(_) => {}
+                    }
+                    tokio::select! {
+                        () = tokio::time::sleep(poll) => {}
+                        () = tx.closed() => break,
+                    }
+                }
+            });
+            return Self { rx, transport };
+        }
+        let mut all = Self::connect(exchange);
+        tokio::spawn(async move {
+            while let Some(batch) = all.next().await {
+                let batch: Vec<HrTicker> = {
+                    let selection = selection.borrow();
+                    batch
+                        .into_iter()
+                        .filter(|t| selection.contains(&t.s))
+                        .collect()
+                };
+                if !batch.is_empty() && tx.send(batch).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Self::streaming(rx)
+    }
+
+    /// Plays back a file written by `record`, keeping its timing divided by `speed`. The file
+    /// is opened up front so a bad path is reported to the caller.
+    pub fn replay(path: &Path, speed: f64) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let path = path.to_path_buf();
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            if let Err(err) = replay::replay(file, speed, tx).await {
+                error::report(format!("replaying {}: {}", path.display(), err));
+            }
+        });
+        Ok(Self::streaming(rx))
+    }
+
+    /// Appends every batch to `path` as NDJSON on its way through.
+    pub fn record(self, path: &Path) -> io::Result<Self> {
+        let file = replay::create(path)?;
+        let path = path.to_path_buf();
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(async move {
+            if let Err(err) = replay::record(file, self.rx, tx).await {
+                error::report(format!("recording to {}: {}", path.display(), err));
+            }
+        });
+        Ok(Self {
+            rx,
+            transport: self.transport,
+        })
+    }
+
+    /// Whether the batches currently come from REST polling rather than the websocket.
+    pub fn is_polling(&self) -> bool {
+        *self.transport.borrow() == Transport::Polling
+    }
+
+    /// The next batch, or `None` once the source has ended.
+    pub async fn next(&mut self) -> Option<Vec<HrTicker>> {
+        self.rx.recv().await
+    }
+}
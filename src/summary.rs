@@ -0,0 +1,127 @@
+//! Market summary bar above the footer: breadth and totals of the symbols the table lists, and
+//! composite metrics of the whole market.
+
+use crate::{
+    exchange::{Exchange, Market},
+    model::HrTicker,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One figure of the summary bar.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SummaryWidget {
+    /// Symbols up and down over 24h.
+    Breadth,
+    /// Mean 24h percent change.
+    AverageChange,
+    /// 24h quote volume of the USD-quoted markets.
+    QuoteVolume,
+    /// Last price of the most traded USD-quoted BTC market.
+    BtcPrice,
+    /// BTC's share of the total crypto market cap, from CoinGecko.
+    BtcDominance,
+    /// 24h quote volume of every USD-quoted futures market of the exchange.
+    FuturesVolume,
+    /// Mean 24h change of every coin but BTC and the stablecoins, each counted once.
+    AltIndex,
+}
+
+/// `[summary]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Figures shown, in order; an empty list hides the bar.
+    pub widgets: Vec<SummaryWidget>,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            widgets: vec![
+                SummaryWidget::Breadth,
+                SummaryWidget::AverageChange,
+                SummaryWidget::QuoteVolume,
+                SummaryWidget::BtcPrice,
+            ],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub gainers: usize,
+    pub losers: usize,
+    /// `None` without any symbols.
+    pub average_change: Option<f64>,
+    /// In USD, summed over the USD-quoted markets only, as other quotes do not add up.
+    pub quote_volume: f64,
+    pub btc_price: Option<f32>,
+    /// `None` on spot exchanges.
+    pub futures_volume: Option<f64>,
+    pub alt_change: Option<f64>,
+}
+
+/// Summarizes `listed`, the tickers the table shows. The BTC price and the market-wide metrics
+/// are taken from `all`, so they do not depend on the table's filters.
+pub fn summarize<'a>(
+    exchange: Exchange,
+    listed: impl IntoIterator<Item = &'a HrTicker>,
+    all: impl IntoIterator<Item = &'a HrTicker>,
+) -> Summary {
+    let mut summary = Summary::default();
+    let (mut count, mut change) = (0, 0.0);
+    for ticker in listed {
+        count += 1;
+        change += ticker.P as f64;
+        if ticker.P > 0.0 {
+            summary.gainers += 1;
+        } else if ticker.P < 0.0 {
+            summary.losers += 1;
+        }
+        if exchange
+            .instrument(&ticker.s)
+            .is_some_and(|instrument| instrument.is_usd_quoted())
+        {
+            summary.quote_volume += quote_volume(ticker);
+        }
+    }
+    summary.average_change = (count > 0).then(|| change / count as f64);
+
+    let futures = exchange.market() == Market::Futures;
+    let mut futures_volume = 0.0;
+    let mut btc: Option<&HrTicker> = None;
+    // The most traded USD-quoted market of each alt.
+    let mut alts: HashMap<String, Option<&HrTicker>> = HashMap::new();
+    for ticker in all {
+        let Some(instrument) = exchange.instrument(&ticker.s) else {
+            continue;
+        };
+        if !instrument.is_usd_quoted() {
+            continue;
+        }
+        futures_volume += quote_volume(ticker);
+        if instrument.is_stablecoin() {
+            continue;
+        }
+        let most_traded = match instrument.base.as_str() {
+            "BTC" | "XBT" => &mut btc,
+            _ => alts.entry(instrument.base).or_default(),
+        };
+        if most_traded.is_none_or(|t| quote_volume(t) < quote_volume(ticker)) {
+            *most_traded = Some(ticker);
+        }
+    }
+    summary.btc_price = btc.map(|ticker| ticker.c);
+    summary.futures_volume = futures.then_some(futures_volume);
+    summary.alt_change = (!alts.is_empty()).then(|| {
+        let changes = alts.values().flatten().map(|ticker| ticker.P as f64);
+        changes.sum::<f64>() / alts.len() as f64
+    });
+    summary
+}
+
+fn quote_volume(ticker: &HrTicker) -> f64 {
+    ticker.q.parse().unwrap_or(0.0)
+}
@@ -0,0 +1,81 @@
+//! Background tasks of a session: cancelled together on shutdown and joined, so their errors are
+//! reported once the terminal is back to normal.
+
+use crate::error::Error;
+use std::{future::Future, time::Duration};
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+
+pub type TaskResult = Result<(), Error>;
+
+#[derive(Default)]
+pub struct Supervisor {
+    token: CancellationToken,
+    tasks: JoinSet<(String, TaskResult)>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancelled when shutdown starts, for tasks that clean up after themselves.
+    pub fn token(&self) -> CancellationToken {
+        self.token.clone()
+    }
+
+    /// Runs `task` until it finishes or shutdown starts, when it is dropped.
+    pub fn spawn<F>(&mut self, name: impl Into<String>, task: F)
+    where
+        F: Future<Output = TaskResult> + Send + 'static,
+    {
+        let name = name.into();
+        let token = self.token.clone();
+        self.tasks.spawn(async move {
+            // The task goes first, so one that already finished reports its result.
+            let result = tokio::select! {
+                biased;
+                result = task => result,
+                () = token.cancelled() => Ok(()),
+            };
+            (name, result)
+        });
+    }
+
+    /// Runs `task` to completion; it is expected to watch `token()` and return soon after
+    /// shutdown starts, e.g. to flush what it has buffered.
+    pub fn spawn_graceful<F>(&mut self, name: impl Into<String>, task: F)
+    where
+        F: Future<Output = TaskResult> + Send + 'static,
+    {
+        let name = name.into();
+        self.tasks.spawn(async move { (name, task.await) });
+    }
+
+    /// Cancels every task and waits up to `timeout` for them to finish. Returns a message for
+    /// each task that failed, panicked or did not stop in time.
+    pub async fn shutdown(mut self, timeout: Duration) -> Vec<String> {
+        self.token.cancel();
+        let mut errors = Vec::new();
+        let joined = tokio::time::timeout(timeout, async {
+            while let Some(joined) = self.tasks.join_next().await {
+                match joined {
+                    Ok((_, Ok(()))) => {}
+                    Ok((name, Err(err))) => errors.push(format!("{}: {}", name, err)),
+                    Err(err) if err.is_panic() => errors.push(format!("a task panicked: {}", err)),
+                    Err(_) => {}
+                }
+            }
+        })
+        .await;
+        if joined.is_err() {
+            errors.push(format!(
+                "{} task(s) did not stop within {:?}",
+                self.tasks.len(),
+                timeout
+            ));
+            self.tasks.abort_all();
+        }
+        errors
+    }
+}
@@ -0,0 +1,39 @@
+//! Exchange switcher: a dialog listing every exchange and market the live stream can connect
+//! to, opened with `x`.
+
+use crate::exchange::Exchange;
+use ratatui::widgets::ListState;
+
+pub struct ExchangeSwitcher {
+    pub state: ListState,
+}
+
+impl ExchangeSwitcher {
+    /// Opens with `current` selected.
+    pub fn new(current: Exchange) -> Self {
+        let i = Exchange::ALL.iter().position(|e| *e == current);
+        Self {
+            state: ListState::default().with_selected(Some(i.unwrap_or(0))),
+        }
+    }
+
+    pub fn next(&mut self) {
+        let i = self.state.selected().unwrap_or(0);
+        self.state
+            .select(Some((i + 1).min(Exchange::ALL.len() - 1)));
+    }
+
+    pub fn previous(&mut self) {
+        let i = self.state.selected().unwrap_or(0);
+        self.state.select(Some(i.saturating_sub(1)));
+    }
+
+    pub fn chosen(&self) -> Exchange {
+        Exchange::ALL[self.state.selected().unwrap_or(0)]
+    }
+}
+
+/// `Binance Spot   spot`: the exchange's name and the kind of market it streams.
+pub fn label(exchange: Exchange) -> String {
+    format!("{:<14}{}", exchange.name(), exchange.market().name())
+}
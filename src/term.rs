@@ -3,7 +3,7 @@ use std::{
     time::Duration,
 };
 
-use color_eyre::{eyre::WrapErr, Result};
+use crate::error::Error;
 use ratatui::{
     backend::{Backend, CrosstermBackend},
     crossterm::{
@@ -11,36 +11,30 @@ use ratatui::{
         terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
         ExecutableCommand,
     },
-    layout::Rect,
-    terminal::{Terminal, TerminalOptions, Viewport},
+    terminal::Terminal,
 };
 
-pub fn init() -> Result<Terminal<impl Backend>> {
-    // this size is to match the size of the terminal when running the demo
-    // using vhs in a 1280x640 sized window (github social preview size)
-    let options = TerminalOptions {
-        viewport: Viewport::Fixed(Rect::new(0, 0, 81, 18)),
-    };
-    let terminal = Terminal::with_options(CrosstermBackend::new(io::stdout()), options)?;
-    enable_raw_mode().context("enable raw mode")?;
+pub fn init() -> Result<Terminal<impl Backend>, Error> {
+    let terminal = Terminal::new(CrosstermBackend::new(io::stdout())).map_err(Error::Terminal)?;
+    enable_raw_mode().map_err(Error::Terminal)?;
     stdout()
         .execute(EnterAlternateScreen)
-        .wrap_err("enter alternate screen")?;
+        .map_err(Error::Terminal)?;
     Ok(terminal)
 }
 
-pub fn restore() -> Result<()> {
-    disable_raw_mode().context("disable raw mode")?;
+pub fn restore() -> Result<(), Error> {
+    disable_raw_mode().map_err(Error::Terminal)?;
     stdout()
         .execute(LeaveAlternateScreen)
-        .wrap_err("leave alternate screen")?;
+        .map_err(Error::Terminal)?;
     Ok(())
 }
 
-pub fn next_event(timeout: Duration) -> Result<Option<Event>> {
-    if !event::poll(timeout)? {
+pub fn next_event(timeout: Duration) -> Result<Option<Event>, Error> {
+    if !event::poll(timeout).map_err(Error::Terminal)? {
         return Ok(None);
     }
-    let event = event::read()?;
+    let event = event::read().map_err(Error::Terminal)?;
     Ok(Some(event))
 }
@@ -0,0 +1,366 @@
+use ratatui::{
+    buffer::Buffer,
+    style::{palette::tailwind, Color},
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// An RGB color written as `#rrggbb` in the config file.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct HexColor(pub u8, pub u8, pub u8);
+
+impl HexColor {
+    fn lerp(self, other: HexColor, t: f32) -> HexColor {
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+        HexColor(
+            mix(self.0, other.0),
+            mix(self.1, other.1),
+            mix(self.2, other.2),
+        )
+    }
+}
+
+impl From<HexColor> for Color {
+    fn from(c: HexColor) -> Self {
+        Color::Rgb(c.0, c.1, c.2)
+    }
+}
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .ok_or_else(|| format!("invalid color {:?}, expected #rrggbb", value))
+        };
+        if hex.len() != 6 {
+            return Err(format!("invalid color {:?}, expected #rrggbb", value));
+        }
+        Ok(HexColor(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+impl From<HexColor> for String {
+    fn from(c: HexColor) -> Self {
+        c.to_string()
+    }
+}
+
+impl fmt::Display for HexColor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Colors for the Percent Change column, from `down` through `neutral` to `up`.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct PercentGradient {
+    pub down: HexColor,
+    pub neutral: HexColor,
+    pub up: HexColor,
+    /// Absolute percent change at which the gradient reaches `down` or `up`.
+    pub saturation: f32,
+    /// Changes at or beyond this absolute percent are rendered bold.
+    pub bold_threshold: Option<f32>,
+}
+
+impl Default for PercentGradient {
+    fn default() -> Self {
+        Self {
+            down: HexColor(0xdc, 0x26, 0x26),
+            neutral: HexColor(0x94, 0xa3, 0xb8),
+            up: HexColor(0x16, 0xa3, 0x4a),
+            saturation: 10.0,
+            bold_threshold: Some(5.0),
+        }
+    }
+}
+
+impl PercentGradient {
+    pub fn color(&self, percent: f32) -> Color {
+        let t = (percent.abs() / self.saturation.max(f32::EPSILON)).min(1.0);
+        let target = if percent < 0.0 { self.down } else { self.up };
+        self.neutral.lerp(target, t).into()
+    }
+
+    pub fn is_bold(&self, percent: f32) -> bool {
+        self.bold_threshold
+            .is_some_and(|threshold| percent.abs() >= threshold)
+    }
+}
+
+/// Mixes `to` into `from` by `t` (0.0 = `from`, 1.0 = `to`). Non-RGB colors switch halfway.
+pub fn blend(from: Color, to: Color, t: f32) -> Color {
+    match (from, to) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => HexColor(r1, g1, b1)
+            .lerp(HexColor(r2, g2, b2), t.clamp(0.0, 1.0))
+            .into(),
+        _ if t < 0.5 => from,
+        _ => to,
+    }
+}
+
+/// A named set of UI colors. User palettes only need the colors they change; the rest are
+/// taken from the default palette.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Palette {
+    pub name: String,
+    pub background: HexColor,
+    pub alt_row: HexColor,
+    pub header_bg: HexColor,
+    pub header_fg: HexColor,
+    pub row_fg: HexColor,
+    /// Selected row, and the accent for pinned symbols and borders.
+    pub selection: HexColor,
+    pub pinned: HexColor,
+    pub border: HexColor,
+    /// Last price after an uptick, gains and buy-side values.
+    pub up: HexColor,
+    /// Last price after a downtick, losses and sell-side values.
+    pub down: HexColor,
+    /// Row background a price change flashes from.
+    pub flash_up: HexColor,
+    pub flash_down: HexColor,
+}
+
+const fn rgb(color: Color) -> HexColor {
+    match color {
+        Color::Rgb(r, g, b) => HexColor(r, g, b),
+        _ => HexColor(0, 0, 0),
+    }
+}
+
+impl Palette {
+    /// A palette on the slate background with `accent` for the header, selection and borders.
+    fn tailwind(name: &str, accent: &tailwind::Palette) -> Self {
+        Self {
+            name: name.to_string(),
+            background: rgb(tailwind::SLATE.c950),
+            alt_row: rgb(tailwind::SLATE.c900),
+            header_bg: rgb(accent.c900),
+            header_fg: rgb(tailwind::SLATE.c200),
+            row_fg: rgb(tailwind::SLATE.c200),
+            selection: rgb(accent.c400),
+            pinned: rgb(accent.c300),
+            border: rgb(accent.c400),
+            up: rgb(Color::Green),
+            down: rgb(Color::Red),
+            flash_up: rgb(tailwind::GREEN.c800),
+            flash_down: rgb(tailwind::RED.c800),
+        }
+    }
+
+    /// Dark text on a near-white background, for light terminals.
+    fn light() -> Self {
+        Self {
+            name: "light".to_string(),
+            background: rgb(tailwind::SLATE.c50),
+            alt_row: rgb(tailwind::SLATE.c200),
+            header_bg: rgb(tailwind::BLUE.c200),
+            header_fg: rgb(tailwind::SLATE.c900),
+            row_fg: rgb(tailwind::SLATE.c900),
+            selection: rgb(tailwind::BLUE.c700),
+            pinned: rgb(tailwind::BLUE.c600),
+            border: rgb(tailwind::BLUE.c500),
+            up: rgb(tailwind::GREEN.c700),
+            down: rgb(tailwind::RED.c700),
+            flash_up: rgb(tailwind::GREEN.c200),
+            flash_down: rgb(tailwind::RED.c200),
+        }
+    }
+
+    /// The palettes that ship with the app, in h/l cycling order.
+    pub fn builtin() -> Vec<Palette> {
+        vec![
+            Palette::tailwind("blue", &tailwind::BLUE),
+            Palette::tailwind("emerald", &tailwind::EMERALD),
+            Palette::tailwind("indigo", &tailwind::INDIGO),
+            Palette::tailwind("red", &tailwind::RED),
+            Palette::light(),
+        ]
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::tailwind(DEFAULT_PALETTE, &tailwind::INDIGO)
+    }
+}
+
+const DEFAULT_PALETTE: &str = "indigo";
+
+/// How many colors the terminal can show. Palettes are written in RGB and mapped down to the
+/// nearest color the terminal supports when drawing.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    /// 24-bit RGB.
+    Truecolor,
+    /// The xterm 256-color palette.
+    Ansi256,
+    /// The 16 basic ANSI colors, as configured in the terminal.
+    Ansi16,
+}
+
+/// xterm's RGB values for the 16 basic colors, in `Color` order.
+const ANSI16: [(Color, HexColor); 16] = [
+    (Color::Black, HexColor(0x00, 0x00, 0x00)),
+    (Color::Red, HexColor(0xcd, 0x00, 0x00)),
+    (Color::Green, HexColor(0x00, 0xcd, 0x00)),
+    (Color::Yellow, HexColor(0xcd, 0xcd, 0x00)),
+    (Color::Blue, HexColor(0x00, 0x00, 0xee)),
+    (Color::Magenta, HexColor(0xcd, 0x00, 0xcd)),
+    (Color::Cyan, HexColor(0x00, 0xcd, 0xcd)),
+    (Color::Gray, HexColor(0xe5, 0xe5, 0xe5)),
+    (Color::DarkGray, HexColor(0x7f, 0x7f, 0x7f)),
+    (Color::LightRed, HexColor(0xff, 0x00, 0x00)),
+    (Color::LightGreen, HexColor(0x00, 0xff, 0x00)),
+    (Color::LightYellow, HexColor(0xff, 0xff, 0x00)),
+    (Color::LightBlue, HexColor(0x5c, 0x5c, 0xff)),
+    (Color::LightMagenta, HexColor(0xff, 0x00, 0xff)),
+    (Color::LightCyan, HexColor(0x00, 0xff, 0xff)),
+    (Color::White, HexColor(0xff, 0xff, 0xff)),
+];
+
+/// Channel values of the 6x6x6 color cube at indices 16..=231 of the 256-color palette.
+const CUBE_LEVELS: [u8; 6] = [0x00, 0x5f, 0x87, 0xaf, 0xd7, 0xff];
+
+impl ColorMode {
+    /// Guesses the terminal's color support from `COLORTERM` and `TERM`.
+    pub fn detect() -> Self {
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        let term = std::env::var("TERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            ColorMode::Truecolor
+        } else if term.contains("256color") {
+            ColorMode::Ansi256
+        } else {
+            ColorMode::Ansi16
+        }
+    }
+
+    /// The closest color to `color` this mode can show. Named and indexed colors pass through.
+    pub fn apply(self, color: Color) -> Color {
+        let Color::Rgb(r, g, b) = color else {
+            return color;
+        };
+        let target = HexColor(r, g, b);
+        match self {
+            ColorMode::Truecolor => color,
+            ColorMode::Ansi256 => {
+                let level = |v: u8| {
+                    (0..CUBE_LEVELS.len())
+                        .min_by_key(|i| CUBE_LEVELS[*i].abs_diff(v))
+                        .unwrap_or(0)
+                };
+                let (ri, gi, bi) = (level(r), level(g), level(b));
+                let cube = HexColor(CUBE_LEVELS[ri], CUBE_LEVELS[gi], CUBE_LEVELS[bi]);
+                let cube_index = 16 + 36 * ri + 6 * gi + bi;
+                // 24 grays from 0x08 to 0xee in steps of 10.
+                let average = (r as u32 + g as u32 + b as u32) / 3;
+                let gray_step = (average.saturating_sub(3) / 10).min(23);
+                let level = (8 + gray_step * 10) as u8;
+                let gray = HexColor(level, level, level);
+                if distance(target, gray) < distance(target, cube) {
+                    Color::Indexed(232 + gray_step as u8)
+                } else {
+                    Color::Indexed(cube_index as u8)
+                }
+            }
+            ColorMode::Ansi16 => ANSI16
+                .iter()
+                .min_by_key(|(_, rgb)| distance(target, *rgb))
+                .map_or(color, |(named, _)| *named),
+        }
+    }
+}
+
+/// The RGB value `color` is shown with, taking xterm's palette for named and indexed colors;
+/// `None` for the terminal's default color.
+pub fn to_rgb(color: Color) -> Option<HexColor> {
+    match color {
+        Color::Reset => None,
+        Color::Rgb(r, g, b) => Some(HexColor(r, g, b)),
+        Color::Indexed(i @ 0..=15) => Some(ANSI16[i as usize].1),
+        Color::Indexed(i @ 16..=231) => {
+            let i = (i - 16) as usize;
+            Some(HexColor(
+                CUBE_LEVELS[i / 36],
+                CUBE_LEVELS[i / 6 % 6],
+                CUBE_LEVELS[i % 6],
+            ))
+        }
+        Color::Indexed(i) => {
+            let level = 8 + (i - 232) * 10;
+            Some(HexColor(level, level, level))
+        }
+        named => ANSI16
+            .iter()
+            .find(|(c, _)| *c == named)
+            .map(|(_, rgb)| *rgb),
+    }
+}
+
+fn distance(a: HexColor, b: HexColor) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// Maps every color in a drawn frame to what `mode` can show.
+pub fn downgrade(buffer: &mut Buffer, mode: ColorMode) {
+    if mode == ColorMode::Truecolor {
+        return;
+    }
+    for cell in &mut buffer.content {
+        cell.fg = mode.apply(cell.fg);
+        cell.bg = mode.apply(cell.bg);
+    }
+}
+
+/// `[theme]` section of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(default)]
+pub struct ThemeConfig {
+    /// Name of the palette to start with.
+    pub name: String,
+    /// Overrides the detected color support; `--color-mode` takes precedence.
+    pub color_mode: Option<ColorMode>,
+    /// User palettes, `[[theme.palettes]]`. One named like a built-in palette replaces it.
+    pub palettes: Vec<Palette>,
+    pub percent_gradient: PercentGradient,
+    /// How long a row flashes after its last price changes; 0 disables flashing.
+    pub flash_ms: u64,
+}
+
+impl Default for ThemeConfig {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_PALETTE.to_string(),
+            color_mode: None,
+            palettes: Vec::new(),
+            percent_gradient: PercentGradient::default(),
+            flash_ms: 600,
+        }
+    }
+}
+
+impl ThemeConfig {
+    /// Built-in palettes followed by the user's, with user palettes replacing built-ins of the
+    /// same name.
+    pub fn all_palettes(&self) -> Vec<Palette> {
+        let mut palettes = Palette::builtin();
+        for palette in &self.palettes {
+            match palettes.iter_mut().find(|p| p.name == palette.name) {
+                Some(existing) => *existing = palette.clone(),
+                None => palettes.push(palette.clone()),
+            }
+        }
+        palettes
+    }
+}
@@ -0,0 +1,2469 @@
+//! Rendering of every view, pane and popup.
+
+use crate::{
+    accessibility,
+    account::AccountStatus,
+    app::{App, Pane, View, COLUMN_WIDTH, ITEM_HEIGHT},
+    chart::{self, CandleChart, ChartView, GridStyle, Overlay},
+    command::Command,
+    denomination::{Converter, Denomination},
+    exchange::Exchange,
+    format, group, heatmap,
+    indicators::{self, Trend},
+    keymap::Action,
+    model::{HrTicker, SortColumn, TickerMap},
+    movers,
+    notify::Level,
+    paper::Side,
+    ratelimit,
+    status::Health,
+    summary::{self, SummaryWidget},
+    switcher,
+    workspace::{PaneKind, Slot},
+};
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{
+        Axis, Block, BorderType, Borders, Cell, Chart, Clear, Dataset, GraphType, HighlightSpacing,
+        List, ListItem, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState, Table,
+        TableState, Wrap,
+    },
+    Frame,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+/// Width of the exchange switcher, enough for its title.
+const SWITCHER_WIDTH: u16 = 46;
+/// Toasts shown at once; older ones stay in the notification history.
+const MAX_TOASTS: usize = 3;
+const TOAST_WIDTH: u16 = 48;
+
+/// Draws the whole screen for the current view.
+pub fn ui(f: &mut Frame, app: &mut App, tickers: Arc<Mutex<TickerMap>>) {
+    render_screen(f, app, tickers);
+    render_command_palette(f, app);
+    render_exchange_switcher(f, app);
+    render_note_editor(f, app);
+    render_toasts(f, app);
+}
+
+fn render_screen(f: &mut Frame, app: &mut App, tickers: Arc<Mutex<TickerMap>>) {
+    {
+        // The announcement of the selected row takes a line of the footer, and the summary bar
+        // one above it.
+        let announce = app.config.accessibility.enabled && app.config.accessibility.announce;
+        let footer =
+            if announce { 4 } else { 3 } + u16::from(!app.config.summary.widgets.is_empty());
+        // Maintenance and delisting warnings take a line at the top.
+        let warnings = app.maintenance_warnings();
+        let area = if warnings.is_empty() {
+            f.size()
+        } else {
+            let rows =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(f.size());
+            render_maintenance_banner(f, &warnings, rows[0]);
+            rows[1]
+        };
+        let rects = Layout::vertical([Constraint::Min(5), Constraint::Length(footer)]).split(area);
+        app.set_colors();
+
+        if let Some(layout) = app.workspace_layout().cloned() {
+            render_workspace(f, app, &layout, rects[0], &tickers);
+            if app.rule_editor.is_some() {
+                render_rule_editor(f, app);
+            }
+            render_order_ticket(f, app);
+            render_footer(f, app, rects[1], &tickers);
+            render_chart(f, app);
+            return;
+        }
+
+        match app.view {
+            View::Comparison => {
+                render_comparison(f, app, rects[0]);
+                render_footer(f, app, rects[1], &tickers);
+                return;
+            }
+            View::Portfolio => {
+                render_portfolio(f, app, rects[0], &tickers);
+                render_footer(f, app, rects[1], &tickers);
+                return;
+            }
+            View::Account => {
+                render_account(f, app, rects[0], &tickers);
+                render_footer(f, app, rects[1], &tickers);
+                return;
+            }
+            View::Paper => {
+                render_paper(f, app, rects[0], &tickers);
+                render_footer(f, app, rects[1], &tickers);
+                render_order_ticket(f, app);
+                return;
+            }
+            View::Heatmap => {
+                render_heatmap(f, app, rects[0], &tickers);
+                render_footer(f, app, rects[1], &tickers);
+                render_chart(f, app);
+                return;
+            }
+            View::Movers => {
+                let halves = Layout::horizontal([Constraint::Fill(1); 2]).split(rects[0]);
+                render_movers(f, app, halves[0], &tickers, Pane::Gainers);
+                render_movers(f, app, halves[1], &tickers, Pane::Losers);
+                render_footer(f, app, rects[1], &tickers);
+                render_chart(f, app);
+                return;
+            }
+            View::Grid => {
+                render_grid(f, app, rects[0], &tickers);
+                render_footer(f, app, rects[1], &tickers);
+                render_chart(f, app);
+                return;
+            }
+            View::Table => {}
+        }
+
+        let bottom_panes: Vec<Pane> = [
+            (app.show_liquidations, Pane::Liquidations),
+            (app.show_alerts, Pane::Alerts),
+            (app.show_notifications, Pane::Notifications),
+            (app.show_alert_history, Pane::AlertHistory),
+            (app.show_news, Pane::News),
+            (app.show_plugins, Pane::Plugins),
+            (app.show_status, Pane::Status),
+        ]
+        .into_iter()
+        .filter_map(|(shown, pane)| shown.then_some(pane))
+        .collect();
+        let (main_area, bottom_areas) = if bottom_panes.is_empty() {
+            (rects[0], Vec::new())
+        } else {
+            let areas =
+                Layout::vertical([Constraint::Min(5), Constraint::Length(12)]).split(rects[0]);
+            let bottom =
+                Layout::horizontal(vec![Constraint::Fill(1); bottom_panes.len()]).split(areas[1]);
+            (areas[0], bottom.to_vec())
+        };
+
+        let (table_area, detail_area) = if app.show_detail {
+            let columns =
+                Layout::horizontal([Constraint::Min(40), Constraint::Length(42)]).split(main_area);
+            (columns[0], Some(columns[1]))
+        } else {
+            (main_area, None)
+        };
+
+        render_table(f, app, table_area, Arc::clone(&tickers));
+
+        render_scrollbar(f, app, table_area);
+
+        if let Some(area) = detail_area {
+            render_detail(f, app, area, Arc::clone(&tickers));
+        }
+
+        for (pane, area) in bottom_panes.into_iter().zip(bottom_areas) {
+            match pane {
+                Pane::Liquidations => render_liquidations(f, app, area),
+                Pane::Alerts => render_alerts(f, app, area),
+                Pane::AlertHistory => render_alert_history(f, app, area),
+                Pane::News => render_news(f, app, area),
+                Pane::Plugins => render_plugins(f, app, area),
+                Pane::Status => render_status(f, app, area),
+                _ => render_notifications(f, app, area),
+            }
+        }
+
+        if app.rule_editor.is_some() {
+            render_rule_editor(f, app);
+        }
+
+        render_order_ticket(f, app);
+
+        render_footer(f, app, rects[1], &tickers);
+
+        render_chart(f, app);
+    }
+}
+
+fn render_workspace(
+    f: &mut Frame,
+    app: &mut App,
+    layout: &Slot,
+    area: Rect,
+    tickers: &Arc<Mutex<TickerMap>>,
+) {
+    for (pane, area) in layout.areas(area) {
+        match pane {
+            PaneKind::Table => {
+                render_table(f, app, area, Arc::clone(tickers));
+                render_scrollbar(f, app, area);
+            }
+            PaneKind::Detail => render_detail(f, app, area, Arc::clone(tickers)),
+            PaneKind::Chart => render_chart_pane(f, app, area),
+            PaneKind::Comparison => render_comparison(f, app, area),
+            PaneKind::Portfolio => render_portfolio(f, app, area, tickers),
+            PaneKind::Paper => render_paper(f, app, area, tickers),
+            PaneKind::Account => render_account(f, app, area, tickers),
+            PaneKind::Liquidations => render_liquidations(f, app, area),
+            PaneKind::Alerts => render_alerts(f, app, area),
+            PaneKind::Notifications => render_notifications(f, app, area),
+            PaneKind::AlertHistory => render_alert_history(f, app, area),
+            PaneKind::News => render_news(f, app, area),
+            PaneKind::Plugins => render_plugins(f, app, area),
+            PaneKind::Status => render_status(f, app, area),
+            PaneKind::Grid => render_grid(f, app, area, tickers),
+            PaneKind::Heatmap => render_heatmap(f, app, area, tickers),
+            PaneKind::Gainers => render_movers(f, app, area, tickers, Pane::Gainers),
+            PaneKind::Losers => render_movers(f, app, area, tickers, Pane::Losers),
+        }
+    }
+}
+
+fn render_chart(f: &mut Frame, app: &App) {
+    if let Some(charts) = &app.compare_charts {
+        render_compare(f, app, charts);
+    }
+    let Some(chart) = &app.chart else {
+        return;
+    };
+    let area = centered_rect(90, 70, f.size());
+    f.render_widget(Clear, area);
+    let range = chart
+        .range
+        .map_or(String::new(), |range| format!(" {}", range.name()));
+    let title = format!(
+        "{} {} {}{} | ({}) switch source  ({}) range",
+        chart.symbol,
+        chart::INTERVAL,
+        chart.source.name(),
+        range,
+        app.keymap.keys_for(Action::ToggleHistory),
+        app.keymap.keys_for(Action::CycleChartRange)
+    );
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .title(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    render_candles(f, app, chart, inner, true);
+}
+
+/// Two symbols' closes on one chart, each as its percent change since the first candle shown.
+fn render_compare(f: &mut Frame, app: &App, charts: &[ChartView; 2]) {
+    let area = centered_rect(90, 70, f.size());
+    f.render_widget(Clear, area);
+    let colors = [Color::LightBlue, Color::Magenta];
+    let mut title = Vec::new();
+    for (chart, color) in charts.iter().zip(colors) {
+        if !title.is_empty() {
+            title.push(Span::raw(" vs "));
+        }
+        title.push(Span::styled(
+            chart.symbol.clone(),
+            Style::default().fg(color),
+        ));
+    }
+    title.push(Span::raw(format!(
+        " {} {} | ({}) close",
+        chart::INTERVAL,
+        charts[0].source.name(),
+        app.keymap.keys_for(Action::CompareCharts)
+    )));
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .title(Line::from(title));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if let Some(message) = charts.iter().find_map(chart_message) {
+        f.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(app.colors.row_fg))
+                .wrap(Wrap { trim: true }),
+            inner,
+        );
+        return;
+    }
+    // Braille fits two points per column.
+    let series = chart::relative_closes(
+        &charts[0].candles,
+        &charts[1].candles,
+        chart::plot_width(inner) * 2,
+    );
+    let points = series[0].len();
+    if points < 2 {
+        f.render_widget(
+            Paragraph::new("No overlapping candles yet")
+                .style(Style::default().fg(app.colors.row_fg)),
+            inner,
+        );
+        return;
+    }
+    let values = || series.iter().flatten().map(|(_, percent)| *percent);
+    let high = values().fold(f64::MIN, f64::max);
+    let low = values().fold(f64::MAX, f64::min);
+    let percent = |value: f64| app.config.format.localize(&format!("{:+.2}%", value));
+    let datasets = series
+        .iter()
+        .zip(charts)
+        .zip(colors)
+        .map(|((points, chart), color)| {
+            Dataset::default()
+                .name(format!(
+                    "{} {}",
+                    chart.symbol,
+                    percent(points[points.len() - 1].1)
+                ))
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(color))
+                .data(points)
+        })
+        .collect();
+    let axis = Style::default().fg(app.colors.footer_border_color);
+    let compare = Chart::new(datasets)
+        .x_axis(Axis::default().bounds([0.0, (points - 1) as f64]))
+        .y_axis(
+            Axis::default()
+                .style(axis)
+                .bounds([low, high.max(low + f64::EPSILON)])
+                .labels(vec![Span::raw(percent(low)), Span::raw(percent(high))]),
+        );
+    f.render_widget(compare, inner);
+}
+
+/// The chart pane of a workspace, following the selected symbol.
+fn render_chart_pane(f: &mut Frame, app: &App, area: Rect) {
+    let title = match &app.chart_pane {
+        Some(chart) => format!(
+            "{} {} {}",
+            chart.symbol,
+            chart::INTERVAL,
+            chart.source.name()
+        ),
+        None => "Chart".to_string(),
+    };
+    let block = pane_block(app, Pane::Chart, title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    match &app.chart_pane {
+        Some(chart) => render_candles(f, app, chart, inner, false),
+        None => f.render_widget(
+            Paragraph::new("Select a symbol to chart")
+                .style(Style::default().fg(app.colors.row_fg)),
+            inner,
+        ),
+    }
+}
+
+/// Why `chart` has no candles to draw, if it has none.
+fn chart_message(chart: &ChartView) -> Option<String> {
+    if let Some(err) = &chart.error {
+        Some(format!("Error: {}", err))
+    } else if chart.is_loading() && chart.candles.is_empty() {
+        Some("Loading…".to_string())
+    } else if chart.candles.is_empty() {
+        Some(format!("No candles for {}", chart.symbol))
+    } else {
+        None
+    }
+}
+
+/// Draws `chart` with its legend and status line; `raster` draws the plot as an image when the
+/// terminal can show one.
+fn render_candles(f: &mut Frame, app: &App, chart: &ChartView, area: Rect, raster: bool) {
+    match chart_message(chart) {
+        Some(message) => f.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(app.colors.row_fg))
+                .wrap(Wrap { trim: true }),
+            area,
+        ),
+        None => {
+            let rects = Layout::vertical([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ])
+            .split(area);
+            let profile = if app.volume_profile {
+                chart::profile_width(rects[1])
+            } else {
+                0
+            };
+            chart.fit(chart::plot_width(rects[1]) - profile as usize);
+            let end = chart.window().end;
+            let closes: Vec<f64> = chart.candles.iter().map(|c| c.close).collect();
+            let rsi = indicators::rsi(&closes, indicators::RSI_PERIOD)
+                .map_or("-".to_string(), |rsi| format!("{:.1}", rsi));
+            let mut legend = vec![Span::styled(
+                format!("RSI {} {}", indicators::RSI_PERIOD, rsi),
+                Style::default().fg(app.colors.row_fg),
+            )];
+            let mut candles = CandleChart::new(&chart.candles[..end])
+                .colors(
+                    app.colors.up,
+                    app.colors.down,
+                    app.colors.footer_border_color,
+                )
+                .span(chart.zoom)
+                .cursor(chart.cursor)
+                .number_format(&app.config.format)
+                .volume_profile(profile)
+                .style(app.chart_style)
+                .log_scale(app.log_scale);
+            for overlay in Overlay::ALL {
+                let keys = app.keymap.keys_for(overlay_action(overlay));
+                if !app.chart_overlays.contains(&overlay) {
+                    legend.push(Span::styled(
+                        format!("  ({}) {}", keys, overlay_name(overlay)),
+                        Style::default().fg(app.colors.footer_border_color),
+                    ));
+                    continue;
+                }
+                legend.push(Span::styled(
+                    format!("  ({})", keys),
+                    Style::default().fg(app.colors.row_fg),
+                ));
+                for (name, values, color) in overlay_lines(overlay, &chart.candles, &closes) {
+                    if !name.is_empty() {
+                        legend.push(Span::styled(
+                            format!(" • {}", name),
+                            Style::default().fg(color),
+                        ));
+                    }
+                    candles = candles.overlay(values[..end].to_vec(), color);
+                }
+            }
+            legend.push(Span::styled(
+                format!(
+                    "  ({}) {}",
+                    app.keymap.keys_for(Action::CycleChartStyle),
+                    app.chart_style.name()
+                ),
+                Style::default().fg(app.colors.row_fg),
+            ));
+            for (action, name, on) in [
+                (Action::ToggleVolumeProfile, "profile", app.volume_profile),
+                (Action::ToggleLogScale, "log", app.log_scale),
+            ] {
+                legend.push(Span::styled(
+                    format!("  ({}) {}", app.keymap.keys_for(action), name),
+                    Style::default().fg(if on {
+                        app.colors.row_fg
+                    } else {
+                        app.colors.footer_border_color
+                    }),
+                ));
+            }
+            if let Some(graphics) = app.graphics.filter(|_| raster) {
+                candles = candles.raster(graphics, &app.chart_image);
+            }
+            f.render_widget(Paragraph::new(Line::from(legend)), rects[0]);
+            f.render_widget(candles, rects[1]);
+            f.render_widget(
+                Paragraph::new(chart_status(app, chart))
+                    .style(Style::default().fg(app.colors.row_fg)),
+                rects[2],
+            );
+        }
+    }
+}
+
+/// Treemap of the highest-volume symbols, sized by 24h quote volume and colored by percent
+/// change on the theme's gradient.
+fn render_heatmap(f: &mut Frame, app: &mut App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let tickers = tickers.lock().unwrap();
+    let mut ranked: Vec<(&HrTicker, f64)> = tickers
+        .values()
+        .map(|ticker| (ticker, ticker.q.parse().unwrap_or(0.0)))
+        .filter(|(_, volume)| *volume > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.s.cmp(&b.0.s)));
+    ranked.truncate(app.config.heatmap.max_symbols);
+    app.heatmap_symbols = ranked.iter().map(|(ticker, _)| ticker.s.clone()).collect();
+    app.heatmap_selected = app
+        .heatmap_selected
+        .min(app.heatmap_symbols.len().saturating_sub(1));
+
+    let block = pane_block(app, Pane::Heatmap, "Heatmap — 24h quote volume");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let weights: Vec<f64> = ranked.iter().map(|(_, volume)| *volume).collect();
+    let gradient = &app.config.theme.percent_gradient;
+    for (i, ((ticker, _), cell)) in ranked
+        .iter()
+        .zip(heatmap::treemap(&weights, inner))
+        .enumerate()
+    {
+        if cell.area() == 0 {
+            continue;
+        }
+        let background = gradient.color(ticker.P);
+        let mut style = Style::default().bg(background).fg(contrasting(background));
+        if i == app.heatmap_selected && app.focus == Pane::Heatmap {
+            style = style.add_modifier(Modifier::REVERSED | Modifier::BOLD);
+        }
+        let mut lines = vec![Line::from(ticker.s.clone())];
+        if cell.height >= 2 {
+            lines.push(Line::from(format!("{:+.2}%", ticker.P)));
+        }
+        if cell.height >= 4 {
+            lines.push(Line::from(ticker.c.to_string()));
+        }
+        // Vertically centered.
+        let padding = cell.height.saturating_sub(lines.len() as u16) / 2;
+        let text = Rect {
+            y: cell.y + padding,
+            height: cell.height - padding,
+            ..cell
+        };
+        f.render_widget(Block::default().style(style), cell);
+        f.render_widget(
+            Paragraph::new(lines)
+                .style(style)
+                .alignment(Alignment::Center),
+            text,
+        );
+    }
+}
+
+/// The top gainers, or with `Pane::Losers` the top losers, of the symbols the table lists.
+fn render_movers(
+    f: &mut Frame,
+    app: &mut App,
+    area: Rect,
+    tickers: &Arc<Mutex<TickerMap>>,
+    pane: Pane,
+) {
+    let tickers = tickers.lock().unwrap();
+    let basis = app.movers_basis;
+    let (gainers, losers) = movers::rank(
+        tickers.values().filter(|ticker| app.shows(&ticker.s)),
+        basis,
+        app.config.movers.count,
+    );
+    let (title, ranked, color) = if pane == Pane::Gainers {
+        ("Top gainers", gainers, app.colors.up)
+    } else {
+        ("Top losers", losers, app.colors.down)
+    };
+    let symbols = ranked.iter().map(|(ticker, _)| ticker.s.clone()).collect();
+    if pane == Pane::Gainers {
+        app.gainers = symbols;
+    } else {
+        app.losers = symbols;
+    }
+
+    let header = ["Symbol", "Last", "Change", "Volume"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(
+            Style::default()
+                .fg(app.colors.header_fg)
+                .bg(app.colors.header_bg),
+        );
+    let rows: Vec<Row> = ranked
+        .iter()
+        .map(|(ticker, change)| {
+            Row::new(vec![
+                Cell::from(ticker.s.clone()),
+                Cell::from(ticker.c.to_string()),
+                Cell::from(format!("{:+.2}%", change)).style(Style::default().fg(color)),
+                Cell::from(format::compact(ticker.q.parse().unwrap_or(0.0))),
+            ])
+            .style(Style::default().fg(app.colors.row_fg))
+        })
+        .collect();
+    let title = format!(
+        "{} — {} ({} switches)",
+        title,
+        basis.title(),
+        app.keymap.keys_for(Action::SortNext)
+    );
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(14),
+            Constraint::Length(12),
+            Constraint::Length(9),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(header)
+    .block(pane_block(app, pane, title))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let state = if pane == Pane::Gainers {
+        &mut app.gainers_state
+    } else {
+        &mut app.losers_state
+    };
+    if let Some(i) = state.selected() {
+        state.select((!ranked.is_empty()).then(|| i.min(ranked.len() - 1)));
+    }
+    f.render_stateful_widget(table, area, state);
+}
+
+/// Black or white, whichever reads better on `background`.
+fn contrasting(background: Color) -> Color {
+    match background {
+        Color::Rgb(r, g, b) if 299 * r as u32 + 587 * g as u32 + 114 * b as u32 > 150_000 => {
+            Color::Black
+        }
+        _ => Color::White,
+    }
+}
+
+/// Mini-charts of the top symbols in the table, `rows` × `columns` of them.
+fn render_grid(f: &mut Frame, app: &App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let block = pane_block(app, Pane::Grid, format!("Charts {}", chart::INTERVAL));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if app.grid.is_empty() {
+        f.render_widget(
+            Paragraph::new("Waiting for symbols…").style(Style::default().fg(app.colors.row_fg)),
+            inner,
+        );
+        return;
+    }
+    let (rows, columns) = (app.config.grid.rows.max(1), app.config.grid.columns.max(1));
+    let cells: Vec<Rect> = Layout::vertical(vec![Constraint::Fill(1); rows as usize])
+        .split(inner)
+        .iter()
+        .flat_map(|row| {
+            Layout::horizontal(vec![Constraint::Fill(1); columns as usize])
+                .split(*row)
+                .to_vec()
+        })
+        .collect();
+    let tickers = tickers.lock().unwrap();
+    for (chart, area) in app.grid.iter().zip(cells) {
+        let mut title = vec![Span::raw(chart.symbol.clone())];
+        if let Some(ticker) = tickers.get(&chart.symbol) {
+            let color = if ticker.P >= 0.0 {
+                app.colors.up
+            } else {
+                app.colors.down
+            };
+            title.push(Span::raw(format!(" {}", ticker.c)));
+            title.push(Span::styled(
+                format!(" {:+.2}%", ticker.P),
+                Style::default().fg(color),
+            ));
+        }
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.colors.footer_border_color))
+            .title(Line::from(title));
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        render_mini_chart(f, app, chart, inner);
+    }
+}
+
+fn render_mini_chart(f: &mut Frame, app: &App, chart: &ChartView, area: Rect) {
+    if let Some(message) = chart_message(chart) {
+        f.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(app.colors.row_fg))
+                .wrap(Wrap { trim: true }),
+            area,
+        );
+        return;
+    }
+    match app.config.grid.style {
+        GridStyle::Candles => f.render_widget(
+            CandleChart::new(&chart.candles)
+                .colors(
+                    app.colors.up,
+                    app.colors.down,
+                    app.colors.footer_border_color,
+                )
+                .number_format(&app.config.format),
+            area,
+        ),
+        GridStyle::Line => {
+            // Like the candle chart, a cell with no room beside the price axis is left blank.
+            let plot = chart::plot_width(area);
+            if plot <= 1 {
+                return;
+            }
+            // Braille fits two points per column.
+            let shown = plot * 2;
+            let candles = &chart.candles[chart.candles.len().saturating_sub(shown)..];
+            let points: Vec<(f64, f64)> = candles
+                .iter()
+                .enumerate()
+                .map(|(i, candle)| (i as f64, candle.close))
+                .collect();
+            let high = candles.iter().map(|c| c.close).fold(f64::MIN, f64::max);
+            let low = candles.iter().map(|c| c.close).fold(f64::MAX, f64::min);
+            let color = if candles[candles.len() - 1].close >= candles[0].close {
+                app.colors.up
+            } else {
+                app.colors.down
+            };
+            let axis = Style::default().fg(app.colors.footer_border_color);
+            let line = Chart::new(vec![Dataset::default()
+                .marker(Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(color))
+                .data(&points)])
+            .x_axis(Axis::default().bounds([0.0, (points.len() - 1).max(1) as f64]))
+            .y_axis(Axis::default().style(axis).bounds([low, high]).labels(vec![
+                Span::raw(app.config.format.localize(&chart::price_label(low))),
+                Span::raw(app.config.format.localize(&chart::price_label(high))),
+            ]));
+            f.render_widget(line, area);
+        }
+    }
+}
+
+/// OHLCV of the candle under the crosshair, or the chart's keys when there is none.
+fn chart_status(app: &App, chart: &ChartView) -> String {
+    let loading = match chart.range {
+        Some(range) if chart.is_loading_older() => format!("  Loading the {}…", range.name()),
+        None if chart.is_loading_older() => "  Loading older candles…".to_string(),
+        _ => String::new(),
+    };
+    let label = |price| app.config.format.localize(&chart::price_label(price));
+    match chart.cursor.and_then(|i| chart.candles.get(i)) {
+        Some(candle) => format!(
+            "{}  O {}  H {}  L {}  C {}  V {}{}",
+            app.config.format.timestamp(candle.open_time),
+            label(candle.open),
+            label(candle.high),
+            label(candle.low),
+            label(candle.close),
+            app.config.format.localize(&format::compact(candle.volume)),
+            loading
+        ),
+        None => format!(
+            "({}/{}) crosshair  ({}/{}) pan  ({}/{}) zoom  ({}) latest{}",
+            app.keymap.keys_for(Action::PreviousColor),
+            app.keymap.keys_for(Action::NextColor),
+            app.keymap.keys_for(Action::ScrollLeft),
+            app.keymap.keys_for(Action::ScrollRight),
+            app.keymap.keys_for(Action::ZoomIn),
+            app.keymap.keys_for(Action::ZoomOut),
+            app.keymap.keys_for(Action::Last),
+            loading
+        ),
+    }
+}
+
+fn overlay_action(overlay: Overlay) -> Action {
+    match overlay {
+        Overlay::Sma => Action::ToggleSma,
+        Overlay::Ema => Action::ToggleEma,
+        Overlay::Bollinger => Action::ToggleBollinger,
+        Overlay::Vwap => Action::ToggleVwap,
+    }
+}
+
+fn overlay_name(overlay: Overlay) -> String {
+    match overlay {
+        Overlay::Sma => format!("SMA {}", indicators::SMA_PERIOD),
+        Overlay::Ema => format!("EMA {}/{}", indicators::FAST_EMA, indicators::SLOW_EMA),
+        Overlay::Bollinger => format!(
+            "BB {},{}",
+            indicators::BOLLINGER_PERIOD,
+            indicators::BOLLINGER_WIDTH
+        ),
+        Overlay::Vwap => "VWAP".to_string(),
+    }
+}
+
+/// The lines an overlay draws over `candles`, with their legend names and colors.
+fn overlay_lines(
+    overlay: Overlay,
+    candles: &[chart::Candle],
+    closes: &[f64],
+) -> Vec<(String, Vec<Option<f64>>, Color)> {
+    match overlay {
+        Overlay::Sma => vec![(
+            overlay_name(overlay),
+            indicators::sma(closes, indicators::SMA_PERIOD),
+            Color::LightBlue,
+        )],
+        Overlay::Ema => vec![
+            (
+                format!("EMA {}", indicators::FAST_EMA),
+                indicators::ema(closes, indicators::FAST_EMA),
+                Color::Yellow,
+            ),
+            (
+                format!("EMA {}", indicators::SLOW_EMA),
+                indicators::ema(closes, indicators::SLOW_EMA),
+                Color::Magenta,
+            ),
+        ],
+        Overlay::Bollinger => {
+            let (lower, upper) = indicators::bollinger(
+                closes,
+                indicators::BOLLINGER_PERIOD,
+                indicators::BOLLINGER_WIDTH,
+            );
+            // One legend entry for both bands.
+            vec![
+                (overlay_name(overlay), upper, Color::Gray),
+                (String::new(), lower, Color::Gray),
+            ]
+        }
+        Overlay::Vwap => vec![("VWAP".to_string(), indicators::vwap(candles), Color::Cyan)],
+    }
+}
+
+/// helper function to create a centered rect using up certain percentage of the available rect `r`
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::vertical([
+        Constraint::Percentage((100 - percent_y) / 2),
+        Constraint::Percentage(percent_y),
+        Constraint::Percentage((100 - percent_y) / 2),
+    ])
+    .split(r);
+
+    Layout::horizontal([
+        Constraint::Percentage((100 - percent_x) / 2),
+        Constraint::Percentage(percent_x),
+        Constraint::Percentage((100 - percent_x) / 2),
+    ])
+    .split(popup_layout[1])[1]
+}
+
+/// Draws the ticker table into `area`, sorted and scrolled as `app` specifies.
+/// What a table row shows.
+enum RowKind<'a> {
+    Market,
+    /// The aggregate of a group with several markets, with the group's key.
+    Group(&'a str),
+    /// A market listed under its expanded group.
+    Member,
+}
+
+pub fn render_table(f: &mut Frame, app: &mut App, area: Rect, tickers: Arc<Mutex<TickerMap>>) {
+    let tickers = tickers.lock().unwrap();
+    let converter = app.converter(&tickers);
+    let mut tickers: Vec<&HrTicker> = tickers.values().filter(|t| app.shows(&t.s)).collect();
+    let groups = if app.group_by_base {
+        group::group(app.exchange, tickers.iter().copied())
+    } else {
+        Vec::new()
+    };
+    let tickers: Vec<(&HrTicker, RowKind)> = if app.group_by_base {
+        let mut totals: Vec<&HrTicker> = groups.iter().map(|group| &group.total).collect();
+        app.sort_tickers(&mut totals);
+        let mut rows = Vec::new();
+        for total in totals {
+            let Some(group) = groups.iter().find(|group| group.key == total.s) else {
+                continue;
+            };
+            if group.members.len() == 1 {
+                rows.push((group.members[0], RowKind::Market));
+                continue;
+            }
+            rows.push((total, RowKind::Group(&group.key)));
+            if app.expanded_groups.contains(&group.key) {
+                let mut members = group.members.clone();
+                app.sort_tickers(&mut members);
+                rows.extend(members.into_iter().map(|member| (member, RowKind::Member)));
+            }
+        }
+        rows
+    } else {
+        app.sort_tickers(&mut tickers);
+        tickers
+            .into_iter()
+            .map(|ticker| (ticker, RowKind::Market))
+            .collect()
+    };
+    app.visible_symbols = tickers
+        .iter()
+        .map(|(ticker, kind)| match kind {
+            RowKind::Group(key) => groups
+                .iter()
+                .find(|group| group.key == *key)
+                .map_or_else(|| ticker.s.clone(), |group| group.members[0].s.clone()),
+            _ => ticker.s.clone(),
+        })
+        .collect();
+    app.visible_groups = tickers
+        .iter()
+        .map(|(_, kind)| match kind {
+            RowKind::Group(key) => Some(key.to_string()),
+            _ => None,
+        })
+        .collect();
+    app.ticker_length = tickers.len();
+    app.restore_selection();
+    // Borders and the header row take three lines.
+    app.page_size = (area.height.saturating_sub(3) as usize / ITEM_HEIGHT).max(1);
+    app.clamp_viewport();
+    let header_style = Style::default()
+        .fg(app.colors.header_fg)
+        .bg(app.colors.header_bg);
+
+    // Determine the style for the sorted column
+    let sort_column_style = Style::default()
+        .add_modifier(Modifier::BOLD)
+        .fg(Color::Yellow);
+
+    // The block's borders take two columns.
+    let columns = app.visible_columns(area.width.saturating_sub(2));
+    let hidden_left = app.column_offset > 0;
+    let hidden_right = columns.len() + app.column_offset < app.columns().len();
+
+    // Create the header with highlighting on the sorted column
+    let header = columns
+        .iter()
+        .map(|column| {
+            let title = match column {
+                SortColumn::PercentChange => app.change_basis.title(),
+                SortColumn::Script => &app.config.script.column_title,
+                column => column.title(),
+            };
+            Cell::from(title).style(if app.sort_column == *column {
+                sort_column_style
+            } else {
+                header_style
+            })
+        })
+        .collect::<Row>()
+        .style(header_style)
+        .height(1);
+
+    let selected_style = Style::default()
+        .add_modifier(Modifier::REVERSED)
+        .fg(app.colors.selected_style_fg);
+
+    let market = Arc::clone(&app.market_data);
+    let market = market.lock().unwrap();
+    // Only the rows in the viewport are built; with hundreds of symbols the rest would be
+    // formatted every frame for nothing.
+    let offset = app.state.offset();
+    let rows = tickers
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(app.page_size)
+        .map(|(i, (ticker, kind))| {
+            let color = if i % 2 == 0 {
+                app.colors.normal_row_color
+            } else {
+                app.colors.alt_row_color
+            };
+
+            let last_price_color = if ticker.c > ticker.previous_price {
+                app.colors.up
+            } else if ticker.c < ticker.previous_price {
+                app.colors.down
+            } else {
+                app.colors.row_fg
+            };
+
+            let color = if app.changed_while_paused(&ticker.s) {
+                app.colors.header_bg
+            } else {
+                app.flash(ticker, color)
+            };
+
+            let (symbol, mut row_style) = if app.is_pinned(&ticker.s) {
+                (
+                    format!("★ {}", ticker.s),
+                    Style::default()
+                        .fg(app.colors.pinned_fg)
+                        .bg(color)
+                        .add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (
+                    ticker.s.clone(),
+                    Style::default().fg(app.colors.row_fg).bg(color),
+                )
+            };
+            let symbol = match kind {
+                RowKind::Market => symbol,
+                RowKind::Group(key) => {
+                    row_style = row_style.add_modifier(Modifier::BOLD);
+                    let marker = if app.expanded_groups.contains(*key) {
+                        '▾'
+                    } else {
+                        '▸'
+                    };
+                    format!("{} {}", marker, key)
+                }
+                RowKind::Member => format!("  {}", symbol),
+            };
+            let symbol = if app.is_stale(ticker) {
+                row_style = row_style.fg(Color::DarkGray).add_modifier(Modifier::DIM);
+                format!("◌ {}", symbol)
+            } else {
+                symbol
+            };
+
+            let market = app.market_data(&market, &ticker.s);
+            columns
+                .iter()
+                .map(|column| match column {
+                    SortColumn::Symbol => match app.config.notes.get(&ticker.s) {
+                        // The first tag as a chip after the symbol, cut off by the column.
+                        Some(note) if !note.tags.is_empty() => Cell::from(Line::from(vec![
+                            Span::raw(format!("{} ", symbol)),
+                            Span::styled(
+                                format!("#{}", note.tags[0]),
+                                Style::default()
+                                    .fg(app.colors.header_fg)
+                                    .bg(app.colors.header_bg),
+                            ),
+                        ])),
+                        _ => Cell::from(symbol.clone()),
+                    },
+                    SortColumn::Last => {
+                        let mut text = price_text(app, &converter, *column, ticker, ticker.c);
+                        if app.config.accessibility.enabled {
+                            let tick = ticker.c as f64 - ticker.previous_price as f64;
+                            text = format!("{} {}", text, accessibility::glyph(tick));
+                        }
+                        Cell::from(text).style(Style::default().fg(last_price_color))
+                    }
+                    SortColumn::Open => {
+                        Cell::from(price_text(app, &converter, *column, ticker, ticker.o))
+                    }
+                    SortColumn::High => {
+                        Cell::from(price_text(app, &converter, *column, ticker, ticker.h))
+                    }
+                    SortColumn::Low => {
+                        Cell::from(price_text(app, &converter, *column, ticker, ticker.l))
+                    }
+                    SortColumn::PercentChange => percent_cell(app, app.change_basis.change(ticker)),
+                    SortColumn::Rsi | SortColumn::EmaTrend => indicator_cell(app, *column, ticker),
+                    SortColumn::Change1m | SortColumn::Change5m | SortColumn::Change15m => {
+                        let window = column.window_ms().unwrap_or(0);
+                        percent_cell(app, ticker.rolling_change(window))
+                    }
+                    column => Cell::from(app.cell_text(*column, ticker, market)),
+                })
+                .collect::<Row>()
+                .style(row_style)
+                .height(1)
+        })
+        .collect::<Vec<Row>>();
+
+    let mut block = pane_block(
+        app,
+        Pane::Table,
+        format!(
+            "Crypto Tickers — {}{}{}{}{}{}{}",
+            app.exchange.name(),
+            match app.denomination {
+                Denomination::Quote => String::new(),
+                denomination => format!(
+                    " [in {}]",
+                    denomination.name(app.config.denomination.fiat.as_deref())
+                ),
+            },
+            if app.watchlist_only {
+                " [WATCHLIST]"
+            } else {
+                ""
+            },
+            match &app.tag_filter {
+                Some(tag) => format!(" [#{}]", tag),
+                None => String::new(),
+            },
+            if app.paused { " [PAUSED]" } else { "" },
+            if hidden_left { " ◀" } else { "" },
+            if hidden_right { " ▶" } else { "" }
+        ),
+    );
+    if !app.config.watchlists.is_empty() {
+        block = block.title(watchlist_tabs(app));
+    }
+    let table = Table::new(rows, vec![Constraint::Length(COLUMN_WIDTH); columns.len()])
+        .header(header)
+        .block(block)
+        .highlight_style(selected_style)
+        .highlight_spacing(HighlightSpacing::default());
+
+    // The table only holds the viewport's rows, so its state is relative to the offset.
+    let mut state = TableState::default().with_selected(app.state.selected().map(|i| i - offset));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+/// `price` of `ticker` in the chosen denomination, or `-` while it cannot be converted.
+fn price_text(
+    app: &App,
+    converter: &Option<Converter>,
+    column: SortColumn,
+    ticker: &HrTicker,
+    price: f32,
+) -> String {
+    let format = &app.config.format;
+    match converter {
+        Some(converter) => converter.convert(&ticker.s, price).map_or_else(
+            || "-".to_string(),
+            |price| format.column(column, price, |price| format::significant(price, 6)),
+        ),
+        None => format.column(column, price as f64, |_| price.to_string()),
+    }
+}
+
+/// A percent change colored on the theme's gradient, or `-` when unknown.
+fn percent_cell(app: &App, percent: Option<f32>) -> Cell<'static> {
+    let Some(percent) = percent else {
+        return Cell::from("-");
+    };
+    let gradient = &app.config.theme.percent_gradient;
+    let mut style = Style::default().fg(gradient.color(percent));
+    if gradient.is_bold(percent) {
+        style = style.add_modifier(Modifier::BOLD);
+    }
+    let format = &app.config.format;
+    let text = format.localize(&format.derived(percent as f64));
+    if app.config.accessibility.enabled {
+        return Cell::from(accessibility::signed(&text, percent as f64)).style(style);
+    }
+    Cell::from(text).style(style)
+}
+
+/// RSI colored when overbought (down) or oversold (up), and the EMA trend in its direction.
+fn indicator_cell(app: &App, column: SortColumn, ticker: &HrTicker) -> Cell<'static> {
+    let text = app.cell_text(column, ticker, None);
+    let indicators = app.indicators_for(&ticker.s).unwrap_or_default();
+    let color = match column {
+        SortColumn::Rsi => match indicators.rsi {
+            Some(rsi) if rsi >= 70.0 => Some(app.colors.down),
+            Some(rsi) if rsi <= 30.0 => Some(app.colors.up),
+            _ => None,
+        },
+        _ => match indicators.trend {
+            Some(Trend::Bullish) => Some(app.colors.up),
+            Some(Trend::Bearish) => Some(app.colors.down),
+            None => None,
+        },
+    };
+    match color {
+        Some(color) => Cell::from(text).style(Style::default().fg(color)),
+        None => Cell::from(text),
+    }
+}
+
+/// Scrollbar of the table, derived from the offset its last render scrolled to: the thumb
+/// spans the visible rows.
+fn render_scrollbar(f: &mut Frame, app: &App, area: Rect) {
+    let rows = app.ticker_length * ITEM_HEIGHT;
+    let page = app.page_size * ITEM_HEIGHT;
+    // One position per offset the table can scroll to.
+    let positions = if rows == 0 {
+        0
+    } else {
+        rows.saturating_sub(page) + 1
+    };
+    let mut state = ScrollbarState::new(positions)
+        .position(app.state.offset() * ITEM_HEIGHT)
+        .viewport_content_length(page);
+    f.render_stateful_widget(
+        Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None),
+        area.inner(Margin {
+            vertical: 1,
+            horizontal: 1,
+        }),
+        &mut state,
+    );
+}
+
+fn render_comparison(f: &mut Frame, app: &mut App, area: Rect) {
+    let comparison = Arc::clone(&app.comparison);
+    let comparison = comparison.lock().unwrap();
+    let mut exchanges = vec![app.exchange];
+    exchanges.extend(
+        app.config
+            .compare_exchanges
+            .iter()
+            .filter(|e| **e != app.exchange),
+    );
+    let min_exchanges = exchanges.len().min(2);
+
+    let header_style = Style::default()
+        .fg(app.colors.header_fg)
+        .bg(app.colors.header_bg);
+    let header = std::iter::once("Market".to_string())
+        .chain(exchanges.iter().map(|e| e.name().to_string()))
+        .chain(std::iter::once("Spread (bps)".to_string()))
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(header_style)
+        .height(1);
+
+    let rows = comparison
+        .rows(min_exchanges)
+        .into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let color = if i % 2 == 0 {
+                app.colors.normal_row_color
+            } else {
+                app.colors.alt_row_color
+            };
+            let spread = row
+                .spread_bps
+                .map(|bps| app.config.format.derived(bps))
+                .unwrap_or_else(|| "-".to_string());
+            std::iter::once(row.key.to_string())
+                .chain(exchanges.iter().map(|e| {
+                    row.prices
+                        .get(e)
+                        .map(|price| price.to_string())
+                        .unwrap_or_else(|| "-".to_string())
+                }))
+                .chain(std::iter::once(spread))
+                .map(Cell::from)
+                .collect::<Row>()
+                .style(Style::default().fg(app.colors.row_fg).bg(color))
+        })
+        .collect::<Vec<Row>>();
+
+    let widths = vec![Constraint::Length(14); exchanges.len() + 2];
+    let len = rows.len();
+    if let Some(i) = app.comparison_state.selected() {
+        app.comparison_state
+            .select((len > 0).then(|| i.min(len - 1)));
+    }
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(pane_block(app, Pane::Comparison, "Exchange Comparison"))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(app.colors.selected_style_fg),
+        );
+    f.render_stateful_widget(table, area, &mut app.comparison_state);
+}
+
+fn render_portfolio(f: &mut Frame, app: &mut App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let tickers = tickers.lock().unwrap();
+    let header = [
+        "Symbol", "Quantity", "Cost", "Price", "Value", "P&L", "P&L %", "Alloc %",
+    ]
+    .into_iter()
+    .map(Cell::from)
+    .collect::<Row>()
+    .style(
+        Style::default()
+            .fg(app.colors.header_fg)
+            .bg(app.colors.header_bg),
+    );
+
+    let format = &app.config.format;
+    let rows = app
+        .portfolio
+        .positions(&tickers)
+        .into_iter()
+        .enumerate()
+        .map(|(i, position)| {
+            let color = if i % 2 == 0 {
+                app.colors.normal_row_color
+            } else {
+                app.colors.alt_row_color
+            };
+            let pnl_color = if position.unrealized_pnl >= 0.0 {
+                app.colors.up
+            } else {
+                app.colors.down
+            };
+            Row::new(vec![
+                Cell::from(position.holding.symbol.clone()),
+                Cell::from(position.holding.quantity.to_string()),
+                Cell::from(position.holding.cost_basis.to_string()),
+                Cell::from(position.price.map_or("-".to_string(), |p| p.to_string())),
+                Cell::from(format.derived(position.market_value)),
+                Cell::from(format.derived(position.unrealized_pnl))
+                    .style(Style::default().fg(pnl_color)),
+                Cell::from(format.derived(position.pnl_percent))
+                    .style(Style::default().fg(pnl_color)),
+                Cell::from(format.derived(position.allocation)),
+            ])
+            .style(Style::default().fg(app.colors.row_fg).bg(color))
+        })
+        .collect::<Vec<Row>>();
+
+    let table = Table::new(rows, vec![Constraint::Length(12); 8])
+        .header(header)
+        .block(pane_block(app, Pane::Portfolio, "Portfolio"))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(app.colors.selected_style_fg),
+        );
+    f.render_stateful_widget(table, area, &mut app.portfolio_state);
+}
+
+fn render_paper(f: &mut Frame, app: &mut App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let tickers = tickers.lock().unwrap();
+    let rects = Layout::vertical([Constraint::Min(5), Constraint::Length(12)]).split(area);
+    let header_style = Style::default()
+        .fg(app.colors.header_fg)
+        .bg(app.colors.header_bg);
+    let format = &app.config.format;
+
+    let header = ["Symbol", "Quantity", "Avg Price", "Last", "Unrealized"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(header_style);
+    let rows = app
+        .paper
+        .positions
+        .iter()
+        .enumerate()
+        .map(|(i, (symbol, position))| {
+            let color = if i % 2 == 0 {
+                app.colors.normal_row_color
+            } else {
+                app.colors.alt_row_color
+            };
+            let last = tickers.get(symbol).map(|t| t.c as f64);
+            let pnl = last.map(|price| position.unrealized_pnl(price));
+            let pnl_color = if pnl.unwrap_or(0.0) >= 0.0 {
+                app.colors.up
+            } else {
+                app.colors.down
+            };
+            Row::new(vec![
+                Cell::from(symbol.clone()),
+                Cell::from(position.quantity.to_string()),
+                Cell::from(format.derived(position.average_price)),
+                Cell::from(last.map_or("-".to_string(), |p| p.to_string())),
+                Cell::from(pnl.map_or("-".to_string(), |pnl| format.derived(pnl)))
+                    .style(Style::default().fg(pnl_color)),
+            ])
+            .style(Style::default().fg(app.colors.row_fg).bg(color))
+        })
+        .collect::<Vec<Row>>();
+
+    let mut title = format!(
+        "Paper Trading — realized {} | unrealized {}",
+        format.derived(app.paper.realized_pnl),
+        format.derived(app.paper.unrealized_pnl(&tickers)),
+    );
+    if let Some(status) = &app.paper_status {
+        title.push_str(&format!(" | {}", status));
+    }
+    let table = Table::new(rows, vec![Constraint::Length(14); 5])
+        .header(header)
+        .block(pane_block(app, Pane::Paper, title))
+        .highlight_style(
+            Style::default()
+                .add_modifier(Modifier::REVERSED)
+                .fg(app.colors.selected_style_fg),
+        );
+    f.render_stateful_widget(table, rects[0], &mut app.paper_state);
+
+    let header = ["Time", "Symbol", "Side", "Quantity", "Price", "Fee"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(header_style);
+    let rows = app
+        .paper
+        .trades
+        .iter()
+        .rev()
+        .map(|trade| {
+            let side_color = match trade.side {
+                Side::Buy => app.colors.up,
+                Side::Sell => app.colors.down,
+            };
+            Row::new(vec![
+                Cell::from(format::timestamp(trade.time)),
+                Cell::from(trade.symbol.clone()),
+                Cell::from(trade.side.name()).style(Style::default().fg(side_color)),
+                Cell::from(trade.quantity.to_string()),
+                Cell::from(app.config.format.derived(trade.price)),
+                Cell::from(app.config.format.derived(trade.fee)),
+            ])
+            .style(Style::default().fg(app.colors.row_fg))
+        })
+        .collect::<Vec<Row>>();
+    let widths = [
+        Constraint::Length(20),
+        Constraint::Length(14),
+        Constraint::Length(6),
+        Constraint::Length(14),
+        Constraint::Length(14),
+        Constraint::Length(14),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Trades"));
+    f.render_widget(table, rects[1]);
+}
+
+fn render_account(f: &mut Frame, app: &App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let Some(account) = &app.account else {
+        let message = Paragraph::new(
+            "No Binance API credentials. Set BINANCE_API_KEY and BINANCE_API_SECRET, \
+             or add a [binance] table to credentials.toml in the config directory.",
+        )
+        .style(Style::default().fg(app.colors.row_fg))
+        .wrap(Wrap { trim: true })
+        .block(pane_block(app, Pane::Account, "Binance Account"));
+        f.render_widget(message, area);
+        return;
+    };
+    let account = account.lock().unwrap();
+    let tickers = tickers.lock().unwrap();
+    let format = &app.config.format;
+
+    let header = [
+        "Symbol", "Side", "Size", "Entry", "Last", "Margin", "PnL", "ROE %",
+    ]
+    .into_iter()
+    .map(Cell::from)
+    .collect::<Row>()
+    .style(
+        Style::default()
+            .fg(app.colors.header_fg)
+            .bg(app.colors.header_bg),
+    );
+
+    let rows = account
+        .positions
+        .values()
+        .enumerate()
+        .map(|(i, position)| {
+            let color = if i % 2 == 0 {
+                app.colors.normal_row_color
+            } else {
+                app.colors.alt_row_color
+            };
+            let last = tickers.get(&position.symbol).map(|t| t.c as f64);
+            let pnl = last.map(|price| position.unrealized_pnl(price));
+            let margin = last.and_then(|price| position.margin(price));
+            let roe = pnl
+                .zip(margin)
+                .filter(|(_, margin)| *margin > 0.0)
+                .map(|(pnl, margin)| pnl / margin * 100.0);
+            let pnl_color = if pnl.unwrap_or(0.0) >= 0.0 {
+                app.colors.up
+            } else {
+                app.colors.down
+            };
+            let side = match position.side.as_str() {
+                "BOTH" if position.amount > 0.0 => "LONG",
+                "BOTH" => "SHORT",
+                side => side,
+            };
+            let or_dash = |value: Option<f64>| value.map_or("-".to_string(), |v| format.derived(v));
+            Row::new(vec![
+                Cell::from(position.symbol.clone()),
+                Cell::from(side.to_string()),
+                Cell::from(position.amount.to_string()),
+                Cell::from(position.entry_price.to_string()),
+                Cell::from(last.map_or("-".to_string(), |p| p.to_string())),
+                Cell::from(or_dash(margin)),
+                Cell::from(or_dash(pnl)).style(Style::default().fg(pnl_color)),
+                Cell::from(or_dash(roe)).style(Style::default().fg(pnl_color)),
+            ])
+            .style(Style::default().fg(app.colors.row_fg).bg(color))
+        })
+        .collect::<Vec<Row>>();
+
+    let balances = account
+        .balances
+        .iter()
+        .map(|(asset, balance)| {
+            format!(
+                "{} {} (cross {})",
+                asset,
+                format.derived(balance.wallet),
+                format.derived(balance.cross_wallet)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    let status = match &account.status {
+        AccountStatus::Connecting => "connecting".to_string(),
+        AccountStatus::Live => "live".to_string(),
+        AccountStatus::Error(err) => format!("error: {}", err),
+    };
+    let title = format!("Binance Account [{}] — {}", status, balances);
+    let table = Table::new(rows, vec![Constraint::Length(12); 8])
+        .header(header)
+        .block(pane_block(app, Pane::Account, title));
+    f.render_widget(table, area);
+}
+
+fn render_order_ticket(f: &mut Frame, app: &App) {
+    let Some(ticket) = &app.order_ticket else {
+        return;
+    };
+    let area = centered_rect(40, 20, f.size());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![Line::from(format!("Quantity: {}▏", ticket.input))];
+    lines.push(match &ticket.error {
+        Some(err) => Line::from(err.clone()).style(Style::default().fg(Color::Red)),
+        None => Line::from("Market order at the last price — Enter fill, Esc cancel")
+            .style(Style::default().fg(Color::DarkGray)),
+    });
+    let color = match ticket.side {
+        Side::Buy => app.colors.up,
+        Side::Sell => app.colors.down,
+    };
+    let popup = Paragraph::new(lines)
+        .style(
+            Style::default()
+                .fg(app.colors.row_fg)
+                .bg(app.colors.buffer_bg),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .border_style(Style::default().fg(color))
+                .title(format!("Paper {} {}", ticket.side.name(), ticket.symbol)),
+        );
+    f.render_widget(popup, area);
+}
+
+fn render_detail(f: &mut Frame, app: &App, area: Rect, tickers: Arc<Mutex<TickerMap>>) {
+    let tickers = tickers.lock().unwrap();
+    let ticker = app.selected_symbol().and_then(|symbol| tickers.get(symbol));
+
+    let format = &app.config.format;
+    let number = |value: &dyn ToString| format.localize(&value.to_string());
+    let lines = match ticker {
+        Some(t) => {
+            let mut fields = vec![
+                ("Symbol", t.s.clone()),
+                ("Last price", number(&t.c)),
+                ("Last quantity", number(&t.Q)),
+                ("Price change", number(&t.p)),
+                ("Percent change", number(&format.derived(t.P as f64))),
+                ("Weighted avg", number(&t.w)),
+                ("Open", number(&t.o)),
+                ("High", number(&t.h)),
+                ("Low", number(&t.l)),
+                ("Base volume", number(&t.v)),
+                ("Quote volume", number(&t.q)),
+                ("Trades", number(&t.n)),
+                ("First trade ID", t.F.to_string()),
+                ("Last trade ID", t.L.to_string()),
+                ("Stats open", format.timestamp(t.O)),
+                ("Stats close", format.timestamp(t.C)),
+                ("Event time", format.timestamp(t.E)),
+                (
+                    "Updated",
+                    t.last_updated
+                        .map_or("-".to_string(), |at| format::age(at.elapsed())),
+                ),
+            ];
+            if app.is_stale(t) {
+                let since = t.last_updated.map_or(0, |at| at.elapsed().as_secs());
+                fields.push(("Stale", format!("no update for {}s", since)));
+            }
+            let market = Arc::clone(&app.market_data);
+            let market = market.lock().unwrap();
+            if let Some(m) = app.market_data(&market, &t.s) {
+                if let Some(rank) = m.rank {
+                    fields.push(("Market cap rank", rank.to_string()));
+                }
+                if let Some(cap) = m.market_cap {
+                    fields.push(("Market cap", format::compact(cap)));
+                }
+                if let Some(supply) = m.circulating_supply {
+                    fields.push(("Circulating", format::compact(supply)));
+                }
+            }
+            if let Some(rate) = t.funding_rate {
+                fields.push((
+                    "Funding rate",
+                    format!("{}%", app.config.format.derived(rate as f64 * 100.0)),
+                ));
+            }
+            if let Some(note) = app.config.notes.get(&t.s) {
+                if !note.tags.is_empty() {
+                    let tags: Vec<String> =
+                        note.tags.iter().map(|tag| format!("#{}", tag)).collect();
+                    fields.push(("Tags", tags.join(" ")));
+                }
+                if !note.note.is_empty() {
+                    fields.push(("Note", note.note.clone()));
+                }
+            }
+            fields
+                .into_iter()
+                .map(|(label, value)| Line::from(format!("{:<15} {}", label, value)))
+                .collect()
+        }
+        None => vec![Line::from("Select a row to see its details")],
+    };
+
+    let detail = Paragraph::new(lines)
+        .style(Style::default().fg(app.colors.row_fg))
+        .scroll((app.detail_scroll, 0))
+        .block(pane_block(app, Pane::Detail, "24h Statistics"));
+    f.render_widget(detail, area);
+}
+
+fn render_liquidations(f: &mut Frame, app: &mut App, area: Rect) {
+    let liquidations = Arc::clone(&app.liquidations);
+    let liquidations = liquidations.lock().unwrap();
+    app.liquidations_scroll = app
+        .liquidations_scroll
+        .min(liquidations.recent.len().saturating_sub(1));
+
+    let header = ["Time", "Symbol", "Side", "Quantity", "Price", "Notional"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(
+            Style::default()
+                .fg(app.colors.header_fg)
+                .bg(app.colors.header_bg),
+        );
+
+    let rows = liquidations
+        .recent
+        .iter()
+        .skip(app.liquidations_scroll)
+        .map(|liquidation| {
+            let notional = liquidation.notional();
+            let side_color = if liquidation.side == "SELL" {
+                app.colors.down
+            } else {
+                app.colors.up
+            };
+            let mut style = Style::default().fg(app.colors.row_fg);
+            if notional >= app.config.liquidations.large_notional {
+                style = style
+                    .add_modifier(Modifier::BOLD)
+                    .bg(app.colors.alt_row_color);
+            }
+            Row::new(vec![
+                Cell::from(
+                    format::timestamp(liquidation.time)
+                        .split_once(' ')
+                        .map_or(String::new(), |(_, time)| time.to_string()),
+                ),
+                Cell::from(liquidation.symbol.clone()),
+                Cell::from(liquidation.side.clone()).style(Style::default().fg(side_color)),
+                Cell::from(liquidation.quantity.to_string()),
+                Cell::from(liquidation.price.to_string()),
+                Cell::from(format::compact(notional)),
+            ])
+            .style(style)
+        })
+        .collect::<Vec<Row>>();
+
+    let table = Table::new(rows, vec![Constraint::Length(12); 6])
+        .header(header)
+        .block(pane_block(app, Pane::Liquidations, "Liquidations"));
+    f.render_widget(table, area);
+}
+
+/// Weight used of each Binance REST API in the current minute, with the requests waiting for
+/// room and any backoff Binance asked for.
+fn render_status(f: &mut Frame, app: &App, area: Rect) {
+    let header = ["API", "Used", "Remaining", "Queued", "State"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(
+            Style::default()
+                .fg(app.colors.header_fg)
+                .bg(app.colors.header_bg),
+        );
+
+    let now = ratelimit::now_ms();
+    let rows = ratelimit::budgets()
+        .into_iter()
+        .map(|budget| {
+            let remaining = budget.remaining(now);
+            let (state, color) = match budget.backoff(now) {
+                Some(wait) => (
+                    format!("backing off {}s", wait.as_secs() + 1),
+                    app.colors.down,
+                ),
+                None if budget.queued > 0 => ("throttled".to_string(), app.colors.down),
+                None => ("ok".to_string(), app.colors.up),
+            };
+            Row::new(vec![
+                Cell::from(budget.name),
+                Cell::from(format!("{}/{}", budget.limit - remaining, budget.limit)),
+                Cell::from(remaining.to_string()),
+                Cell::from(budget.queued.to_string()),
+                Cell::from(state).style(Style::default().fg(color)),
+            ])
+            .style(Style::default().fg(app.colors.row_fg))
+        })
+        .collect::<Vec<Row>>();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Min(10),
+        ],
+    )
+    .header(header)
+    .block(pane_block(app, Pane::Status, "Request budget"));
+    f.render_widget(table, area);
+}
+
+fn render_alerts(f: &mut Frame, app: &mut App, area: Rect) {
+    let engine = Arc::clone(&app.alerts);
+    let engine = engine.lock().unwrap();
+
+    let header = ["Symbol", "Condition", "Sinks"]
+        .into_iter()
+        .map(Cell::from)
+        .collect::<Row>()
+        .style(
+            Style::default()
+                .fg(app.colors.header_fg)
+                .bg(app.colors.header_bg),
+        );
+
+    let rows = engine
+        .rules()
+        .iter()
+        .enumerate()
+        .map(|(i, rule)| {
+            let condition = match engine.error(i) {
+                Some(err) => Cell::from(format!("{} — {}", rule.expression(), err))
+                    .style(Style::default().fg(Color::Red)),
+                None => Cell::from(rule.expression()),
+            };
+            Row::new(vec![
+                Cell::from(rule.symbol.clone().unwrap_or_else(|| "*".to_string())),
+                condition,
+                Cell::from(rule.sinks.len().to_string()),
+            ])
+            .style(Style::default().fg(app.colors.row_fg))
+        })
+        .collect::<Vec<Row>>();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(12),
+            Constraint::Min(20),
+            Constraint::Length(5),
+        ],
+    )
+    .header(header)
+    .block(pane_block(app, Pane::Alerts, "Alerts"))
+    .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    f.render_stateful_widget(table, area, &mut app.alerts_state);
+}
+
+fn render_rule_editor(f: &mut Frame, app: &App) {
+    let Some(editor) = &app.rule_editor else {
+        return;
+    };
+    let area = centered_rect(60, 20, f.size());
+    f.render_widget(Clear, area);
+
+    let mut lines = vec![Line::from(format!("{}▏", editor.input)), Line::from("")];
+    match &editor.error {
+        Some((offset, message)) => {
+            let column = editor.input[..*offset].chars().count();
+            lines[1] = Line::from(format!("{}^", " ".repeat(column)))
+                .style(Style::default().fg(Color::Red));
+            lines.push(Line::from(message.clone()).style(Style::default().fg(Color::Red)));
+        }
+        None => lines.push(
+            Line::from(
+                "[SYMBOL:] condition, e.g. BTCUSDT: c crosses 70000 — Enter save, Esc cancel",
+            )
+            .style(Style::default().fg(Color::DarkGray)),
+        ),
+    }
+
+    let title = if editor.index.is_some() {
+        "Edit alert rule"
+    } else {
+        "New alert rule"
+    };
+    let popup = Paragraph::new(lines)
+        .style(
+            Style::default()
+                .fg(app.colors.row_fg)
+                .bg(app.colors.buffer_bg),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .title(title),
+        );
+    f.render_widget(popup, area);
+}
+
+fn render_command_palette(f: &mut Frame, app: &mut App) {
+    let Some(palette) = &mut app.command_palette else {
+        return;
+    };
+    let area = centered_rect(60, 60, f.size());
+    f.render_widget(Clear, area);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .title("Command palette — Enter run, Esc close")
+        .style(
+            Style::default()
+                .fg(app.colors.row_fg)
+                .bg(app.colors.buffer_bg),
+        );
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rects = Layout::vertical([Constraint::Length(2), Constraint::Min(0)]).split(inner);
+    f.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!("> {}▏", palette.input)),
+            Line::from(format!("{} matches", palette.matches.len()))
+                .style(Style::default().fg(Color::DarkGray)),
+        ]),
+        rects[0],
+    );
+
+    let width = rects[1].width as usize;
+    let items: Vec<ListItem> = palette
+        .matches
+        .iter()
+        .map(|command| {
+            let label = command.label();
+            let keys = match command {
+                Command::Action(action) => app.keymap.keys_for(*action),
+                _ => String::new(),
+            };
+            let padding = width.saturating_sub(label.chars().count() + keys.chars().count() + 2);
+            ListItem::new(Line::from(vec![
+                Span::raw(label),
+                Span::raw(" ".repeat(padding)),
+                Span::styled(keys, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(Style::default().fg(app.colors.selected_style_fg))
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, rects[1], &mut palette.state);
+}
+
+fn render_note_editor(f: &mut Frame, app: &App) {
+    let Some(editor) = &app.note_editor else {
+        return;
+    };
+    let area = centered_rect(60, 20, f.size());
+    f.render_widget(Clear, area);
+    let lines = vec![
+        Line::from(format!("{}▏", editor.input)),
+        Line::from("tag, tag: note — Enter save, Esc cancel; empty removes the note")
+            .style(Style::default().fg(Color::DarkGray)),
+    ];
+    let popup = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .style(
+            Style::default()
+                .fg(app.colors.row_fg)
+                .bg(app.colors.buffer_bg),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .title(format!("Note for {}", editor.symbol)),
+        );
+    f.render_widget(popup, area);
+}
+
+fn render_exchange_switcher(f: &mut Frame, app: &mut App) {
+    let Some(switcher) = &mut app.exchange_switcher else {
+        return;
+    };
+    // Borders and one line per exchange.
+    let screen = f.size();
+    let (width, height) = (
+        SWITCHER_WIDTH.min(screen.width),
+        (Exchange::ALL.len() as u16 + 2).min(screen.height),
+    );
+    let area = Rect::new(
+        screen.x + (screen.width - width) / 2,
+        screen.y + (screen.height - height) / 2,
+        width,
+        height,
+    );
+    f.render_widget(Clear, area);
+    let items: Vec<ListItem> = Exchange::ALL
+        .iter()
+        .map(|exchange| {
+            let style = if *exchange == app.exchange {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(switcher::label(*exchange)).style(style)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Double)
+                .title("Switch exchange — Enter connect, Esc close"),
+        )
+        .style(
+            Style::default()
+                .fg(app.colors.row_fg)
+                .bg(app.colors.buffer_bg),
+        )
+        .highlight_style(Style::default().fg(app.colors.selected_style_fg))
+        .highlight_symbol("▶ ");
+    f.render_stateful_widget(list, area, &mut switcher.state);
+}
+
+fn level_style(level: Level) -> (&'static str, Color) {
+    match level {
+        Level::Info => ("Info", Color::Green),
+        Level::Warning => ("Warning", Color::Yellow),
+        Level::Error => ("Error", Color::Red),
+    }
+}
+
+/// Recent notifications stacked in the top-right corner, newest on top, over everything else.
+fn render_toasts(f: &mut Frame, app: &App) {
+    let screen = f.size();
+    let width = TOAST_WIDTH.min(screen.width);
+    let mut y = screen.y;
+    for toast in app.toasts().take(MAX_TOASTS) {
+        if y + 3 > screen.bottom() {
+            break;
+        }
+        let area = Rect::new(screen.right() - width, y, width, 3);
+        y += 3;
+        let (level, color) = level_style(toast.level);
+        let title = match toast.alert {
+            Some(_) => format!(
+                "{} — {} snooze",
+                level,
+                app.keymap.keys_for(Action::SnoozeAlert)
+            ),
+            None => level.to_string(),
+        };
+        let popup = Paragraph::new(Line::from(toast.message.as_str()))
+            .style(
+                Style::default()
+                    .fg(app.colors.row_fg)
+                    .bg(app.colors.buffer_bg),
+            )
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(color))
+                    .title(title),
+            );
+        f.render_widget(Clear, area);
+        f.render_widget(popup, area);
+    }
+}
+
+/// Every notification kept, newest first.
+fn render_notifications(f: &mut Frame, app: &mut App, area: Rect) {
+    app.notifications_scroll = app
+        .notifications_scroll
+        .min(app.notifications.len().saturating_sub(1));
+    let rows: Vec<Row> = app
+        .notifications
+        .iter()
+        .rev()
+        .skip(app.notifications_scroll)
+        .map(|notification| {
+            let (level, color) = level_style(notification.level);
+            Row::new(vec![
+                Cell::from(
+                    format::timestamp(notification.time)
+                        .split_once(' ')
+                        .map_or(String::new(), |(_, time)| time.to_string()),
+                ),
+                Cell::from(level).style(Style::default().fg(color)),
+                Cell::from(notification.message.clone()),
+            ])
+            .style(Style::default().fg(app.colors.row_fg))
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        ["Time", "Level", "Message"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(
+                Style::default()
+                    .fg(app.colors.header_fg)
+                    .bg(app.colors.header_bg),
+            ),
+    )
+    .block(pane_block(app, Pane::Notifications, "Notifications"));
+    f.render_widget(table, area);
+}
+
+/// Triggered alerts from the alert log, newest first, of one symbol once Enter filters them.
+fn render_alert_history(f: &mut Frame, app: &mut App, area: Rect) {
+    let engine = Arc::clone(&app.alerts);
+    let engine = engine.lock().unwrap();
+    let symbol = app.alert_history_symbol.as_deref();
+    let count = engine.history.alerts(symbol).count();
+    app.alert_history_scroll = app.alert_history_scroll.min(count.saturating_sub(1));
+    let rows: Vec<Row> = engine
+        .history
+        .alerts(symbol)
+        .skip(app.alert_history_scroll)
+        .map(|alert| {
+            Row::new(vec![
+                Cell::from(app.config.format.timestamp(alert.time)),
+                Cell::from(alert.symbol.clone()),
+                Cell::from(alert.price.to_string()),
+                Cell::from(alert.rule.clone()),
+            ])
+            .style(Style::default().fg(app.colors.row_fg))
+        })
+        .collect();
+    let title = match symbol {
+        Some(symbol) => format!("Alert history — {}", symbol),
+        None => "Alert history".to_string(),
+    };
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(22),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        ["Time", "Symbol", "Price", "Rule"]
+            .into_iter()
+            .map(Cell::from)
+            .collect::<Row>()
+            .style(
+                Style::default()
+                    .fg(app.colors.header_fg)
+                    .bg(app.colors.header_bg),
+            ),
+    )
+    .block(pane_block(app, Pane::AlertHistory, title));
+    f.render_widget(table, area);
+}
+
+/// Headlines, newest first; the ones mentioning a symbol the table lists are highlighted with
+/// the symbols' base assets.
+/// The panes of the plugins side by side, each under its plugin's title.
+fn render_plugins(f: &mut Frame, app: &App, area: Rect) {
+    let block = pane_block(app, Pane::Plugins, "Plugins");
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if app.plugins.panes.is_empty() {
+        let message = if app.plugins.is_empty() {
+            "No plugins with a pane loaded"
+        } else {
+            "Loading…"
+        };
+        f.render_widget(
+            Paragraph::new(message).style(Style::default().fg(app.colors.row_fg)),
+            inner,
+        );
+        return;
+    }
+    let areas = Layout::horizontal(vec![Constraint::Fill(1); app.plugins.panes.len()]).split(inner);
+    for ((name, pane), area) in app.plugins.panes.iter().zip(areas.iter()) {
+        let title = if pane.title.is_empty() {
+            name
+        } else {
+            &pane.title
+        };
+        let lines: Vec<Line> = pane
+            .lines
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect();
+        f.render_widget(
+            Paragraph::new(lines)
+                .style(Style::default().fg(app.colors.row_fg))
+                .block(
+                    Block::default()
+                        .title(title.as_str())
+                        .title_style(Style::default().add_modifier(Modifier::BOLD)),
+                ),
+            *area,
+        );
+    }
+}
+
+fn render_news(f: &mut Frame, app: &mut App, area: Rect) {
+    let block = pane_block(app, Pane::News, "News");
+    if app.news.headlines.is_empty() {
+        let message = if !app.config.news.has_sources() {
+            "No news feeds configured"
+        } else if app.news.is_loading() {
+            "Loading…"
+        } else {
+            "No headlines"
+        };
+        f.render_widget(
+            Paragraph::new(message)
+                .style(Style::default().fg(app.colors.row_fg))
+                .block(block),
+            area,
+        );
+        return;
+    }
+    let bases: HashSet<String> = app
+        .visible_symbols
+        .iter()
+        .filter_map(|symbol| {
+            let market = symbol.split('_').next().unwrap_or(symbol);
+            app.exchange.instrument(market)
+        })
+        .map(|instrument| instrument.base)
+        .collect();
+    let rows: Vec<Row> = app
+        .news
+        .headlines
+        .iter()
+        .map(|headline| {
+            let time = headline
+                .published
+                .map_or(String::new(), |time| app.config.format.timestamp(time));
+            let mentions = headline.mentions(&bases);
+            let (title, style) = if mentions.is_empty() {
+                (
+                    headline.title.clone(),
+                    Style::default().fg(app.colors.row_fg),
+                )
+            } else {
+                (
+                    format!("[{}] {}", mentions.join(" "), headline.title),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                )
+            };
+            Row::new(vec![
+                Cell::from(time),
+                Cell::from(headline.source.clone()),
+                Cell::from(title),
+            ])
+            .style(style)
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(22),
+            Constraint::Length(16),
+            Constraint::Fill(1),
+        ],
+    )
+    .block(block)
+    .highlight_style(
+        Style::default()
+            .add_modifier(Modifier::REVERSED)
+            .fg(app.colors.selected_style_fg),
+    );
+    f.render_stateful_widget(table, area, &mut app.news_state);
+}
+
+/// Bordered block for `pane`, highlighted when it has focus.
+fn pane_block<'a>(app: &App, pane: Pane, title: impl Into<Line<'a>>) -> Block<'a> {
+    let block = Block::default().borders(Borders::ALL).title(title);
+    if app.focus == pane && app.visible_panes().len() > 1 {
+        block.border_style(Style::default().fg(app.colors.focused_border_color))
+    } else {
+        block
+    }
+}
+
+/// `1 Table │ 2 Trading │ ...`, with the shown workspace highlighted.
+fn workspace_tabs(app: &App) -> Line<'static> {
+    let names = std::iter::once("Table")
+        .chain(app.config.workspaces.iter().map(|w| w.name.as_str()))
+        .take(9);
+    let mut spans = Vec::new();
+    for (i, name) in names.enumerate() {
+        if i > 0 {
+            spans.push(Span::raw("│"));
+        }
+        let style = if i == app.workspace {
+            Style::default()
+                .fg(app.colors.selected_style_fg)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(app.colors.row_fg)
+        };
+        spans.push(Span::styled(format!(" {} {} ", i + 1, name), style));
+    }
+    Line::from(spans)
+}
+
+/// `All │ majors │ ...` on the right of the table's border, with the shown watchlist
+/// highlighted.
+fn watchlist_tabs(app: &App) -> Line<'static> {
+    let names = std::iter::once("All").chain(app.config.watchlists.iter().map(|w| w.name.as_str()));
+    let mut spans = Vec::new();
+    for (i, name) in names.enumerate() {
+        if i > 0 {
+            spans.push(Span::raw("│"));
+        }
+        let style = if i == app.watchlist {
+            Style::default()
+                .fg(app.colors.selected_style_fg)
+                .add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Style::default().fg(app.colors.row_fg)
+        };
+        spans.push(Span::styled(format!(" {} ", name), style));
+    }
+    Line::from(spans).alignment(Alignment::Right)
+}
+
+/// Market breadth and totals of the symbols the table lists, on one line.
+fn render_summary(f: &mut Frame, app: &App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let tickers = tickers.lock().unwrap();
+    let summary = summary::summarize(
+        app.exchange,
+        tickers.values().filter(|ticker| app.shows(&ticker.s)),
+        tickers.values(),
+    );
+    let format = &app.config.format;
+    let plain = Style::default().fg(app.colors.row_fg);
+    let change_color = |change: f64| {
+        if change >= 0.0 {
+            app.colors.up
+        } else {
+            app.colors.down
+        }
+    };
+    let mut spans = Vec::new();
+    for widget in &app.config.summary.widgets {
+        if !spans.is_empty() {
+            spans.push(Span::styled(
+                "  │  ",
+                Style::default().fg(app.colors.footer_border_color),
+            ));
+        }
+        match widget {
+            SummaryWidget::Breadth => {
+                spans.push(Span::styled(
+                    format!("▲ {}", summary.gainers),
+                    Style::default().fg(app.colors.up),
+                ));
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!("▼ {}", summary.losers),
+                    Style::default().fg(app.colors.down),
+                ));
+            }
+            SummaryWidget::AverageChange => match summary.average_change {
+                Some(change) => spans.push(Span::styled(
+                    format!("Avg {}%", format.localize(&format!("{:+.2}", change))),
+                    Style::default().fg(change_color(change)),
+                )),
+                None => spans.push(Span::styled("Avg -", plain)),
+            },
+            SummaryWidget::QuoteVolume => spans.push(Span::styled(
+                format!(
+                    "Vol ${}",
+                    format.localize(&format::compact(summary.quote_volume))
+                ),
+                plain,
+            )),
+            SummaryWidget::BtcDominance => spans.push(Span::styled(
+                match *app.global.lock().unwrap() {
+                    Some(global) => format!(
+                        "BTC.D {}%",
+                        format.localize(&format!("{:.1}", global.btc_dominance))
+                    ),
+                    None => "BTC.D -".to_string(),
+                },
+                plain,
+            )),
+            SummaryWidget::FuturesVolume => spans.push(Span::styled(
+                format!(
+                    "Futures vol {}",
+                    summary.futures_volume.map_or("-".to_string(), |volume| {
+                        format!("${}", format.localize(&format::compact(volume)))
+                    })
+                ),
+                plain,
+            )),
+            SummaryWidget::AltIndex => match summary.alt_change {
+                Some(change) => spans.push(Span::styled(
+                    format!("Alts {}%", format.localize(&format!("{:+.2}", change))),
+                    Style::default().fg(change_color(change)),
+                )),
+                None => spans.push(Span::styled("Alts -", plain)),
+            },
+            SummaryWidget::BtcPrice => spans.push(Span::styled(
+                format!(
+                    "BTC {}",
+                    summary
+                        .btc_price
+                        .map_or("-".to_string(), |price| format.localize(&price.to_string()))
+                ),
+                plain,
+            )),
+        }
+    }
+    f.render_widget(
+        Paragraph::new(Line::from(spans)).style(Style::default().bg(app.colors.buffer_bg)),
+        area,
+    );
+}
+
+fn render_maintenance_banner(f: &mut Frame, warnings: &[String], area: Rect) {
+    let banner = format!(" ⚠ {}", warnings.join("  ·  "));
+    f.render_widget(
+        Paragraph::new(banner).style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ),
+        area,
+    );
+}
+
+fn render_footer(f: &mut Frame, app: &App, area: Rect, tickers: &Arc<Mutex<TickerMap>>) {
+    let area = if app.config.summary.widgets.is_empty() {
+        area
+    } else {
+        let rows = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).split(area);
+        render_summary(f, app, rows[0], tickers);
+        rows[1]
+    };
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Double)
+        .border_style(Style::default().fg(app.colors.footer_border_color))
+        .style(Style::default().bg(app.colors.buffer_bg));
+    if !app.config.workspaces.is_empty() {
+        block = block.title(workspace_tabs(app));
+    }
+    let mut inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if inner.height >= 2 {
+        let rows = Layout::vertical([Constraint::Length(1), Constraint::Length(1)]).split(inner);
+        inner = rows[0];
+        let tickers = tickers.lock().unwrap();
+        let announcement = app
+            .selected_symbol()
+            .and_then(|symbol| tickers.get(symbol))
+            .map_or_else(
+                || "No row selected".to_string(),
+                accessibility::announcement,
+            );
+        f.render_widget(
+            Paragraph::new(announcement).style(Style::default().fg(app.colors.row_fg)),
+            rows[1],
+        );
+    }
+
+    let (status, status_color) = match app.status.lock().unwrap().health() {
+        Health::Connecting => ("◌ connecting".to_string(), Color::Yellow),
+        Health::Connected { latency } => (
+            match latency {
+                Some(latency) => format!("● connected {}ms", latency.as_millis()),
+                None => "● connected".to_string(),
+            },
+            Color::Green,
+        ),
+        // An outage reported by the exchange itself rather than a broken connection.
+        Health::Stale { .. } | Health::Disconnected
+            if app.maintenance.status.system_maintenance =>
+        {
+            ("⚠ exchange maintenance".to_string(), Color::Yellow)
+        }
+        Health::Stale { since } => (format!("◌ stale {}s", since.as_secs()), Color::Yellow),
+        Health::Polling => ("◍ polling mode".to_string(), Color::Yellow),
+        Health::Disconnected => ("○ disconnected".to_string(), Color::Red),
+    };
+    let summary = (!app.portfolio.is_empty()).then(|| {
+        let summary = app.portfolio.summary(&tickers.lock().unwrap());
+        let color = if summary.unrealized_pnl >= 0.0 {
+            app.colors.up
+        } else {
+            app.colors.down
+        };
+        let text = format!(
+            "Portfolio {} P&L {} ({}%)",
+            format::compact(summary.market_value),
+            format::compact(summary.unrealized_pnl),
+            app.config.format.derived(summary.pnl_percent),
+        );
+        (text, color)
+    });
+    let clock = app.config.format.clock();
+    let rects = Layout::horizontal([
+        Constraint::Min(0),
+        Constraint::Length(
+            summary
+                .as_ref()
+                .map_or(0, |(text, _)| text.chars().count() as u16 + 2),
+        ),
+        Constraint::Length(status.chars().count() as u16 + 1),
+        Constraint::Length(clock.as_ref().map_or(0, |clock| clock.len() as u16 + 2)),
+    ])
+    .split(inner);
+
+    let info_footer = if let Some((first, _)) = app.pending_key {
+        Paragraph::new(Line::from(app.keymap.pending_hint(first)))
+            .style(Style::default().fg(Color::Yellow))
+    } else {
+        Paragraph::new(Line::from(app.help_text.as_str()))
+            .style(Style::default().fg(app.colors.row_fg))
+    };
+    f.render_widget(info_footer, rects[0]);
+    let status = Paragraph::new(Line::from(status))
+        .style(Style::default().fg(status_color))
+        .right_aligned();
+    f.render_widget(status, rects[2]);
+    if let Some((text, color)) = summary {
+        let summary = Paragraph::new(Line::from(text))
+            .style(Style::default().fg(color))
+            .right_aligned();
+        f.render_widget(summary, rects[1]);
+    }
+    if let Some(clock) = clock {
+        let clock = Paragraph::new(Line::from(clock))
+            .style(Style::default().fg(app.colors.row_fg))
+            .right_aligned();
+        f.render_widget(clock, rects[3]);
+    }
+}
@@ -0,0 +1,266 @@
+//! Checks of the config file and command line, run before the terminal is taken over so that a
+//! mistake is reported with where it is and how to fix it rather than once the TUI is up.
+
+use crate::{app::MAX_FPS, config::Config, exchange::Instrument, keymap::KeyBinding, net::Proxy};
+use std::{fmt, fs, io, path::Path};
+use toml::{Table, Value};
+
+/// Something in the config file or on the command line that cannot be used.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// Where the problem is, e.g. `config.toml:3:9` or `--symbols`.
+    pub location: String,
+    pub message: String,
+    /// How to fix it, e.g. the closest valid name.
+    pub help: Option<String>,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)?;
+        if let Some(help) = &self.help {
+            write!(f, "\n  help: {}", help)?;
+        }
+        Ok(())
+    }
+}
+
+/// The problems found in one config file, which is named `file` in their locations.
+struct Checker<'a> {
+    text: &'a str,
+    file: &'a str,
+    /// Problems with their line and column, to be listed in the order of the file.
+    problems: Vec<(Option<(usize, usize)>, Problem)>,
+}
+
+impl Checker<'_> {
+    fn report(&mut self, position: Option<(usize, usize)>, message: String, help: Option<String>) {
+        let location = match position {
+            Some((line, column)) => format!("{}:{}:{}", self.file, line, column),
+            None => self.file.to_string(),
+        };
+        let problem = Problem {
+            location,
+            message,
+            help,
+        };
+        self.problems.push((position, problem));
+    }
+
+    fn finish(mut self) -> Vec<Problem> {
+        self.problems.sort_by_key(|(position, _)| *position);
+        self.problems
+            .into_iter()
+            .map(|(_, problem)| problem)
+            .collect()
+    }
+
+    /// Reports keys of `written` that are in none of `known`, the same table as the app writes
+    /// it, and checks the tables under keys that are. `from` is the line the table starts on.
+    fn unknown_keys(&mut self, written: &Table, known: &[&Table], prefix: &str, from: usize) {
+        for (key, value) in written {
+            let name = format!("{}{}", prefix, key);
+            let position = key_position(self.text, key, from);
+            let entries: Vec<&Value> = known.iter().filter_map(|table| table.get(key)).collect();
+            if entries.is_empty() {
+                let keys = known
+                    .iter()
+                    .flat_map(|table| table.keys().map(String::as_str));
+                let help =
+                    closest(key, keys).map(|key| format!("did you mean `{}{}`?", prefix, key));
+                self.report(position, format!("unknown key `{}`", name), help);
+                continue;
+            }
+            let Value::Table(table) = value else {
+                continue;
+            };
+            let known: Vec<&Table> = entries
+                .iter()
+                .filter_map(|value| value.as_table())
+                .collect();
+            if !known.is_empty() {
+                let from = position.map_or(from, |(line, _)| line);
+                self.unknown_keys(table, &known, &format!("{}.", name), from);
+            }
+        }
+    }
+
+    fn symbol(&mut self, symbol: &str) {
+        if !is_symbol(symbol) {
+            self.report(
+                value_position(self.text, symbol),
+                format!("{:?} is not a symbol: no known quote asset", symbol),
+                Some(SYMBOL_HELP.to_string()),
+            );
+        }
+    }
+}
+
+const SYMBOL_HELP: &str = "write symbols like BTCUSDT, BTC-USD or BTC/USD";
+
+/// Whether `symbol` names a market, in the format of any exchange.
+fn is_symbol(symbol: &str) -> bool {
+    Instrument::from_concatenated(symbol).is_some()
+        || ['-', '/']
+            .into_iter()
+            .any(|separator| Instrument::from_separated(symbol, separator).is_some())
+}
+
+/// Line and column, counted from 1, of byte `offset` of `text`.
+fn offset_position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
+
+/// Position of the first line, from line `from` on, that sets `key` or opens it as a table.
+fn key_position(text: &str, key: &str, from: usize) -> Option<(usize, usize)> {
+    text.lines()
+        .enumerate()
+        .skip(from.saturating_sub(1))
+        .find_map(|(i, line)| {
+            let start = line.len() - line.trim_start_matches([' ', '\t', '[']).len();
+            let rest = line[start..].strip_prefix(key)?;
+            rest.trim_start()
+                .starts_with(['=', '.', ']'])
+                .then_some((i + 1, start + 1))
+        })
+}
+
+/// Position of the first quoted `value` in `text`.
+fn value_position(text: &str, value: &str) -> Option<(usize, usize)> {
+    let quoted = format!("{:?}", value);
+    text.find(&quoted)
+        .or_else(|| text.find(&format!("'{}'", value)))
+        .map(|offset| offset_position(text, offset))
+}
+
+/// The candidate closest to `word`, when one is close enough to be a likely typo.
+fn closest<'a>(word: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    candidates
+        .map(|candidate| {
+            (
+                strsim::jaro_winkler(&word, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(score, _)| *score > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Parses and checks the config file `text`, named `file` in the problems found: its syntax and
+/// types, keys the app does not know, the theme, key bindings, symbols, the proxy and the frame
+/// rate.
+pub fn check(text: &str, file: &str) -> Result<Config, Vec<Problem>> {
+    let mut checker = Checker {
+        text,
+        file,
+        problems: Vec::new(),
+    };
+    let config = match toml::from_str::<Config>(text) {
+        Ok(config) => config,
+        Err(err) => {
+            let position = err.span().map(|span| offset_position(text, span.start));
+            checker.report(position, err.message().trim().to_string(), None);
+            return Err(checker.finish());
+        }
+    };
+
+    if let Ok(written) = toml::from_str::<Table>(text) {
+        let known: Vec<Table> = [&config, &Config::default()]
+            .into_iter()
+            .filter_map(|config| match Value::try_from(config) {
+                Ok(Value::Table(table)) => Some(table),
+                _ => None,
+            })
+            .collect();
+        let known: Vec<&Table> = known.iter().collect();
+        checker.unknown_keys(&written, &known, "", 1);
+    }
+
+    let palettes = config.theme.all_palettes();
+    if !palettes.iter().any(|p| p.name == config.theme.name) {
+        let names: Vec<&str> = palettes.iter().map(|p| p.name.as_str()).collect();
+        let help = match closest(&config.theme.name, names.iter().copied()) {
+            Some(name) => format!("did you mean {:?}?", name),
+            None => format!("the themes are {}", names.join(", ")),
+        };
+        checker.report(
+            value_position(text, &config.theme.name),
+            format!("unknown theme {:?}", config.theme.name),
+            Some(help),
+        );
+    }
+
+    for (action, keys) in &config.keys {
+        for key in keys {
+            if let Err(err) = KeyBinding::parse_sequence(key) {
+                checker.report(
+                    value_position(text, key),
+                    format!("{} (bound to {})", err, action.description()),
+                    Some("write keys like q, Ctrl+p, Shift+Tab, PageDown, F5 or g g".to_string()),
+                );
+            }
+        }
+    }
+
+    let watchlists = config.watchlists.iter().flat_map(|w| &w.symbols);
+    let alerts = config
+        .alerts
+        .iter()
+        .filter_map(|alert| alert.symbol.as_ref());
+    for symbol in config.pinned.iter().chain(watchlists).chain(alerts) {
+        checker.symbol(symbol);
+    }
+
+    if let Some(Err(err)) = config.proxy.as_deref().map(str::parse::<Proxy>) {
+        let position = key_position(text, "proxy", 1);
+        checker.report(
+            position,
+            err,
+            Some("e.g. socks5://127.0.0.1:1080".to_string()),
+        );
+    }
+    if !(1..=MAX_FPS).contains(&config.fps) {
+        checker.report(
+            key_position(text, "fps", 1),
+            format!("fps = {} is out of range", config.fps),
+            Some(format!("pick a frame rate from 1 to {}", MAX_FPS)),
+        );
+    }
+
+    if checker.problems.is_empty() {
+        Ok(config)
+    } else {
+        Err(checker.finish())
+    }
+}
+
+/// Loads and checks the config file at `path`; a missing file is the default config.
+pub fn load(path: &Path) -> Result<Config, Vec<Problem>> {
+    match fs::read_to_string(path) {
+        Ok(text) => check(&text, &path.display().to_string()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+        Err(err) => Err(vec![Problem {
+            location: path.display().to_string(),
+            message: err.to_string(),
+            help: None,
+        }]),
+    }
+}
+
+/// Problems with the symbols given to the command line option `flag`.
+pub fn check_symbols(flag: &str, symbols: &[String]) -> Vec<Problem> {
+    symbols
+        .iter()
+        .filter(|symbol| !is_symbol(symbol))
+        .map(|symbol| Problem {
+            location: flag.to_string(),
+            message: format!("{:?} is not a symbol: no known quote asset", symbol),
+            help: Some(SYMBOL_HELP.to_string()),
+        })
+        .collect()
+}
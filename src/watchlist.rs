@@ -0,0 +1,133 @@
+//! Watchlists: named subsets of the table, shown as tabs and switched with `[` and `]`. Each
+//! tab remembers its own sort column, tag filter and selection.
+
+use crate::{
+    error::Error,
+    exchange::{kraken, Exchange, Instrument},
+    model::SortColumn,
+    notes::SymbolNote,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// `[[watchlists]]` entry of the config file. A symbol is listed when it matches any of the
+/// criteria set.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct WatchlistConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub symbols: Vec<String>,
+    /// Symbols whose note has a tag starting with this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    /// Symbols listed on the exchange since the app started.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub new_listings: bool,
+    /// Column the tab is sorted by when first shown.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sort: Option<SortColumn>,
+}
+
+impl WatchlistConfig {
+    /// Whether `symbol`, with its note, is on the list; `listed` are this session's new
+    /// listings.
+    pub fn contains(&self, symbol: &str, note: Option<&SymbolNote>, listed: &[String]) -> bool {
+        self.symbols.iter().any(|s| s.eq_ignore_ascii_case(symbol))
+            || self
+                .tag
+                .as_ref()
+                .is_some_and(|tag| note.is_some_and(|note| note.has_tag(tag)))
+            || (self.new_listings && listed.iter().any(|s| s == symbol))
+    }
+}
+
+/// A watchlist read from a file, and the entries that name no market.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Import {
+    pub watchlist: WatchlistConfig,
+    pub unmatched: Vec<String>,
+}
+
+/// Reads `path` into a watchlist named after the file, with its symbols in `exchange`'s format.
+pub fn import(path: &Path, exchange: Exchange) -> Result<Import, Error> {
+    let text = fs::read_to_string(path)
+        .map_err(|err| Error::Config(format!("watchlist file {}: {}", path.display(), err)))?;
+    let (symbols, unmatched) = parse(&text, exchange);
+    let name = path.file_stem().map_or_else(
+        || "imported".to_string(),
+        |stem| stem.to_string_lossy().into_owned(),
+    );
+    Ok(Import {
+        watchlist: WatchlistConfig {
+            name,
+            symbols,
+            tag: None,
+            new_listings: false,
+            sort: None,
+        },
+        unmatched,
+    })
+}
+
+/// Symbols of a TradingView watchlist export, such as `###Majors,BINANCE:BTCUSDT.P,KRAKEN:XBTUSD`,
+/// or of a list separated by newlines or commas, in `exchange`'s format and without repeats.
+/// Section headers and other lines starting with `#` are skipped; entries without a known quote asset are returned apart.
+pub fn parse(text: &str, exchange: Exchange) -> (Vec<String>, Vec<String>) {
+    let mut symbols: Vec<String> = Vec::new();
+    let mut unmatched = Vec::new();
+    for entry in text.split([',', '\n']).map(str::trim) {
+        if entry.is_empty() || entry.starts_with('#') {
+            continue;
+        }
+        match normalize(entry, exchange) {
+            Some(symbol) if !symbols.contains(&symbol) => symbols.push(symbol),
+            Some(_) => {}
+            None => unmatched.push(entry.to_string()),
+        }
+    }
+    (symbols, unmatched)
+}
+
+/// `entry` without its TradingView exchange prefix and contract suffix, as `exchange`'s symbol.
+/// A leading Kraken legacy code is renamed before the symbol is split, as `XBTUSD` would
+/// otherwise split into `XB` and `TUSD`.
+fn normalize(entry: &str, exchange: Exchange) -> Option<String> {
+    let symbol = entry.rsplit(':').next().unwrap_or(entry);
+    let symbol = symbol
+        .split('.')
+        .next()
+        .unwrap_or(symbol)
+        .to_ascii_uppercase();
+    let symbol = kraken::ASSET_ALIASES
+        .iter()
+        .find_map(|(legacy, common)| {
+            symbol
+                .strip_prefix(legacy)
+                .map(|rest| format!("{}{}", common, rest))
+        })
+        .unwrap_or(symbol);
+    let instrument = ['-', '/']
+        .into_iter()
+        .find_map(|separator| Instrument::from_separated(&symbol, separator))
+        .or_else(|| Instrument::from_concatenated(&symbol))?;
+    Some(exchange.symbol(&instrument))
+}
+
+/// What a tab restores when it is shown again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TabState {
+    pub sort_column: SortColumn,
+    pub tag_filter: Option<String>,
+    /// Symbol selected when the tab was left.
+    pub selected: Option<String>,
+}
+
+impl TabState {
+    pub fn new(sort_column: SortColumn) -> Self {
+        Self {
+            sort_column,
+            tag_filter: None,
+            selected: None,
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Workspaces: named screen layouts built from panes, switched with the number keys.
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use serde::{Deserialize, Serialize};
+
+/// What a slot in a workspace layout shows.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PaneKind {
+    Table,
+    Detail,
+    /// Candlestick chart of the selected symbol.
+    Chart,
+    Comparison,
+    Portfolio,
+    Paper,
+    Account,
+    Liquidations,
+    Alerts,
+    /// History of toast notifications.
+    Notifications,
+    /// Triggered alerts, from the alert log.
+    AlertHistory,
+    /// Headlines of the configured news feeds.
+    News,
+    /// Panes of the loaded WASM plugins.
+    Plugins,
+    /// Request weight used of the Binance REST APIs.
+    Status,
+    /// Mini-charts of the top symbols in the table.
+    Grid,
+    Heatmap,
+    /// Top gainers, and top losers, of the movers view.
+    Gainers,
+    Losers,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SplitDirection {
+    /// Side by side.
+    Horizontal,
+    /// Stacked top to bottom.
+    Vertical,
+}
+
+/// A pane, or a split holding further slots. `size` is a percentage of the parent; slots
+/// without one share what is left.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum Slot {
+    Pane {
+        pane: PaneKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        size: Option<u16>,
+    },
+    Split {
+        split: SplitDirection,
+        panes: Vec<Slot>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        size: Option<u16>,
+    },
+}
+
+impl Slot {
+    fn size(&self) -> Option<u16> {
+        match self {
+            Slot::Pane { size, .. } | Slot::Split { size, .. } => *size,
+        }
+    }
+
+    /// The area of every pane in this slot when it fills `area`, in layout order.
+    pub fn areas(&self, area: Rect) -> Vec<(PaneKind, Rect)> {
+        match self {
+            Slot::Pane { pane, .. } => vec![(*pane, area)],
+            Slot::Split { split, panes, .. } => {
+                let constraints = panes.iter().map(|slot| match slot.size() {
+                    Some(percent) => Constraint::Percentage(percent),
+                    None => Constraint::Fill(1),
+                });
+                let rects = match split {
+                    SplitDirection::Horizontal => Layout::horizontal(constraints).split(area),
+                    SplitDirection::Vertical => Layout::vertical(constraints).split(area),
+                };
+                panes
+                    .iter()
+                    .zip(rects.iter())
+                    .flat_map(|(slot, rect)| slot.areas(*rect))
+                    .collect()
+            }
+        }
+    }
+
+    /// Every pane in this slot, in layout order.
+    pub fn panes(&self) -> Vec<PaneKind> {
+        self.areas(Rect::default())
+            .into_iter()
+            .map(|(pane, _)| pane)
+            .collect()
+    }
+}
+
+/// `[[workspaces]]` entry of the config file.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct WorkspaceConfig {
+    pub name: String,
+    pub layout: Slot,
+}
+
+/// Workspaces 2 and up when the config does not list any; workspace 1 is always the table.
+pub fn defaults() -> Vec<WorkspaceConfig> {
+    vec![
+        WorkspaceConfig {
+            name: "Trading".to_string(),
+            layout: Slot::Split {
+                split: SplitDirection::Horizontal,
+                panes: vec![
+                    Slot::Pane {
+                        pane: PaneKind::Table,
+                        size: Some(55),
+                    },
+                    Slot::Split {
+                        split: SplitDirection::Vertical,
+                        panes: vec![
+                            Slot::Pane {
+                                pane: PaneKind::Chart,
+                                size: Some(60),
+                            },
+                            Slot::Pane {
+                                pane: PaneKind::Detail,
+                                size: None,
+                            },
+                        ],
+                        size: None,
+                    },
+                ],
+                size: None,
+            },
+        },
+        WorkspaceConfig {
+            name: "Portfolio".to_string(),
+            layout: Slot::Split {
+                split: SplitDirection::Vertical,
+                panes: vec![
+                    Slot::Pane {
+                        pane: PaneKind::Portfolio,
+                        size: None,
+                    },
+                    Slot::Pane {
+                        pane: PaneKind::Paper,
+                        size: None,
+                    },
+                ],
+                size: None,
+            },
+        },
+    ]
+}
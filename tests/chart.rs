@@ -0,0 +1,261 @@
+use crypto_tui_ticker::{
+    chart::{self, Candle, CandleChart, ChartRange, ChartStyle},
+    graphics::{self, Canvas, Graphics, Protocol},
+};
+use plotters::{element::Rectangle, style::Color as _};
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, widgets::Widget};
+use std::cell::RefCell;
+
+/// Candles stepping up by 1 from `close`, each opening at the previous close.
+fn rising(count: usize, close: f64) -> Vec<Candle> {
+    (0..count)
+        .map(|i| {
+            let close = close + i as f64;
+            Candle {
+                open_time: i as u64 * 60_000,
+                open: close - 1.0,
+                high: close,
+                low: close - 1.0,
+                close,
+                volume: 1.0,
+            }
+        })
+        .collect()
+}
+
+/// Rows of the chart above its time axis: 12 columns of price axis and 8 of plot.
+fn draw(chart: CandleChart) -> Vec<String> {
+    let area = Rect::new(0, 0, 20, 6);
+    let mut buf = Buffer::empty(area);
+    chart.render(area, &mut buf);
+    (0..area.height - 1)
+        .map(|y| (0..area.width).map(|x| buf.get(x, y).symbol()).collect())
+        .collect()
+}
+
+fn plot(row: &str) -> String {
+    row.chars().skip(12).collect()
+}
+
+#[test]
+fn fresh_klines_replace_the_cached_tail() {
+    let cached = rising(5, 10.0);
+    // The last cached candle was still open, and one more has started since.
+    let fresh = vec![
+        Candle {
+            close: 20.0,
+            ..cached[4]
+        },
+        Candle {
+            open_time: 5 * 60_000,
+            ..cached[4]
+        },
+    ];
+    let merged = chart::merge_klines(&cached, fresh.clone());
+    assert_eq!(merged.len(), 6);
+    assert_eq!(merged[..4], cached[..4]);
+    assert_eq!(merged[4..], fresh[..]);
+
+    // The oldest candles give way beyond the limit.
+    let cached = rising(chart::CANDLE_LIMIT, 10.0);
+    let merged = chart::merge_klines(
+        &cached,
+        rising(chart::CANDLE_LIMIT + 2, 10.0)[chart::CANDLE_LIMIT - 2..].to_vec(),
+    );
+    assert_eq!(merged.len(), chart::CANDLE_LIMIT);
+    assert_eq!(merged[0].open_time, 2 * 60_000);
+}
+
+#[test]
+fn zooming_in_widens_candles() {
+    let candles = rising(4, 10.0);
+    let rows = draw(CandleChart::new(&candles).span(Some(4)));
+    // Two columns per candle: the body and a gap.
+    assert_eq!(plot(&rows[0]), "      ┃ ");
+    assert_eq!(plot(&rows[4]), "┃       ");
+}
+
+#[test]
+fn zooming_out_merges_candles_into_columns() {
+    let candles = rising(16, 10.0);
+    // The plot is 8 columns wide, so only the last 8 candles fit at one per column...
+    let rows = draw(CandleChart::new(&candles));
+    assert_eq!(rows[4][..11].trim(), "17.000");
+    // ...while merging two per column shows all 16.
+    let rows = draw(CandleChart::new(&candles).span(Some(16)));
+    assert_eq!(rows[4][..11].trim(), "9.0000");
+    assert_eq!(rows[0][..11].trim(), "25.000");
+    assert!(plot(&rows[4]).starts_with('┃'));
+}
+
+#[test]
+fn crosshair_marks_the_candle_and_its_close() {
+    let candles = rising(8, 10.0);
+    let rows = draw(CandleChart::new(&candles).cursor(Some(0)));
+    assert!(rows
+        .iter()
+        .all(|row| plot(row).starts_with(['┃', '│', '┊'])));
+    assert_eq!(plot(&rows[4]), "┃┃┈┈┈┈┈┈");
+}
+
+#[test]
+fn raster_charts_draw_the_plot_as_an_image() {
+    let candles = rising(8, 10.0);
+    let image = RefCell::new(None);
+    let graphics = Graphics {
+        protocol: Protocol::Kitty,
+        cell: (4, 8),
+    };
+    let rows = draw(
+        CandleChart::new(&candles)
+            .colors(Color::Rgb(0, 200, 0), Color::Red, Color::DarkGray)
+            .raster(graphics, &image),
+    );
+    // The axis is still text, the plot is left to the image.
+    assert_eq!(rows[0][..11].trim(), "17.000");
+    assert!(
+        rows.iter().all(|row| plot(row).trim().is_empty()),
+        "{:?}",
+        rows
+    );
+
+    let image = image.into_inner().unwrap();
+    assert_eq!(image.area, Rect::new(12, 0, 8, 5));
+    assert_eq!((image.canvas.width, image.canvas.height), (32, 40));
+    // The last candle's wick reaches the top row, in the middle of its column.
+    assert_eq!(image.canvas.pixel(30, 4), [0, 200, 0, 255]);
+    assert_eq!(image.canvas.pixel(0, 4), [0, 0, 0, 0]);
+
+    let png = image.canvas.png();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    // IEND and its fixed CRC.
+    assert!(png.ends_with(&[b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+    let escape = graphics::escape(Protocol::Kitty, &image);
+    assert!(
+        escape.starts_with("\x1b_Ga=T,f=100,i=1,q=2,C=1,c=8,r=5,"),
+        "{}",
+        escape
+    );
+    assert!(escape.ends_with("\x1b\\"));
+}
+
+#[test]
+fn line_and_area_styles_trace_the_closes() {
+    let candles = rising(8, 10.0);
+    let rows = draw(CandleChart::new(&candles).style(ChartStyle::Line));
+    assert_eq!(
+        rows.iter().map(|row| plot(row)).collect::<Vec<_>>(),
+        ["      ⢀⡄", "    ⢀⡴⠋ ", "  ⢀⡴⠋   ", "⢀⡴⠋     ", "⠉       "]
+    );
+
+    // The area below the line is shaded down to the time axis.
+    let rows = draw(CandleChart::new(&candles).style(ChartStyle::Area));
+    assert_eq!(plot(&rows[2]), "  ⢀⣴⣿⣿⣿⡇");
+    assert_eq!(plot(&rows[4]), "⣿⣿⣿⣿⣿⣿⣿⡇");
+}
+
+#[test]
+fn log_scale_gives_equal_ratios_equal_heights() {
+    let candles: Vec<Candle> = [1.0, 10.0, 100.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, price)| Candle {
+            open_time: i as u64 * 60_000,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: 1.0,
+        })
+        .collect();
+    let rows = draw(CandleChart::new(&candles));
+    assert_eq!(rows[2][..11].trim(), "50.500");
+    assert_eq!(plot(&rows[4]), "┃┃      ");
+
+    let rows = draw(CandleChart::new(&candles).log_scale(true));
+    assert_eq!(rows[2][..11].trim(), "10.000");
+    assert_eq!(plot(&rows[0]), "  ┃     ");
+    assert_eq!(plot(&rows[2]), " ┃      ");
+    assert_eq!(plot(&rows[4]), "┃       ");
+}
+
+#[test]
+fn chart_ranges_cycle_back_to_the_latest_candles() {
+    let mut range = None;
+    let mut candles = Vec::new();
+    loop {
+        range = ChartRange::next(range);
+        let Some(range) = range else {
+            break;
+        };
+        candles.push(range.candles());
+    }
+    // One minute candles for a day, a week and 30 days.
+    assert_eq!(candles, [1440, 10_080, 43_200]);
+}
+
+#[test]
+fn volume_profile_spreads_each_candle_over_its_range() {
+    let mut candles = rising(2, 11.0);
+    candles[1].volume = 3.0;
+    // Five levels from 12 down to 10: the first candle covers the bottom three and the second
+    // the top three, which share the middle one.
+    let third = 1.0 / 3.0;
+    assert_eq!(
+        chart::volume_profile(&candles, 12.0, 10.0, 5),
+        [1.0, 1.0, 1.0 + third, third, third]
+    );
+    // Levels round like rows, and prices outside the range are clamped to its ends.
+    let candles = [Candle {
+        high: 20.0,
+        low: 11.9,
+        ..candles[0]
+    }];
+    assert_eq!(
+        chart::volume_profile(&candles, 12.0, 10.0, 5),
+        [1.0, 0.0, 0.0, 0.0, 0.0]
+    );
+}
+
+#[test]
+fn compared_closes_start_from_the_same_interval() {
+    // The second symbol misses the first two intervals and the fifth.
+    let a = rising(6, 100.0);
+    let mut b = rising(6, 50.0);
+    b.drain(..2);
+    b.remove(2);
+    let [a, b] = chart::relative_closes(&a, &b, 10);
+    assert_eq!(
+        a,
+        [
+            (0.0, 0.0),
+            (1.0, 1.0 / 102.0 * 100.0),
+            (2.0, 3.0 / 102.0 * 100.0)
+        ]
+    );
+    assert_eq!(
+        b,
+        [
+            (0.0, 0.0),
+            (1.0, 1.0 / 52.0 * 100.0),
+            (2.0, 3.0 / 52.0 * 100.0)
+        ]
+    );
+
+    // Only the latest intervals that fit are shown, measured from the first of them.
+    let [a, _] = chart::relative_closes(&rising(6, 100.0), &rising(6, 50.0), 2);
+    assert_eq!(a, [(0.0, 0.0), (1.0, 1.0 / 104.0 * 100.0)]);
+}
+
+#[test]
+fn sixel_encodes_six_rows_per_band() {
+    let mut canvas = Canvas::new(3, 7);
+    let red = graphics::rgb(Color::Rgb(255, 0, 0));
+    canvas
+        .draw(|area| area.draw(&Rectangle::new([(0, 0), (1, 7)], red.filled())))
+        .unwrap();
+    assert_eq!(
+        canvas.sixel(),
+        "\x1bP0;1;0q\"1;1;3;7#0;2;100;0;0#0~??$-#0@??$-\x1b\\"
+    );
+}
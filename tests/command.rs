@@ -0,0 +1,28 @@
+use crypto_tui_ticker::{command::fuzzy_score, notes::SymbolNote};
+
+#[test]
+fn fuzzy_matches_characters_in_order() {
+    assert!(fuzzy_score("btc", "BTCUSDT").is_some());
+    assert!(fuzzy_score("bu", "BTCUSDT").is_some());
+    assert!(fuzzy_score("usdtbtc", "BTCUSDT").is_none());
+    assert_eq!(fuzzy_score("", "anything"), Some(0));
+}
+
+#[test]
+fn word_starts_and_runs_rank_higher() {
+    let score = |text| fuzzy_score("btc", text).unwrap();
+    assert!(score("BTCUSDT") > score("BATCHUSDT"));
+    assert!(fuzzy_score("tb", "theme: blue") > fuzzy_score("tb", "toggle table"));
+}
+
+#[test]
+fn notes_parse_tags_before_the_colon() {
+    let note = SymbolNote::parse("#breakout watch, earnings-ish: check: the 200 EMA");
+    assert_eq!(note.tags, ["breakout watch", "earnings-ish"]);
+    assert_eq!(note.note, "check: the 200 EMA");
+    assert_eq!(SymbolNote::parse(&note.input()), note);
+    assert!(note.has_tag("#Breakout"));
+    assert!(!note.has_tag("watch"));
+    assert_eq!(SymbolNote::parse("just a note").tags, Vec::<String>::new());
+    assert!(SymbolNote::parse(" : ").is_empty());
+}
@@ -0,0 +1,80 @@
+//! Shared fixtures: canned Binance payloads and a local websocket server that plays them.
+#![allow(dead_code)]
+
+use crypto_tui_ticker::model::HrTicker;
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::{net::TcpListener, sync::mpsc};
+use tokio_tungstenite::tungstenite::Message;
+
+/// A Binance `24hrTicker` event as it appears in `!ticker@arr` frames.
+pub fn ticker(symbol: &str, last: f32, percent: f32) -> Value {
+    json!({
+        "e": "24hrTicker",
+        "E": 1_718_000_000_000u64,
+        "s": symbol,
+        "p": "0",
+        "P": percent.to_string(),
+        "w": last.to_string(),
+        "c": last.to_string(),
+        "Q": "1",
+        "o": last.to_string(),
+        "h": last.to_string(),
+        "l": last.to_string(),
+        "v": "1000",
+        "q": "100000",
+        "O": 0,
+        "C": 0,
+        "F": 0,
+        "L": 0,
+        "n": 5
+    })
+}
+
+/// [`ticker`] parsed, as the ticker store holds it.
+pub fn hr_ticker(symbol: &str, last: f32, percent: f32) -> HrTicker {
+    serde_json::from_value(ticker(symbol, last, percent)).unwrap()
+}
+
+/// [`hr_ticker`] with `quote_volume` traded over the day.
+pub fn hr_ticker_with_volume(symbol: &str, last: f32, percent: f32, quote_volume: f64) -> HrTicker {
+    let mut ticker = ticker(symbol, last, percent);
+    ticker["q"] = json!(quote_volume.to_string());
+    serde_json::from_value(ticker).unwrap()
+}
+
+/// Accepts one websocket client on a local port and sends it `frames` in order, then keeps the
+/// connection open until the client goes away. Returns the URL to connect to.
+pub async fn mock_server(frames: Vec<String>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        for frame in frames {
+            ws.send(Message::Text(frame)).await.unwrap();
+        }
+        while ws.next().await.is_some() {}
+    });
+    format!("ws://{}", addr)
+}
+
+/// Like `mock_server`, but also returns the text frames the client sends.
+pub async fn recording_server(frames: Vec<String>) -> (String, mpsc::UnboundedReceiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+        for frame in frames {
+            ws.send(Message::Text(frame)).await.unwrap();
+        }
+        while let Some(Ok(message)) = ws.next().await {
+            if let Message::Text(text) = message {
+                let _ = tx.send(text);
+            }
+        }
+    });
+    (format!("ws://{}", addr), rx)
+}
@@ -0,0 +1,83 @@
+#![cfg(unix)]
+
+use crypto_tui_ticker::{
+    app::View,
+    control::{self, ControlRequest},
+};
+use std::os::unix::fs::PermissionsExt;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::UnixStream,
+    sync::mpsc,
+};
+
+#[tokio::test]
+async fn commands_are_passed_to_the_app_and_answered() {
+    let dir = std::env::temp_dir().join(format!("control-{}", std::process::id()));
+    let path = dir.join("control.sock");
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let server = tokio::spawn(control::serve(path.clone(), tx));
+    // The app's side: select succeeds, everything else fails.
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let reply = match message.request {
+                ControlRequest::Select { symbol } => Ok(Some(symbol)),
+                request => Err(format!("{:?}", request)),
+            };
+            let _ = message.reply.send(reply);
+        }
+    });
+
+    let stream = loop {
+        match UnixStream::connect(&path).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::task::yield_now().await,
+        }
+    };
+    // Only the user can reach the socket, from the moment it exists.
+    let mode = |path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!((mode(&dir), mode(&path)), (0o700, 0o600));
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    writer
+        .write_all(
+            b"{\"command\": \"select\", \"symbol\": \"BTCUSDT\"}\n\
+              {\"command\": \"view\", \"view\": \"heatmap\"}\n\
+              {\"command\": \"launch\"}\n",
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        lines.next_line().await.unwrap().unwrap(),
+        r#"{"ok":true,"result":"BTCUSDT"}"#
+    );
+    assert_eq!(
+        lines.next_line().await.unwrap().unwrap(),
+        format!(
+            r#"{{"ok":false,"error":"{:?}"}}"#,
+            ControlRequest::View {
+                view: View::Heatmap
+            }
+        )
+    );
+    let invalid = lines.next_line().await.unwrap().unwrap();
+    assert!(invalid.starts_with(r#"{"ok":false,"error":"invalid command"#));
+
+    // A running instance keeps its socket.
+    let (tx, _rx) = mpsc::unbounded_channel();
+    assert!(control::serve(path.clone(), tx).await.is_err());
+    server.abort();
+    let _ = server.await;
+    assert!(!path.exists());
+    let _ = std::fs::remove_dir(&dir);
+}
+
+#[tokio::test]
+async fn sockets_are_not_bound_where_other_users_can_write() {
+    // The temporary directory itself is shared, so another user could have planted the socket.
+    let path = std::env::temp_dir().join(format!("control-{}.sock", std::process::id()));
+    let (tx, _rx) = mpsc::unbounded_channel();
+    let err = control::serve(path.clone(), tx).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+    assert!(!path.exists());
+}
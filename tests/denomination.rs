@@ -0,0 +1,80 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::{
+    denomination::{Converter, Denomination},
+    exchange::Exchange,
+    format,
+    model::TickerMap,
+};
+use std::collections::HashMap;
+
+fn store(tickers: &[(&str, f32)]) -> TickerMap {
+    tickers
+        .iter()
+        .map(|(symbol, last)| (symbol.to_string(), hr_ticker(symbol, *last, 0.0)))
+        .collect()
+}
+
+fn converter(denomination: Denomination, tickers: &TickerMap, fx: &[(&str, f64)]) -> Converter {
+    let fx: HashMap<String, f64> = fx.iter().map(|(c, r)| (c.to_string(), *r)).collect();
+    Converter::new(denomination, Exchange::Binance, tickers, Some("eur"), &fx).unwrap()
+}
+
+fn assert_close(converted: Option<f64>, expected: f64) {
+    let converted = converted.unwrap();
+    assert!(
+        (converted - expected).abs() < expected * 1e-6,
+        "{} != {}",
+        converted,
+        expected
+    );
+}
+
+#[test]
+fn prices_convert_through_usd() {
+    let tickers = store(&[
+        ("BTCUSDT", 50_000.0),
+        ("ETHUSDT", 2_500.0),
+        ("ETHBTC", 0.05),
+        ("SOLUSDC", 100.0),
+    ]);
+    let btc = converter(Denomination::Btc, &tickers, &[]);
+    assert_close(btc.convert("SOLUSDC", 100.0), 0.002);
+    assert_close(btc.convert("ETHBTC", 0.05), 0.05);
+    let sats = converter(Denomination::Sats, &tickers, &[]);
+    assert_close(sats.convert("SOLUSDC", 100.0), 200_000.0);
+    assert_close(sats.convert("SOL/USD", 100.0), 200_000.0);
+    let eth = converter(Denomination::Eth, &tickers, &[]);
+    assert_close(eth.convert("SOLUSDC", 100.0), 0.04);
+    // No USD price of XRP's quote.
+    assert_eq!(eth.convert("XRPBNB", 1.0), None);
+
+    assert!(Converter::new(
+        Denomination::Quote,
+        Exchange::Binance,
+        &tickers,
+        None,
+        &HashMap::new()
+    )
+    .is_none());
+}
+
+#[test]
+fn fiat_prefers_a_live_stablecoin_market() {
+    let tickers = store(&[("SOLUSDT", 100.0)]);
+    let eur = converter(Denomination::Fiat, &tickers, &[("EUR", 0.9)]);
+    assert_close(eur.convert("SOLUSDT", 100.0), 90.0);
+
+    let tickers = store(&[("SOLUSDT", 100.0), ("EURUSDT", 1.25)]);
+    let eur = converter(Denomination::Fiat, &tickers, &[("EUR", 0.9)]);
+    assert_close(eur.convert("SOLUSDT", 100.0), 80.0);
+}
+
+#[test]
+fn significant_digits_drop_trailing_zeros() {
+    assert_eq!(format::significant(0.0000234567, 3), "0.0000235");
+    assert_eq!(format::significant(1.5, 6), "1.5");
+    assert_eq!(format::significant(200_000.0, 6), "200000");
+    assert_eq!(format::significant(0.0, 6), "0");
+}
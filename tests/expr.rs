@@ -0,0 +1,184 @@
+use crypto_tui_ticker::alerts::expr::{Arith, Compare, Expr, Field, Num};
+
+fn field(field: Field) -> Num {
+    Num::Field(field)
+}
+
+fn lit(value: f64) -> Num {
+    Num::Literal(value)
+}
+
+fn binary(op: Arith, a: Num, b: Num) -> Num {
+    Num::Binary(op, Box::new(a), Box::new(b))
+}
+
+fn cmp(op: Compare, a: Num, b: Num) -> Expr {
+    Expr::Compare(op, a, b)
+}
+
+fn and(a: Expr, b: Expr) -> Expr {
+    Expr::And(Box::new(a), Box::new(b))
+}
+
+fn or(a: Expr, b: Expr) -> Expr {
+    Expr::Or(Box::new(a), Box::new(b))
+}
+
+fn parse(source: &str) -> Expr {
+    Expr::parse(source).unwrap_or_else(|err| panic!("{:?}: {}", source, err))
+}
+
+#[test]
+fn and_binds_tighter_than_or_until_parenthesized() {
+    let last_above = || cmp(Compare::Gt, field(Field::Last), lit(1.0));
+    let last_below = || cmp(Compare::Lt, field(Field::Last), lit(2.0));
+    let percent_above = || cmp(Compare::Gt, field(Field::PercentChange), lit(3.0));
+
+    assert_eq!(
+        parse("c > 1 || c < 2 && P > 3"),
+        or(last_above(), and(last_below(), percent_above()))
+    );
+    assert_eq!(
+        parse("c > 1 && c < 2 || P > 3"),
+        or(and(last_above(), last_below()), percent_above())
+    );
+    assert_eq!(
+        parse("(c > 1 || c < 2) && P > 3"),
+        and(or(last_above(), last_below()), percent_above())
+    );
+    // `!` applies to the comparison right after it, not to the rest of the rule.
+    assert_eq!(
+        parse("!c > 1 && P > 3"),
+        and(Expr::Not(Box::new(last_above())), percent_above())
+    );
+    assert_eq!(
+        parse("!(c > 1 && P > 3)"),
+        Expr::Not(Box::new(and(last_above(), percent_above())))
+    );
+}
+
+#[test]
+fn arithmetic_binds_tighter_than_comparisons() {
+    // Products before sums, and unary minus before both.
+    assert_eq!(
+        parse("c + 2 * 3 > -o / 4"),
+        cmp(
+            Compare::Gt,
+            binary(
+                Arith::Add,
+                field(Field::Last),
+                binary(Arith::Mul, lit(2.0), lit(3.0))
+            ),
+            binary(Arith::Div, Num::Neg(Box::new(field(Field::Open))), lit(4.0)),
+        )
+    );
+    // A parenthesis opening an operand rather than a condition.
+    assert_eq!(
+        parse("(c + 2) * 3 > 1"),
+        cmp(
+            Compare::Gt,
+            binary(
+                Arith::Mul,
+                binary(Arith::Add, field(Field::Last), lit(2.0)),
+                lit(3.0)
+            ),
+            lit(1.0),
+        )
+    );
+    // Sums and products associate to the left.
+    assert_eq!(
+        parse("c - 1 - 2 < 8 / 4 / 2"),
+        cmp(
+            Compare::Lt,
+            binary(
+                Arith::Sub,
+                binary(Arith::Sub, field(Field::Last), lit(1.0)),
+                lit(2.0)
+            ),
+            binary(Arith::Div, binary(Arith::Div, lit(8.0), lit(4.0)), lit(2.0)),
+        )
+    );
+}
+
+#[test]
+fn every_operator_parses() {
+    let comparisons = [
+        ("<", Compare::Lt),
+        ("<=", Compare::Le),
+        (">", Compare::Gt),
+        (">=", Compare::Ge),
+        ("==", Compare::Eq),
+        ("!=", Compare::Ne),
+        ("crosses", Compare::Crosses),
+        ("crosses_above", Compare::CrossesAbove),
+        ("crosses_below", Compare::CrossesBelow),
+    ];
+    for (op, compare) in comparisons {
+        assert_eq!(
+            parse(&format!("last {} 5", op)),
+            cmp(compare, field(Field::Last), lit(5.0)),
+            "{}",
+            op
+        );
+    }
+
+    let arithmetic = [
+        ("+", Arith::Add),
+        ("-", Arith::Sub),
+        ("*", Arith::Mul),
+        ("/", Arith::Div),
+    ];
+    for (op, arith) in arithmetic {
+        assert_eq!(
+            parse(&format!("h {} l > 0", op)),
+            cmp(
+                Compare::Gt,
+                binary(arith, field(Field::High), field(Field::Low)),
+                lit(0.0)
+            ),
+            "{}",
+            op
+        );
+    }
+
+    assert_eq!(
+        parse("abs(change(15)) >= 2.5e1"),
+        cmp(Compare::Ge, Num::Abs(Box::new(Num::Change(15))), lit(25.0))
+    );
+}
+
+#[test]
+fn malformed_rules_report_what_went_wrong_and_where() {
+    let cases = [
+        ("price > 1 && volum > 5", "unknown field `volum`", 13),
+        ("c > 1 &&", "unexpected end of expression", 8),
+        ("c > 1 +", "unexpected end of expression", 7),
+        ("c >", "unexpected end of expression", 3),
+        ("(c > 1", "expected `)`", 6),
+        ("(c > 1 || (P > 2) && o < 3", "expected `)`", 26),
+        ("c > (1 + 2", "expected `)`", 10),
+        ("c > 1)", "unexpected `)`", 5),
+        (
+            "c 70000",
+            "expected a comparison such as `<`, `>=` or `crosses`",
+            2,
+        ),
+        ("c > 1 @ 2", "unexpected `@`", 6),
+        ("abs c > 1", "expected `(` after `abs`", 4),
+        ("c > 1..2", "invalid number `1..2`", 4),
+        ("c > * 2", "expected a field or number, found `*`", 4),
+    ];
+    for (source, message, offset) in cases {
+        let err = Expr::parse(source).unwrap_err();
+        assert_eq!(
+            (err.message.as_str(), err.offset),
+            (message, offset),
+            "{}",
+            source
+        );
+    }
+
+    // Columns in messages count from 1.
+    let err = Expr::parse("price > 1 && volum > 5").unwrap_err();
+    assert_eq!(err.to_string(), "unknown field `volum` at column 14");
+}
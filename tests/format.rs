@@ -0,0 +1,66 @@
+use crypto_tui_ticker::{
+    format::{self, Clock, NumberFormat},
+    model::SortColumn,
+};
+use std::time::Duration;
+
+fn european() -> NumberFormat {
+    NumberFormat {
+        thousands_separator: ".".to_string(),
+        decimal_separator: ",".to_string(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn localize_groups_digits_and_swaps_the_decimal_point() {
+    let format = european();
+    assert_eq!(format.localize("1234567.891"), "1.234.567,891");
+    assert_eq!(format.localize("-1234"), "-1.234");
+    assert_eq!(format.localize("999.5"), "999,5");
+    assert_eq!(format.localize("1234.5M"), "1.234,5M");
+    assert_eq!(format.localize("-"), "-");
+    assert_eq!(format.localize("1000SHIBUSDT"), "1000SHIBUSDT");
+    assert_eq!(NumberFormat::default().localize("1234.5"), "1234.5");
+}
+
+#[test]
+fn columns_use_fixed_decimals_or_si_suffixes() {
+    let mut format = european();
+    format.decimals.insert(SortColumn::Last, 2);
+    format.si_suffixes = true;
+    let plain = |value: f64| value.to_string();
+    assert_eq!(
+        format.column(SortColumn::Last, 64123.456, plain),
+        "64.123,46"
+    );
+    assert_eq!(
+        format.column(SortColumn::Volume, 1_234_567.0, plain),
+        "1,23M"
+    );
+    assert_eq!(format.column(SortColumn::Open, 1234.5, plain), "1.234,5");
+}
+
+#[test]
+fn times_are_human_readable() {
+    assert_eq!(format::age(Duration::from_secs(42)), "42s ago");
+    assert_eq!(format::age(Duration::from_secs(150)), "2m ago");
+    assert_eq!(format::age(Duration::from_secs(3 * 3600 + 5)), "3h ago");
+
+    let twelve_hour = NumberFormat {
+        clock: Clock::H12,
+        ..Default::default()
+    };
+    let stamp = twelve_hour.timestamp(1_700_000_000_000);
+    assert!(stamp.ends_with("AM") || stamp.ends_with("PM"), "{}", stamp);
+    assert_eq!(
+        NumberFormat::default().timestamp(1_700_000_000_000).len(),
+        19
+    );
+
+    let off = NumberFormat {
+        clock: Clock::Off,
+        ..Default::default()
+    };
+    assert!(off.clock().is_none());
+}
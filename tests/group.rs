@@ -0,0 +1,39 @@
+mod common;
+
+use common::hr_ticker_with_volume;
+use crypto_tui_ticker::{exchange::Exchange, group};
+
+#[test]
+fn markets_of_a_base_share_a_key() {
+    assert_eq!(group::key(Exchange::Binance, "BTCUSDT"), "BTC/USD");
+    assert_eq!(group::key(Exchange::Binance, "BTCUSDC"), "BTC/USD");
+    assert_eq!(group::key(Exchange::Binance, "BTCUSD_PERP"), "BTC/USD");
+    assert_eq!(group::key(Exchange::Binance, "ETHBTC"), "ETH/BTC");
+    assert_eq!(group::key(Exchange::Coinbase, "BTC-USD"), "BTC/USD");
+    assert_eq!(group::key(Exchange::Binance, "1000XYZ"), "1000XYZ");
+}
+
+#[test]
+fn groups_weight_prices_by_quote_volume() {
+    let tickers = [
+        hr_ticker_with_volume("BTCUSDC", 100.0, 1.0, 1_000.0),
+        hr_ticker_with_volume("BTCUSDT", 200.0, 4.0, 3_000.0),
+        hr_ticker_with_volume("ETHUSDT", 50.0, -2.0, 500.0),
+    ];
+    let groups = group::group(Exchange::Binance, &tickers);
+    assert_eq!(groups.len(), 2);
+
+    let btc = &groups[0];
+    assert_eq!(btc.key, "BTC/USD");
+    let members: Vec<&str> = btc.members.iter().map(|t| t.s.as_str()).collect();
+    assert_eq!(members, ["BTCUSDT", "BTCUSDC"]);
+    assert_eq!(btc.total.s, "BTC/USD");
+    assert_eq!(btc.total.c, 175.0);
+    assert_eq!(btc.total.P, 3.25);
+    assert_eq!(btc.total.q, "4000");
+    assert_eq!(btc.total.v, "2000");
+    assert_eq!(btc.total.n, 10);
+
+    assert_eq!(groups[1].members.len(), 1);
+    assert_eq!(groups[1].total.c, 50.0);
+}
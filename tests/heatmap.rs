@@ -0,0 +1,32 @@
+use crypto_tui_ticker::heatmap::treemap;
+use ratatui::layout::Rect;
+
+#[test]
+fn treemap_tiles_the_area_in_proportion() {
+    let area = Rect::new(2, 1, 60, 20);
+    let weights = [40.0, 30.0, 15.0, 10.0, 5.0];
+    let blocks = treemap(&weights, area);
+    assert_eq!(blocks.len(), weights.len());
+
+    let covered: u16 = blocks.iter().map(|block| block.area()).sum();
+    assert_eq!(covered, area.area());
+    for (i, a) in blocks.iter().enumerate() {
+        assert_eq!(area.union(*a), area, "{:?} leaves the area", a);
+        for b in &blocks[i + 1..] {
+            assert!(!a.intersects(*b), "{:?} overlaps {:?}", a, b);
+        }
+    }
+    // Each block's share of the area is within a few cells of its weight's share.
+    for (block, weight) in blocks.iter().zip(weights) {
+        let expected = area.area() as f64 * weight / 100.0;
+        assert!((block.area() as f64 - expected).abs() < 40.0, "{:?}", block);
+    }
+}
+
+#[test]
+fn treemap_of_nothing_is_empty() {
+    assert_eq!(
+        treemap(&[0.0, 0.0], Rect::new(0, 0, 10, 10)),
+        vec![Rect::default(); 2]
+    );
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::model::{BasisPrices, ChangeBasis, PriceHistory};
+
+const MINUTE: u64 = 60_000;
+
+#[test]
+fn changes_are_measured_from_the_start_of_each_window() {
+    let mut history = PriceHistory::default();
+    let start = 1_700_000_000_000;
+    for minute in 0..=20 {
+        history.record(start + minute * MINUTE, 100.0 + minute as f32);
+    }
+    // Latest sample is at minute 20, priced 120.
+    assert_eq!(
+        history.change(MINUTE, 120.0),
+        Some((120.0 - 119.0) / 119.0 * 100.0)
+    );
+    assert_eq!(
+        history.change(5 * MINUTE, 120.0),
+        Some((120.0 - 115.0) / 115.0 * 100.0)
+    );
+    assert_eq!(
+        history.change(15 * MINUTE, 120.0),
+        Some((120.0 - 105.0) / 105.0 * 100.0)
+    );
+}
+
+#[test]
+fn short_history_has_no_change() {
+    let mut history = PriceHistory::default();
+    history.record(10 * MINUTE, 100.0);
+    history.record(12 * MINUTE, 110.0);
+    assert!(history.change(MINUTE, 110.0).is_some());
+    assert_eq!(history.change(5 * MINUTE, 110.0), None);
+    assert_eq!(PriceHistory::default().change(MINUTE, 1.0), None);
+}
+
+#[test]
+fn longer_bases_need_history_since_their_start() {
+    const DAY: u64 = 24 * 60 * MINUTE;
+    let mut ticker = hr_ticker("BTCUSDT", 1.0, 2.5);
+    // From two hours before midnight UTC to 50 minutes after it, a point a minute.
+    let start = 19_700 * DAY - 120 * MINUTE;
+    for minute in 0..=170 {
+        ticker
+            .basis_prices
+            .record(start + minute * MINUTE, 100.0 + minute as f32);
+    }
+    ticker.c = 270.0;
+    assert_eq!(ChangeBasis::Day.change(&ticker), Some(2.5));
+    assert_eq!(
+        ChangeBasis::Hour.change(&ticker),
+        Some((270.0 - 210.0) / 210.0 * 100.0)
+    );
+    assert_eq!(ChangeBasis::FourHours.change(&ticker), None);
+    assert_eq!(
+        ChangeBasis::UtcDay.change(&ticker),
+        Some((270.0 - 220.0) / 220.0 * 100.0)
+    );
+
+    // Started during the day, the price at midnight is unknown.
+    let mut prices = BasisPrices::default();
+    prices.record(19_700 * DAY + 60 * MINUTE, 100.0);
+    assert_eq!(prices.since_midnight(110.0), None);
+}
@@ -0,0 +1,82 @@
+use crypto_tui_ticker::{
+    chart::Candle,
+    indicators::{bollinger, ema, rsi, sma, vwap, Indicators, Trend},
+};
+
+fn candle(open_time: u64, close: f64, volume: f64) -> Candle {
+    Candle {
+        open_time,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume,
+    }
+}
+
+#[test]
+fn ema_is_seeded_with_the_simple_average() {
+    let values = ema(&[1.0, 2.0, 3.0, 4.0], 3);
+    assert_eq!(values[..2], [None, None]);
+    assert_eq!(values[2], Some(2.0));
+    // k = 2 / (3 + 1) = 0.5
+    assert_eq!(values[3], Some(3.0));
+}
+
+#[test]
+fn rsi_follows_gains_and_losses() {
+    let rising: Vec<f64> = (0..20).map(f64::from).collect();
+    assert_eq!(rsi(&rising, 14), Some(100.0));
+    let choppy: Vec<f64> = (0..20)
+        .map(|i| if i % 2 == 0 { 10.0 } else { 11.0 })
+        .collect();
+    let value = rsi(&choppy, 14).unwrap();
+    assert!((value - 50.0).abs() < 5.0, "{}", value);
+    assert_eq!(rsi(&rising[..14], 14), None);
+}
+
+#[test]
+fn vwap_restarts_each_utc_day() {
+    let day = 86_400_000;
+    let candles = [
+        candle(day - 60_000, 100.0, 1.0),
+        candle(day, 200.0, 1.0),
+        candle(day + 60_000, 300.0, 3.0),
+    ];
+    assert_eq!(
+        vwap(&candles),
+        [Some(100.0), Some(200.0), Some((200.0 + 900.0) / 4.0)]
+    );
+}
+
+#[test]
+fn rising_prices_are_a_bullish_trend() {
+    let candles: Vec<Candle> = (0..30)
+        .map(|i| candle(i * 60_000, 100.0 + i as f64, 1.0))
+        .collect();
+    let indicators = Indicators::compute(&candles);
+    assert_eq!(indicators.trend, Some(Trend::Bullish));
+    assert_eq!(indicators.rsi, Some(100.0));
+}
+
+#[test]
+fn sma_averages_each_window() {
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+    assert_eq!(
+        sma(&values, 3),
+        vec![None, None, Some(2.0), Some(3.0), Some(4.0)]
+    );
+}
+
+#[test]
+fn bollinger_bands_are_two_deviations_from_the_average() {
+    let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+    let (lower, upper) = bollinger(&values, 8, 2.0);
+    assert_eq!(lower[6], None);
+    // Mean 5, population standard deviation 2.
+    assert_eq!(lower[7], Some(1.0));
+    assert_eq!(upper[7], Some(9.0));
+
+    let (lower, upper) = bollinger(&[3.0; 4], 2, 2.0);
+    assert_eq!(lower, upper);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::{exchange::Exchange, listings::Listings, model::TickerMap};
+use std::time::{Duration, Instant};
+
+fn add(tickers: &mut TickerMap, symbol: &str) {
+    tickers.insert(symbol.to_string(), hr_ticker(symbol, 1.0, 0.0));
+}
+
+#[test]
+fn symbols_after_the_startup_grace_are_new_listings() {
+    let path = std::env::temp_dir().join(format!("symbols-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let start = Instant::now();
+    let minutes = |m: u64| start + Duration::from_secs(m * 60);
+
+    let mut listings = Listings::open(path.clone());
+    let mut tickers = TickerMap::new();
+    add(&mut tickers, "BTCUSDT");
+    assert!(listings
+        .observe(Exchange::Binance, &tickers, start)
+        .is_empty());
+    // Quiet symbols trickle in during the grace.
+    add(&mut tickers, "ETHUSDT");
+    assert!(listings
+        .observe(Exchange::Binance, &tickers, start + Duration::from_secs(30))
+        .is_empty());
+    add(&mut tickers, "NEWUSDT");
+    assert_eq!(
+        listings.observe(Exchange::Binance, &tickers, minutes(2)),
+        ["NEWUSDT"]
+    );
+
+    // The next session knows them, even when they show up after its grace.
+    let mut listings = Listings::open(path.clone());
+    let mut tickers = TickerMap::new();
+    add(&mut tickers, "BTCUSDT");
+    listings.observe(Exchange::Binance, &tickers, start);
+    add(&mut tickers, "NEWUSDT");
+    add(&mut tickers, "NEWERUSDT");
+    assert_eq!(
+        listings.observe(Exchange::Binance, &tickers, minutes(5)),
+        ["NEWERUSDT"]
+    );
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,63 @@
+use crypto_tui_ticker::maintenance::{Announcement, AnnouncementKind, ExchangeStatus};
+use std::collections::HashSet;
+
+const DAY: u64 = 24 * 60 * 60_000;
+
+fn announcement(kind: AnnouncementKind, title: &str, released: u64) -> Announcement {
+    Announcement {
+        kind,
+        title: title.to_string(),
+        released,
+    }
+}
+
+#[test]
+fn only_recent_delistings_of_listed_symbols_are_shown() {
+    let now = 100 * DAY;
+    let status = ExchangeStatus {
+        system_maintenance: true,
+        announcements: vec![
+            announcement(
+                AnnouncementKind::Maintenance,
+                "Binance Will Perform Scheduled System Maintenance",
+                now - DAY,
+            ),
+            announcement(
+                AnnouncementKind::Maintenance,
+                "Wallet Maintenance Completed",
+                now - 10 * DAY,
+            ),
+            announcement(
+                AnnouncementKind::Delisting,
+                "Binance Will Delist ABC, XYZ on 2026-10-20",
+                now - DAY,
+            ),
+            announcement(
+                AnnouncementKind::Delisting,
+                "Binance Will Delist QRS on 2026-10-20",
+                now - DAY,
+            ),
+            announcement(
+                AnnouncementKind::Delisting,
+                "Notice of Removal of Spot Trading Pairs - 2026-10-18: ETHBTC",
+                now - 2 * DAY,
+            ),
+            announcement(
+                AnnouncementKind::Delisting,
+                "Binance Will Delist ABC Margin Pairs",
+                now - 30 * DAY,
+            ),
+        ],
+    };
+    let symbols: HashSet<String> = ["ABC", "ABCUSDT", "ETH", "ETHBTC"].map(String::from).into();
+    assert_eq!(
+        status.warnings(&symbols, now),
+        [
+            "Binance reports system maintenance",
+            "Binance Will Perform Scheduled System Maintenance",
+            "Binance Will Delist ABC, XYZ on 2026-10-20",
+            "Notice of Removal of Spot Trading Pairs - 2026-10-18: ETHBTC",
+        ]
+    );
+    assert!(ExchangeStatus::default().warnings(&symbols, now).is_empty());
+}
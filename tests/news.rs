@@ -0,0 +1,56 @@
+use crypto_tui_ticker::news::{self, Headline};
+use std::collections::HashSet;
+
+#[test]
+fn rss_and_atom_feeds_are_read() {
+    let rss = r#"<?xml version="1.0"?>
+        <rss version="2.0" xmlns:media="http://search.yahoo.com/mrss/">
+          <channel>
+            <title>Coin Daily</title>
+            <image><title>Coin Daily logo</title><link>https://example.com</link></image>
+            <item>
+              <title><![CDATA[BTC tops $70k & $SOL follows]]></title>
+              <link>https://example.com/btc</link>
+              <media:title>Thumbnail</media:title>
+              <pubDate>Tue, 01 Oct 2024 12:00:00 GMT</pubDate>
+            </item>
+            <item><title>Markets quiet</title><link>https://example.com/quiet</link></item>
+          </channel>
+        </rss>"#;
+    let headlines = news::parse_feed(rss).unwrap();
+    assert_eq!(
+        headlines,
+        [
+            Headline {
+                title: "BTC tops $70k & $SOL follows".to_string(),
+                url: "https://example.com/btc".to_string(),
+                source: "Coin Daily".to_string(),
+                published: Some(1_727_784_000_000),
+            },
+            Headline {
+                title: "Markets quiet".to_string(),
+                url: "https://example.com/quiet".to_string(),
+                source: "Coin Daily".to_string(),
+                published: None,
+            },
+        ]
+    );
+    let bases: HashSet<String> = ["BTC", "SOL", "ETH"].map(String::from).into();
+    assert_eq!(headlines[0].mentions(&bases), ["BTC", "SOL"]);
+    assert!(headlines[1].mentions(&bases).is_empty());
+
+    let atom = r#"<feed xmlns="http://www.w3.org/2005/Atom">
+          <title>Chain &amp; Co</title>
+          <entry>
+            <title>ETH upgrade ships</title>
+            <link rel="self" href="https://example.com/self"/>
+            <link rel="alternate" href="https://example.com/eth"/>
+            <updated>2024-10-01T12:00:00Z</updated>
+          </entry>
+        </feed>"#;
+    let headlines = news::parse_feed(atom).unwrap();
+    assert_eq!(headlines.len(), 1);
+    assert_eq!(headlines[0].url, "https://example.com/eth");
+    assert_eq!(headlines[0].source, "Chain & Co");
+    assert_eq!(headlines[0].published, Some(1_727_784_000_000));
+}
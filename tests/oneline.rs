@@ -0,0 +1,25 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::{format::NumberFormat, oneline};
+
+#[test]
+fn symbols_are_summarized_in_the_order_given() {
+    let tickers = [
+        hr_ticker("BTCUSDT", 64210.5, 1.234),
+        hr_ticker("ETHUSDT", 3120.1, -0.5),
+        hr_ticker("SOLUSDT", 150.0, 0.0),
+    ];
+    let symbols: Vec<String> = ["ethusdt", "BTCUSDT", "DOGEUSDT", "SOLUSDT"]
+        .map(String::from)
+        .to_vec();
+    let format = NumberFormat::default();
+    assert_eq!(
+        oneline::line(&symbols, &tickers, &format, false),
+        "ETHUSDT ▼3120.1 -0.50%  BTCUSDT ▲64210.5 +1.23%  DOGEUSDT ?  SOLUSDT ▲150 0.00%"
+    );
+    assert_eq!(
+        oneline::line(&symbols[..1], &tickers, &format, true),
+        "ETHUSDT v3120.1 -0.50%"
+    );
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::{
+    model::TickerMap,
+    paper::{PaperAccount, PaperConfig, Side},
+};
+use std::path::PathBuf;
+
+/// Fills at the last price exactly, with a 10 bps fee.
+const CONFIG: PaperConfig = PaperConfig {
+    slippage_bps: 0.0,
+    fee_bps: 10.0,
+};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+/// A config directory of the test's own, without a trade log yet.
+fn dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("paper-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+/// Fills `quantity` of BTCUSDT at `price`.
+fn fill(account: &mut PaperAccount, side: Side, quantity: f64, price: f32) {
+    let tickers: TickerMap = [("BTCUSDT".to_string(), hr_ticker("BTCUSDT", price, 0.0))].into();
+    account
+        .execute(&CONFIG, &tickers, "BTCUSDT", side, quantity)
+        .unwrap();
+}
+
+/// The BTCUSDT position as (quantity, average price), if there is one.
+fn position(account: &PaperAccount) -> Option<(f64, f64)> {
+    account
+        .positions
+        .get("BTCUSDT")
+        .map(|position| (position.quantity, position.average_price))
+}
+
+#[test]
+fn fills_open_add_reduce_and_flip_positions() {
+    let dir = dir("fills");
+    let mut account = PaperAccount::load(Some(&dir));
+
+    // Opening and adding average the entry; every fill pays its fee out of realized P&L.
+    fill(&mut account, Side::Buy, 2.0, 100.0);
+    assert_eq!(position(&account), Some((2.0, 100.0)));
+    assert!(
+        close(account.realized_pnl, -0.2),
+        "{}",
+        account.realized_pnl
+    );
+    fill(&mut account, Side::Buy, 2.0, 110.0);
+    assert_eq!(position(&account), Some((4.0, 105.0)));
+    assert!(
+        close(account.realized_pnl, -0.42),
+        "{}",
+        account.realized_pnl
+    );
+
+    // A partial close realizes its share and keeps the entry of the rest.
+    fill(&mut account, Side::Sell, 1.0, 120.0);
+    assert_eq!(position(&account), Some((3.0, 105.0)));
+    assert!(
+        close(account.realized_pnl, 14.46),
+        "{}",
+        account.realized_pnl
+    );
+
+    // Selling through zero closes the long and opens a short at the fill price.
+    fill(&mut account, Side::Sell, 5.0, 90.0);
+    assert_eq!(position(&account), Some((-2.0, 90.0)));
+    assert!(
+        close(account.realized_pnl, -30.99),
+        "{}",
+        account.realized_pnl
+    );
+    let tickers: TickerMap = [("BTCUSDT".to_string(), hr_ticker("BTCUSDT", 80.0, 0.0))].into();
+    assert!(close(account.unrealized_pnl(&tickers), 20.0));
+
+    // Covering the short closes the position.
+    fill(&mut account, Side::Buy, 2.0, 80.0);
+    assert_eq!(position(&account), None);
+    assert!(
+        close(account.realized_pnl, -11.15),
+        "{}",
+        account.realized_pnl
+    );
+    assert_eq!(account.trades.len(), 5);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn loading_replays_the_trade_log() {
+    let dir = dir("replay");
+    let mut account = PaperAccount::load(Some(&dir));
+    fill(&mut account, Side::Buy, 3.0, 100.0);
+    fill(&mut account, Side::Sell, 1.0, 130.0);
+
+    let replayed = PaperAccount::load(Some(&dir));
+    assert_eq!(position(&replayed), position(&account));
+    assert_eq!(position(&replayed), Some((2.0, 100.0)));
+    assert!(close(replayed.realized_pnl, account.realized_pnl));
+    assert_eq!(replayed.trades.len(), 2);
+    assert_eq!(replayed.trades[1].side, Side::Sell);
+
+    // A line that fails to parse is skipped rather than losing the rest.
+    let log = dir.join("paper_trades.jsonl");
+    let mut lines = std::fs::read_to_string(&log).unwrap();
+    lines.insert_str(0, "not a trade\n");
+    std::fs::write(&log, lines).unwrap();
+    assert_eq!(PaperAccount::load(Some(&dir)).trades.len(), 2);
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn orders_need_a_price_and_a_positive_quantity() {
+    let dir = dir("orders");
+    let mut account = PaperAccount::load(Some(&dir));
+    let tickers: TickerMap = [("BTCUSDT".to_string(), hr_ticker("BTCUSDT", 100.0, 0.0))].into();
+    for quantity in [0.0, -1.0, f64::NAN] {
+        assert!(account
+            .execute(&CONFIG, &tickers, "BTCUSDT", Side::Buy, quantity)
+            .is_err());
+    }
+    assert_eq!(
+        account
+            .execute(&CONFIG, &tickers, "ETHUSDT", Side::Buy, 1.0)
+            .unwrap_err(),
+        "no price for ETHUSDT yet"
+    );
+    assert!(account.trades.is_empty() && !dir.exists());
+
+    // Slippage fills buys above the last price and sells below it.
+    let config = PaperConfig {
+        slippage_bps: 100.0,
+        fee_bps: 0.0,
+    };
+    let buy = account
+        .execute(&config, &tickers, "BTCUSDT", Side::Buy, 1.0)
+        .unwrap();
+    let sell = account
+        .execute(&config, &tickers, "BTCUSDT", Side::Sell, 1.0)
+        .unwrap();
+    assert!(close(buy.price, 101.0) && close(sell.price, 99.0));
+    std::fs::remove_dir_all(dir).unwrap();
+}
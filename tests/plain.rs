@@ -0,0 +1,22 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::{format::NumberFormat, plain};
+
+#[test]
+fn lines_are_colored_by_move_and_change_only_when_asked() {
+    let format = NumberFormat::default();
+    let btc = hr_ticker("BTCUSDT", 64210.5, 1.234);
+    let line = plain::line(&btc, None, &format, false);
+    assert!(
+        line.ends_with("  BTCUSDT           64210.5    +1.23%"),
+        "{}",
+        line
+    );
+    assert!(!line.contains('\x1b'));
+
+    // The price fell, while the day is still up.
+    let line = plain::line(&btc, Some(64300.0), &format, true);
+    assert!(line.contains("\x1b[31m     64210.5\x1b[0m"), "{:?}", line);
+    assert!(line.contains("\x1b[32m  +1.23%\x1b[0m"), "{:?}", line);
+}
@@ -0,0 +1,230 @@
+use crypto_tui_ticker::plugins::{Grant, Plugin, PluginPane, PluginTicker};
+
+const TICKERS: &str = r#"[{"symbol":"FOOUSD","price":2.5,"change":10}]"#;
+const STORED: &str = r#"{"title":"Stored","lines":["ok"]}"#;
+const DENIED: &str = r#"{"title":"Denied"}"#;
+
+/// A plugin with one ticker whose pane says whether it could store a value.
+fn plugin_wasm() -> Vec<u8> {
+    let data = |text: &str| text.replace('"', "\\\"");
+    let source = format!(
+        r#"(module
+            (import "host" "storage_set" (func $set (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (global $next (mut i32) (i32.const 1024))
+            (data (i32.const 0) "{tickers}")
+            (data (i32.const 100) "{stored}")
+            (data (i32.const 200) "{denied}")
+            (data (i32.const 300) "keyvalue")
+            (func (export "alloc") (param $len i32) (result i32)
+                (global.get $next)
+                (global.set $next (i32.add (global.get $next) (local.get $len))))
+            (func (export "tickers") (result i64) (i64.const {tickers_len}))
+            (func (export "pane") (result i64)
+                (if (result i64)
+                    (i32.eqz (call $set (i32.const 300) (i32.const 3) (i32.const 303) (i32.const 5)))
+                    (then (i64.const {stored_packed}))
+                    (else (i64.const {denied_packed})))))"#,
+        tickers = data(TICKERS),
+        stored = data(STORED),
+        denied = data(DENIED),
+        tickers_len = TICKERS.len(),
+        stored_packed = (100i64 << 32) | STORED.len() as i64,
+        denied_packed = (200i64 << 32) | DENIED.len() as i64,
+    );
+    wat::parse_str(source).unwrap()
+}
+
+#[test]
+fn plugins_supply_tickers_and_store_only_with_a_grant() {
+    let wasm = plugin_wasm();
+    let path = std::env::temp_dir().join(format!("plugin-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut plugin = Plugin::new("sample", &wasm, Grant::default(), Some(path.clone())).unwrap();
+    assert_eq!(
+        plugin.tickers().unwrap(),
+        Some(vec![PluginTicker {
+            symbol: "FOOUSD".to_string(),
+            price: 2.5,
+            change: 10.0,
+            high: None,
+            low: None,
+            volume: 0.0,
+        }])
+    );
+    assert_eq!(plugin.pane().unwrap().unwrap().title, "Denied");
+    assert!(!path.exists());
+
+    let grant = Grant {
+        http: Vec::new(),
+        storage: true,
+    };
+    let mut plugin = Plugin::new("sample", &wasm, grant, Some(path.clone())).unwrap();
+    assert_eq!(
+        plugin.pane().unwrap(),
+        Some(PluginPane {
+            title: "Stored".to_string(),
+            lines: vec!["ok".to_string()],
+        })
+    );
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        r#"{"key":"value"}"#
+    );
+    std::fs::remove_file(path).unwrap();
+
+    assert!(Plugin::new("broken", b"not wasm", Grant::default(), None).is_err());
+}
+
+/// A plugin whose pane fetches `url` `times` over and is the last body `http_get` returns, or
+/// `DENIED` on -1.
+fn fetching_wasm(url: &str, times: u32) -> Vec<u8> {
+    let source = format!(
+        r#"(module
+            (import "host" "http_get" (func $get (param i32 i32) (result i64)))
+            (memory (export "memory") 1)
+            (global $next (mut i32) (i32.const 1024))
+            (data (i32.const 0) "{url}")
+            (data (i32.const 200) "{denied}")
+            (func (export "alloc") (param $len i32) (result i32)
+                (global.get $next)
+                (global.set $next (i32.add (global.get $next) (local.get $len))))
+            (func (export "pane") (result i64)
+                (local $body i64)
+                (local $left i32)
+                (local.set $left (i32.const {times}))
+                (loop $again
+                    (local.set $body (call $get (i32.const 0) (i32.const {url_len})))
+                    (local.set $left (i32.sub (local.get $left) (i32.const 1)))
+                    (br_if $again (i32.gt_s (local.get $left) (i32.const 0))))
+                (if (result i64) (i64.eq (local.get $body) (i64.const -1))
+                    (then (i64.const {denied_packed}))
+                    (else (local.get $body)))))"#,
+        url = url,
+        url_len = url.len(),
+        times = times,
+        denied = DENIED.replace('"', "\\\""),
+        denied_packed = (200i64 << 32) | DENIED.len() as i64,
+    );
+    wat::parse_str(source).unwrap()
+}
+
+/// Serves `STORED` on a local port, redirecting `/in` to the same host and `/out` to another
+/// name for it. Returns the port.
+async fn http_server() -> u16 {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            while !request.ends_with(b"\r\n\r\n") {
+                request.push(stream.read_u8().await.unwrap());
+            }
+            let request = String::from_utf8(request).unwrap();
+            let path = request.split(' ').nth(1).unwrap_or_default().to_string();
+            let reply = match path.as_str() {
+                "/in" => format!("HTTP/1.1 302 Found\r\nLocation: http://127.0.0.1:{port}/pane\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+                "/out" => format!("HTTP/1.1 302 Found\r\nLocation: http://localhost:{port}/pane\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"),
+                _ => format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", STORED.len(), STORED),
+            };
+            stream.write_all(reply.as_bytes()).await.unwrap();
+        }
+    });
+    port
+}
+
+/// A plugin granted the local server, fetching `path` from it `times` per call.
+fn fetching_plugin(port: u16, path: &str, times: u32) -> Plugin {
+    let wasm = fetching_wasm(&format!("http://127.0.0.1:{}{}", port, path), times);
+    let grant = Grant {
+        http: vec!["127.0.0.1".to_string()],
+        storage: false,
+    };
+    Plugin::new("fetch", &wasm, grant, None).unwrap()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plugin_fetches_follow_redirects_only_to_granted_hosts() {
+    let port = http_server().await;
+    let title = |path: &'static str| {
+        tokio::task::spawn_blocking(move || {
+            let mut plugin = fetching_plugin(port, path, 1);
+            plugin.pane().unwrap().unwrap().title
+        })
+    };
+    assert_eq!(title("/in").await.unwrap(), "Stored");
+    assert_eq!(title("/out").await.unwrap(), "Denied");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plugin_fetches_are_capped_per_call() {
+    let port = http_server().await;
+    let titles = tokio::task::spawn_blocking(move || {
+        // Four requests a call, counted afresh on each call.
+        let mut within = fetching_plugin(port, "/pane", 4);
+        let mut over = fetching_plugin(port, "/pane", 5);
+        [within.pane(), within.pane(), over.pane()].map(|pane| pane.unwrap().unwrap().title)
+    })
+    .await
+    .unwrap();
+    assert_eq!(titles, ["Stored", "Stored", "Denied"]);
+}
+
+/// A plugin whose pane stores `len` bytes under `key`, then says whether it could.
+fn storing_wasm(len: u32) -> Vec<u8> {
+    let source = format!(
+        r#"(module
+            (import "host" "storage_set" (func $set (param i32 i32 i32 i32) (result i32)))
+            (memory (export "memory") 32)
+            (global $next (mut i32) (i32.const 1024))
+            (data (i32.const 100) "{stored}")
+            (data (i32.const 200) "{denied}")
+            (data (i32.const 300) "key")
+            (func (export "alloc") (param $len i32) (result i32)
+                (global.get $next)
+                (global.set $next (i32.add (global.get $next) (local.get $len))))
+            (func (export "pane") (result i64)
+                (if (result i64)
+                    (i32.eqz (call $set (i32.const 300) (i32.const 3) (i32.const 65536) (i32.const {len})))
+                    (then (i64.const {stored_packed}))
+                    (else (i64.const {denied_packed})))))"#,
+        stored = STORED.replace('"', "\\\""),
+        denied = DENIED.replace('"', "\\\""),
+        len = len,
+        stored_packed = (100i64 << 32) | STORED.len() as i64,
+        denied_packed = (200i64 << 32) | DENIED.len() as i64,
+    );
+    wat::parse_str(source).unwrap()
+}
+
+#[test]
+fn plugin_storage_is_capped() {
+    let path = std::env::temp_dir().join(format!("plugin-quota-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let grant = Grant {
+        http: Vec::new(),
+        storage: true,
+    };
+    let title = |len| {
+        let mut plugin = Plugin::new(
+            "quota",
+            &storing_wasm(len),
+            grant.clone(),
+            Some(path.clone()),
+        )
+        .unwrap();
+        plugin.pane().unwrap().unwrap().title
+    };
+    // A value replacing another under the same key only counts once.
+    assert_eq!(title(600 << 10), "Stored");
+    assert_eq!(title(600 << 10), "Stored");
+    assert_eq!(title(1 << 20), "Denied");
+    let stored: std::collections::BTreeMap<String, String> =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(stored["key"].len(), 600 << 10);
+    std::fs::remove_file(path).unwrap();
+}
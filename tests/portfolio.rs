@@ -0,0 +1,92 @@
+mod common;
+
+use common::hr_ticker;
+use crypto_tui_ticker::{model::TickerMap, portfolio::Portfolio};
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-9
+}
+
+/// Loads `toml` as the holdings file of a config directory of the test's own.
+fn load(name: &str, toml: &str) -> Portfolio {
+    let dir = std::env::temp_dir().join(format!("portfolio-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("portfolio.toml"), toml).unwrap();
+    let portfolio = Portfolio::load(Some(&dir));
+    std::fs::remove_dir_all(dir).unwrap();
+    portfolio
+}
+
+#[test]
+fn holdings_are_valued_at_the_latest_prices() {
+    let portfolio = load(
+        "valued",
+        r#"
+        [[holdings]]
+        symbol = "BTCUSDT"
+        quantity = 0.5
+        cost_basis = 60000
+
+        [[holdings]]
+        symbol = "ETHUSDT"
+        quantity = 2
+        cost_basis = 4000
+
+        [[holdings]]
+        symbol = "SOLUSDT"
+        quantity = 10
+        cost_basis = 100
+        "#,
+    );
+    let tickers: TickerMap = [
+        hr_ticker("BTCUSDT", 67000.0, 2.5),
+        hr_ticker("ETHUSDT", 3500.0, -1.25),
+    ]
+    .into_iter()
+    .map(|ticker| (ticker.s.clone(), ticker))
+    .collect();
+
+    let positions = portfolio.positions(&tickers);
+    let values: Vec<_> = positions
+        .iter()
+        .map(|p| (p.price, p.market_value, p.unrealized_pnl))
+        .collect();
+    assert_eq!(
+        values,
+        [
+            (Some(67000.0), 33500.0, 3500.0),
+            (Some(3500.0), 7000.0, -1000.0),
+            // SOLUSDT has not ticked yet, so it stays at cost.
+            (None, 1000.0, 0.0),
+        ]
+    );
+    assert!(close(positions[0].pnl_percent, 3500.0 / 30000.0 * 100.0));
+    assert!(close(positions[1].pnl_percent, -12.5));
+    assert!(close(positions[0].allocation, 33500.0 / 41500.0 * 100.0));
+    let allocated: f64 = positions.iter().map(|p| p.allocation).sum();
+    assert!(close(allocated, 100.0), "{}", allocated);
+
+    let summary = portfolio.summary(&tickers);
+    assert!(close(summary.market_value, 41500.0));
+    assert!(close(summary.unrealized_pnl, 2500.0));
+    assert!(close(summary.pnl_percent, 2500.0 / 39000.0 * 100.0));
+}
+
+#[test]
+fn missing_or_broken_holdings_files_load_empty() {
+    assert!(Portfolio::load(None).is_empty());
+    let missing = std::env::temp_dir().join(format!("portfolio-missing-{}", std::process::id()));
+    assert!(Portfolio::load(Some(&missing)).is_empty());
+    assert!(load("broken", "[[holdings]]\nsymbol = 1").is_empty());
+
+    // An empty portfolio has no P&L rather than a division by zero.
+    let summary = Portfolio::default().summary(&TickerMap::new());
+    assert_eq!(
+        (
+            summary.market_value,
+            summary.unrealized_pnl,
+            summary.pnl_percent
+        ),
+        (0.0, 0.0, 0.0)
+    );
+}
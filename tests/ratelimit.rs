@@ -0,0 +1,36 @@
+use crypto_tui_ticker::ratelimit::Budget;
+use std::time::Duration;
+
+const MINUTE: u64 = 60_000;
+
+#[test]
+fn requests_wait_for_the_next_minute_once_the_budget_is_used() {
+    let mut budget = Budget::new("test", "example.com", 100);
+    let start = 10 * MINUTE + 15_000;
+    assert_eq!(budget.reserve(40, start), None);
+    assert_eq!(budget.reserve(40, start), None);
+    // 90 of the 100 are used before requests are held back.
+    assert_eq!(
+        budget.reserve(40, start),
+        Some(Duration::from_millis(45_000))
+    );
+    assert_eq!(budget.remaining(start), 20);
+    assert_eq!(budget.reserve(40, 11 * MINUTE), None);
+    assert_eq!(budget.remaining(11 * MINUTE), 60);
+
+    // Weight reported by the exchange counts other clients too.
+    budget.record_used(85, 11 * MINUTE + 1);
+    assert_eq!(
+        budget.reserve(10, 11 * MINUTE + 2),
+        Some(Duration::from_millis(59_998))
+    );
+}
+
+#[test]
+fn a_backoff_holds_every_request() {
+    let mut budget = Budget::new("test", "example.com", 100);
+    budget.back_off(Duration::from_secs(30), 0);
+    assert_eq!(budget.reserve(1, 10_000), Some(Duration::from_secs(20)));
+    assert_eq!(budget.backoff(30_000), None);
+    assert_eq!(budget.reserve(1, 30_000), None);
+}
@@ -0,0 +1,707 @@
+mod common;
+
+use common::hr_ticker;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crypto_tui_ticker::{
+    alerts::AlertEngine,
+    app::{App, Stores},
+    ascii,
+    chart::{GridConfig, GridStyle},
+    config::Config,
+    exchange::Exchange,
+    keymap::Action,
+    model::{update_tickers, HrTicker, SortColumn, TickerMap},
+    notes::SymbolNote,
+    notify,
+    recorder::{Recorder, RecorderConfig},
+    session::Session,
+    stream::Selection,
+    ui::{render_table, ui},
+    watchlist::WatchlistConfig,
+    workspace::PaneKind,
+};
+use ratatui::{
+    backend::TestBackend,
+    buffer::Buffer,
+    style::{Color, Modifier},
+    Terminal,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+use tokio::sync::watch;
+
+fn app(tickers: Vec<HrTicker>) -> (App, Arc<Mutex<TickerMap>>) {
+    app_with(Config::default(), tickers)
+}
+
+/// An app over a fixed set of tickers, saving its config to a directory of its own rather
+/// than the user's.
+fn app_with(config: Config, tickers: Vec<HrTicker>) -> (App, Arc<Mutex<TickerMap>>) {
+    app_on(config, Exchange::Binance, tickers)
+}
+
+fn app_on(
+    mut config: Config,
+    exchange: Exchange,
+    tickers: Vec<HrTicker>,
+) -> (App, Arc<Mutex<TickerMap>>) {
+    static APPS: AtomicUsize = AtomicUsize::new(0);
+    let app = APPS.fetch_add(1, Ordering::Relaxed);
+    config.home = Some(std::env::temp_dir().join(format!(
+        "crypto_tui_ticker-{}-{}",
+        std::process::id(),
+        app
+    )));
+    let store = Arc::new(Mutex::new(TickerMap::new()));
+    update_tickers(tickers, store.clone());
+    let stores = Stores {
+        tickers: store.clone(),
+        comparison: Default::default(),
+        market_data: Default::default(),
+        global: Default::default(),
+        fx_rates: Default::default(),
+        indicators: Default::default(),
+        liquidations: Default::default(),
+        status: Default::default(),
+        alerts: Arc::new(Mutex::new(AlertEngine::new(Vec::new()))),
+        account: None,
+        selection: watch::Sender::new(Selection::All),
+        exchange: Some(watch::Sender::new(exchange)),
+    };
+    (App::new(config, exchange, stores), store)
+}
+
+fn draw(app: &mut App, tickers: &Arc<Mutex<TickerMap>>, width: u16, height: u16) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal
+        .draw(|f| render_table(f, app, f.size(), tickers.clone()))
+        .unwrap();
+    terminal.backend().buffer().clone()
+}
+
+/// The lines of `buffer` with styling stripped.
+fn lines(buffer: &Buffer) -> Vec<String> {
+    (0..buffer.area.height)
+        .map(|y| {
+            (0..buffer.area.width)
+                .map(|x| buffer.get(x, y).symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Renders the table and returns its lines with styling stripped.
+fn render(app: &mut App, tickers: &Arc<Mutex<TickerMap>>, width: u16, height: u16) -> Vec<String> {
+    lines(&draw(app, tickers, width, height))
+}
+
+/// Renders the whole screen, popups and footer included, and returns its lines with styling
+/// stripped.
+fn screen(app: &mut App, tickers: &Arc<Mutex<TickerMap>>, width: u16, height: u16) -> Vec<String> {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    terminal.draw(|f| ui(f, app, tickers.clone())).unwrap();
+    lines(terminal.backend().buffer())
+}
+
+async fn press(app: &mut App, code: KeyCode) {
+    app.handle_key_press(KeyEvent::new(code, KeyModifiers::NONE))
+        .await;
+}
+
+fn sample() -> Vec<HrTicker> {
+    vec![
+        hr_ticker("ETHUSDT", 3500.0, -1.25),
+        hr_ticker("BTCUSDT", 67000.0, 2.5),
+        hr_ticker("SOLUSDT", 150.0, 7.0),
+    ]
+}
+
+/// Symbols of the table rows, top to bottom.
+fn symbols(lines: &[String]) -> Vec<&str> {
+    lines[2..lines.len() - 1]
+        .iter()
+        .filter_map(|line| line.trim_start_matches('│').split_whitespace().next())
+        .collect()
+}
+
+#[test]
+fn table_snapshot() {
+    let (mut app, tickers) = app(sample());
+    assert_eq!(
+        render(&mut app, &tickers, 80, 6),
+        [
+            "┌Crypto Tickers — Binance ▶────────────────────────────────────────────────────┐",
+            "│Symbol     Last       Percent Ch Open       High       Low        Volume      │",
+            "│BTCUSDT    67000      2.5        67000      67000      67000      1000        │",
+            "│ETHUSDT    3500       -1.25      3500       3500       3500       1000        │",
+            "│SOLUSDT    150        7          150        150        150        1000        │",
+            "└──────────────────────────────────────────────────────────────────────────────┘",
+        ]
+    );
+}
+
+#[test]
+fn narrow_table_scrolls_columns() {
+    let (mut app, tickers) = app(sample());
+    let lines = render(&mut app, &tickers, 40, 6);
+    assert!(lines[0].contains('▶'), "{:?}", lines[0]);
+    assert_eq!(lines[1], "│Symbol     Last       Percent Ch      │");
+}
+
+#[tokio::test]
+async fn sort_keys_reorder_rows() {
+    let (mut app, tickers) = app(sample());
+    // Tab cycles Symbol -> Last -> Percent Change, which sorts largest first.
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Tab).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["SOLUSDT", "BTCUSDT", "ETHUSDT"]);
+
+    press(&mut app, KeyCode::Char('r')).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["ETHUSDT", "BTCUSDT", "SOLUSDT"]);
+
+    // Each column remembers its own direction.
+    press(&mut app, KeyCode::Tab).await;
+    press(&mut app, KeyCode::Char('r')).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["SOLUSDT", "ETHUSDT", "BTCUSDT"]);
+    press(&mut app, KeyCode::F(3)).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["ETHUSDT", "BTCUSDT", "SOLUSDT"]);
+}
+
+#[tokio::test]
+async fn sort_by_key_jumps_to_a_column() {
+    let (mut app, tickers) = app(sample());
+    press(&mut app, KeyCode::Char('S')).await;
+    let footer = &screen(&mut app, &tickers, 160, 20)[18];
+    assert!(
+        footer.contains("S… (s) sort by symbol | (l) sort by last"),
+        "{}",
+        footer
+    );
+    press(&mut app, KeyCode::Char('p')).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["SOLUSDT", "BTCUSDT", "ETHUSDT"]);
+
+    // Choosing the sort column again reverses it.
+    press(&mut app, KeyCode::Char('S')).await;
+    press(&mut app, KeyCode::Char('p')).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["ETHUSDT", "BTCUSDT", "SOLUSDT"]);
+}
+
+#[tokio::test]
+async fn volume_sorts_by_amount_not_digits() {
+    let mut tickers = sample();
+    for (ticker, volume) in tickers.iter_mut().zip(["999", "1000", "25000.5"]) {
+        ticker.v = volume.to_string();
+    }
+    let (mut app, tickers) = app(tickers);
+    press(&mut app, KeyCode::Char('S')).await;
+    press(&mut app, KeyCode::Char('v')).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["SOLUSDT", "BTCUSDT", "ETHUSDT"]);
+}
+
+#[tokio::test]
+async fn updates_reorder_rows_on_the_next_render() {
+    let (mut app, tickers) = app(sample());
+    press(&mut app, KeyCode::Tab).await;
+    update_tickers([hr_ticker("SOLUSDT", 90000.0, 7.0)], tickers.clone());
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["SOLUSDT", "BTCUSDT", "ETHUSDT"]);
+    assert!(lines[2].contains("90000"), "{:?}", lines[2]);
+}
+
+#[test]
+fn user_palette_is_selected_by_name() {
+    let config: Config = toml::from_str(
+        r##"
+        [theme]
+        name = "night"
+
+        [[theme.palettes]]
+        name = "night"
+        header_bg = "#102030"
+        "##,
+    )
+    .unwrap();
+    let (mut app, tickers) = app_with(config, sample());
+    let buffer = draw(&mut app, &tickers, 80, 6);
+    assert_eq!(buffer.get(1, 1).bg, Color::Rgb(0x10, 0x20, 0x30));
+}
+
+#[tokio::test]
+async fn number_keys_switch_workspaces() {
+    let (mut app, tickers) = app(sample());
+    press(&mut app, KeyCode::Char('2')).await;
+    let screen = screen(&mut app, &tickers, 120, 20).join("\n");
+    assert!(screen.contains("Crypto Tickers"), "{}", screen);
+    assert!(screen.contains(" 2 Trading "), "{}", screen);
+    assert!(screen.contains("Select a symbol to chart"), "{}", screen);
+}
+
+#[tokio::test]
+async fn ctrl_c_quits_even_when_quit_is_rebound() {
+    let config = Config {
+        keys: [(Action::Quit, vec!["x".to_string()])].into(),
+        ..Default::default()
+    };
+    let (mut app, _) = app_with(config, sample());
+    // Also from inside a popup that takes text input.
+    app.handle_key_press(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL))
+        .await;
+    press(&mut app, KeyCode::Char('q')).await;
+    assert!(app.is_running());
+    app.handle_key_press(KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL))
+        .await;
+    assert!(!app.is_running());
+}
+
+#[tokio::test]
+async fn grid_view_replaces_the_table() {
+    let (mut app, tickers) = app(sample());
+    press(&mut app, KeyCode::Char('M')).await;
+    assert!(screen(&mut app, &tickers, 120, 20)[0].starts_with("┌Charts 1m"));
+    press(&mut app, KeyCode::Char('M')).await;
+    assert!(screen(&mut app, &tickers, 120, 20)[0].contains("Crypto Tickers"));
+}
+
+#[tokio::test]
+async fn line_grid_leaves_cells_too_narrow_to_plot_blank() {
+    // Exchanges without klines chart the recorded ticks, so the grid needs no network.
+    let path = std::env::temp_dir().join(format!("grid-{}.sqlite", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let mut recorder = Recorder::open(&path).unwrap();
+    for minute in 0..5 {
+        let mut ticks = sample();
+        for tick in &mut ticks {
+            tick.E = 1_718_000_000_000 + minute * 60_000;
+            tick.c += minute as f32;
+        }
+        recorder.record(&ticks).unwrap();
+    }
+    drop(recorder);
+    let config = Config {
+        recorder: RecorderConfig {
+            path: Some(path.clone()),
+            ..Default::default()
+        },
+        grid: GridConfig {
+            rows: 1,
+            columns: 3,
+            style: GridStyle::Line,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let (mut app, tickers) = app_on(config, Exchange::Coinbase, sample());
+    // The grid charts the symbols the table last listed.
+    screen(&mut app, &tickers, 40, 10);
+    press(&mut app, KeyCode::Char('M')).await;
+
+    // Three cells across 40 columns leave no room for a plot beside the price axis.
+    let mut lines = Vec::new();
+    for _ in 0..100 {
+        app.update_chart();
+        lines = screen(&mut app, &tickers, 40, 10);
+        if !lines.join("\n").contains("Loading") {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    let screen = lines.join("\n");
+    assert!(
+        !screen.contains("Loading") && !screen.contains("candles"),
+        "{}",
+        screen
+    );
+    assert!(lines[2].chars().all(|c| c == '│' || c == ' '), "{}", screen);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn watchlist_only_lists_pinned_symbols() {
+    let config = Config {
+        pinned: vec!["ETHUSDT".to_string()],
+        ..Default::default()
+    };
+    let (mut app, tickers) = app_with(config, sample());
+    press(&mut app, KeyCode::Char('w')).await;
+    let screen = render(&mut app, &tickers, 80, 8);
+    assert!(screen[0].contains("[WATCHLIST]"), "{:?}", screen);
+    assert!(screen.iter().any(|line| line.contains("ETHUSDT")));
+    assert!(!screen.iter().any(|line| line.contains("BTCUSDT")));
+}
+
+#[tokio::test]
+async fn watchlist_tabs_keep_their_own_sort_and_selection() {
+    let config = Config {
+        watchlists: vec![WatchlistConfig {
+            name: "majors".to_string(),
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            tag: None,
+            new_listings: false,
+            sort: Some(SortColumn::PercentChange),
+        }],
+        ..Default::default()
+    };
+    let (mut app, tickers) = app_with(config, sample());
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert!(lines[0].ends_with(" All │ majors ┐"), "{:?}", lines);
+    press(&mut app, KeyCode::End).await;
+
+    press(&mut app, KeyCode::Char(']')).await;
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert_eq!(symbols(&lines)[..3], ["BTCUSDT", "ETHUSDT", "│"]);
+    press(&mut app, KeyCode::End).await;
+    let buffer = draw(&mut app, &tickers, 80, 8);
+    assert_eq!(selected_line(&buffer), Some(3));
+
+    // The All tab is back as it was left, sorted by symbol with the last row selected.
+    press(&mut app, KeyCode::Char('[')).await;
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert_eq!(symbols(&lines)[..3], ["BTCUSDT", "ETHUSDT", "SOLUSDT"]);
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 8)), Some(4));
+
+    press(&mut app, KeyCode::Char(']')).await;
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 8)), Some(3));
+}
+
+#[tokio::test]
+async fn a_saved_session_reopens_as_it_was_left() {
+    let config = Config {
+        watchlists: vec![WatchlistConfig {
+            name: "majors".to_string(),
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            tag: None,
+            new_listings: false,
+            sort: None,
+        }],
+        ..Default::default()
+    };
+    let (mut app, tickers) = app_with(config.clone(), sample());
+    render(&mut app, &tickers, 80, 8);
+    press(&mut app, KeyCode::Char(']')).await;
+    press(&mut app, KeyCode::Tab).await;
+    render(&mut app, &tickers, 80, 8);
+    press(&mut app, KeyCode::End).await;
+    press(&mut app, KeyCode::Char('d')).await;
+    press(&mut app, KeyCode::Char('L')).await;
+    press(&mut app, KeyCode::Char('l')).await;
+    render(&mut app, &tickers, 80, 8);
+    let session = app.session();
+    assert_eq!(session.selected.as_deref(), Some("ETHUSDT"));
+    assert_eq!(session.watchlist.as_deref(), Some("majors"));
+    assert_ne!(session.sort_column, Some(SortColumn::Symbol));
+    assert_ne!(session.theme, Some(Config::default().theme.name));
+    assert_eq!(session.panes, [PaneKind::Detail, PaneKind::Liquidations]);
+
+    let path = std::env::temp_dir().join(format!("session-{}.json", std::process::id()));
+    session.save(&path).unwrap();
+    let saved = Session::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(saved, session);
+
+    // The selection waits for its symbol, which only the first tickers list.
+    let (mut restored, store) = app_with(config, Vec::new());
+    restored.restore_session(saved);
+    render(&mut restored, &store, 80, 8);
+    update_tickers(sample(), store.clone());
+    render(&mut restored, &store, 80, 8);
+    assert_eq!(restored.session(), session);
+}
+
+#[test]
+fn default_workspaces_survive_a_config_round_trip() {
+    let config = Config::default();
+    let saved = toml::to_string_pretty(&config).unwrap();
+    let loaded: Config = toml::from_str(&saved).unwrap();
+    assert_eq!(loaded.workspaces, config.workspaces);
+}
+
+#[tokio::test]
+async fn command_palette_selects_a_symbol() {
+    let (mut app, tickers) = app(sample());
+    render(&mut app, &tickers, 80, 6);
+    app.handle_key_press(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL))
+        .await;
+    // Plain "sol" ranks the sort-by-column actions first.
+    for c in "solu".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    let buffer = draw(&mut app, &tickers, 80, 6);
+    let selected: String = (1..11)
+        .map(|x| buffer.get(x, 4))
+        .filter(|cell| cell.modifier.contains(Modifier::REVERSED))
+        .map(|cell| cell.symbol())
+        .collect();
+    assert_eq!(selected.trim(), "SOLUSDT");
+}
+
+#[tokio::test]
+async fn reported_errors_are_toasted_and_kept_in_history() {
+    notify::set_interactive(true);
+    let config = Config {
+        keys: [(Action::Pin, vec!["Hyper+p".to_string()])].into(),
+        ..Default::default()
+    };
+    let (mut app, tickers) = app_with(config, sample());
+    app.collect_notifications();
+    // The toast sits in the top-right corner.
+    let shown = screen(&mut app, &tickers, 100, 20);
+    let top = shown[..3].join("\n");
+    assert!(top.contains("Error"), "{:?}", shown);
+    assert!(top.contains("config: key binding"), "{:?}", shown);
+
+    press(&mut app, KeyCode::Char('N')).await;
+    let shown = screen(&mut app, &tickers, 100, 20).join("\n");
+    assert!(shown.contains("Notifications"), "{}", shown);
+    assert_eq!(shown.matches("config: key binding").count(), 2, "{}", shown);
+}
+
+#[tokio::test]
+async fn movers_view_splits_gainers_and_losers() {
+    let (mut app, tickers) = app(sample());
+    press(&mut app, KeyCode::Char('t')).await;
+    let lines = screen(&mut app, &tickers, 120, 12);
+    assert!(lines[0].contains("Top gainers — 24h"), "{}", lines[0]);
+    assert!(lines[0].contains("Top losers — 24h"), "{}", lines[0]);
+    // Largest move first in each list; ETHUSDT is the only loser.
+    assert!(
+        lines[2].contains("SOLUSDT") && lines[2].contains("ETHUSDT"),
+        "{:?}",
+        lines
+    );
+    assert!(
+        lines[2].contains("+7.00%") && lines[2].contains("-1.25%"),
+        "{:?}",
+        lines
+    );
+    assert!(lines[3].contains("BTCUSDT"), "{:?}", lines);
+    assert!(!lines[4].contains("USDT"), "{:?}", lines);
+}
+
+#[tokio::test]
+async fn grouped_rows_expand_with_enter() {
+    let mut tickers = sample();
+    tickers.push(hr_ticker("BTCUSDC", 66000.0, 1.5));
+    let (mut app, tickers) = app(tickers);
+    app.handle_key_press(KeyEvent::new(KeyCode::Char('g'), KeyModifiers::CONTROL))
+        .await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert_eq!(symbols(&lines), ["▸", "ETHUSDT", "SOLUSDT"]);
+    assert!(
+        lines[2].contains("▸ BTC/USD") && lines[2].contains("66500"),
+        "{:?}",
+        lines
+    );
+
+    press(&mut app, KeyCode::Down).await;
+    press(&mut app, KeyCode::Enter).await;
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert_eq!(
+        symbols(&lines),
+        ["▾", "BTCUSDC", "BTCUSDT", "ETHUSDT", "SOLUSDT"]
+    );
+}
+
+/// Line of the selected table row, if it is on screen.
+fn selected_line(buffer: &Buffer) -> Option<u16> {
+    (0..buffer.area.height).find(|&y| buffer.get(1, y).modifier.contains(Modifier::REVERSED))
+}
+
+#[tokio::test]
+async fn selection_stays_on_screen_when_rows_shrink() {
+    let many = (0..12)
+        .map(|i| hr_ticker(&format!("C{:02}USDT", i), 1.0 + i as f32, 0.0))
+        .collect();
+    let (mut app, tickers) = app(many);
+    draw(&mut app, &tickers, 80, 8);
+    press(&mut app, KeyCode::End).await;
+    // Five rows fit, so the viewport scrolls to keep the last one in view.
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 8)), Some(6));
+
+    tickers
+        .lock()
+        .unwrap()
+        .retain(|symbol, _| symbol.as_str() < "C03");
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert!(lines[4].contains("C02USDT"), "{:?}", lines);
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 8)), Some(4));
+}
+
+#[tokio::test]
+async fn only_the_rows_in_view_are_drawn() {
+    let many = (0..400)
+        .map(|i| hr_ticker(&format!("C{:03}USDT", i), 1.0 + i as f32, 0.0))
+        .collect();
+    let (mut app, tickers) = app(many);
+    draw(&mut app, &tickers, 80, 8);
+    press(&mut app, KeyCode::End).await;
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert_eq!(
+        symbols(&lines),
+        ["C395USDT", "C396USDT", "C397USDT", "C398USDT", "C399USDT"]
+    );
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 8)), Some(6));
+
+    press(&mut app, KeyCode::Up).await;
+    press(&mut app, KeyCode::PageUp).await;
+    let lines = render(&mut app, &tickers, 80, 8);
+    assert!(lines[2].contains("C393USDT"), "{:?}", lines);
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 8)), Some(2));
+}
+
+#[test]
+fn cached_order_follows_new_data() {
+    let (mut app, tickers) = app(sample());
+    app.sort_by(SortColumn::Last);
+    let order = symbols(&render(&mut app, &tickers, 80, 6)).join(" ");
+    assert_eq!(order, "BTCUSDT ETHUSDT SOLUSDT");
+    assert_eq!(symbols(&render(&mut app, &tickers, 80, 6)).join(" "), order);
+
+    update_tickers([hr_ticker("SOLUSDT", 99000.0, 7.0)], tickers.clone());
+    assert_eq!(
+        symbols(&render(&mut app, &tickers, 80, 6)).join(" "),
+        "SOLUSDT BTCUSDT ETHUSDT"
+    );
+    app.sort_by(SortColumn::Last);
+    assert_eq!(
+        symbols(&render(&mut app, &tickers, 80, 6)).join(" "),
+        "ETHUSDT BTCUSDT SOLUSDT"
+    );
+}
+
+#[tokio::test]
+async fn switching_exchange_starts_the_table_over() {
+    let (mut app, tickers) = app(sample());
+    press(&mut app, KeyCode::Down).await;
+    press(&mut app, KeyCode::Char('x')).await;
+    let screen = screen(&mut app, &tickers, 80, 20).join("\n");
+    assert!(screen.contains("Switch exchange"), "{}", screen);
+    assert!(screen.contains("▶ Binance       futures"), "{}", screen);
+
+    press(&mut app, KeyCode::Down).await;
+    press(&mut app, KeyCode::Enter).await;
+    assert!(tickers.lock().unwrap().is_empty());
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert!(
+        lines[0].contains("Crypto Tickers — Binance Spot"),
+        "{}",
+        lines[0]
+    );
+    assert!(
+        !lines.iter().any(|line| line.contains("USDT")),
+        "{:?}",
+        lines
+    );
+
+    // Rows from the new exchange start unselected.
+    update_tickers(sample(), tickers.clone());
+    assert_eq!(selected_line(&draw(&mut app, &tickers, 80, 6)), None);
+}
+
+#[tokio::test]
+async fn tags_show_as_chips_and_filter_the_table() {
+    let mut config = Config::default();
+    config.notes.insert(
+        "SOLUSDT".to_string(),
+        SymbolNote::parse("breakout watch, alt: retest of 150"),
+    );
+    let (mut app, tickers) = app_with(config, sample());
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert!(lines[4].starts_with("│SOLUSDT #b"), "{}", lines[4]);
+
+    app.handle_key_press(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL))
+        .await;
+    for c in "#break".chars() {
+        press(&mut app, KeyCode::Char(c)).await;
+    }
+    press(&mut app, KeyCode::Enter).await;
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert!(lines[0].contains("[#breakout watch]"), "{}", lines[0]);
+    assert!(lines[2].starts_with("│SOLUSDT"), "{}", lines[2]);
+    assert!(
+        !lines.iter().any(|line| line.contains("BTCUSDT")),
+        "{:?}",
+        lines
+    );
+}
+
+#[tokio::test]
+async fn selected_row_copies_as_tab_separated_cells() {
+    let (mut app, tickers) = app(sample());
+    render(&mut app, &tickers, 80, 6);
+    press(&mut app, KeyCode::Char('j')).await;
+    // Columns scrolled out of view are copied too.
+    assert_eq!(
+        app.selected_row().as_deref(),
+        Some("BTCUSDT\t67000\t2.5\t67000\t67000\t67000\t1000\t-\t-\t-")
+    );
+}
+
+#[test]
+fn ascii_mode_replaces_unicode_symbols() {
+    let (mut app, tickers) = app(sample());
+    let mut buffer = draw(&mut app, &tickers, 80, 6);
+    ascii::downgrade(&mut buffer);
+    let lines: Vec<String> = (0..6)
+        .map(|y| (0..80).map(|x| buffer.get(x, y).symbol()).collect())
+        .collect();
+    assert!(lines.iter().all(|line| line.is_ascii()), "{:?}", lines);
+    assert!(
+        lines[0].starts_with("+Crypto Tickers - Binance >---"),
+        "{}",
+        lines[0]
+    );
+    assert!(lines[2].starts_with("|BTCUSDT"), "{}", lines[2]);
+    assert_eq!(ascii::to_ascii('\u{28c0}'), '.');
+    assert_eq!(ascii::to_ascii('\u{2809}'), '\'');
+}
+
+#[tokio::test]
+async fn accessibility_mode_spells_out_moves_and_announces_the_selection() {
+    let mut config = Config::default();
+    config.accessibility.enabled = true;
+    let (mut app, tickers) = app_with(config, sample());
+    let lines = render(&mut app, &tickers, 80, 6);
+    assert!(lines[2].contains("▲ +2.5"), "{}", lines[2]);
+    assert!(lines[3].contains("▼ -1.25"), "{}", lines[3]);
+
+    press(&mut app, KeyCode::Down).await;
+    let footer = &screen(&mut app, &tickers, 120, 20)[18];
+    assert!(
+        footer.contains("BTCUSDT: last 67000, up 2.5% in 24 hours"),
+        "{}",
+        footer
+    );
+}
+
+#[tokio::test]
+async fn chords_wait_for_their_second_key() {
+    let (mut app, tickers) = app(sample());
+    let footer = |app: &mut App| screen(app, &tickers, 160, 20)[18].clone();
+    footer(&mut app);
+    press(&mut app, KeyCode::Down).await;
+    press(&mut app, KeyCode::Down).await;
+    assert_eq!(app.session().selected.as_deref(), Some("ETHUSDT"));
+
+    press(&mut app, KeyCode::Char('g')).await;
+    assert!(footer(&mut app).contains("g… (g) first"));
+    press(&mut app, KeyCode::Char('g')).await;
+    assert!(!footer(&mut app).contains("g… (g) first"));
+    assert_eq!(app.session().selected.as_deref(), Some("BTCUSDT"));
+
+    // A key that does not complete the chord only cancels it.
+    press(&mut app, KeyCode::Char('g')).await;
+    press(&mut app, KeyCode::Char('j')).await;
+    assert_eq!(app.session().selected.as_deref(), Some("BTCUSDT"));
+}
@@ -0,0 +1,43 @@
+use crypto_tui_ticker::{
+    config::Config,
+    exchange::Exchange,
+    setup::{self, Answers},
+};
+use std::io::Cursor;
+
+#[test]
+fn setup_answers_are_written_as_a_loadable_config() {
+    let mut input = Cursor::new("2\nusdt, usdc\n9\nemerald\nBTC/USDT,ethusdt,nonsense\n");
+    let mut output = Vec::new();
+    let answers = setup::ask(&mut input, &mut output).unwrap();
+    assert_eq!(
+        answers,
+        Answers {
+            exchange: Exchange::BinanceSpot,
+            quotes: vec!["USDT".to_string(), "USDC".to_string()],
+            theme: "emerald".to_string(),
+            watchlist: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+        }
+    );
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("Pick a number from 1 to 5."));
+    assert!(output.contains("Skipped nonsense"));
+
+    let toml = setup::config_toml(&answers);
+    assert!(toml.lines().any(|line| line.starts_with("# Quote assets")));
+    let config: Config = toml::from_str(&toml).unwrap();
+    assert_eq!(config.exchange, Exchange::BinanceSpot);
+    assert_eq!(config.quotes, answers.quotes);
+    assert_eq!(config.theme.name, "emerald");
+    assert_eq!(config.watchlists[0].name, setup::WATCHLIST_NAME);
+    assert_eq!(config.watchlists[0].symbols, answers.watchlist);
+}
+
+#[test]
+fn setup_keeps_the_defaults_when_input_ends() {
+    let answers = setup::ask(&mut Cursor::new(""), &mut Vec::new()).unwrap();
+    assert_eq!(answers, Answers::default());
+    let config: Config = toml::from_str(&setup::config_toml(&answers)).unwrap();
+    assert_eq!(config.exchange, Exchange::Binance);
+    assert!(config.quotes.is_empty() && config.watchlists.is_empty());
+}
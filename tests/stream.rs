@@ -0,0 +1,488 @@
+mod common;
+
+use common::{mock_server, recording_server, ticker};
+use crypto_tui_ticker::{
+    alerts::{expr::Expr, history::AlertHistory, Alert, AlertEngine, AlertRule},
+    exchange::binance,
+    model::{self, update_tickers, Changes, HrTicker, TickerMap},
+    script::Script,
+    stream::Selection,
+};
+use futures::{SinkExt, StreamExt};
+use serde_json::json;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    net::TcpListener,
+    sync::{mpsc, watch},
+};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Connects to a mock server playing `frames` and returns the batches it produced.
+async fn stream(frames: Vec<String>, batches: usize) -> Vec<Vec<HrTicker>> {
+    let url = mock_server(frames).await;
+    let (tx, mut rx) = mpsc::channel(16);
+    binance::subscribe_url(&url, tx).await.unwrap();
+    let mut received = Vec::new();
+    for _ in 0..batches {
+        let batch = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .expect("timed out waiting for a batch")
+            .expect("stream ended early");
+        received.push(batch);
+    }
+    received
+}
+
+#[tokio::test]
+async fn updates_are_merged_into_the_store() {
+    let batches = stream(
+        vec![
+            json!([ticker("BTCUSDT", 100.0, 1.5), ticker("ETHUSDT", 10.0, -2.0)]).to_string(),
+            json!([ticker("BTCUSDT", 101.0, 2.5)]).to_string(),
+        ],
+        2,
+    )
+    .await;
+
+    let store = Arc::new(Mutex::new(TickerMap::new()));
+    let generation = model::generation();
+    for batch in batches {
+        update_tickers(batch, store.clone());
+    }
+    // Each merge that changes the store tells the UI there is something new to draw.
+    assert!(model::generation() >= generation + 2);
+    let store = store.lock().unwrap();
+    assert_eq!(store.len(), 2);
+    let btc = &store["BTCUSDT"];
+    assert_eq!(btc.c, 101.0);
+    assert_eq!(btc.P, 2.5);
+    assert_eq!(btc.previous_price, 100.0);
+    assert!(btc.last_changed.is_some());
+    assert!(btc.last_updated.is_some());
+    assert_eq!(store["ETHUSDT"].c, 10.0);
+}
+
+#[test]
+fn only_changed_fields_are_marked() {
+    let parse = |symbol, price, percent| -> HrTicker {
+        serde_json::from_value(ticker(symbol, price, percent)).unwrap()
+    };
+    let store = Arc::new(Mutex::new(TickerMap::new()));
+    let added = update_tickers(
+        [parse("ETHUSDT", 10.0, 1.0), parse("BTCUSDT", 100.0, 1.0)],
+        store.clone(),
+    );
+    assert_eq!(added, 2);
+    assert_eq!(store.lock().unwrap()["ETHUSDT"].changes, Changes::ALL);
+
+    let generation = model::generation();
+    let changed = update_tickers(
+        [parse("ETHUSDT", 10.0, 1.0), parse("BTCUSDT", 100.0, 2.0)],
+        store.clone(),
+    );
+    assert_eq!(changed, 1);
+    assert!(model::generation() > generation);
+    let store = store.lock().unwrap();
+    let (eth, btc) = (&store["ETHUSDT"], &store["BTCUSDT"]);
+    assert!(eth.changes.is_empty());
+    assert!(eth.last_changed.is_none());
+    assert!(btc.changes.intersects(Changes::CHANGE));
+    assert!(!btc.changes.intersects(Changes::LAST));
+    // Symbols stay in the order they were first seen.
+    assert_eq!(store.keys().collect::<Vec<_>>(), ["ETHUSDT", "BTCUSDT"]);
+}
+
+#[test]
+fn event_time_advances_with_each_update() {
+    let at = |time: u64| -> HrTicker {
+        let mut ticker = ticker("BTCUSDT", 100.0, 1.0);
+        ticker["E"] = json!(time);
+        serde_json::from_value(ticker).unwrap()
+    };
+    let store = Arc::new(Mutex::new(TickerMap::new()));
+    update_tickers([at(1_000)], store.clone());
+    // Only the event time differs, which is still an update for charts and the recorder.
+    assert_eq!(update_tickers([at(600_000)], store.clone()), 1);
+    let store = store.lock().unwrap();
+    assert_eq!(store["BTCUSDT"].E, 600_000);
+    assert!(store["BTCUSDT"].changes.intersects(Changes::TRADES));
+}
+
+#[tokio::test]
+async fn malformed_frames_are_skipped() {
+    let batches = stream(
+        vec![
+            "not json".to_string(),
+            json!([ticker("BTCUSDT", 100.0, 0.0)]).to_string(),
+        ],
+        1,
+    )
+    .await;
+    assert_eq!(batches[0][0].s, "BTCUSDT");
+}
+
+#[tokio::test]
+async fn alerts_fire_once_per_crossing_for_their_symbol() {
+    let batches = stream(
+        vec![
+            json!([ticker("BTCUSDT", 99.0, 0.0), ticker("ETHUSDT", 200.0, 0.0)]).to_string(),
+            json!([ticker("BTCUSDT", 101.0, 0.0), ticker("ETHUSDT", 201.0, 0.0)]).to_string(),
+            json!([ticker("BTCUSDT", 102.0, 0.0)]).to_string(),
+        ],
+        3,
+    )
+    .await;
+
+    let rule = AlertRule {
+        name: "btc breakout".to_string(),
+        symbol: Some("BTCUSDT".to_string()),
+        when: Some("c crosses_above 100".to_string()),
+        above: None,
+        below: None,
+        moves: None,
+        within_mins: None,
+        drops_from_high: None,
+        volume_spike: None,
+        trades_spike: None,
+        cooldown_secs: 0,
+        once: false,
+        sinks: Vec::new(),
+    };
+    let mut engine = AlertEngine::new(vec![rule]);
+    let fired: Vec<Vec<String>> = batches
+        .iter()
+        .map(|batch| {
+            engine
+                .evaluate(batch)
+                .into_iter()
+                .map(|(alert, _)| alert.symbol)
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        fired,
+        vec![vec![], vec!["BTCUSDT".to_string()], Vec::<String>::new()]
+    );
+}
+
+#[test]
+fn cooldowns_snoozes_and_one_shot_rules_stay_quiet() {
+    let rule = |name: &str, cooldown_secs, once| AlertRule {
+        name: name.to_string(),
+        symbol: None,
+        when: Some("c crosses_above 100".to_string()),
+        above: None,
+        below: None,
+        moves: None,
+        within_mins: None,
+        drops_from_high: None,
+        volume_spike: None,
+        trades_spike: None,
+        cooldown_secs,
+        once,
+        sinks: Vec::new(),
+    };
+    let mut engine = AlertEngine::new(vec![
+        rule("repeating", 0, false),
+        rule("cooldown", 60, false),
+        rule("once", 0, true),
+    ]);
+    fn fired(engine: &mut AlertEngine, symbol: &str, last: f32) -> Vec<String> {
+        let ticker: HrTicker = serde_json::from_value(ticker(symbol, last, 0.0)).unwrap();
+        engine
+            .evaluate(&[ticker])
+            .into_iter()
+            .map(|(alert, _)| alert.rule)
+            .collect()
+    }
+    fired(&mut engine, "BTCUSDT", 99.0);
+    let all = ["repeating", "cooldown", "once"];
+    assert_eq!(fired(&mut engine, "BTCUSDT", 101.0), all);
+    fired(&mut engine, "BTCUSDT", 99.0);
+    assert_eq!(fired(&mut engine, "BTCUSDT", 101.0), ["repeating"]);
+
+    // Quiet rules are quiet per symbol.
+    fired(&mut engine, "ETHUSDT", 99.0);
+    assert_eq!(fired(&mut engine, "ETHUSDT", 101.0), all);
+    let alert = Alert {
+        rule: "repeating".to_string(),
+        symbol: "ETHUSDT".to_string(),
+        price: 101.0,
+        time: 0,
+    };
+    engine.snooze(&alert, Duration::from_secs(60));
+    fired(&mut engine, "ETHUSDT", 99.0);
+    assert!(fired(&mut engine, "ETHUSDT", 101.0).is_empty());
+}
+
+#[test]
+fn moves_and_drawdowns_are_measured_on_local_history() {
+    let rule = |name: &str| AlertRule {
+        name: name.to_string(),
+        symbol: None,
+        when: None,
+        above: None,
+        below: None,
+        moves: None,
+        within_mins: None,
+        drops_from_high: None,
+        volume_spike: None,
+        trades_spike: None,
+        cooldown_secs: 0,
+        once: false,
+        sinks: Vec::new(),
+    };
+    let mut engine = AlertEngine::new(vec![
+        AlertRule {
+            moves: Some(2.0),
+            within_mins: Some(1),
+            ..rule("fast move")
+        },
+        AlertRule {
+            drops_from_high: Some(5.0),
+            ..rule("off the high")
+        },
+    ]);
+    let mut fired = |seconds: u64, last: f32| -> Vec<String> {
+        let mut ticker = ticker("BTCUSDT", last, 0.0);
+        ticker["E"] = json!(seconds * 1000);
+        let ticker: HrTicker = serde_json::from_value(ticker).unwrap();
+        engine
+            .evaluate(&[ticker])
+            .into_iter()
+            .map(|(alert, _)| alert.rule)
+            .collect()
+    };
+    assert!(fired(0, 100.0).is_empty());
+    assert!(fired(30, 101.0).is_empty());
+    assert_eq!(fired(60, 102.5), ["fast move"]);
+    assert!(fired(90, 110.0).is_empty());
+    assert_eq!(fired(120, 104.0), ["off the high"]);
+
+    let err = Expr::parse("change(0) > 1").unwrap_err();
+    assert_eq!(err.message, "`change` takes whole minutes from 1 to 1440");
+}
+
+#[test]
+fn volume_spikes_are_measured_against_the_usual_rate() {
+    let mut engine = AlertEngine::new(vec![AlertRule {
+        name: "spike".to_string(),
+        symbol: None,
+        when: None,
+        above: None,
+        below: None,
+        moves: None,
+        within_mins: None,
+        drops_from_high: None,
+        volume_spike: Some(5.0),
+        trades_spike: None,
+        cooldown_secs: 0,
+        once: false,
+        sinks: Vec::new(),
+    }]);
+    let mut fired = |minute: u64, volume: u64| -> usize {
+        let mut ticker = ticker("BTCUSDT", 100.0, 0.0);
+        ticker["E"] = json!(minute * 60_000 + 1_000);
+        ticker["v"] = json!(volume.to_string());
+        let ticker: HrTicker = serde_json::from_value(ticker).unwrap();
+        engine.evaluate(&[ticker]).len()
+    };
+    // 10 a minute for long enough to be the baseline, then 100 in a minute.
+    for minute in 0..=12 {
+        assert_eq!(fired(minute, 1_000 + minute * 10), 0);
+    }
+    assert_eq!(fired(13, 1_000 + 12 * 10 + 100), 1);
+}
+
+#[test]
+fn scripts_raise_and_drop_alerts_and_fill_their_column() {
+    let mut engine = AlertEngine::new(vec![AlertRule {
+        name: "above 100".to_string(),
+        symbol: None,
+        when: Some("c > 100".to_string()),
+        above: None,
+        below: None,
+        moves: None,
+        within_mins: None,
+        drops_from_high: None,
+        volume_spike: None,
+        trades_spike: None,
+        cooldown_secs: 0,
+        once: false,
+        sinks: Vec::new(),
+    }]);
+    engine.script = Some(
+        Script::compile(
+            r#"
+            fn on_ticker_update(t) {
+                let high = this[t.symbol] ?? 0.0;
+                if t.price > high { this[t.symbol] = t.price; }
+                if t.price < high * 0.9 { "dropped 10%" }
+            }
+            fn on_alert(alert) { alert.symbol != "ETHUSDT" }
+            fn custom_column(symbol) { this[symbol] }
+            "#,
+        )
+        .unwrap(),
+    );
+    let mut fired = |symbol: &str, last: f32| -> Vec<String> {
+        let ticker: HrTicker = serde_json::from_value(ticker(symbol, last, 0.0)).unwrap();
+        engine
+            .evaluate(&[ticker])
+            .into_iter()
+            .map(|(alert, _)| alert.rule)
+            .collect()
+    };
+    assert_eq!(fired("BTCUSDT", 120.0), ["above 100"]);
+    assert_eq!(fired("BTCUSDT", 100.0), ["dropped 10%"]);
+    assert!(fired("BTCUSDT", 99.0).is_empty());
+    assert!(fired("ETHUSDT", 120.0).is_empty());
+    let script = engine.script.as_ref().unwrap();
+    assert_eq!(script.column("BTCUSDT"), Some("120.0"));
+    assert_eq!(script.column("SOLUSDT"), None);
+}
+
+#[test]
+fn alert_log_is_read_back_newest_first() {
+    let path = std::env::temp_dir().join(format!("alerts-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let alert = |symbol: &str, time| Alert {
+        rule: "breakout".to_string(),
+        symbol: symbol.to_string(),
+        price: 100.0,
+        time,
+    };
+    let mut history = AlertHistory::open(path.clone());
+    for (symbol, time) in [("BTCUSDT", 1), ("ETHUSDT", 2), ("BTCUSDT", 3)] {
+        history.record(&alert(symbol, time)).unwrap();
+    }
+
+    let history = AlertHistory::open(path.clone());
+    let times = |symbol| -> Vec<u64> { history.alerts(symbol).map(|a| a.time).collect() };
+    assert_eq!(times(None), [3, 2, 1]);
+    assert_eq!(times(Some("BTCUSDT")), [3, 1]);
+    std::fs::remove_file(path).unwrap();
+}
+
+async fn recv<T>(rx: &mut mpsc::UnboundedReceiver<T>) -> T {
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("timed out waiting for the client")
+        .unwrap()
+}
+
+async fn next_request(requests: &mut mpsc::UnboundedReceiver<String>) -> serde_json::Value {
+    serde_json::from_str(&recv(requests).await).unwrap()
+}
+
+#[tokio::test]
+async fn selected_symbols_are_subscribed_and_updated() {
+    let (url, mut requests) = recording_server(vec![
+        json!({ "result": null, "id": 1 }).to_string(),
+        ticker("BTCUSDT", 100.0, 1.0).to_string(),
+    ])
+    .await;
+    let (selection, selected) = watch::channel(Selection::of(&["btcusdt".to_string()]));
+    let (tx, mut rx) = mpsc::channel(16);
+    binance::subscribe_selected(&url, selected, tx)
+        .await
+        .unwrap();
+
+    let subscribe = next_request(&mut requests).await;
+    assert_eq!(subscribe["method"], "SUBSCRIBE");
+    assert_eq!(subscribe["params"], json!(["btcusdt@ticker"]));
+
+    // The reply to the request is not a ticker; the single-symbol event is.
+    let batch = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(batch.len(), 1);
+    assert_eq!(batch[0].s, "BTCUSDT");
+
+    selection.send_replace(Selection::All);
+    let unsubscribe = next_request(&mut requests).await;
+    assert_eq!(unsubscribe["method"], "UNSUBSCRIBE");
+    assert_eq!(unsubscribe["params"], json!(["btcusdt@ticker"]));
+    let subscribe = next_request(&mut requests).await;
+    assert_eq!(subscribe["method"], "SUBSCRIBE");
+    assert_eq!(subscribe["params"], json!(["!ticker@arr"]));
+    assert!(subscribe["id"].as_u64() > unsubscribe["id"].as_u64());
+}
+
+#[tokio::test]
+async fn pings_are_answered_and_dropped_connections_resubscribe() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    let (seen_tx, mut seen) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        for _ in 0..2 {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(stream).await.unwrap();
+            ws.send(Message::Ping(b"keepalive".to_vec())).await.unwrap();
+            // Report what the client sends until its pong, then drop the connection.
+            while let Some(Ok(message)) = ws.next().await {
+                let pong = matches!(message, Message::Pong(_));
+                seen_tx.send(message).unwrap();
+                if pong {
+                    break;
+                }
+            }
+        }
+    });
+
+    let (_selection, selected) = watch::channel(Selection::of(&["ETHUSDT".to_string()]));
+    let (tx, _rx) = mpsc::channel(16);
+    binance::subscribe_selected(&url, selected, tx)
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        let Message::Text(subscribe) = recv(&mut seen).await else {
+            panic!("expected a SUBSCRIBE request first");
+        };
+        assert!(subscribe.contains("ethusdt@ticker"), "{}", subscribe);
+        assert_eq!(recv(&mut seen).await, Message::Pong(b"keepalive".to_vec()));
+    }
+}
+
+#[tokio::test]
+async fn rest_tickers_are_normalized() {
+    let body = json!([{
+        "symbol": "BTCUSDT",
+        "priceChange": "-150.5",
+        "priceChangePercent": "-0.25",
+        "weightedAvgPrice": "60100.0",
+        "lastPrice": "60000.0",
+        "lastQty": "0.01",
+        "openPrice": "60150.5",
+        "highPrice": "61000.0",
+        "lowPrice": "59000.0",
+        "volume": "1200",
+        "quoteVolume": "72000000",
+        "openTime": 1_717_913_600_000u64,
+        "closeTime": 1_718_000_000_000u64,
+        "firstId": -1,
+        "lastId": 99,
+        "count": 100
+    }]);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = axum::Router::new().route(
+        "/fapi/v1/ticker/24hr",
+        axum::routing::get(move || async move { axum::Json(body) }),
+    );
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let url = format!("http://{}/fapi/v1/ticker/24hr", addr);
+    let tickers = binance::rest_tickers(&url).await.unwrap();
+    assert_eq!(tickers.len(), 1);
+    let btc = &tickers[0];
+    assert_eq!(btc.s, "BTCUSDT");
+    assert_eq!((btc.c, btc.P, btc.o), (60000.0, -0.25, 60150.5));
+    assert_eq!(btc.q, "72000000");
+    assert_eq!((btc.E, btc.F, btc.L), (1_718_000_000_000, 0, 99));
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::hr_ticker_with_volume;
+use crypto_tui_ticker::{exchange::Exchange, summary};
+
+#[test]
+fn summary_counts_the_listed_symbols() {
+    let tickers = [
+        hr_ticker_with_volume("BTCUSDT", 67000.0, 2.0, 5e9),
+        hr_ticker_with_volume("BTCUSDC", 66990.0, 1.0, 1e8),
+        hr_ticker_with_volume("ETHBTC", 0.05, -3.0, 1e3),
+        hr_ticker_with_volume("SOLUSDT", 150.0, 0.0, 1e6),
+    ];
+    let summary = summary::summarize(Exchange::Binance, &tickers, &tickers);
+    assert_eq!((summary.gainers, summary.losers), (2, 1));
+    assert_eq!(summary.average_change, Some(0.0));
+    // BTC-quoted volume is left out.
+    assert_eq!(summary.quote_volume, 5e9 + 1e8 + 1e6);
+    assert_eq!(summary.btc_price, Some(67000.0));
+
+    // Filtering BTC out of the table keeps its price.
+    let summary = summary::summarize(Exchange::Binance, &tickers[3..], &tickers);
+    assert_eq!((summary.gainers, summary.losers), (0, 0));
+    assert_eq!(summary.btc_price, Some(67000.0));
+    let summary = summary::summarize(Exchange::Binance, [], []);
+    assert_eq!(summary.average_change, None);
+    assert_eq!(summary.btc_price, None);
+}
+
+#[test]
+fn alt_index_weighs_each_coin_once() {
+    let tickers = [
+        hr_ticker_with_volume("BTCUSDT", 67000.0, 2.0, 5e9),
+        hr_ticker_with_volume("ETHUSDT", 3500.0, 4.0, 2e9),
+        hr_ticker_with_volume("ETHUSDC", 3501.0, 1.0, 1e7),
+        hr_ticker_with_volume("SOLUSDT", 150.0, -2.0, 1e6),
+        hr_ticker_with_volume("USDCUSDT", 1.0, 0.01, 1e8),
+    ];
+    let summary = summary::summarize(Exchange::Binance, [], &tickers);
+    assert_eq!(summary.alt_change, Some(1.0));
+    assert_eq!(summary.futures_volume, Some(5e9 + 2e9 + 1e7 + 1e6 + 1e8));
+    let spot = summary::summarize(Exchange::BinanceSpot, [], &tickers);
+    assert_eq!(spot.futures_volume, None);
+}
@@ -0,0 +1,45 @@
+use crypto_tui_ticker::{supervisor::Supervisor, Error};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[tokio::test]
+async fn shutdown_cancels_tasks_and_reports_failures() {
+    let mut tasks = Supervisor::new();
+    tasks.spawn("forever", async {
+        std::future::pending::<()>().await;
+        Ok(())
+    });
+    tasks.spawn("failing", async {
+        Err(Error::Other("no route to host".to_string()))
+    });
+
+    let flushed = Arc::new(AtomicBool::new(false));
+    let token = tasks.token();
+    let done = flushed.clone();
+    tasks.spawn_graceful("graceful", async move {
+        token.cancelled().await;
+        done.store(true, Ordering::SeqCst);
+        Ok(())
+    });
+
+    let errors = tasks.shutdown(Duration::from_secs(1)).await;
+    assert_eq!(errors, ["failing: no route to host"]);
+    assert!(flushed.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn tasks_ignoring_shutdown_are_reported() {
+    let mut tasks = Supervisor::new();
+    tasks.spawn_graceful("stuck", async {
+        std::future::pending::<()>().await;
+        Ok(())
+    });
+
+    let errors = tasks.shutdown(Duration::from_millis(50)).await;
+    assert_eq!(errors, ["1 task(s) did not stop within 50ms"]);
+}
@@ -0,0 +1,37 @@
+use crypto_tui_ticker::theme::ColorMode;
+use ratatui::style::Color;
+
+#[test]
+fn rgb_maps_to_the_nearest_256_color() {
+    assert_eq!(
+        ColorMode::Ansi256.apply(Color::Rgb(0xff, 0x00, 0x00)),
+        Color::Indexed(196)
+    );
+    assert_eq!(
+        ColorMode::Ansi256.apply(Color::Rgb(0x80, 0x80, 0x80)),
+        Color::Indexed(244)
+    );
+    assert_eq!(
+        ColorMode::Ansi256.apply(Color::Rgb(0x5f, 0x87, 0xaf)),
+        Color::Indexed(67)
+    );
+}
+
+#[test]
+fn rgb_maps_to_the_nearest_ansi16_color() {
+    assert_eq!(
+        ColorMode::Ansi16.apply(Color::Rgb(0x02, 0x06, 0x17)),
+        Color::Black
+    );
+    assert_eq!(
+        ColorMode::Ansi16.apply(Color::Rgb(0x16, 0xa3, 0x4a)),
+        Color::Green
+    );
+    assert_eq!(ColorMode::Ansi16.apply(Color::Yellow), Color::Yellow);
+}
+
+#[test]
+fn truecolor_is_unchanged() {
+    let color = Color::Rgb(0x12, 0x34, 0x56);
+    assert_eq!(ColorMode::Truecolor.apply(color), color);
+}
@@ -0,0 +1,54 @@
+use crypto_tui_ticker::validate::{self, Problem};
+
+#[test]
+fn config_problems_point_at_their_line_with_a_fix() {
+    let text = "\
+pinned = [\"BTCUSDT\", \"nonsense\"]
+updte_hz = 2
+
+[theme]
+name = \"indgo\"
+
+[keys]
+quit = [\"Crtl+q\"]
+";
+    let problems = validate::check(text, "config.toml").unwrap_err();
+    let summary: Vec<(&str, Option<&str>)> = problems
+        .iter()
+        .map(|p| (p.location.as_str(), p.help.as_deref()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (
+                "config.toml:1:22",
+                Some("write symbols like BTCUSDT, BTC-USD or BTC/USD")
+            ),
+            ("config.toml:2:1", Some("did you mean `update_hz`?")),
+            ("config.toml:5:8", Some("did you mean \"indigo\"?")),
+            (
+                "config.toml:8:9",
+                Some("write keys like q, Ctrl+p, Shift+Tab, PageDown, F5 or g g")
+            ),
+        ]
+    );
+    assert_eq!(problems[2].message, "unknown theme \"indgo\"");
+}
+
+#[test]
+fn malformed_config_is_reported_with_its_position() {
+    let problems = validate::check("fps = 10\n[chart]\nstyle = 3\n", "config.toml").unwrap_err();
+    assert_eq!(problems.len(), 1);
+    assert_eq!(problems[0].location, "config.toml:3:9");
+
+    let config = validate::check("fps = 30\n[chart]\nstyle = \"area\"\n", "config.toml").unwrap();
+    assert_eq!(config.fps, 30);
+    assert_eq!(
+        validate::check_symbols("--symbols", &["BTCUSDT".to_string(), "zz".to_string()]),
+        [Problem {
+            location: "--symbols".to_string(),
+            message: "\"zz\" is not a symbol: no known quote asset".to_string(),
+            help: Some("write symbols like BTCUSDT, BTC-USD or BTC/USD".to_string()),
+        }]
+    );
+}
@@ -0,0 +1,21 @@
+use crypto_tui_ticker::{exchange::Exchange, watchlist};
+
+#[test]
+fn tradingview_exports_are_normalized_to_the_exchange() {
+    let export = "###Majors,BINANCE:BTCUSDT.P,KRAKEN:XBTUSD,COINBASE:ETH-USD\n\
+                  ###Stocks,NASDAQ:AAPL,BINANCE:BTCUSDT";
+    let (symbols, unmatched) = watchlist::parse(export, Exchange::Binance);
+    assert_eq!(symbols, ["BTCUSDT", "BTCUSD", "ETHUSD"]);
+    assert_eq!(unmatched, ["NASDAQ:AAPL"]);
+
+    let (symbols, _) = watchlist::parse(export, Exchange::Coinbase);
+    assert_eq!(symbols, ["BTC-USDT", "BTC-USD", "ETH-USD"]);
+}
+
+#[test]
+fn plain_lists_may_use_newlines_or_commas() {
+    let list = "# long term\nsolusdt, DOGE/USDT\n\nBTC\n";
+    let (symbols, unmatched) = watchlist::parse(list, Exchange::BinanceSpot);
+    assert_eq!(symbols, ["SOLUSDT", "DOGEUSDT"]);
+    assert_eq!(unmatched, ["BTC"]);
+}